@@ -298,6 +298,9 @@ impl Server {
             .add_request_handler(forward_read_only_project_request::<proto::GetHover>)
             .add_request_handler(forward_read_only_project_request::<proto::GetDefinition>)
             .add_request_handler(forward_read_only_project_request::<proto::GetTypeDefinition>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetCallHierarchy>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetTypeHierarchy>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetSemanticTokens>)
             .add_request_handler(forward_read_only_project_request::<proto::GetReferences>)
             .add_request_handler(forward_find_search_candidates_request)
             .add_request_handler(forward_read_only_project_request::<proto::GetDocumentHighlights>)
@@ -309,11 +312,26 @@ impl Server {
             .add_request_handler(forward_read_only_project_request::<proto::ResolveInlayHint>)
             .add_request_handler(forward_read_only_project_request::<proto::OpenBufferByPath>)
             .add_request_handler(forward_read_only_project_request::<proto::GitBranches>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitStageStatuses>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitFileHistory>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitFileAtCommit>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitCommitDiff>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitChangedPaths>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitStashList>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitStashPush>)
+            .add_request_handler(forward_mutating_project_request::<proto::GitStashAction>)
+            .add_request_handler(forward_read_only_project_request::<proto::GitBranchUrl>)
             .add_request_handler(forward_read_only_project_request::<proto::GetStagedText>)
             .add_request_handler(
                 forward_mutating_project_request::<proto::RegisterBufferWithLanguageServers>,
             )
             .add_request_handler(forward_mutating_project_request::<proto::UpdateGitBranch>)
+            .add_request_handler(forward_mutating_project_request::<proto::StageGitPaths>)
+            .add_request_handler(forward_mutating_project_request::<proto::UnstageGitPaths>)
+            .add_request_handler(forward_mutating_project_request::<proto::DiscardGitPaths>)
+            .add_request_handler(forward_mutating_project_request::<proto::SetGitIndexText>)
+            .add_request_handler(forward_mutating_project_request::<proto::UnstageGitRows>)
+            .add_request_handler(forward_mutating_project_request::<proto::CommitGitChanges>)
             .add_request_handler(forward_mutating_project_request::<proto::GetCompletions>)
             .add_request_handler(
                 forward_mutating_project_request::<proto::ApplyCompletionAdditionalEdits>,
@@ -323,6 +341,11 @@ impl Server {
                 forward_mutating_project_request::<proto::ResolveCompletionDocumentation>,
             )
             .add_request_handler(forward_mutating_project_request::<proto::GetCodeActions>)
+            .add_request_handler(forward_mutating_project_request::<proto::GetCodeLens>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetFoldingRanges>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetSelectionRanges>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetDocumentLinks>)
+            .add_request_handler(forward_read_only_project_request::<proto::ResolveDocumentLink>)
             .add_request_handler(forward_mutating_project_request::<proto::ApplyCodeAction>)
             .add_request_handler(forward_mutating_project_request::<proto::PrepareRename>)
             .add_request_handler(forward_mutating_project_request::<proto::PerformRename>)
@@ -342,6 +365,10 @@ impl Server {
             .add_message_handler(create_buffer_for_peer)
             .add_request_handler(update_buffer)
             .add_message_handler(broadcast_project_message_from_host::<proto::RefreshInlayHints>)
+            .add_message_handler(
+                broadcast_project_message_from_host::<proto::RefreshSemanticTokens>,
+            )
+            .add_message_handler(broadcast_project_message_from_host::<proto::RefreshCodeLens>)
             .add_message_handler(broadcast_project_message_from_host::<proto::UpdateBufferFile>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferReloaded>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferSaved>)
@@ -13,7 +13,10 @@ use client::{User, RECEIVE_TIMEOUT};
 use collections::{HashMap, HashSet};
 use fs::{FakeFs, Fs as _, RemoveOptions};
 use futures::{channel::mpsc, StreamExt as _};
-use git::repository::GitFileStatus;
+use git::{
+    history::CommitFileDiff,
+    repository::{DiffBase, GitFileStatus},
+};
 use gpui::{
     px, size, AppContext, BackgroundExecutor, Model, Modifiers, MouseButton, MouseDownEvent,
     TestAppContext, UpdateGlobal,
@@ -2771,6 +2774,115 @@ async fn test_git_diff_base_change(
     });
 }
 
+#[gpui::test]
+async fn test_git_diff_base_is_local_to_each_collaborator(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree("/dir", json!({ ".git": {}, "a.txt": "one\ntwo\nthree\n" }))
+        .await;
+    let sha = git::Oid::from_str("a1b2c3d4e5f60718293a4b5c6d7e8f9012345678").unwrap();
+    client_a.fs().set_index_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("a.txt"), "one\nthree\n".into())],
+    );
+    client_a
+        .fs()
+        .set_revision_for_repo(Path::new("/dir/.git"), "main", sha);
+    client_a.fs().set_commit_diff_for_repo(
+        Path::new("/dir/.git"),
+        sha,
+        vec![CommitFileDiff {
+            path: Path::new("a.txt").into(),
+            old_text: None,
+            new_text: Some("one\n".into()),
+        }],
+    );
+
+    let (project_a, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.join_remote_project(project_id, cx_b).await;
+
+    let buffer_a = project_a
+        .update(cx_a, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    let change_set_a = project_a
+        .update(cx_a, |p, cx| p.open_unstaged_changes(buffer_a.clone(), cx))
+        .await
+        .unwrap();
+    let buffer_b = project_b
+        .update(cx_b, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    let change_set_b = project_b
+        .update(cx_b, |p, cx| p.open_unstaged_changes(buffer_b.clone(), cx))
+        .await
+        .unwrap();
+
+    // The guest diffs against another base, without changing the host's.
+    project_b
+        .update(cx_b, |p, cx| {
+            p.set_git_diff_base(DiffBase::MergeBase("main".into()), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    change_set_a.read_with(cx_a, |change_set, cx| {
+        assert_eq!(
+            change_set.base_text_string(cx).as_deref(),
+            Some("one\nthree\n")
+        );
+    });
+    change_set_b.read_with(cx_b, |change_set, cx| {
+        assert_eq!(change_set.base_text_string(cx).as_deref(), Some("one\n"));
+    });
+    project_a.read_with(cx_a, |p, cx| assert!(p.git_diff_base(cx).is_index()));
+
+    // Changes to the index reach the host, and leave the guest's base text alone.
+    client_a.fs().set_index_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("a.txt"), "one\ntwo\n".into())],
+    );
+    executor.run_until_parked();
+    change_set_a.read_with(cx_a, |change_set, cx| {
+        assert_eq!(
+            change_set.base_text_string(cx).as_deref(),
+            Some("one\ntwo\n")
+        );
+    });
+    change_set_b.read_with(cx_b, |change_set, cx| {
+        assert_eq!(change_set.base_text_string(cx).as_deref(), Some("one\n"));
+    });
+
+    // Going back to the index picks up its latest contents.
+    project_b
+        .update(cx_b, |p, cx| p.set_git_diff_base(DiffBase::Index, cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    change_set_b.read_with(cx_b, |change_set, cx| {
+        assert_eq!(
+            change_set.base_text_string(cx).as_deref(),
+            Some("one\ntwo\n")
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_git_branch_name(
     executor: BackgroundExecutor,
@@ -257,6 +257,9 @@ gpui::actions!(
         FindAllReferences,
         Fold,
         FoldAll,
+        FoldAllComments,
+        FoldAllImports,
+        FoldAllRegions,
         FoldFunctionBodies,
         FoldRecursive,
         FoldSelectedRanges,
@@ -264,6 +267,7 @@ gpui::actions!(
         ToggleFoldRecursive,
         Format,
         FormatSelections,
+        GoToConflict,
         GoToDeclaration,
         GoToDeclarationSplit,
         GoToDefinition,
@@ -272,6 +276,7 @@ gpui::actions!(
         GoToHunk,
         GoToImplementation,
         GoToImplementationSplit,
+        GoToPrevConflict,
         GoToPrevDiagnostic,
         GoToPrevHunk,
         GoToTypeDefinition,
@@ -362,6 +367,7 @@ gpui::actions!(
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
         SplitSelectionIntoLines,
+        StageSelectedHunks,
         SwitchSourceHeader,
         Tab,
         TabPrev,
@@ -385,6 +391,7 @@ gpui::actions!(
         UnfoldRecursive,
         UniqueLinesCaseInsensitive,
         UniqueLinesCaseSensitive,
+        UnstageSelectedHunks,
     ]
 );
 
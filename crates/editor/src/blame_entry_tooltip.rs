@@ -5,6 +5,7 @@ use gpui::{
     AppContext, Asset, ClipboardItem, Element, ParentElement, Render, ScrollHandle,
     StatefulInteractiveElement, WeakView,
 };
+use project::ProjectPath;
 use settings::Settings;
 use std::hash::Hash;
 use theme::ThemeSettings;
@@ -12,7 +13,10 @@ use time::UtcOffset;
 use ui::{prelude::*, tooltip_container, Avatar, Divider, IconButtonShape};
 use workspace::Workspace;
 
-use crate::git::blame::{CommitDetails, GitRemote};
+use crate::git::{
+    blame::{CommitDetails, GitRemote},
+    file_history::FileHistoryView,
+};
 use crate::EditorStyle;
 
 struct CommitAvatar<'a> {
@@ -90,6 +94,7 @@ impl Asset for CommitAvatarAsset {
 pub(crate) struct BlameEntryTooltip {
     blame_entry: BlameEntry,
     details: Option<CommitDetails>,
+    project_path: Option<ProjectPath>,
     editor_style: EditorStyle,
     workspace: Option<WeakView<Workspace>>,
     scroll_handle: ScrollHandle,
@@ -99,6 +104,7 @@ impl BlameEntryTooltip {
     pub(crate) fn new(
         blame_entry: BlameEntry,
         details: Option<CommitDetails>,
+        project_path: Option<ProjectPath>,
         style: &EditorStyle,
         workspace: Option<WeakView<Workspace>>,
     ) -> Self {
@@ -106,6 +112,7 @@ impl BlameEntryTooltip {
             editor_style: style.clone(),
             blame_entry,
             details,
+            project_path,
             workspace,
             scroll_handle: ScrollHandle::new(),
         }
@@ -148,6 +155,12 @@ impl Render for BlameEntryTooltip {
             .as_ref()
             .and_then(|details| details.pull_request.clone());
 
+        let file_history = self
+            .project_path
+            .clone()
+            .zip(self.workspace.clone())
+            .map(|(project_path, workspace)| (project_path, workspace, self.blame_entry.sha));
+
         let ui_font_size = ThemeSettings::get_global(cx).ui_font_size;
         let message_max_height = cx.line_height() * 12 + (ui_font_size / 0.4);
 
@@ -214,6 +227,33 @@ impl Render for BlameEntryTooltip {
                                                 }),
                                             )
                                         })
+                                        .when_some(
+                                            file_history,
+                                            |this, (project_path, workspace, sha)| {
+                                                this.child(
+                                                    Button::new("file-history-button", "History")
+                                                        .color(Color::Muted)
+                                                        .icon(IconName::HistoryRerun)
+                                                        .icon_color(Color::Muted)
+                                                        .icon_position(IconPosition::Start)
+                                                        .style(ButtonStyle::Subtle)
+                                                        .on_click(move |_, cx| {
+                                                            cx.stop_propagation();
+                                                            workspace
+                                                                .update(cx, |workspace, cx| {
+                                                                    FileHistoryView::deploy(
+                                                                        workspace,
+                                                                        project_path.clone(),
+                                                                        None,
+                                                                        Some(sha),
+                                                                        cx,
+                                                                    )
+                                                                })
+                                                                .ok();
+                                                        }),
+                                                )
+                                            },
+                                        )
                                         .child(Divider::vertical())
                                         .child(
                                             Button::new(
@@ -0,0 +1,367 @@
+use std::{sync::Arc, time::Duration};
+
+use collections::{BTreeMap, HashMap, HashSet};
+use gpui::{AnyElement, Model, WeakView};
+use language::{language_settings::language_settings, Buffer, BufferId, Point, ToPoint as _};
+use multi_buffer::{Anchor, ExcerptId, ToPoint as _};
+use project::{ClientCodeLensCommand, CodeLens, TaskSourceKind};
+use task::TaskContext;
+use ui::{prelude::*, ViewContext};
+use util::ResultExt;
+use workspace::notifications::NotifyTaskExt;
+
+use crate::{
+    display_map::RenderBlock, scroll::Autoscroll, BlockContext, BlockPlacement, BlockProperties,
+    BlockStyle, CustomBlockId, Editor, FindAllReferences,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+impl Editor {
+    /// Re-requests the code lenses for every buffer in the editor that has them enabled,
+    /// and shows them as blocks above the lines they apply to.
+    pub(crate) fn refresh_code_lens(&mut self, debounce: bool, cx: &mut ViewContext<Self>) {
+        let Some(provider) = self.semantics_provider.clone() else {
+            return;
+        };
+
+        let buffers = self
+            .buffer
+            .read(cx)
+            .all_buffers()
+            .into_iter()
+            .filter(|buffer| {
+                let buffer = buffer.read(cx);
+                language_settings(
+                    buffer.language().map(|language| language.name()),
+                    buffer.file(),
+                    cx,
+                )
+                .code_lens
+            })
+            .collect::<Vec<_>>();
+        let buffer_ids = buffers
+            .iter()
+            .map(|buffer| buffer.read(cx).remote_id())
+            .collect::<HashSet<_>>();
+        let stale_buffer_ids = self
+            .code_lens_blocks
+            .keys()
+            .filter(|buffer_id| !buffer_ids.contains(buffer_id))
+            .copied()
+            .collect::<Vec<_>>();
+        for buffer_id in stale_buffer_ids {
+            self.remove_code_lens_blocks(buffer_id, cx);
+        }
+        if buffers.is_empty() {
+            self.code_lens_task = None;
+            return;
+        }
+
+        self.code_lens_task = Some(cx.spawn(|editor, mut cx| async move {
+            if debounce {
+                cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+            }
+
+            let tasks = editor
+                .update(&mut cx, |_, cx| {
+                    buffers
+                        .iter()
+                        .filter_map(|buffer| {
+                            let task = provider.code_lens(buffer, cx)?;
+                            let buffer = buffer.clone();
+                            Some(async move { (buffer, task.await) })
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .ok()?;
+            let responses = futures::future::join_all(tasks).await;
+
+            editor
+                .update(&mut cx, |editor, cx| {
+                    for (buffer, code_lens) in responses {
+                        if let Some(code_lens) = code_lens.log_err() {
+                            editor.update_code_lens_blocks(buffer, code_lens, cx);
+                        }
+                    }
+                })
+                .ok()
+        }));
+    }
+
+    pub(crate) fn remove_code_lens_blocks(
+        &mut self,
+        buffer_id: BufferId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(blocks) = self.code_lens_blocks.remove(&buffer_id) {
+            self.remove_blocks(blocks.into_iter().map(|block| block.id).collect(), None, cx);
+        }
+    }
+
+    /// Shows the given code lenses of a buffer, reusing the blocks of the lenses that were
+    /// already shown so that refreshing them doesn't make them flicker.
+    fn update_code_lens_blocks(
+        &mut self,
+        buffer: Model<Buffer>,
+        code_lens: Vec<CodeLens>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let buffer_id = buffer.read(cx).remote_id();
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let mut code_lens_by_row = BTreeMap::<u32, Vec<CodeLens>>::default();
+        for lens in code_lens {
+            let row = lens.range.start.to_point(&buffer_snapshot).row;
+            code_lens_by_row.entry(row).or_default().push(lens);
+        }
+
+        let multi_buffer = self.buffer.read(cx);
+        let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+        let excerpts = multi_buffer.excerpts_for_buffer(&buffer, cx);
+        let mut old_blocks = self
+            .code_lens_blocks
+            .remove(&buffer_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|block| {
+                let position = block.anchor.to_point(&multi_buffer_snapshot);
+                ((block.anchor.excerpt_id, position), block)
+            })
+            .collect::<HashMap<_, _>>();
+
+        let editor = cx.view().downgrade();
+        let mut blocks = Vec::new();
+        let mut new_blocks = Vec::new();
+        let mut renderers = HashMap::default();
+        for (row, code_lens) in code_lens_by_row {
+            // Align the lenses with the code they apply to, rather than with the start of the line.
+            let indent = buffer_snapshot.indent_size_for_line(row).len;
+            let position = buffer_snapshot.anchor_before(Point::new(row, indent));
+            let code_lens = Arc::<[CodeLens]>::from(code_lens);
+            for (excerpt_id, excerpt_range) in &excerpts {
+                if excerpt_range
+                    .context
+                    .start
+                    .cmp(&position, &buffer_snapshot)
+                    .is_gt()
+                    || excerpt_range
+                        .context
+                        .end
+                        .cmp(&position, &buffer_snapshot)
+                        .is_lt()
+                {
+                    continue;
+                }
+                let Some(anchor) = multi_buffer_snapshot.anchor_in_excerpt(*excerpt_id, position)
+                else {
+                    continue;
+                };
+
+                let key = (*excerpt_id, anchor.to_point(&multi_buffer_snapshot));
+                let render = code_lens_renderer(&editor, &buffer, *excerpt_id, &code_lens);
+                if let Some(mut block) = old_blocks.remove(&key) {
+                    if !same_commands(&block.code_lens, &code_lens) {
+                        renderers.insert(block.id, render);
+                        block.code_lens = code_lens.clone();
+                    }
+                    blocks.push(block);
+                } else {
+                    new_blocks.push((anchor, code_lens.clone(), render));
+                }
+            }
+        }
+
+        let new_block_ids = self.insert_blocks(
+            new_blocks
+                .iter()
+                .map(|(anchor, _, render)| BlockProperties {
+                    placement: BlockPlacement::Above(*anchor),
+                    height: 1,
+                    style: BlockStyle::Flex,
+                    priority: 0,
+                    render: render.clone(),
+                }),
+            None,
+            cx,
+        );
+        blocks.extend(new_block_ids.into_iter().zip(new_blocks).map(
+            |(id, (anchor, code_lens, _))| CodeLensBlock {
+                id,
+                anchor,
+                code_lens,
+            },
+        ));
+
+        if !old_blocks.is_empty() {
+            self.remove_blocks(
+                old_blocks.into_values().map(|block| block.id).collect(),
+                None,
+                cx,
+            );
+        }
+        if !renderers.is_empty() {
+            self.replace_blocks(renderers, None, cx);
+        }
+        if !blocks.is_empty() {
+            self.code_lens_blocks.insert(buffer_id, blocks);
+        }
+    }
+
+    fn apply_code_lens(
+        &mut self,
+        buffer: Model<Buffer>,
+        excerpt_id: ExcerptId,
+        lens: CodeLens,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+
+        match lens.client_command() {
+            Some(ClientCodeLensCommand::Run(task)) => {
+                let task_source_kind = match buffer.read(cx).language() {
+                    Some(language) => TaskSourceKind::Language {
+                        name: language.name().0,
+                    },
+                    None => TaskSourceKind::UserInput,
+                };
+                workspace.update(cx, |workspace, cx| {
+                    workspace::tasks::schedule_task(
+                        workspace,
+                        task_source_kind,
+                        &task,
+                        &TaskContext::default(),
+                        false,
+                        cx,
+                    );
+                });
+            }
+            Some(ClientCodeLensCommand::ShowReferences) => {
+                let Some(position) = self
+                    .buffer
+                    .read(cx)
+                    .snapshot(cx)
+                    .anchor_in_excerpt(excerpt_id, lens.range.start)
+                else {
+                    return;
+                };
+                self.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+                    selections.select_anchor_ranges([position..position])
+                });
+                if let Some(task) = self.find_all_references(&FindAllReferences, cx) {
+                    task.detach_and_log_err(cx);
+                }
+            }
+            None => {
+                let title = lens
+                    .lsp_lens
+                    .command
+                    .as_ref()
+                    .map(|command| command.title.clone())
+                    .unwrap_or_default();
+                let apply_code_lens =
+                    project.update(cx, |project, cx| project.apply_code_lens(buffer, lens, cx));
+                let workspace = workspace.downgrade();
+                cx.spawn(|editor, cx| async move {
+                    let project_transaction = apply_code_lens.await?;
+                    Self::open_project_transaction(
+                        &editor,
+                        workspace,
+                        project_transaction,
+                        title,
+                        cx,
+                    )
+                    .await
+                })
+                .detach_and_notify_err(cx);
+            }
+        }
+    }
+}
+
+/// The block showing the code lenses of a line.
+pub(crate) struct CodeLensBlock {
+    id: CustomBlockId,
+    anchor: Anchor,
+    code_lens: Arc<[CodeLens]>,
+}
+
+fn same_commands(a: &[CodeLens], b: &[CodeLens]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(a, b)| a.server_id == b.server_id && a.lsp_lens.command == b.lsp_lens.command)
+}
+
+fn code_lens_renderer(
+    editor: &WeakView<Editor>,
+    buffer: &Model<Buffer>,
+    excerpt_id: ExcerptId,
+    code_lens: &Arc<[CodeLens]>,
+) -> RenderBlock {
+    let editor = editor.clone();
+    let buffer = buffer.clone();
+    let code_lens = code_lens.clone();
+    Arc::new(move |cx| render_code_lens(&editor, &buffer, excerpt_id, &code_lens, cx))
+}
+
+fn render_code_lens(
+    editor: &WeakView<Editor>,
+    buffer: &Model<Buffer>,
+    excerpt_id: ExcerptId,
+    code_lens: &[CodeLens],
+    cx: &mut BlockContext,
+) -> AnyElement {
+    let muted_color = cx.theme().colors().text_muted;
+    let text_color = cx.theme().colors().text;
+    let mut children = Vec::with_capacity(code_lens.len() * 2);
+    for (ix, lens) in code_lens.iter().enumerate() {
+        let Some(command) = lens.lsp_lens.command.as_ref() else {
+            continue;
+        };
+        if !children.is_empty() {
+            children.push(div().child("|").into_any_element());
+        }
+        // Lenses without a command are merely labels.
+        if command.command.is_empty() {
+            children.push(div().child(command.title.clone()).into_any_element());
+            continue;
+        }
+        children.push(
+            div()
+                .id(ix)
+                .cursor_pointer()
+                .hover(move |style| style.text_color(text_color))
+                .child(command.title.clone())
+                .on_click({
+                    let editor = editor.clone();
+                    let buffer = buffer.clone();
+                    let lens = lens.clone();
+                    move |_, cx| {
+                        editor
+                            .update(cx, |editor, cx| {
+                                editor.apply_code_lens(buffer.clone(), excerpt_id, lens.clone(), cx)
+                            })
+                            .ok();
+                    }
+                })
+                .into_any_element(),
+        );
+    }
+
+    h_flex()
+        .id(cx.block_id)
+        .block_mouse_down()
+        .h(cx.line_height)
+        .pl(cx.anchor_x)
+        .gap_1()
+        .font_buffer(cx)
+        .text_ui_sm(cx)
+        .text_color(muted_color)
+        .children(children)
+        .into_any_element()
+}
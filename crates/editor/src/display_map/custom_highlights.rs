@@ -21,6 +21,8 @@ pub struct CustomHighlightsChunks<'a> {
     highlight_endpoints: Peekable<vec::IntoIter<HighlightEndpoint>>,
     active_highlights: BTreeMap<TypeId, HighlightStyle>,
     text_highlights: Option<&'a TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>>,
+    semantic_token_endpoints: Peekable<vec::IntoIter<(Range<usize>, HighlightStyle)>>,
+    semantic_token_highlights: Option<&'a [(Range<Anchor>, HighlightStyle)]>,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -36,8 +38,10 @@ impl<'a> CustomHighlightsChunks<'a> {
         range: Range<usize>,
         language_aware: bool,
         text_highlights: Option<&'a TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>>,
+        semantic_token_highlights: Option<&'a [(Range<Anchor>, HighlightStyle)]>,
         multibuffer_snapshot: &'a MultiBufferSnapshot,
     ) -> Self {
+        let semantic_token_highlights = semantic_token_highlights.filter(|_| language_aware);
         Self {
             buffer_chunks: multibuffer_snapshot.chunks(range.clone(), language_aware),
             buffer_chunk: None,
@@ -49,6 +53,12 @@ impl<'a> CustomHighlightsChunks<'a> {
                 text_highlights,
                 multibuffer_snapshot,
             ),
+            semantic_token_highlights,
+            semantic_token_endpoints: create_semantic_token_endpoints(
+                &range,
+                semantic_token_highlights,
+                multibuffer_snapshot,
+            ),
             active_highlights: Default::default(),
             multibuffer_snapshot,
         }
@@ -57,6 +67,11 @@ impl<'a> CustomHighlightsChunks<'a> {
     pub fn seek(&mut self, new_range: Range<usize>) {
         self.highlight_endpoints =
             create_highlight_endpoints(&new_range, self.text_highlights, self.multibuffer_snapshot);
+        self.semantic_token_endpoints = create_semantic_token_endpoints(
+            &new_range,
+            self.semantic_token_highlights,
+            self.multibuffer_snapshot,
+        );
         self.offset = new_range.start;
         self.buffer_chunks.seek(new_range);
         self.buffer_chunk.take();
@@ -112,6 +127,41 @@ fn create_highlight_endpoints(
     highlight_endpoints.into_iter().peekable()
 }
 
+/// Semantic tokens are sorted and never overlap, so unlike the text highlights
+/// they can be tracked as plain ranges instead of start and end endpoints.
+fn create_semantic_token_endpoints(
+    range: &Range<usize>,
+    semantic_token_highlights: Option<&[(Range<Anchor>, HighlightStyle)]>,
+    buffer: &MultiBufferSnapshot,
+) -> iter::Peekable<vec::IntoIter<(Range<usize>, HighlightStyle)>> {
+    let mut endpoints = Vec::new();
+    if let Some(semantic_token_highlights) = semantic_token_highlights {
+        let start = buffer.anchor_after(range.start);
+        let end = buffer.anchor_after(range.end);
+        let start_ix = match semantic_token_highlights.binary_search_by(|(probe, _)| {
+            if probe.end.cmp(&start, buffer).is_gt() {
+                cmp::Ordering::Greater
+            } else {
+                cmp::Ordering::Less
+            }
+        }) {
+            Ok(i) | Err(i) => i,
+        };
+
+        for (token_range, style) in &semantic_token_highlights[start_ix..] {
+            if token_range.start.cmp(&end, buffer).is_ge() {
+                break;
+            }
+            let token_range =
+                token_range.start.to_offset(buffer)..token_range.end.to_offset(buffer);
+            if !token_range.is_empty() {
+                endpoints.push((token_range, *style));
+            }
+        }
+    }
+    endpoints.into_iter().peekable()
+}
+
 impl<'a> Iterator for CustomHighlightsChunks<'a> {
     type Item = Chunk<'a>;
 
@@ -131,6 +181,21 @@ impl<'a> Iterator for CustomHighlightsChunks<'a> {
             }
         }
 
+        let mut semantic_token_style = None;
+        while let Some((token_range, style)) = self.semantic_token_endpoints.peek() {
+            if token_range.end <= self.offset {
+                self.semantic_token_endpoints.next();
+            } else {
+                if token_range.start <= self.offset {
+                    semantic_token_style = Some(*style);
+                    next_highlight_endpoint = next_highlight_endpoint.min(token_range.end);
+                } else {
+                    next_highlight_endpoint = next_highlight_endpoint.min(token_range.start);
+                }
+                break;
+            }
+        }
+
         let chunk = self
             .buffer_chunk
             .get_or_insert_with(|| self.buffer_chunks.next().unwrap());
@@ -148,8 +213,8 @@ impl<'a> Iterator for CustomHighlightsChunks<'a> {
             text: prefix,
             ..chunk.clone()
         };
-        if !self.active_highlights.is_empty() {
-            let mut highlight_style = HighlightStyle::default();
+        if semantic_token_style.is_some() || !self.active_highlights.is_empty() {
+            let mut highlight_style = semantic_token_style.unwrap_or_default();
             for active_highlight in self.active_highlights.values() {
                 highlight_style.highlight(*active_highlight);
             }
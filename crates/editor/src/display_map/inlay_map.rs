@@ -995,6 +995,9 @@ impl InlaySnapshot {
             buffer_range,
             language_aware,
             highlights.text_highlights,
+            highlights
+                .semantic_token_highlights
+                .map(|highlights| highlights.as_slice()),
             &self.buffer,
         );
 
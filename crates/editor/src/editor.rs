@@ -17,10 +17,12 @@ mod blame_entry_tooltip;
 mod blink_manager;
 mod clangd_ext;
 mod code_context_menus;
+mod code_lens;
 pub mod display_map;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
+mod folding_ranges;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
@@ -31,6 +33,7 @@ mod inlay_hint_cache;
 pub mod items;
 mod linked_editing_ranges;
 mod lsp_ext;
+mod merge_conflicts;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
@@ -38,6 +41,7 @@ mod proposed_changes_editor;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod semantic_tokens;
 pub mod tasks;
 
 #[cfg(test)]
@@ -104,6 +108,7 @@ use language::{
 };
 use language::{point_to_lsp, BufferRow, CharClassifier, Runnable, RunnableRange};
 use linked_editing_ranges::refresh_linked_ranges;
+use merge_conflicts::MergeConflictCodeActionProvider;
 use mouse_context_menu::MouseContextMenu;
 pub use proposed_changes_editor::{
     ProposedChangeLocation, ProposedChangesEditor, ProposedChangesEditorToolbar,
@@ -331,6 +336,7 @@ pub fn init(cx: &mut AppContext) {
         }
     });
     git::project_diff::init(cx);
+    git::file_history::init(cx);
 }
 
 pub struct SearchWithinRange;
@@ -632,7 +638,17 @@ pub struct Editor {
     code_actions_task: Option<Task<Result<()>>>,
     document_highlights_task: Option<Task<()>>,
     linked_editing_range_task: Option<Task<Option<()>>>,
+    selection_ranges_task: Option<Task<()>>,
     linked_edit_ranges: linked_editing_ranges::LinkedEditingRanges,
+    semantic_tokens: HashMap<BufferId, Vec<project::SemanticToken>>,
+    semantic_tokens_task: Option<Task<Option<()>>>,
+    /// The buffers whose semantic tokens have been invalidated and not yet fetched again.
+    stale_semantic_tokens: HashSet<BufferId>,
+    code_lens_blocks: HashMap<BufferId, Vec<code_lens::CodeLensBlock>>,
+    code_lens_task: Option<Task<Option<()>>>,
+    folding_ranges: HashMap<BufferId, folding_ranges::BufferFoldingRanges>,
+    folding_ranges_task: Option<Task<Option<()>>>,
+    merge_conflicts: merge_conflicts::MergeConflicts,
     pending_rename: Option<RenameState>,
     searchable: bool,
     cursor_shape: CursorShape,
@@ -1155,6 +1171,14 @@ impl Editor {
                 project_subscriptions.push(cx.subscribe(project, |editor, _, event, cx| {
                     if let project::Event::RefreshInlayHints = event {
                         editor.refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                    } else if let project::Event::RefreshSemanticTokens = event {
+                        editor.refresh_semantic_tokens(None, false, cx);
+                    } else if let project::Event::RefreshCodeLens = event {
+                        editor.refresh_code_lens(false, cx);
+                    } else if let project::Event::LanguageServerAdded(..) = event {
+                        editor.refresh_semantic_tokens(None, false, cx);
+                        editor.refresh_code_lens(false, cx);
+                        editor.refresh_folding_ranges(false, cx);
                     } else if let project::Event::SnippetEdit(id, snippet_edits) = event {
                         if let Some(buffer) = editor.buffer.read(cx).buffer(*id) {
                             let focus_handle = editor.focus_handle(cx);
@@ -1208,6 +1232,11 @@ impl Editor {
             get_unstaged_changes_for_buffers(&project, buffer.read(cx).all_buffers(), cx);
             code_action_providers.push(Rc::new(project) as Rc<_>);
         }
+        if mode == EditorMode::Full {
+            code_action_providers.push(Rc::new(MergeConflictCodeActionProvider {
+                editor: cx.view().downgrade(),
+            }) as Rc<_>);
+        }
 
         let mut this = Self {
             focus_handle,
@@ -1266,6 +1295,7 @@ impl Editor {
             code_actions_task: Default::default(),
             document_highlights_task: Default::default(),
             linked_editing_range_task: Default::default(),
+            selection_ranges_task: None,
             pending_rename: Default::default(),
             searchable: true,
             cursor_shape: EditorSettings::get_global(cx)
@@ -1333,6 +1363,14 @@ impl Editor {
             ],
             tasks_update_task: None,
             linked_edit_ranges: Default::default(),
+            semantic_tokens: Default::default(),
+            semantic_tokens_task: None,
+            stale_semantic_tokens: Default::default(),
+            code_lens_blocks: Default::default(),
+            code_lens_task: None,
+            folding_ranges: Default::default(),
+            folding_ranges_task: None,
+            merge_conflicts: Default::default(),
             previous_search_ranges: None,
             breadcrumb_header: None,
             focused_block: None,
@@ -1358,6 +1396,10 @@ impl Editor {
                 this.start_git_blame_inline(false, cx);
             }
 
+            for buffer in buffer.read(cx).all_buffers() {
+                this.refresh_merge_conflicts(&buffer, false, cx);
+            }
+
             if let Some(buffer) = buffer.read(cx).as_singleton() {
                 if let Some(project) = this.project.as_ref() {
                     let lsp_store = project.read(cx).lsp_store();
@@ -7864,6 +7906,15 @@ impl Editor {
         }
     }
 
+    /// Returns the cursor position stored in a navigation history entry of an editor.
+    /// Besides the entries pushed by editors, a plain [`Point`] is accepted as entry data,
+    /// e.g. for positions restored from a previous session.
+    pub fn navigation_entry_position(data: &(dyn std::any::Any + Send)) -> Option<Point> {
+        data.downcast_ref::<NavigationData>()
+            .map(|data| data.cursor_position)
+            .or_else(|| data.downcast_ref::<Point>().copied())
+    }
+
     pub fn select_to_end(&mut self, _: &SelectToEnd, cx: &mut ViewContext<Self>) {
         let buffer = self.buffer.read(cx).snapshot(cx);
         let mut selection = self.selections.first::<usize>(cx);
@@ -8776,25 +8827,163 @@ impl Editor {
         &mut self,
         _: &SelectLargerSyntaxNode,
         cx: &mut ViewContext<Self>,
+    ) {
+        let old_selections = self.selections.all::<usize>(cx).into_boxed_slice();
+        match self.lsp_selection_ranges(&old_selections, cx) {
+            Some(selection_ranges) => {
+                self.selection_ranges_task = Some(cx.spawn(|editor, mut cx| async move {
+                    let selection_ranges = selection_ranges.await;
+                    editor
+                        .update(&mut cx, |editor, cx| {
+                            // Ignore the response if the selections changed while waiting for it.
+                            if *editor.selections.all::<usize>(cx) == *old_selections {
+                                editor.select_larger_ranges(old_selections, selection_ranges, cx);
+                            }
+                        })
+                        .ok();
+                }));
+            }
+            None => self.select_larger_ranges(old_selections, Vec::new(), cx),
+        }
+    }
+
+    /// Requests the language servers' selection ranges for every selection in a buffer
+    /// that prefers them over the syntax tree, returning the ranges of each selection in order.
+    ///
+    /// Returns `None` if none of the selections should use the language servers.
+    fn lsp_selection_ranges(
+        &self,
+        selections: &[Selection<usize>],
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Vec<Option<Vec<Range<Anchor>>>>>> {
+        let provider = self.semantics_provider.clone()?;
+        let multi_buffer = self.buffer.read(cx);
+        let snapshot = multi_buffer.snapshot(cx);
+
+        let mut positions_by_buffer = HashMap::<BufferId, (Model<Buffer>, Vec<_>)>::default();
+        for (ix, selection) in selections.iter().enumerate() {
+            let anchor = snapshot.anchor_after(selection.start);
+            let Some(buffer) = anchor
+                .buffer_id
+                .and_then(|buffer_id| multi_buffer.buffer(buffer_id))
+            else {
+                continue;
+            };
+            let settings = language_settings(
+                buffer.read(cx).language().map(|language| language.name()),
+                buffer.read(cx).file(),
+                cx,
+            );
+            if !settings.lsp_selection_ranges {
+                continue;
+            }
+            positions_by_buffer
+                .entry(buffer.read(cx).remote_id())
+                .or_insert_with(|| (buffer.clone(), Vec::new()))
+                .1
+                .push((ix, anchor));
+        }
+        if positions_by_buffer.is_empty() {
+            return None;
+        }
+
+        let tasks = positions_by_buffer
+            .into_values()
+            .filter_map(|(buffer, positions)| {
+                let task = provider.selection_ranges(
+                    &buffer,
+                    positions
+                        .iter()
+                        .map(|(_, anchor)| anchor.text_anchor)
+                        .collect(),
+                    cx,
+                )?;
+                Some(async move { (positions, task.await) })
+            })
+            .collect::<Vec<_>>();
+        let selection_count = selections.len();
+        Some(cx.background_executor().spawn(async move {
+            let mut selection_ranges = vec![None; selection_count];
+            for (positions, response) in future::join_all(tasks).await {
+                let Some(response) = response.log_err() else {
+                    continue;
+                };
+                for ((ix, anchor), ranges) in positions.into_iter().zip(response) {
+                    selection_ranges[ix] = Some(
+                        ranges
+                            .into_iter()
+                            .map(|range| {
+                                Anchor {
+                                    buffer_id: anchor.buffer_id,
+                                    excerpt_id: anchor.excerpt_id,
+                                    text_anchor: range.start,
+                                }..Anchor {
+                                    buffer_id: anchor.buffer_id,
+                                    excerpt_id: anchor.excerpt_id,
+                                    text_anchor: range.end,
+                                }
+                            })
+                            .collect(),
+                    );
+                }
+            }
+            selection_ranges
+        }))
+    }
+
+    /// Grows each selection to the smallest enclosing range, using the given language server
+    /// selection ranges when they contain one, and the syntax tree otherwise.
+    fn select_larger_ranges(
+        &mut self,
+        old_selections: Box<[Selection<usize>]>,
+        lsp_selection_ranges: Vec<Option<Vec<Range<Anchor>>>>,
+        cx: &mut ViewContext<Self>,
     ) {
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let buffer = self.buffer.read(cx).snapshot(cx);
-        let old_selections = self.selections.all::<usize>(cx).into_boxed_slice();
 
         let mut stack = mem::take(&mut self.select_larger_syntax_node_stack);
         let mut selected_larger_node = false;
         let new_selections = old_selections
             .iter()
-            .map(|selection| {
+            .enumerate()
+            .map(|(ix, selection)| {
                 let old_range = selection.start..selection.end;
+                let is_visible = |range: &Range<usize>| {
+                    !display_map.intersects_fold(range.start)
+                        && !display_map.intersects_fold(range.end)
+                };
+                let lsp_range = lsp_selection_ranges
+                    .get(ix)
+                    .and_then(|ranges| ranges.as_ref())
+                    .and_then(|ranges| {
+                        ranges
+                            .iter()
+                            .map(|range| range.to_offset(&buffer))
+                            .find(|range| {
+                                range.start <= old_range.start
+                                    && range.end >= old_range.end
+                                    && *range != old_range
+                                    && is_visible(range)
+                            })
+                    });
+                if let Some(new_range) = lsp_range {
+                    selected_larger_node = true;
+                    return Selection {
+                        id: selection.id,
+                        start: new_range.start,
+                        end: new_range.end,
+                        goal: SelectionGoal::None,
+                        reversed: selection.reversed,
+                    };
+                }
+
                 let mut new_range = old_range.clone();
                 while let Some(containing_range) =
                     buffer.range_for_syntax_ancestor(new_range.clone())
                 {
                     new_range = containing_range;
-                    if !display_map.intersects_fold(new_range.start)
-                        && !display_map.intersects_fold(new_range.end)
-                    {
+                    if is_visible(&new_range) {
                         break;
                     }
                 }
@@ -12203,6 +12392,7 @@ impl Editor {
                     self.update_visible_inline_completion(cx);
                 }
                 if let Some(buffer) = buffer_edited {
+                    self.refresh_merge_conflicts(buffer, true, cx);
                     let buffer_id = buffer.read(cx).remote_id();
                     if !self.registered_buffers.contains_key(&buffer_id) {
                         if let Some(lsp_store) = self.lsp_store(cx) {
@@ -12258,6 +12448,15 @@ impl Editor {
                     (telemetry, is_via_ssh)
                 };
                 refresh_linked_ranges(self, cx);
+                self.refresh_semantic_tokens(
+                    buffer_edited
+                        .as_ref()
+                        .map(|buffer| buffer.read(cx).remote_id()),
+                    true,
+                    cx,
+                );
+                self.refresh_code_lens(true, cx);
+                self.refresh_folding_ranges(true, cx);
                 telemetry.log_edit_event("editor", is_via_ssh);
             }
             multi_buffer::Event::ExcerptsAdded {
@@ -12278,9 +12477,33 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                self.refresh_semantic_tokens(None, false, cx);
+                self.refresh_code_lens(false, cx);
+                self.refresh_folding_ranges(false, cx);
+                self.refresh_merge_conflicts(buffer, false, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
+                self.clear_semantic_tokens_for_removed_buffers(cx);
+                let removed_buffer_ids = self
+                    .code_lens_blocks
+                    .keys()
+                    .filter(|buffer_id| self.buffer.read(cx).buffer(**buffer_id).is_none())
+                    .copied()
+                    .collect::<Vec<_>>();
+                for buffer_id in removed_buffer_ids {
+                    self.remove_code_lens_blocks(buffer_id, cx);
+                }
+                let removed_buffer_ids = self
+                    .folding_ranges
+                    .keys()
+                    .filter(|buffer_id| self.buffer.read(cx).buffer(**buffer_id).is_none())
+                    .copied()
+                    .collect::<Vec<_>>();
+                for buffer_id in removed_buffer_ids {
+                    self.remove_folding_ranges(buffer_id, cx);
+                }
+                self.remove_stale_merge_conflicts(cx);
                 let buffer = self.buffer.read(cx);
                 self.registered_buffers
                     .retain(|buffer_id, _| buffer.buffer(*buffer_id).is_some());
@@ -12299,6 +12522,9 @@ impl Editor {
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, cx);
+                self.refresh_semantic_tokens(None, false, cx);
+                self.refresh_code_lens(false, cx);
+                self.refresh_folding_ranges(false, cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
@@ -12329,6 +12555,9 @@ impl Editor {
     fn settings_changed(&mut self, cx: &mut ViewContext<Self>) {
         self.tasks_update_task = Some(self.refresh_runnables(cx));
         self.refresh_inline_completion(true, false, cx);
+        self.refresh_semantic_tokens(None, false, cx);
+        self.refresh_code_lens(false, cx);
+        self.refresh_folding_ranges(false, cx);
         self.refresh_inlay_hints(
             InlayHintRefreshReason::SettingsChange(inlay_hint_settings(
                 self.selections.newest_anchor().head(),
@@ -13383,6 +13612,44 @@ pub trait SemanticsProvider {
 
     fn supports_inlay_hints(&self, buffer: &Model<Buffer>, cx: &AppContext) -> bool;
 
+    fn semantic_tokens(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<project::SemanticToken>>>>;
+
+    fn code_lens(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<project::CodeLens>>>>;
+
+    fn folding_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<project::FoldingRange>>>>;
+
+    fn selection_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        positions: Vec<text::Anchor>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<Vec<Range<text::Anchor>>>>>>;
+
+    fn document_links(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<project::DocumentLink>>>>;
+
+    fn resolve_document_link(
+        &self,
+        link: project::DocumentLink,
+        buffer: Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<project::DocumentLink>>>;
+
     fn document_highlights(
         &self,
         buffer: &Model<Buffer>,
@@ -13781,6 +14048,62 @@ impl SemanticsProvider for Model<Project> {
         }))
     }
 
+    fn semantic_tokens(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<project::SemanticToken>>>> {
+        Some(self.update(cx, |project, cx| {
+            project.semantic_tokens(buffer.clone(), cx)
+        }))
+    }
+
+    fn code_lens(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<project::CodeLens>>>> {
+        Some(self.update(cx, |project, cx| project.code_lens(buffer, cx)))
+    }
+
+    fn folding_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<project::FoldingRange>>>> {
+        Some(self.update(cx, |project, cx| project.folding_ranges(buffer, cx)))
+    }
+
+    fn selection_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        positions: Vec<text::Anchor>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<Vec<Range<text::Anchor>>>>>> {
+        Some(self.update(cx, |project, cx| {
+            project.selection_ranges(buffer, positions, cx)
+        }))
+    }
+
+    fn document_links(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<project::DocumentLink>>>> {
+        Some(self.update(cx, |project, cx| project.document_links(buffer, cx)))
+    }
+
+    fn resolve_document_link(
+        &self,
+        link: project::DocumentLink,
+        buffer: Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<project::DocumentLink>>> {
+        Some(self.update(cx, |project, cx| {
+            project.resolve_document_link(link, buffer, cx)
+        }))
+    }
+
     fn range_for_rename(
         &self,
         buffer: &Model<Buffer>,
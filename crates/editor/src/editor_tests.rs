@@ -10076,6 +10076,114 @@ async fn test_diagnostics_with_links(cx: &mut TestAppContext) {
     cx.update_editor(|editor, _| assert!(editor.hover_state.diagnostic_popover.is_some()))
 }
 
+#[gpui::test]
+async fn test_merge_conflicts(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(indoc! {"
+        ˇfn main() {
+        <<<<<<< HEAD
+            one();
+        =======
+            two();
+        >>>>>>> feature
+        }
+        <<<<<<< HEAD
+        a
+        ||||||| base
+        b
+        =======
+        c
+        >>>>>>> feature
+    "});
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.run_until_parked();
+
+    cx.update_editor(|editor, cx| editor.go_to_conflict(&GoToConflict, cx));
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+        ˇ<<<<<<< HEAD
+            one();
+        =======
+            two();
+        >>>>>>> feature
+        }
+        <<<<<<< HEAD
+        a
+        ||||||| base
+        b
+        =======
+        c
+        >>>>>>> feature
+    "});
+    cx.update_editor(|editor, cx| editor.go_to_conflict(&GoToConflict, cx));
+    cx.update_editor(|editor, cx| editor.go_to_conflict(&GoToConflict, cx));
+    cx.update_editor(|editor, cx| editor.go_to_prev_conflict(&GoToPrevConflict, cx));
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+        <<<<<<< HEAD
+            one();
+        =======
+            two();
+        >>>>>>> feature
+        }
+        ˇ<<<<<<< HEAD
+        a
+        ||||||| base
+        b
+        =======
+        c
+        >>>>>>> feature
+    "});
+
+    let (provider, buffer) = cx.update_editor(|editor, cx| {
+        (
+            MergeConflictCodeActionProvider {
+                editor: cx.view().downgrade(),
+            },
+            editor.buffer().read(cx).as_singleton().unwrap(),
+        )
+    });
+    let actions = cx
+        .update(|cx| {
+            let anchor = buffer.read(cx).anchor_before(Point::new(8, 0));
+            provider.code_actions(&buffer, anchor..anchor, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        actions
+            .iter()
+            .map(|action| action.lsp_action.title.as_str())
+            .collect::<Vec<_>>(),
+        ["Accept Ours", "Accept Theirs", "Accept Both", "Accept Base"]
+    );
+    cx.update(|cx| {
+        provider.apply_code_action(
+            buffer.clone(),
+            actions[2].clone(),
+            ExcerptId::min(),
+            true,
+            cx,
+        )
+    })
+    .await
+    .unwrap();
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {"
+        fn main() {
+        <<<<<<< HEAD
+            one();
+        =======
+            two();
+        >>>>>>> feature
+        }
+        ˇa
+        c
+    "});
+}
+
 #[gpui::test]
 async fn go_to_hunk(executor: BackgroundExecutor, cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -311,10 +311,12 @@ impl EditorElement {
             register_action(view, cx, Editor::expand_excerpts_up);
             register_action(view, cx, Editor::expand_excerpts_down);
         }
+        register_action(view, cx, Editor::go_to_conflict);
         register_action(view, cx, Editor::go_to_diagnostic);
         register_action(view, cx, Editor::go_to_prev_diagnostic);
         register_action(view, cx, Editor::go_to_next_hunk);
         register_action(view, cx, Editor::go_to_prev_hunk);
+        register_action(view, cx, Editor::go_to_prev_conflict);
         register_action(view, cx, |editor, a, cx| {
             editor.go_to_definition(a, cx).detach_and_log_err(cx);
         });
@@ -348,6 +350,9 @@ impl EditorElement {
         register_action(view, cx, Editor::fold);
         register_action(view, cx, Editor::fold_at_level);
         register_action(view, cx, Editor::fold_all);
+        register_action(view, cx, Editor::fold_all_comments);
+        register_action(view, cx, Editor::fold_all_imports);
+        register_action(view, cx, Editor::fold_all_regions);
         register_action(view, cx, Editor::fold_function_bodies);
         register_action(view, cx, Editor::fold_at);
         register_action(view, cx, Editor::fold_recursive);
@@ -456,6 +461,10 @@ impl EditorElement {
         register_action(view, cx, Editor::accept_inline_completion);
         register_action(view, cx, Editor::revert_file);
         register_action(view, cx, Editor::revert_selected_hunks);
+        if view.read(cx).can_stage_hunks(cx) {
+            register_action(view, cx, Editor::stage_selected_hunks);
+            register_action(view, cx, Editor::unstage_selected_hunks);
+        }
         register_action(view, cx, Editor::apply_all_diff_hunks);
         register_action(view, cx, Editor::apply_selected_diff_hunks);
         register_action(view, cx, Editor::open_active_item_in_terminal);
@@ -5012,8 +5021,10 @@ fn render_inline_blame_entry(
     };
 
     let details = blame.read(cx).details_for_entry(&blame_entry);
+    let project_path = blame.read(cx).project_path(cx);
 
-    let tooltip = cx.new_view(|_| BlameEntryTooltip::new(blame_entry, details, style, workspace));
+    let tooltip = cx
+        .new_view(|_| BlameEntryTooltip::new(blame_entry, details, project_path, style, workspace));
 
     h_flex()
         .id("inline-blame")
@@ -5061,10 +5072,18 @@ fn render_blame_entry(
 
     let details = blame.read(cx).details_for_entry(&blame_entry);
 
+    let project_path = blame.read(cx).project_path(cx);
+
     let workspace = editor.read(cx).workspace.as_ref().map(|(w, _)| w.clone());
 
     let tooltip = cx.new_view(|_| {
-        BlameEntryTooltip::new(blame_entry.clone(), details.clone(), style, workspace)
+        BlameEntryTooltip::new(
+            blame_entry.clone(),
+            details.clone(),
+            project_path,
+            style,
+            workspace,
+        )
     });
 
     h_flex()
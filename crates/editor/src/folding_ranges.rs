@@ -0,0 +1,191 @@
+use std::{ops::Range, time::Duration};
+
+use collections::HashSet;
+use gpui::Model;
+use language::{language_settings::language_settings, Buffer, BufferId};
+use lsp::FoldingRangeKind;
+use multi_buffer::{Anchor, MultiBufferSnapshot};
+use project::FoldingRange;
+use ui::ViewContext;
+use util::ResultExt;
+
+use crate::{
+    actions::{FoldAllComments, FoldAllImports, FoldAllRegions},
+    Crease, CreaseId, Editor,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The folding ranges a language server reported for a buffer, along with the creases
+/// that make them foldable in this editor.
+pub(crate) struct BufferFoldingRanges {
+    ranges: Vec<FoldingRange>,
+    crease_ids: Vec<CreaseId>,
+}
+
+impl Editor {
+    /// Re-requests the folding ranges for every buffer in the editor that has them enabled,
+    /// and replaces the buffer's previous ranges with creases for the new ones.
+    pub(crate) fn refresh_folding_ranges(&mut self, debounce: bool, cx: &mut ViewContext<Self>) {
+        let Some(provider) = self.semantics_provider.clone() else {
+            return;
+        };
+
+        let buffers = self
+            .buffer
+            .read(cx)
+            .all_buffers()
+            .into_iter()
+            .filter(|buffer| {
+                let buffer = buffer.read(cx);
+                language_settings(
+                    buffer.language().map(|language| language.name()),
+                    buffer.file(),
+                    cx,
+                )
+                .lsp_folding_ranges
+            })
+            .collect::<Vec<_>>();
+        let buffer_ids = buffers
+            .iter()
+            .map(|buffer| buffer.read(cx).remote_id())
+            .collect::<HashSet<_>>();
+        let stale_buffer_ids = self
+            .folding_ranges
+            .keys()
+            .filter(|buffer_id| !buffer_ids.contains(buffer_id))
+            .copied()
+            .collect::<Vec<_>>();
+        for buffer_id in stale_buffer_ids {
+            self.remove_folding_ranges(buffer_id, cx);
+        }
+        if buffers.is_empty() {
+            self.folding_ranges_task = None;
+            return;
+        }
+
+        self.folding_ranges_task = Some(cx.spawn(|editor, mut cx| async move {
+            if debounce {
+                cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+            }
+
+            let tasks = editor
+                .update(&mut cx, |_, cx| {
+                    buffers
+                        .iter()
+                        .filter_map(|buffer| {
+                            let task = provider.folding_ranges(buffer, cx)?;
+                            let buffer = buffer.clone();
+                            Some(async move { (buffer, task.await) })
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .ok()?;
+            let responses = futures::future::join_all(tasks).await;
+
+            editor
+                .update(&mut cx, |editor, cx| {
+                    for (buffer, ranges) in responses {
+                        if let Some(ranges) = ranges.log_err() {
+                            editor.insert_folding_ranges(buffer, ranges, cx);
+                        }
+                    }
+                })
+                .ok()
+        }));
+    }
+
+    pub(crate) fn remove_folding_ranges(
+        &mut self,
+        buffer_id: BufferId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(folding_ranges) = self.folding_ranges.remove(&buffer_id) {
+            self.remove_creases(folding_ranges.crease_ids, cx);
+        }
+    }
+
+    fn insert_folding_ranges(
+        &mut self,
+        buffer: Model<Buffer>,
+        ranges: Vec<FoldingRange>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let buffer_id = buffer.read(cx).remote_id();
+        self.remove_folding_ranges(buffer_id, cx);
+        if ranges.is_empty() {
+            return;
+        }
+
+        let placeholder = self.display_map.read(cx).fold_placeholder.clone();
+        let multi_buffer_snapshot = self.buffer.read(cx).snapshot(cx);
+        let creases = multi_buffer_ranges(&multi_buffer_snapshot, buffer_id, &ranges)
+            .map(|(_, range)| Crease::simple(range, placeholder.clone()))
+            .collect::<Vec<_>>();
+        let crease_ids = self.insert_creases(creases, cx);
+        self.folding_ranges
+            .insert(buffer_id, BufferFoldingRanges { ranges, crease_ids });
+        cx.notify();
+    }
+
+    pub fn fold_all_comments(&mut self, _: &FoldAllComments, cx: &mut ViewContext<Self>) {
+        self.fold_all_of_kind(FoldingRangeKind::Comment, cx);
+    }
+
+    pub fn fold_all_imports(&mut self, _: &FoldAllImports, cx: &mut ViewContext<Self>) {
+        self.fold_all_of_kind(FoldingRangeKind::Imports, cx);
+    }
+
+    pub fn fold_all_regions(&mut self, _: &FoldAllRegions, cx: &mut ViewContext<Self>) {
+        self.fold_all_of_kind(FoldingRangeKind::Region, cx);
+    }
+
+    /// Folds every range of the given kind reported by the language servers, in all excerpts.
+    fn fold_all_of_kind(&mut self, kind: FoldingRangeKind, cx: &mut ViewContext<Self>) {
+        let placeholder = self.display_map.read(cx).fold_placeholder.clone();
+        let multi_buffer_snapshot = self.buffer.read(cx).snapshot(cx);
+        let creases = self
+            .folding_ranges
+            .iter()
+            .flat_map(|(buffer_id, folding_ranges)| {
+                multi_buffer_ranges(&multi_buffer_snapshot, *buffer_id, &folding_ranges.ranges)
+            })
+            .filter(|(range_kind, _)| *range_kind == Some(&kind))
+            .map(|(_, range)| Crease::simple(range, placeholder.clone()))
+            .collect::<Vec<_>>();
+
+        self.fold_creases(creases, true, cx);
+    }
+}
+
+/// Maps a buffer's folding ranges into every excerpt of the multibuffer that fully contains them.
+fn multi_buffer_ranges<'a>(
+    snapshot: &'a MultiBufferSnapshot,
+    buffer_id: BufferId,
+    ranges: &'a [FoldingRange],
+) -> impl Iterator<Item = (Option<&'a FoldingRangeKind>, Range<Anchor>)> + 'a {
+    snapshot
+        .excerpts()
+        .filter(move |(_, buffer_snapshot, _)| buffer_snapshot.remote_id() == buffer_id)
+        .flat_map(move |(excerpt_id, buffer_snapshot, excerpt_range)| {
+            let context = excerpt_range.context;
+            ranges.iter().filter_map(move |folding_range| {
+                if folding_range
+                    .range
+                    .start
+                    .cmp(&context.start, buffer_snapshot)
+                    .is_lt()
+                    || folding_range
+                        .range
+                        .end
+                        .cmp(&context.end, buffer_snapshot)
+                        .is_gt()
+                {
+                    return None;
+                }
+                let start = snapshot.anchor_in_excerpt(excerpt_id, folding_range.range.start)?;
+                let end = snapshot.anchor_in_excerpt(excerpt_id, folding_range.range.end)?;
+                Some((folding_range.kind.as_ref(), start..end))
+            })
+        })
+}
@@ -1,2 +1,3 @@
 pub mod blame;
+pub mod file_history;
 pub mod project_diff;
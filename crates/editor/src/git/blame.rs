@@ -6,11 +6,11 @@ use git::{
     blame::{Blame, BlameEntry},
     parse_git_remote_url, GitHostingProvider, GitHostingProviderRegistry, Oid, PullRequest,
 };
-use gpui::{Model, ModelContext, Subscription, Task};
+use gpui::{AppContext, Model, ModelContext, Subscription, Task};
 use http_client::HttpClient;
 use language::{markdown, Bias, Buffer, BufferSnapshot, Edit, LanguageRegistry, ParsedMarkdown};
 use multi_buffer::MultiBufferRow;
-use project::{Project, ProjectItem};
+use project::{Project, ProjectItem, ProjectPath};
 use smallvec::SmallVec;
 use sum_tree::SumTree;
 use url::Url;
@@ -192,6 +192,11 @@ impl GitBlame {
         self.commit_details.get(&entry.sha).cloned()
     }
 
+    /// The project path of the blamed buffer's file, if it has one.
+    pub fn project_path(&self, cx: &AppContext) -> Option<ProjectPath> {
+        self.buffer.read(cx).project_path(cx)
+    }
+
     pub fn blame_for_rows<'a>(
         &'a mut self,
         rows: impl 'a + IntoIterator<Item = Option<MultiBufferRow>>,
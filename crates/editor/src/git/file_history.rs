@@ -0,0 +1,566 @@
+use std::{
+    any::Any,
+    ffi::OsStr,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context as _;
+use git::{diff::base_rows_for_rows, history::LogEntry, repository::DiffBase, Oid};
+use gpui::{
+    actions, uniform_list, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Render,
+    ScrollStrategy, Task, UniformListScrollHandle, View, WeakView,
+};
+use language::{Buffer, Capability, DiskState, Language};
+use multi_buffer::{ExcerptRange, MultiBuffer};
+use project::{buffer_store::BufferChangeSet, Project, ProjectItem as _, ProjectPath, WorktreeId};
+use text::{Buffer as TextBuffer, Point};
+use time::UtcOffset;
+use ui::{prelude::*, ListItem, ListItemSpacing, Tooltip};
+use workspace::{
+    item::{Item, ItemEvent},
+    notifications::NotifyTaskExt,
+    Workspace,
+};
+
+use crate::Editor;
+
+actions!(file_history, [OpenFileHistory, OpenSelectionHistory]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(FileHistoryView::register).detach();
+}
+
+/// Lists the commits that touched a file, or a range of lines in it, and opens
+/// the file as it was at a commit or the whole diff that commit introduced.
+pub struct FileHistoryView {
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    project_path: ProjectPath,
+    rows: Option<Range<u32>>,
+    entries: Vec<LogEntry>,
+    highlighted_sha: Option<Oid>,
+    error: Option<SharedString>,
+    loading: bool,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    _load_task: Task<()>,
+}
+
+impl FileHistoryView {
+    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(Self::open_file_history);
+        workspace.register_action(Self::open_selection_history);
+    }
+
+    fn open_file_history(
+        workspace: &mut Workspace,
+        _: &OpenFileHistory,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+            return;
+        };
+        let Some(project_path) = buffer.read(cx).project_path(cx) else {
+            return;
+        };
+        Self::deploy(workspace, project_path, None, None, cx);
+    }
+
+    fn open_selection_history(
+        workspace: &mut Workspace,
+        _: &OpenSelectionHistory,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let (selection, multi_buffer) = editor.update(cx, |editor, cx| {
+            (
+                editor.selections.newest::<Point>(cx),
+                editor.buffer().clone(),
+            )
+        });
+        let multi_buffer = multi_buffer.read(cx);
+        let Some((buffer, start, _)) = multi_buffer.point_to_buffer_point(selection.start, cx)
+        else {
+            return;
+        };
+        let Some((_, end, _)) = multi_buffer.point_to_buffer_point(selection.end, cx) else {
+            return;
+        };
+        let Some(project_path) = buffer.read(cx).project_path(cx) else {
+            return;
+        };
+        // A selection ending at the start of a line does not include that line.
+        let end_row = if end.column == 0 && end.row > start.row {
+            end.row
+        } else {
+            end.row + 1
+        };
+        let rows = start.row..end_row;
+        let text = buffer.read(cx).text();
+        let head_text = workspace.project().read(cx).load_git_base_text(
+            &buffer,
+            &DiffBase::Revision("HEAD".into()),
+            cx,
+        );
+        cx.spawn(|workspace, mut cx| async move {
+            // The history lists the commits that changed these rows in HEAD, and the file's
+            // uncommitted changes may have moved them.
+            let rows = match head_text.await? {
+                Some(head_text) => cx
+                    .background_executor()
+                    .spawn(async move { base_rows_for_rows(&head_text, &text, rows) })
+                    .await
+                    .context("the selected lines were added since the last commit")?,
+                None => rows,
+            };
+            workspace.update(&mut cx, |workspace, cx| {
+                Self::deploy(workspace, project_path, Some(rows), None, cx)
+            })
+        })
+        .detach_and_notify_err(cx);
+    }
+
+    /// Opens the history of the given file, reusing an existing view for the same file and rows.
+    pub fn deploy(
+        workspace: &mut Workspace,
+        project_path: ProjectPath,
+        rows: Option<Range<u32>>,
+        highlighted_sha: Option<Oid>,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let existing = workspace.items_of_type::<Self>(cx).find(|view| {
+            let view = view.read(cx);
+            view.project_path == project_path && view.rows == rows
+        });
+        if let Some(existing) = existing {
+            existing.update(cx, |view, cx| {
+                view.highlighted_sha = highlighted_sha;
+                view.scroll_to_highlighted_entry();
+                cx.notify();
+            });
+            workspace.activate_item(&existing, true, true, cx);
+        } else {
+            let project = workspace.project().clone();
+            let workspace_handle = cx.view().downgrade();
+            let view = cx.new_view(|cx| {
+                Self::new(
+                    project,
+                    workspace_handle,
+                    project_path,
+                    rows,
+                    highlighted_sha,
+                    cx,
+                )
+            });
+            workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+        }
+    }
+
+    fn new(
+        project: Model<Project>,
+        workspace: WeakView<Workspace>,
+        project_path: ProjectPath,
+        rows: Option<Range<u32>>,
+        highlighted_sha: Option<Oid>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let history = project
+            .read(cx)
+            .git_file_history(project_path.clone(), rows.clone(), cx);
+        let load_task = cx.spawn(|this, mut cx| async move {
+            let history = history.await;
+            this.update(&mut cx, |this, cx| {
+                this.loading = false;
+                match history {
+                    Ok(entries) => {
+                        this.entries = entries;
+                        this.scroll_to_highlighted_entry();
+                    }
+                    Err(error) => this.error = Some(format!("{error:#}").into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+
+        Self {
+            project,
+            workspace,
+            project_path,
+            rows,
+            entries: Vec::new(),
+            highlighted_sha,
+            error: None,
+            loading: true,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            _load_task: load_task,
+        }
+    }
+
+    fn scroll_to_highlighted_entry(&self) {
+        if let Some(ix) = self
+            .entries
+            .iter()
+            .position(|entry| Some(entry.sha) == self.highlighted_sha)
+        {
+            self.scroll_handle
+                .scroll_to_item(ix, ScrollStrategy::Center);
+        }
+    }
+
+    fn title(&self) -> String {
+        let file_name = self
+            .project_path
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        match &self.rows {
+            Some(rows) => format!("History: {file_name}:{}-{}", rows.start + 1, rows.end),
+            None => format!("History: {file_name}"),
+        }
+    }
+
+    fn open_file_at_commit(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.entries.get(ix) else {
+            return;
+        };
+        let sha = entry.sha;
+        let project = self.project.clone();
+        let workspace = self.workspace.clone();
+        let text = project.read(cx).git_file_at_commit(
+            self.project_path.clone(),
+            entry.path.clone(),
+            sha,
+            cx,
+        );
+        // The file may have had another path in the commit, before it was renamed.
+        let project_path = ProjectPath {
+            worktree_id: self.project_path.worktree_id,
+            path: Arc::from(entry.path.0.as_path()),
+        };
+        let languages = project.read(cx).languages().clone();
+        cx.spawn(|_, mut cx| async move {
+            let text = text.await?.with_context(|| {
+                format!("file does not exist in commit {}", sha.display_short())
+            })?;
+            let language = languages
+                .language_for_file_path(&project_path.path)
+                .await
+                .ok();
+            workspace.update(&mut cx, |workspace, cx| {
+                let title = format!(
+                    "{} @ {}",
+                    project_path
+                        .path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy(),
+                    sha.display_short()
+                );
+                let buffer = historical_buffer(&project, &project_path, text, language, cx);
+                let multi_buffer =
+                    cx.new_model(|cx| MultiBuffer::singleton(buffer, cx).with_title(title));
+                let editor = cx.new_view(|cx| {
+                    let mut editor =
+                        Editor::for_multibuffer(multi_buffer, Some(project.clone()), true, cx);
+                    editor.set_read_only(true);
+                    editor
+                });
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+            })
+        })
+        .detach_and_notify_err(cx);
+    }
+
+    fn open_commit_diff(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.entries.get(ix) else {
+            return;
+        };
+        let sha = entry.sha;
+        let title = format!("{} {}", sha.display_short(), entry.summary);
+        let project = self.project.clone();
+        let workspace = self.workspace.clone();
+        let worktree_id = self.project_path.worktree_id;
+        let diff = project
+            .read(cx)
+            .git_commit_diff(self.project_path.clone(), sha, cx);
+        let languages = project.read(cx).languages().clone();
+        cx.spawn(|_, mut cx| async move {
+            let files = diff.await?;
+            let mut files_with_languages = Vec::with_capacity(files.len());
+            for file in files {
+                let language = languages.language_for_file_path(&file.path).await.ok();
+                files_with_languages.push((file, language));
+            }
+            workspace.update(&mut cx, |workspace, cx| {
+                let multi_buffer =
+                    cx.new_model(|_| MultiBuffer::new(Capability::ReadOnly).with_title(title));
+                let mut change_sets = Vec::with_capacity(files_with_languages.len());
+                for (file, language) in files_with_languages {
+                    let project_path = ProjectPath {
+                        worktree_id,
+                        path: Arc::from(file.path.0.as_path()),
+                    };
+                    let buffer = historical_buffer(
+                        &project,
+                        &project_path,
+                        file.new_text.unwrap_or_default(),
+                        language,
+                        cx,
+                    );
+                    let change_set = cx.new_model(|cx| {
+                        let snapshot = buffer.read(cx).text_snapshot();
+                        let mut change_set = BufferChangeSet::new(&snapshot);
+                        let _ = change_set.set_base_text(
+                            file.old_text.unwrap_or_default(),
+                            snapshot,
+                            cx,
+                        );
+                        change_set
+                    });
+                    multi_buffer.update(cx, |multi_buffer, cx| {
+                        multi_buffer.push_excerpts(
+                            buffer,
+                            [ExcerptRange {
+                                context: text::Anchor::MIN..text::Anchor::MAX,
+                                primary: None,
+                            }],
+                            cx,
+                        );
+                    });
+                    change_sets.push(change_set);
+                }
+
+                let editor = cx.new_view(|cx| {
+                    let mut editor =
+                        Editor::for_multibuffer(multi_buffer, Some(project.clone()), true, cx);
+                    editor.set_read_only(true);
+                    editor.set_expand_all_diff_hunks();
+                    for change_set in change_sets {
+                        editor.diff_map.add_change_set(change_set, cx);
+                    }
+                    editor
+                });
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+            })
+        })
+        .detach_and_notify_err(cx);
+    }
+
+    fn render_entry(&self, ix: usize, cx: &ViewContext<Self>) -> AnyElement {
+        let entry = &self.entries[ix];
+        let highlighted = Some(entry.sha) == self.highlighted_sha;
+        ListItem::new(ix)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(highlighted)
+            .start_slot(Label::new(entry.sha.display_short()).color(Color::Accent))
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .justify_between()
+                    .child(Label::new(entry.summary.clone()).single_line())
+                    .child(
+                        Label::new(format!(
+                            "{}, {}",
+                            entry.author_name,
+                            relative_timestamp(entry.author_time)
+                        ))
+                        .color(Color::Muted)
+                        .single_line(),
+                    ),
+            )
+            .end_slot(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        IconButton::new(("open-file", ix), IconName::File)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Open File at Commit", cx))
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.open_file_at_commit(ix, cx);
+                            })),
+                    )
+                    .child(
+                        IconButton::new(("show-commit", ix), IconName::FileGit)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Show Commit", cx))
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.open_commit_diff(ix, cx);
+                            })),
+                    ),
+            )
+            .on_click(cx.listener(move |this, _, cx| {
+                this.open_commit_diff(ix, cx);
+            }))
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<ItemEvent> for FileHistoryView {}
+
+impl FocusableView for FileHistoryView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for FileHistoryView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &ItemEvent, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_content_text(&self, _: &WindowContext) -> Option<SharedString> {
+        Some(self.title().into())
+    }
+
+    fn tab_icon(&self, _: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::FileGit))
+    }
+
+    fn tab_tooltip_text(&self, _: &AppContext) -> Option<SharedString> {
+        Some(self.project_path.path.to_string_lossy().to_string().into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("file history")
+    }
+}
+
+impl Render for FileHistoryView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content = if let Some(error) = self.error.clone() {
+            Label::new(error).color(Color::Error).into_any_element()
+        } else if self.loading {
+            Label::new("Loading history…")
+                .color(Color::Muted)
+                .into_any_element()
+        } else if self.entries.is_empty() {
+            Label::new("No commits found")
+                .color(Color::Muted)
+                .into_any_element()
+        } else {
+            uniform_list(
+                cx.view().clone(),
+                "file-history-entries",
+                self.entries.len(),
+                |this, range, cx| range.map(|ix| this.render_entry(ix, cx)).collect(),
+            )
+            .size_full()
+            .track_scroll(self.scroll_handle.clone())
+            .into_any_element()
+        };
+
+        v_flex()
+            .key_context("FileHistory")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .p_2()
+            .gap_2()
+            .bg(cx.theme().colors().editor_background)
+            .child(Headline::new(self.title()).size(HeadlineSize::Small))
+            .child(content)
+    }
+}
+
+fn relative_timestamp(author_time: i64) -> String {
+    let Ok(timestamp) = time::OffsetDateTime::from_unix_timestamp(author_time) else {
+        return "Error parsing date".to_string();
+    };
+    let local = chrono::Local::now().offset().local_minus_utc();
+    time_format::format_localized_timestamp(
+        timestamp,
+        time::OffsetDateTime::now_utc(),
+        UtcOffset::from_whole_seconds(local).unwrap(),
+        time_format::TimestampFormat::Relative,
+    )
+}
+
+/// Creates a read-only buffer holding a file's contents at some commit.
+fn historical_buffer(
+    project: &Model<Project>,
+    project_path: &ProjectPath,
+    text: String,
+    language: Option<Arc<Language>>,
+    cx: &mut AppContext,
+) -> Model<Buffer> {
+    let full_path = project
+        .read(cx)
+        .worktree_for_id(project_path.worktree_id, cx)
+        .map(|worktree| Path::new(worktree.read(cx).root_name()).join(&project_path.path))
+        .unwrap_or_else(|| project_path.path.to_path_buf());
+    let file = Arc::new(HistoricalFile {
+        worktree_id: project_path.worktree_id,
+        path: project_path.path.clone(),
+        full_path,
+    });
+    cx.new_model(|cx| {
+        let text = TextBuffer::new(0, cx.entity_id().as_non_zero_u64().into(), text);
+        let mut buffer = Buffer::build(text, Some(file), Capability::ReadOnly);
+        buffer.set_language(language, cx);
+        buffer
+    })
+}
+
+/// A file that only exists in the git history, so that historical buffers
+/// are labeled with their path and pick up the path's settings.
+struct HistoricalFile {
+    worktree_id: WorktreeId,
+    path: Arc<Path>,
+    full_path: PathBuf,
+}
+
+impl language::File for HistoricalFile {
+    fn as_local(&self) -> Option<&dyn language::LocalFile> {
+        None
+    }
+
+    fn disk_state(&self) -> DiskState {
+        DiskState::New
+    }
+
+    fn path(&self) -> &Arc<Path> {
+        &self.path
+    }
+
+    fn full_path(&self, _: &AppContext) -> PathBuf {
+        self.full_path.clone()
+    }
+
+    fn file_name<'a>(&'a self, _: &'a AppContext) -> &'a OsStr {
+        self.path.file_name().unwrap_or(self.full_path.as_os_str())
+    }
+
+    fn worktree_id(&self, _: &AppContext) -> WorktreeId {
+        self.worktree_id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn to_proto(&self, _: &AppContext) -> rpc::proto::File {
+        rpc::proto::File {
+            worktree_id: self.worktree_id.to_proto(),
+            entry_id: None,
+            path: self.path.to_string_lossy().into(),
+            mtime: None,
+            is_deleted: false,
+        }
+    }
+
+    fn is_private(&self) -> bool {
+        false
+    }
+}
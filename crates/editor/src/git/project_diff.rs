@@ -137,6 +137,10 @@ impl ProjectDiffEditor {
                     project::Event::Closed => {
                         project_diff_editor.buffer_changes.clear();
                     }
+                    project::Event::GitDiffBaseChanged => {
+                        project_diff_editor.schedule_rescan_all(cx);
+                        cx.emit(EditorEvent::TitleChanged);
+                    }
                     _ => {}
                 }
 
@@ -191,6 +195,22 @@ impl ProjectDiffEditor {
             id,
             cx.spawn(|project_diff_editor, mut cx| async move {
                 cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+                // Worktree entries only track the status against the index,
+                // so other diff bases need their changed paths listed by git.
+                let changed_paths_task = project
+                    .update(&mut cx, |project, cx| {
+                        (!project.git_diff_base(cx).is_index())
+                            .then(|| project.git_changed_paths(id, cx))
+                    })
+                    .ok()
+                    .flatten();
+                let changed_paths = match changed_paths_task {
+                    Some(task) => match task.await.log_err() {
+                        Some(paths) => Some(paths.into_iter().collect::<HashSet<_>>()),
+                        None => return,
+                    },
+                    None => None,
+                };
                 let open_tasks = project
                     .update(&mut cx, |project, cx| {
                         let worktree = project.worktree_for_id(id, cx)?;
@@ -199,7 +219,14 @@ impl ProjectDiffEditor {
                             .entries(false, 0)
                             .filter(|entry| !entry.is_external)
                             .filter(|entry| entry.is_file())
-                            .filter_map(|entry| Some((entry.git_status?, entry)))
+                            .filter_map(|entry| match &changed_paths {
+                                Some(changed_paths) => {
+                                    changed_paths.contains(&entry.path).then(|| {
+                                        (entry.git_status.unwrap_or(GitFileStatus::Modified), entry)
+                                    })
+                                }
+                                None => Some((entry.git_status?, entry)),
+                            })
                             .filter_map(|(git_status, entry)| {
                                 Some((git_status, entry.id, project.path_for_entry(entry.id, cx)?))
                             })
@@ -923,8 +950,13 @@ impl Item for ProjectDiffEditor {
             .update(cx, |editor, cx| editor.navigate(data, cx))
     }
 
-    fn tab_tooltip_text(&self, _: &AppContext) -> Option<SharedString> {
-        Some("Project Diff".into())
+    fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
+        let diff_base = self.project.read(cx).git_diff_base(cx);
+        if diff_base.is_index() {
+            Some("Project Diff".into())
+        } else {
+            Some(format!("Project Diff against {diff_base}").into())
+        }
     }
 
     fn tab_content(&self, params: TabContentParams, _: &WindowContext) -> AnyElement {
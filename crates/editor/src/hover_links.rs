@@ -4,9 +4,10 @@ use crate::{
     scroll::ScrollAmount,
     Anchor, Editor, EditorSettings, EditorSnapshot, FindAllReferences, GoToDefinition,
     GoToTypeDefinition, GotoDefinitionKind, InlayId, Navigated, PointForPosition, SelectPhase,
+    SemanticsProvider,
 };
 use gpui::{px, AppContext, AsyncWindowContext, Model, Modifiers, Task, ViewContext};
-use language::{Bias, ToOffset};
+use language::{Bias, Point, ToOffset, ToPoint};
 use linkify::{LinkFinder, LinkKind};
 use lsp::LanguageServerId;
 use project::{
@@ -14,7 +15,7 @@ use project::{
     ResolveState, ResolvedPath,
 };
 use settings::Settings;
-use std::ops::Range;
+use std::{ops::Range, rc::Rc};
 use theme::ActiveTheme as _;
 use util::{maybe, ResultExt, TryFutureExt as _};
 
@@ -524,6 +525,22 @@ pub fn show_link_definition(
                             (range, vec![HoverLink::Url(url)])
                         })
                         .ok()
+                    } else if let Some((link_range, link)) = find_document_link(
+                        &buffer,
+                        provider.clone(),
+                        project.clone(),
+                        buffer_position,
+                        &mut cx,
+                    )
+                    .await
+                    {
+                        let range = maybe!({
+                            let start = snapshot.anchor_in_excerpt(excerpt_id, link_range.start)?;
+                            let end = snapshot.anchor_in_excerpt(excerpt_id, link_range.end)?;
+                            Some(RangeInEditor::Text(start..end))
+                        });
+
+                        Some((range, vec![link]))
                     } else if let Some((filename_range, filename)) =
                         find_file(&buffer, project.clone(), buffer_position, &mut cx).await
                     {
@@ -753,6 +770,94 @@ pub(crate) fn find_url_from_range(
     None
 }
 
+/// Finds the document link at the given position, preferring the links reported by the
+/// language server over the ones matched by the language's links query.
+pub(crate) async fn find_document_link(
+    buffer: &Model<language::Buffer>,
+    provider: Option<Rc<dyn SemanticsProvider>>,
+    project: Option<Model<Project>>,
+    position: text::Anchor,
+    cx: &mut AsyncWindowContext,
+) -> Option<(Range<text::Anchor>, HoverLink)> {
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot()).ok()?;
+    let offset = position.to_offset(&snapshot);
+
+    let document_links = match &provider {
+        Some(provider) => cx
+            .update(|cx| provider.document_links(buffer, cx))
+            .ok()
+            .flatten(),
+        None => None,
+    };
+    let lsp_link = match document_links {
+        Some(task) => task.await.log_err().and_then(|links| {
+            links.into_iter().find_map(|link| {
+                let range =
+                    link.range.start.to_offset(&snapshot)..link.range.end.to_offset(&snapshot);
+                (range.start <= offset && offset <= range.end).then_some((range, link))
+            })
+        }),
+        None => None,
+    };
+    let lsp_link = match lsp_link {
+        Some((range, link)) => match link.target.clone() {
+            Some(target) => Some((range, target)),
+            // Servers may leave the target out, to compute it only for the link that is opened.
+            None => {
+                let resolve_task = provider.and_then(|provider| {
+                    cx.update(|cx| provider.resolve_document_link(link, buffer.clone(), cx))
+                        .ok()
+                        .flatten()
+                });
+                match resolve_task {
+                    Some(task) => task
+                        .await
+                        .log_err()
+                        .and_then(|link| link.target)
+                        .map(|target| (range, target)),
+                    None => None,
+                }
+            }
+        },
+        None => None,
+    };
+    let (range, target) = lsp_link.or_else(|| {
+        let row = offset.to_point(&snapshot).row;
+        snapshot
+            .links(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+            .find(|(range, _)| range.start <= offset && offset <= range.end)
+    })?;
+
+    let link = match lsp::Url::parse(&target) {
+        Ok(url) if url.scheme() == "file" => {
+            let path = url.to_file_path().ok()?;
+            let resolved_path = project?
+                .update(cx, |project, cx| {
+                    project.resolve_abs_file_path(&path.to_string_lossy(), cx)
+                })
+                .ok()?
+                .await?;
+            HoverLink::File(resolved_path)
+        }
+        Ok(_) => HoverLink::Url(target),
+        Err(_) => {
+            let resolved_path = project?
+                .update(cx, |project, cx| {
+                    project.resolve_path_in_buffer(&target, buffer, cx)
+                })
+                .ok()?
+                .await
+                .filter(|path| path.is_file())?;
+            HoverLink::File(resolved_path)
+        }
+    };
+
+    Some((
+        snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end),
+        link,
+    ))
+}
+
 pub(crate) async fn find_file(
     buffer: &Model<language::Buffer>,
     project: Option<Model<Project>>,
@@ -1424,6 +1529,108 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_document_links(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: None,
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            extern crate ˇserde;
+        "});
+        let link_range = cx.lsp_range(indoc! {"
+            extern crate «serde»;
+        "});
+        let mut requests = cx.handle_request::<lsp::request::DocumentLinkRequest, _, _>(
+            move |_, _, _| async move {
+                Ok(Some(vec![lsp::DocumentLink {
+                    range: link_range,
+                    target: Some("https://docs.rs/serde".parse().unwrap()),
+                    tooltip: None,
+                    data: None,
+                }]))
+            },
+        );
+
+        let screen_coord = cx.pixel_position(indoc! {"
+            extern crate seˇrde;
+        "});
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        requests.next().await;
+        cx.background_executor.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            extern crate «serdeˇ»;
+        "});
+
+        cx.simulate_click(screen_coord, Modifiers::secondary_key());
+        assert_eq!(cx.opened_url(), Some("https://docs.rs/serde".into()));
+    }
+
+    #[gpui::test]
+    async fn test_document_links_resolved_lazily(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            extern crate ˇserde;
+        "});
+        let link_range = cx.lsp_range(indoc! {"
+            extern crate «serde»;
+        "});
+        let mut requests = cx.handle_request::<lsp::request::DocumentLinkRequest, _, _>(
+            move |_, _, _| async move {
+                Ok(Some(vec![lsp::DocumentLink {
+                    range: link_range,
+                    target: None,
+                    tooltip: None,
+                    data: Some(serde_json::json!("serde")),
+                }]))
+            },
+        );
+        let mut resolve_requests = cx.handle_request::<lsp::request::DocumentLinkResolve, _, _>(
+            move |link, _, _| async move {
+                assert_eq!(link.data, Some(serde_json::json!("serde")));
+                Ok(lsp::DocumentLink {
+                    target: Some("https://docs.rs/serde".parse().unwrap()),
+                    ..link
+                })
+            },
+        );
+
+        let screen_coord = cx.pixel_position(indoc! {"
+            extern crate seˇrde;
+        "});
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        requests.next().await;
+        resolve_requests.next().await;
+        cx.background_executor.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            extern crate «serdeˇ»;
+        "});
+
+        cx.simulate_click(screen_coord, Modifiers::secondary_key());
+        assert_eq!(cx.opened_url(), Some("https://docs.rs/serde".into()));
+    }
+
     #[gpui::test]
     async fn test_urls_at_beginning_of_buffer(cx: &mut gpui::TestAppContext) {
         init_test(cx, |_| {});
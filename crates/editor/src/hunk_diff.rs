@@ -8,7 +8,7 @@ use multi_buffer::{
     Anchor, AnchorRangeExt, ExcerptRange, MultiBuffer, MultiBufferDiffHunk, MultiBufferRow,
     MultiBufferSnapshot, ToOffset, ToPoint,
 };
-use project::buffer_store::BufferChangeSet;
+use project::{buffer_store::BufferChangeSet, ProjectItem};
 use std::{ops::Range, sync::Arc};
 use sum_tree::TreeMap;
 use text::OffsetRangeExt;
@@ -17,13 +17,14 @@ use ui::{
     ParentElement, PopoverMenu, Styled, Tooltip, ViewContext, VisualContext,
 };
 use util::RangeExt;
-use workspace::Item;
+use workspace::{notifications::NotifyTaskExt, Item};
 
 use crate::{
     editor_settings::CurrentLineHighlight, hunk_status, hunks_for_selections, ApplyAllDiffHunks,
     ApplyDiffHunk, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId, DiffRowHighlight,
     DisplayRow, DisplaySnapshot, Editor, EditorElement, ExpandAllHunkDiffs, GoToHunk, GoToPrevHunk,
-    RevertFile, RevertSelectedHunks, ToDisplayPoint, ToggleHunkDiff,
+    RevertFile, RevertSelectedHunks, StageSelectedHunks, ToDisplayPoint, ToggleHunkDiff,
+    UnstageSelectedHunks,
 };
 
 #[derive(Debug, Clone)]
@@ -521,6 +522,153 @@ impl Editor {
         }
     }
 
+    /// Whether the hunks are computed against the staged text, so that they can be staged and
+    /// unstaged, rather than against a revision.
+    pub(crate) fn can_stage_hunks(&self, cx: &AppContext) -> bool {
+        self.project.as_ref().map_or(false, |project| {
+            project.read(cx).git_diff_base(cx).is_index()
+        })
+    }
+
+    /// Stages the hunks intersecting the selections, by writing their buffer text
+    /// over the corresponding parts of the staged text.
+    pub(crate) fn stage_selected_hunks(
+        &mut self,
+        _: &StageSelectedHunks,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let snapshot = self.snapshot(cx);
+        let hunks = hunks_for_selections(&snapshot, &self.selections.all(cx));
+        let mut hunks_by_buffer = HashMap::<BufferId, Vec<MultiBufferDiffHunk>>::default();
+        for hunk in hunks {
+            hunks_by_buffer
+                .entry(hunk.buffer_id)
+                .or_default()
+                .push(hunk);
+        }
+
+        let mut tasks = Vec::new();
+        for (buffer_id, mut hunks) in hunks_by_buffer {
+            let Some(buffer) = self.buffer.read(cx).buffer(buffer_id) else {
+                continue;
+            };
+            let Some(diff_base) = self.diff_map.diff_bases.get(&buffer_id) else {
+                continue;
+            };
+            let buffer = buffer.read(cx);
+            let Some(project_path) = buffer.project_path(cx) else {
+                continue;
+            };
+
+            let mut staged_text = diff_base
+                .change_set
+                .read(cx)
+                .base_text
+                .as_ref()
+                .map(|base_text| base_text.read(cx).text())
+                .unwrap_or_default();
+            hunks.sort_by_key(|hunk| hunk.diff_base_byte_range.start);
+            for hunk in hunks.iter().rev() {
+                let new_text = buffer
+                    .text_for_range(hunk.buffer_range.clone())
+                    .collect::<String>();
+                staged_text.replace_range(hunk.diff_base_byte_range.clone(), &new_text);
+            }
+            tasks.push(
+                project
+                    .read(cx)
+                    .set_git_index_text(project_path, Some(staged_text), cx),
+            );
+        }
+
+        cx.background_executor()
+            .spawn(async move {
+                futures::future::try_join_all(tasks).await?;
+                anyhow::Ok(())
+            })
+            .detach_and_notify_err(cx);
+    }
+
+    /// Unstages the staged changes intersecting the selections, by reverting them
+    /// to their committed contents in the staged text.
+    pub(crate) fn unstage_selected_hunks(
+        &mut self,
+        _: &UnstageSelectedHunks,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let selections = self.selections.all::<Point>(cx);
+        let mut rows_by_buffer = HashMap::<BufferId, (Model<Buffer>, Vec<Range<u32>>)>::default();
+        for selection in &selections {
+            for (buffer, range, _) in self
+                .buffer
+                .read(cx)
+                .range_to_buffer_ranges(selection.range(), cx)
+            {
+                let buffer_snapshot = buffer.read(cx);
+                let rows = buffer_snapshot.offset_to_point(range.start).row
+                    ..buffer_snapshot.offset_to_point(range.end).row + 1;
+                rows_by_buffer
+                    .entry(buffer_snapshot.remote_id())
+                    .or_insert_with(|| (buffer.clone(), Vec::new()))
+                    .1
+                    .push(rows);
+            }
+        }
+
+        let mut tasks = Vec::new();
+        for (buffer_id, (buffer, rows)) in rows_by_buffer {
+            let buffer = buffer.read(cx);
+            let Some(project_path) = buffer.project_path(cx) else {
+                continue;
+            };
+            let Some(diff_base) = self.diff_map.diff_bases.get(&buffer_id) else {
+                continue;
+            };
+            let change_set = diff_base.change_set.read(cx);
+            // Files without staged text have nothing to unstage.
+            let Some(base_text) = change_set.base_text.as_ref() else {
+                continue;
+            };
+            let base_text = base_text.read(cx);
+
+            let hunk_rows = change_set
+                .diff_to_buffer
+                .hunks_intersecting_range(text::Anchor::MIN..text::Anchor::MAX, buffer)
+                .map(|hunk| {
+                    let base_range = hunk.diff_base_byte_range.to_point(base_text);
+                    let base_end_row = if base_range.end.column > 0 {
+                        base_range.end.row + 1
+                    } else {
+                        base_range.end.row
+                    };
+                    (hunk.row_range, base_range.start.row..base_end_row)
+                })
+                .collect::<Vec<_>>();
+            let staged_rows = rows
+                .iter()
+                .map(|rows| buffer_rows_to_base_rows(rows, &hunk_rows))
+                .collect();
+            tasks.push(
+                project
+                    .read(cx)
+                    .unstage_git_rows(project_path, staged_rows, cx),
+            );
+        }
+
+        cx.background_executor()
+            .spawn(async move {
+                futures::future::try_join_all(tasks).await?;
+                anyhow::Ok(())
+            })
+            .detach_and_notify_err(cx);
+    }
+
     fn has_multiple_hunks(&self, cx: &AppContext) -> bool {
         let snapshot = self.buffer.read(cx).snapshot(cx);
         let mut hunks = self.diff_map.snapshot.diff_hunks(&snapshot);
@@ -1096,6 +1244,31 @@ impl Editor {
     }
 }
 
+/// Maps a range of buffer rows to the diff base, given the buffer and diff base rows of the diff's hunks.
+/// Rows inside of a hunk map to all of the hunk's diff base rows.
+fn buffer_rows_to_base_rows(
+    rows: &Range<u32>,
+    hunk_rows: &[(Range<u32>, Range<u32>)],
+) -> Range<u32> {
+    let map_row = |row: u32, is_start: bool| {
+        let mut base_row = row;
+        for (buffer_rows, base_rows) in hunk_rows {
+            if row < buffer_rows.start {
+                break;
+            } else if row < buffer_rows.end {
+                return if is_start {
+                    base_rows.start
+                } else {
+                    base_rows.end
+                };
+            }
+            base_row = (base_rows.end + row).saturating_sub(buffer_rows.end);
+        }
+        base_row
+    };
+    map_row(rows.start, true)..map_row(rows.end, false)
+}
+
 pub(crate) fn to_diff_hunk(
     hovered_hunk: &HoveredHunk,
     multi_buffer_snapshot: &MultiBufferSnapshot,
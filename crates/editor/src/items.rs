@@ -524,6 +524,18 @@ fn deserialize_selection(
     })
 }
 
+/// The replacement for a search match, which always lies within a single excerpt, as buffers
+/// are searched excerpt by excerpt.
+fn replacement_for_match(
+    snapshot: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+    query: &SearchQuery,
+) -> Option<String> {
+    let range = range.to_offset(snapshot);
+    let excerpt = snapshot.excerpt_containing(range.clone())?;
+    query.replacement_for_range(excerpt.buffer(), excerpt.map_range_to_buffer(range))
+}
+
 fn deserialize_anchor(buffer: &MultiBufferSnapshot, anchor: proto::EditorAnchor) -> Option<Anchor> {
     let excerpt_id = ExcerptId::from_proto(anchor.excerpt_id);
     Some(Anchor {
@@ -537,6 +549,24 @@ impl Item for Editor {
     type Event = EditorEvent;
 
     fn navigate(&mut self, data: Box<dyn std::any::Any>, cx: &mut ViewContext<Self>) -> bool {
+        // Entries restored from a previous session only hold the cursor position.
+        if let Some(position) = data.downcast_ref::<Point>() {
+            let offset = self
+                .buffer
+                .read(cx)
+                .read(cx)
+                .clip_point(*position, Bias::Left);
+            if self.selections.newest::<Point>(cx).head() == offset {
+                return false;
+            }
+            let nav_history = self.nav_history.take();
+            self.change_selections(Some(Autoscroll::center()), cx, |s| {
+                s.select_ranges([offset..offset])
+            });
+            self.nav_history = nav_history;
+            return true;
+        }
+
         if let Ok(data) = data.downcast::<NavigationData>() {
             let newest_selection = self.selections.newest::<Point>(cx);
             let buffer = self.buffer.read(cx).read(cx);
@@ -1316,19 +1346,10 @@ impl SearchableItem for Editor {
         query: &SearchQuery,
         cx: &mut ViewContext<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
-        let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
-        let text: Cow<_> = if text.len() == 1 {
-            text.first().cloned().unwrap().into()
-        } else {
-            let joined_chunks = text.join("");
-            joined_chunks.into()
-        };
-
-        if let Some(replacement) = query.replacement_for(&text) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        if let Some(replacement) = replacement_for_match(&snapshot, identifier, query) {
             self.transact(cx, |this, cx| {
-                this.edit([(identifier.clone(), Arc::from(&*replacement))], cx);
+                this.edit([(identifier.clone(), Arc::from(replacement))], cx);
             });
         }
     }
@@ -1338,20 +1359,11 @@ impl SearchableItem for Editor {
         query: &SearchQuery,
         cx: &mut ViewContext<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
+        let snapshot = self.buffer.read(cx).snapshot(cx);
         let mut edits = vec![];
         for m in matches {
-            let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
-            let text: Cow<_> = if text.len() == 1 {
-                text.first().cloned().unwrap().into()
-            } else {
-                let joined_chunks = text.join("");
-                joined_chunks.into()
-            };
-
-            if let Some(replacement) = query.replacement_for(&text) {
-                edits.push((m.clone(), Arc::from(&*replacement)));
+            if let Some(replacement) = replacement_for_match(&snapshot, m, query) {
+                edits.push((m.clone(), Arc::from(replacement)));
             }
         }
 
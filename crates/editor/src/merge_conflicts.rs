@@ -0,0 +1,523 @@
+use std::{ops::Range, time::Duration};
+
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use gpui::{Model, Task, WeakView, WindowContext};
+use language::{Buffer, BufferId, LanguageServerId};
+use multi_buffer::{Anchor, ExcerptId, MultiBufferSnapshot, ToOffset as _};
+use project::{CodeAction, ProjectTransaction};
+use text::BufferSnapshot;
+use theme::ActiveTheme;
+use ui::ViewContext;
+
+use crate::{
+    actions::{GoToConflict, GoToPrevConflict},
+    scroll::Autoscroll,
+    CodeActionProvider, Direction, Editor, EditorMode,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+/// A region of a buffer delimited by git conflict markers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MergeConflict<T> {
+    /// The whole conflict, including the marker lines.
+    pub range: Range<T>,
+    /// The lines between the `<<<<<<<` marker and the next marker.
+    pub ours: Range<T>,
+    /// The lines between the `|||||||` and `=======` markers, written by the diff3 conflict style.
+    pub base: Option<Range<T>>,
+    /// The lines between the `=======` and `>>>>>>>` markers.
+    pub theirs: Range<T>,
+}
+
+impl<T> MergeConflict<T> {
+    fn try_map<U>(&self, mut f: impl FnMut(&T) -> Option<U>) -> Option<MergeConflict<U>> {
+        let mut map_range = |range: &Range<T>| Some(f(&range.start)?..f(&range.end)?);
+        Some(MergeConflict {
+            range: map_range(&self.range)?,
+            ours: map_range(&self.ours)?,
+            base: match &self.base {
+                Some(base) => Some(map_range(base)?),
+                None => None,
+            },
+            theirs: map_range(&self.theirs)?,
+        })
+    }
+}
+
+impl MergeConflict<text::Anchor> {
+    fn resolved_text(
+        &self,
+        resolution: ConflictResolution,
+        buffer: &BufferSnapshot,
+    ) -> Option<String> {
+        let text =
+            |range: &Range<text::Anchor>| buffer.text_for_range(range.clone()).collect::<String>();
+        Some(match resolution {
+            ConflictResolution::Ours => text(&self.ours),
+            ConflictResolution::Theirs => text(&self.theirs),
+            ConflictResolution::Both => text(&self.ours) + &text(&self.theirs),
+            ConflictResolution::Base => text(self.base.as_ref()?),
+        })
+    }
+}
+
+/// Finds the merge conflicts in the given text, as byte ranges.
+pub fn parse_merge_conflicts(text: &str) -> Vec<MergeConflict<usize>> {
+    enum State {
+        Outside,
+        Ours {
+            start: usize,
+            ours_start: usize,
+        },
+        Base {
+            start: usize,
+            ours: Range<usize>,
+            base_start: usize,
+        },
+        Theirs {
+            start: usize,
+            ours: Range<usize>,
+            base: Option<Range<usize>>,
+            theirs_start: usize,
+        },
+    }
+
+    let mut conflicts = Vec::new();
+    let mut state = State::Outside;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        // An unterminated conflict is discarded when a new one starts.
+        if is_marker(line, OURS_MARKER) {
+            state = State::Ours {
+                start: line_start,
+                ours_start: offset,
+            };
+            continue;
+        }
+
+        state = match state {
+            State::Ours { start, ours_start } if is_marker(line, BASE_MARKER) => State::Base {
+                start,
+                ours: ours_start..line_start,
+                base_start: offset,
+            },
+            State::Ours { start, ours_start } if line == SEPARATOR => State::Theirs {
+                start,
+                ours: ours_start..line_start,
+                base: None,
+                theirs_start: offset,
+            },
+            State::Base {
+                start,
+                ours,
+                base_start,
+            } if line == SEPARATOR => State::Theirs {
+                start,
+                ours,
+                base: Some(base_start..line_start),
+                theirs_start: offset,
+            },
+            State::Theirs {
+                start,
+                ours,
+                base,
+                theirs_start,
+            } if is_marker(line, THEIRS_MARKER) => {
+                conflicts.push(MergeConflict {
+                    range: start..offset,
+                    ours,
+                    base,
+                    theirs: theirs_start..line_start,
+                });
+                State::Outside
+            }
+            state => state,
+        };
+    }
+    conflicts
+}
+
+fn is_marker(line: &str, marker: &str) -> bool {
+    line.strip_prefix(marker)
+        .map_or(false, |rest| rest.is_empty() || rest.starts_with(' '))
+}
+
+/// The ways of resolving a merge conflict, offered as code actions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ConflictResolution {
+    Ours,
+    Theirs,
+    Both,
+    Base,
+}
+
+impl ConflictResolution {
+    const ALL: [Self; 4] = [Self::Ours, Self::Theirs, Self::Both, Self::Base];
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Ours => "Accept Ours",
+            Self::Theirs => "Accept Theirs",
+            Self::Both => "Accept Both",
+            Self::Base => "Accept Base",
+        }
+    }
+
+    fn code_action_kind(self) -> lsp::CodeActionKind {
+        lsp::CodeActionKind::new(match self {
+            Self::Ours => "merge-conflict.accept-ours",
+            Self::Theirs => "merge-conflict.accept-theirs",
+            Self::Both => "merge-conflict.accept-both",
+            Self::Base => "merge-conflict.accept-base",
+        })
+    }
+
+    fn from_code_action(action: &lsp::CodeAction) -> Option<Self> {
+        let kind = action.kind.as_ref()?;
+        Self::ALL
+            .into_iter()
+            .find(|resolution| resolution.code_action_kind() == *kind)
+    }
+}
+
+/// The merge conflicts found in each buffer of an editor.
+#[derive(Default)]
+pub(crate) struct MergeConflicts {
+    conflicts: HashMap<BufferId, Vec<MergeConflict<text::Anchor>>>,
+    tasks: HashMap<BufferId, Task<()>>,
+}
+
+enum ConflictOursHighlight {}
+enum ConflictBaseHighlight {}
+enum ConflictTheirsHighlight {}
+
+impl Editor {
+    /// Re-parses the conflict markers of the given buffer and highlights the sides of its conflicts.
+    pub(crate) fn refresh_merge_conflicts(
+        &mut self,
+        buffer: &Model<Buffer>,
+        debounce: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if self.mode != EditorMode::Full {
+            return;
+        }
+
+        let snapshot = buffer.read(cx).text_snapshot();
+        let buffer_id = snapshot.remote_id();
+        let task = cx.spawn(|editor, mut cx| async move {
+            if debounce {
+                cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+            }
+
+            let conflicts = cx
+                .background_executor()
+                .spawn(async move {
+                    parse_merge_conflicts(&snapshot.text())
+                        .into_iter()
+                        .filter_map(|conflict| {
+                            conflict.try_map(|offset| Some(snapshot.anchor_before(*offset)))
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await;
+
+            editor
+                .update(&mut cx, |editor, cx| {
+                    if conflicts.is_empty() {
+                        if editor
+                            .merge_conflicts
+                            .conflicts
+                            .remove(&buffer_id)
+                            .is_none()
+                        {
+                            return;
+                        }
+                    } else {
+                        editor
+                            .merge_conflicts
+                            .conflicts
+                            .insert(buffer_id, conflicts);
+                    }
+                    editor.highlight_merge_conflicts(cx);
+                    editor.refresh_code_actions(cx);
+                })
+                .ok();
+        });
+        self.merge_conflicts.tasks.insert(buffer_id, task);
+    }
+
+    pub(crate) fn remove_stale_merge_conflicts(&mut self, cx: &mut ViewContext<Self>) {
+        let buffer = self.buffer.read(cx);
+        let merge_conflicts = &mut self.merge_conflicts;
+        merge_conflicts
+            .conflicts
+            .retain(|buffer_id, _| buffer.buffer(*buffer_id).is_some());
+        merge_conflicts
+            .tasks
+            .retain(|buffer_id, _| buffer.buffer(*buffer_id).is_some());
+    }
+
+    fn highlight_merge_conflicts(&mut self, cx: &mut ViewContext<Self>) {
+        self.clear_row_highlights::<ConflictOursHighlight>();
+        self.clear_row_highlights::<ConflictBaseHighlight>();
+        self.clear_row_highlights::<ConflictTheirsHighlight>();
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let conflicts =
+            multi_buffer_conflicts(&snapshot, &self.merge_conflicts.conflicts).collect::<Vec<_>>();
+        let status_colors = cx.theme().status();
+        let ours_color = status_colors.created_background;
+        let base_color = status_colors.hidden_background;
+        let theirs_color = status_colors.info_background;
+        for conflict in conflicts {
+            // Row highlights that end at the start of a line don't include that line,
+            // so each side is highlighted along with its opening marker.
+            self.highlight_rows::<ConflictOursHighlight>(
+                conflict.range.start..conflict.ours.end,
+                ours_color,
+                false,
+                cx,
+            );
+            let theirs_start = match conflict.base {
+                Some(base) => {
+                    self.highlight_rows::<ConflictBaseHighlight>(
+                        conflict.ours.end..base.end,
+                        base_color,
+                        false,
+                        cx,
+                    );
+                    base.end
+                }
+                None => conflict.ours.end,
+            };
+            self.highlight_rows::<ConflictTheirsHighlight>(
+                theirs_start..conflict.range.end,
+                theirs_color,
+                false,
+                cx,
+            );
+        }
+        cx.notify();
+    }
+
+    pub fn go_to_conflict(&mut self, _: &GoToConflict, cx: &mut ViewContext<Self>) {
+        self.go_to_conflict_impl(Direction::Next, cx);
+    }
+
+    pub fn go_to_prev_conflict(&mut self, _: &GoToPrevConflict, cx: &mut ViewContext<Self>) {
+        self.go_to_conflict_impl(Direction::Prev, cx);
+    }
+
+    /// Moves the cursor to the start of the next or previous conflict, wrapping around
+    /// the end of the multibuffer.
+    fn go_to_conflict_impl(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest::<usize>(cx).head();
+        let mut starts = multi_buffer_conflicts(&snapshot, &self.merge_conflicts.conflicts)
+            .map(|conflict| conflict.range.start.to_offset(&snapshot))
+            .collect::<Vec<_>>();
+        starts.sort_unstable();
+
+        let target = match direction {
+            Direction::Next => starts
+                .iter()
+                .find(|start| **start > head)
+                .or(starts.first()),
+            Direction::Prev => starts
+                .iter()
+                .rev()
+                .find(|start| **start < head)
+                .or(starts.last()),
+        };
+        if let Some(target) = target.copied() {
+            self.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+                selections.select_ranges([target..target])
+            });
+        }
+    }
+}
+
+/// Maps the conflicts of every buffer into the excerpts that fully contain them.
+fn multi_buffer_conflicts<'a>(
+    snapshot: &'a MultiBufferSnapshot,
+    conflicts: &'a HashMap<BufferId, Vec<MergeConflict<text::Anchor>>>,
+) -> impl Iterator<Item = MergeConflict<Anchor>> + 'a {
+    snapshot
+        .excerpts()
+        .flat_map(move |(excerpt_id, buffer_snapshot, excerpt_range)| {
+            let context = excerpt_range.context;
+            conflicts
+                .get(&buffer_snapshot.remote_id())
+                .into_iter()
+                .flatten()
+                .filter_map(move |conflict| {
+                    if conflict
+                        .range
+                        .start
+                        .cmp(&context.start, buffer_snapshot)
+                        .is_lt()
+                        || conflict
+                            .range
+                            .end
+                            .cmp(&context.end, buffer_snapshot)
+                            .is_gt()
+                    {
+                        return None;
+                    }
+                    conflict.try_map(|anchor| snapshot.anchor_in_excerpt(excerpt_id, *anchor))
+                })
+        })
+}
+
+/// Offers to resolve the merge conflict under the cursor with one or both of its sides.
+pub(crate) struct MergeConflictCodeActionProvider {
+    pub(crate) editor: WeakView<Editor>,
+}
+
+impl MergeConflictCodeActionProvider {
+    fn conflicts<'a>(
+        &self,
+        buffer_id: BufferId,
+        cx: &'a WindowContext,
+    ) -> &'a [MergeConflict<text::Anchor>] {
+        self.editor
+            .upgrade()
+            .and_then(|editor| {
+                let conflicts = editor.read(cx).merge_conflicts.conflicts.get(&buffer_id)?;
+                Some(conflicts.as_slice())
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl CodeActionProvider for MergeConflictCodeActionProvider {
+    fn code_actions(
+        &self,
+        buffer: &Model<Buffer>,
+        range: Range<text::Anchor>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<Vec<CodeAction>>> {
+        let snapshot = buffer.read(cx).text_snapshot();
+        let actions = self
+            .conflicts(snapshot.remote_id(), cx)
+            .iter()
+            .filter(|conflict| {
+                conflict.range.start.cmp(&range.end, &snapshot).is_le()
+                    && conflict.range.end.cmp(&range.start, &snapshot).is_gt()
+            })
+            .flat_map(|conflict| {
+                ConflictResolution::ALL
+                    .into_iter()
+                    .filter(|resolution| {
+                        *resolution != ConflictResolution::Base || conflict.base.is_some()
+                    })
+                    .map(|resolution| CodeAction {
+                        server_id: LanguageServerId(0),
+                        range: conflict.range.clone(),
+                        lsp_action: lsp::CodeAction {
+                            title: resolution.title().into(),
+                            kind: Some(resolution.code_action_kind()),
+                            ..Default::default()
+                        },
+                    })
+            })
+            .collect();
+        Task::ready(Ok(actions))
+    }
+
+    fn apply_code_action(
+        &self,
+        buffer: Model<Buffer>,
+        action: CodeAction,
+        _excerpt_id: ExcerptId,
+        _push_to_history: bool,
+        cx: &mut WindowContext,
+    ) -> Task<Result<ProjectTransaction>> {
+        let Some(resolution) = ConflictResolution::from_code_action(&action.lsp_action) else {
+            return Task::ready(Err(anyhow!("unknown merge conflict resolution")));
+        };
+        let buffer_id = buffer.read(cx).remote_id();
+        let Some(conflict) = self
+            .conflicts(buffer_id, cx)
+            .iter()
+            .find(|conflict| conflict.range == action.range)
+            .cloned()
+        else {
+            return Task::ready(Err(anyhow!("merge conflict was already resolved")));
+        };
+
+        buffer.update(cx, |buffer, cx| {
+            if let Some(text) = conflict.resolved_text(resolution, &buffer.text_snapshot()) {
+                buffer.edit([(conflict.range, text)], None, cx);
+            }
+        });
+        Task::ready(Ok(ProjectTransaction::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_merge_conflicts() {
+        let text = "\
+            fn main() {\n\
+            <<<<<<< HEAD\n\
+            \x20   one();\n\
+            =======\n\
+            \x20   two();\n\
+            >>>>>>> feature\n\
+            <<<<<<< ours\n\
+            a\n\
+            ||||||| base\n\
+            b\n\
+            =======\n\
+            c\n\
+            >>>>>>> theirs\n\
+            }\n\
+            <<<<<<< unterminated\n\
+            =======\n";
+        let conflicts = parse_merge_conflicts(text);
+        let sides = conflicts
+            .iter()
+            .map(|conflict| {
+                (
+                    &text[conflict.range.clone()],
+                    &text[conflict.ours.clone()],
+                    conflict.base.clone().map(|base| &text[base]),
+                    &text[conflict.theirs.clone()],
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sides,
+            [
+                (
+                    "<<<<<<< HEAD\n    one();\n=======\n    two();\n>>>>>>> feature\n",
+                    "    one();\n",
+                    None,
+                    "    two();\n",
+                ),
+                (
+                    "<<<<<<< ours\na\n||||||| base\nb\n=======\nc\n>>>>>>> theirs\n",
+                    "a\n",
+                    Some("b\n"),
+                    "c\n",
+                ),
+            ]
+        );
+    }
+}
@@ -463,6 +463,56 @@ impl SemanticsProvider for BranchBufferSemanticsProvider {
         }
     }
 
+    fn semantic_tokens(
+        &self,
+        _: &Model<Buffer>,
+        _: &mut AppContext,
+    ) -> Option<Task<anyhow::Result<Vec<project::SemanticToken>>>> {
+        None
+    }
+
+    fn code_lens(
+        &self,
+        _: &Model<Buffer>,
+        _: &mut AppContext,
+    ) -> Option<Task<anyhow::Result<Vec<project::CodeLens>>>> {
+        None
+    }
+
+    fn folding_ranges(
+        &self,
+        _: &Model<Buffer>,
+        _: &mut AppContext,
+    ) -> Option<Task<anyhow::Result<Vec<project::FoldingRange>>>> {
+        None
+    }
+
+    fn selection_ranges(
+        &self,
+        _: &Model<Buffer>,
+        _: Vec<text::Anchor>,
+        _: &mut AppContext,
+    ) -> Option<Task<anyhow::Result<Vec<Vec<Range<text::Anchor>>>>>> {
+        None
+    }
+
+    fn document_links(
+        &self,
+        _: &Model<Buffer>,
+        _: &mut AppContext,
+    ) -> Option<Task<anyhow::Result<Vec<project::DocumentLink>>>> {
+        None
+    }
+
+    fn resolve_document_link(
+        &self,
+        _: project::DocumentLink,
+        _: Model<Buffer>,
+        _: &mut AppContext,
+    ) -> Option<Task<anyhow::Result<project::DocumentLink>>> {
+        None
+    }
+
     fn document_highlights(
         &self,
         buffer: &Model<Buffer>,
@@ -0,0 +1,252 @@
+use std::{cmp, sync::Arc, time::Duration};
+
+use collections::HashMap;
+use gpui::{HighlightStyle, StrikethroughStyle};
+use language::language_settings::language_settings;
+use multi_buffer::Anchor;
+use project::SemanticToken;
+use text::BufferId;
+use theme::{ActiveTheme, SyntaxTheme};
+use ui::ViewContext;
+use util::ResultExt;
+
+use crate::Editor;
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(150);
+
+impl Editor {
+    /// Re-requests the semantic tokens of the given buffer, or of every buffer in the editor when
+    /// `None`, and redraws them on top of the syntax highlighting once the language servers respond.
+    ///
+    /// Buffers stay stale until their tokens arrive, so that a refresh superseding a pending one
+    /// requests the buffers of both.
+    pub(crate) fn refresh_semantic_tokens(
+        &mut self,
+        buffer_id: Option<BufferId>,
+        debounce: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(provider) = self.semantics_provider.clone() else {
+            return;
+        };
+
+        let multi_buffer = self.buffer.read(cx);
+        match buffer_id {
+            Some(buffer_id) => {
+                self.stale_semantic_tokens.insert(buffer_id);
+            }
+            None => self.stale_semantic_tokens.extend(
+                multi_buffer
+                    .all_buffers()
+                    .iter()
+                    .map(|buffer| buffer.read(cx).remote_id()),
+            ),
+        }
+
+        let mut buffers = Vec::new();
+        let mut removed_tokens = false;
+        self.stale_semantic_tokens.retain(|buffer_id| {
+            let enabled = multi_buffer.buffer(*buffer_id).filter(|buffer| {
+                let buffer = buffer.read(cx);
+                language_settings(
+                    buffer.language().map(|language| language.name()),
+                    buffer.file(),
+                    cx,
+                )
+                .semantic_tokens
+            });
+            match enabled {
+                Some(buffer) => {
+                    buffers.push(buffer);
+                    true
+                }
+                None => {
+                    removed_tokens |= self.semantic_tokens.remove(buffer_id).is_some();
+                    false
+                }
+            }
+        });
+        if removed_tokens {
+            self.update_semantic_token_highlights(cx);
+        }
+        if buffers.is_empty() {
+            self.semantic_tokens_task = None;
+            return;
+        }
+
+        self.semantic_tokens_task = Some(cx.spawn(|editor, mut cx| async move {
+            if debounce {
+                cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+            }
+
+            let (buffer_ids, tasks) = editor
+                .update(&mut cx, |_, cx| {
+                    let buffer_ids = buffers
+                        .iter()
+                        .map(|buffer| buffer.read(cx).remote_id())
+                        .collect::<Vec<_>>();
+                    let tasks = buffers
+                        .iter()
+                        .filter_map(|buffer| {
+                            let buffer_id = buffer.read(cx).remote_id();
+                            let task = provider.semantic_tokens(buffer, cx)?;
+                            Some(async move { (buffer_id, task.await) })
+                        })
+                        .collect::<Vec<_>>();
+                    (buffer_ids, tasks)
+                })
+                .ok()?;
+            let responses = futures::future::join_all(tasks).await;
+
+            editor
+                .update(&mut cx, |editor, cx| {
+                    for buffer_id in &buffer_ids {
+                        editor.stale_semantic_tokens.remove(buffer_id);
+                    }
+                    for (buffer_id, tokens) in responses {
+                        if let Some(tokens) = tokens.log_err() {
+                            editor.semantic_tokens.insert(buffer_id, tokens);
+                        }
+                    }
+                    editor.update_semantic_token_highlights(cx);
+                })
+                .ok()
+        }));
+    }
+
+    pub(crate) fn clear_semantic_tokens_for_removed_buffers(&mut self, cx: &mut ViewContext<Self>) {
+        let buffer = self.buffer.read(cx);
+        let len = self.semantic_tokens.len();
+        self.semantic_tokens
+            .retain(|buffer_id, _| buffer.buffer(*buffer_id).is_some());
+        self.stale_semantic_tokens
+            .retain(|buffer_id| buffer.buffer(*buffer_id).is_some());
+        if self.semantic_tokens.len() != len {
+            self.update_semantic_token_highlights(cx);
+        }
+    }
+
+    /// Converts the cached semantic tokens of every excerpt into styled ranges for the display map.
+    fn update_semantic_token_highlights(&mut self, cx: &mut ViewContext<Self>) {
+        let syntax_theme = cx.theme().syntax().clone();
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut styles = HashMap::<(Arc<str>, Vec<Arc<str>>), Option<HighlightStyle>>::default();
+        let mut highlights = Vec::new();
+
+        for (excerpt_id, buffer_snapshot, excerpt_range) in snapshot.excerpts() {
+            let buffer_id = buffer_snapshot.remote_id();
+            let Some(tokens) = self.semantic_tokens.get(&buffer_id) else {
+                continue;
+            };
+
+            let context = excerpt_range.context;
+            let start_ix = tokens.partition_point(|token| {
+                token.range.end.cmp(&context.start, buffer_snapshot).is_le()
+            });
+            for token in &tokens[start_ix..] {
+                if token.range.start.cmp(&context.end, buffer_snapshot).is_ge() {
+                    break;
+                }
+
+                let Some(style) = *styles
+                    .entry((token.token_type.clone(), token.modifiers.clone()))
+                    .or_insert_with(|| semantic_token_style(token, &syntax_theme))
+                else {
+                    continue;
+                };
+
+                let start = cmp::max_by(token.range.start, context.start, |a, b| {
+                    a.cmp(b, buffer_snapshot)
+                });
+                let end = cmp::min_by(token.range.end, context.end, |a, b| {
+                    a.cmp(b, buffer_snapshot)
+                });
+                highlights.push((
+                    Anchor {
+                        buffer_id: Some(buffer_id),
+                        excerpt_id,
+                        text_anchor: start,
+                    }..Anchor {
+                        buffer_id: Some(buffer_id),
+                        excerpt_id,
+                        text_anchor: end,
+                    },
+                    style,
+                ));
+            }
+        }
+
+        self.display_map.update(cx, |display_map, _| {
+            display_map.set_semantic_token_highlights(highlights)
+        });
+        cx.notify();
+    }
+}
+
+/// Picks the most specific syntax theme style for a semantic token, e.g. a `function` token with
+/// the `defaultLibrary` modifier uses `function.builtin` if the theme defines it and `function` otherwise.
+///
+/// Returns `None` for tokens the theme has no style for, which leaves the Tree-sitter highlighting in place.
+fn semantic_token_style(token: &SemanticToken, theme: &SyntaxTheme) -> Option<HighlightStyle> {
+    let capture_name = match token.token_type.as_ref() {
+        "namespace" => "namespace",
+        "type" | "class" | "interface" | "struct" | "typeAlias" | "typeParameter" => "type",
+        "builtinType" => "type.builtin",
+        "enum" => "enum",
+        "enumMember" => "variant",
+        "parameter" => "variable.parameter",
+        "variable" => "variable",
+        "property" => "property",
+        "function" | "method" => "function",
+        "macro" => "function.special",
+        "decorator" | "attribute" => "attribute",
+        "keyword" | "modifier" => "keyword",
+        "comment" => "comment",
+        "string" => "string",
+        "number" => "number",
+        "boolean" => "boolean",
+        "regexp" => "string.regex",
+        "operator" => "operator",
+        "lifetime" => "lifetime",
+        "label" => "label",
+        _ => return None,
+    };
+
+    let mut candidates = token
+        .modifiers
+        .iter()
+        .map(|modifier| {
+            let modifier = match modifier.as_ref() {
+                "defaultLibrary" => "builtin",
+                "documentation" => "doc",
+                "declaration" => "definition",
+                modifier => modifier,
+            };
+            format!("{capture_name}.{modifier}")
+        })
+        .collect::<Vec<_>>();
+    let mut name = capture_name;
+    loop {
+        candidates.push(name.to_string());
+        match name.rsplit_once('.') {
+            Some((parent, _)) => name = parent,
+            None => break,
+        }
+    }
+
+    let mut style = candidates.iter().find_map(|candidate| {
+        let ix = theme.highlight_id(candidate)?;
+        Some(theme.highlights[ix as usize].1)
+    })?;
+    if token
+        .modifiers
+        .iter()
+        .any(|modifier| modifier.as_ref() == "deprecated")
+    {
+        style.strikethrough = Some(StrikethroughStyle {
+            thickness: 1.0.into(),
+            color: None,
+        });
+    }
+    Some(style)
+}
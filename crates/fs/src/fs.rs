@@ -1307,6 +1307,17 @@ impl FakeFs {
         });
     }
 
+    pub fn set_head_for_repo(&self, dot_git: &Path, head_state: &[(&Path, String)]) {
+        self.with_git_state(dot_git, true, |state| {
+            state.head_contents.clear();
+            state.head_contents.extend(
+                head_state
+                    .iter()
+                    .map(|(path, content)| (path.to_path_buf(), content.clone())),
+            );
+        });
+    }
+
     pub fn set_blame_for_repo(&self, dot_git: &Path, blames: Vec<(&Path, git::blame::Blame)>) {
         self.with_git_state(dot_git, true, |state| {
             state.blames.clear();
@@ -1318,6 +1329,36 @@ impl FakeFs {
         });
     }
 
+    pub fn set_file_history_for_repo(
+        &self,
+        dot_git: &Path,
+        path: &Path,
+        entries: Vec<git::history::LogEntry>,
+    ) {
+        self.with_git_state(dot_git, true, |state| {
+            state
+                .file_histories
+                .insert(git::repository::RepoPath::from(path), entries);
+        });
+    }
+
+    pub fn set_commit_diff_for_repo(
+        &self,
+        dot_git: &Path,
+        sha: git::Oid,
+        files: Vec<git::history::CommitFileDiff>,
+    ) {
+        self.with_git_state(dot_git, true, |state| {
+            state.commit_diffs.insert(sha, files);
+        });
+    }
+
+    pub fn set_revision_for_repo(&self, dot_git: &Path, revision: &str, sha: git::Oid) {
+        self.with_git_state(dot_git, true, |state| {
+            state.revisions.insert(revision.to_string(), sha);
+        });
+    }
+
     pub fn set_status_for_repo_via_working_copy_change(
         &self,
         dot_git: &Path,
@@ -1964,7 +2005,14 @@ impl Fs for FakeFs {
                     )))
                 })
                 .clone();
-            Some(git::repository::FakeGitRepository::open(state))
+            let this = self.this.clone();
+            Some(git::repository::FakeGitRepository::open(
+                state,
+                Arc::new(move |abs_path| {
+                    let content = this.upgrade()?.read_file_sync(abs_path).ok()?;
+                    String::from_utf8(content).ok()
+                }),
+            ))
         } else {
             None
         }
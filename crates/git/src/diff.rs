@@ -286,6 +286,103 @@ impl BufferDiff {
     }
 }
 
+/// Returns `text` with its changes relative to `base` reverted, in the hunks that intersect
+/// the given row ranges of `text`.
+///
+/// Used to unstage parts of a file, with the file's committed text as `base` and its index text as `text`.
+pub fn revert_rows(base: &str, text: &str, rows: &[Range<u32>]) -> String {
+    let Some(patch) = BufferDiff::diff(base, text) else {
+        return text.to_string();
+    };
+    let base_lines = base.split_inclusive('\n').collect::<Vec<_>>();
+    let text_lines = text.split_inclusive('\n').collect::<Vec<_>>();
+    let mut result = String::with_capacity(text.len());
+    let mut text_row = 0;
+    for hunk_index in 0..patch.num_hunks() {
+        let Ok((hunk, _)) = patch.hunk(hunk_index) else {
+            continue;
+        };
+        let base_range = hunk_row_range(hunk.old_start(), hunk.old_lines());
+        let text_range = hunk_row_range(hunk.new_start(), hunk.new_lines());
+        let intersects_rows = rows.iter().any(|rows| {
+            let rows = rows.start as usize..(rows.end as usize).max(rows.start as usize + 1);
+            rows.start < text_range.end.max(text_range.start + 1) && text_range.start < rows.end
+        });
+        if !intersects_rows || text_range.start < text_row {
+            continue;
+        }
+
+        let text_range =
+            text_range.start.min(text_lines.len())..text_range.end.min(text_lines.len());
+        let base_range =
+            base_range.start.min(base_lines.len())..base_range.end.min(base_lines.len());
+        result.extend(&text_lines[text_row..text_range.start]);
+        result.extend(&base_lines[base_range]);
+        text_row = text_range.end;
+    }
+    result.extend(&text_lines[text_row.min(text_lines.len())..]);
+    result
+}
+
+/// Returns the rows of `base` that the given rows of `text` correspond to, or `None` if all
+/// of those rows were added in `text`.
+///
+/// Rows in a changed hunk correspond to all the rows that hunk replaced in `base`.
+pub fn base_rows_for_rows(base: &str, text: &str, rows: Range<u32>) -> Option<Range<u32>> {
+    if rows.is_empty() {
+        return None;
+    }
+    let hunks = BufferDiff::diff(base, text)
+        .map(|patch| {
+            (0..patch.num_hunks())
+                .filter_map(|hunk_index| {
+                    let (hunk, _) = patch.hunk(hunk_index).ok()?;
+                    Some((
+                        hunk_row_range(hunk.old_start(), hunk.old_lines()),
+                        hunk_row_range(hunk.new_start(), hunk.new_lines()),
+                    ))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    // Maps a row of `text` to the first row of `base` it corresponds to when `is_end` is
+    // false, or to the row following the last row it corresponds to when `is_end` is true.
+    let base_row = |row: usize, is_end: bool| {
+        let mut base_row_delta = 0;
+        for (base_range, text_range) in &hunks {
+            if row < text_range.start {
+                break;
+            }
+            if row < text_range.end {
+                return if is_end {
+                    base_range.end
+                } else {
+                    base_range.start
+                };
+            }
+            base_row_delta = base_range.end as isize - text_range.end as isize;
+        }
+        (row as isize + base_row_delta) as usize + is_end as usize
+    };
+
+    let start = base_row(rows.start as usize, false);
+    let end = base_row(rows.end as usize - 1, true);
+    (start < end).then(|| start as u32..end as u32)
+}
+
+/// Converts the 1-based start and length of a hunk's side to a 0-based range of rows.
+fn hunk_row_range(start: u32, len: u32) -> Range<usize> {
+    let start = start as usize;
+    let len = len as usize;
+    // Hunks without lines start after the given line, instead of at it.
+    if len == 0 {
+        start..start
+    } else {
+        start - 1..start - 1 + len
+    }
+}
+
 /// Range (crossing new lines), old, new
 #[cfg(any(test, feature = "test-support"))]
 #[track_caller]
@@ -421,4 +518,80 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_revert_rows() {
+        let base = "
+            one
+            two
+            three
+            four
+            five
+        "
+        .unindent();
+        let text = "
+            one
+            TWO
+            three
+            five
+            six
+        "
+        .unindent();
+
+        assert_eq!(
+            revert_rows(&base, &text, &[1..2]),
+            "
+            one
+            two
+            three
+            five
+            six
+        "
+            .unindent()
+        );
+        assert_eq!(
+            revert_rows(&base, &text, &[3..3]),
+            "
+            one
+            TWO
+            three
+            four
+            five
+            six
+        "
+            .unindent()
+        );
+        assert_eq!(revert_rows(&base, &text, &[0..5]), base);
+        assert_eq!(revert_rows(&base, &text, &[]), text);
+    }
+
+    #[test]
+    fn test_base_rows_for_rows() {
+        let base = "
+            one
+            two
+            three
+            four
+            five
+        "
+        .unindent();
+        let text = "
+            zero
+            one
+            TWO
+            three
+            five
+            six
+        "
+        .unindent();
+
+        assert_eq!(base_rows_for_rows(&base, &text, 0..1), None);
+        assert_eq!(base_rows_for_rows(&base, &text, 1..2), Some(0..1));
+        assert_eq!(base_rows_for_rows(&base, &text, 2..3), Some(1..2));
+        assert_eq!(base_rows_for_rows(&base, &text, 3..5), Some(2..5));
+        assert_eq!(base_rows_for_rows(&base, &text, 4..5), Some(4..5));
+        assert_eq!(base_rows_for_rows(&base, &text, 5..6), None);
+        assert_eq!(base_rows_for_rows(&base, &text, 0..6), Some(0..5));
+        assert_eq!(base_rows_for_rows(&base, &base, 1..3), Some(1..3));
+    }
 }
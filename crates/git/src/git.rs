@@ -1,9 +1,11 @@
 pub mod blame;
 pub mod commit;
 pub mod diff;
+pub mod history;
 mod hosting_provider;
 mod remote;
 pub mod repository;
+pub mod stash;
 pub mod status;
 
 use anyhow::{anyhow, Context, Result};
@@ -0,0 +1,195 @@
+use crate::{repository::RepoPath, Oid};
+use anyhow::{anyhow, Context, Result};
+use std::{ops::Range, path::Path, process::Stdio};
+
+/// A commit that changed a file, or a range of lines in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub sha: Oid,
+    pub author_name: String,
+    pub author_email: String,
+    /// The author date, as a Unix timestamp.
+    pub author_time: i64,
+    pub summary: String,
+    /// The path of the file in this commit, which differs from its current path
+    /// if the file was renamed since.
+    pub path: RepoPath,
+}
+
+/// The contents of a file before and after a commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitFileDiff {
+    pub path: RepoPath,
+    /// `None` if the commit added the file.
+    pub old_text: Option<String>,
+    /// `None` if the commit deleted the file.
+    pub new_text: Option<String>,
+}
+
+const LOG_ENTRY_MARKER: char = '\x1e';
+const LOG_FIELD_SEPARATOR: char = '\x1f';
+const GIT_LOG_NO_COMMIT_ERROR: &str = "does not have any commits yet";
+
+/// Lists the commits that changed the given path, newest first.
+/// If `rows` is given, only the commits that changed those zero-based rows of the file
+/// as it is in HEAD are listed, like `git log -L`.
+pub(crate) fn file_history(
+    git_binary: &Path,
+    working_directory: &Path,
+    path: &Path,
+    rows: Option<Range<u32>>,
+) -> Result<Vec<LogEntry>> {
+    let mut command = util::command::new_std_command(git_binary);
+    command.current_dir(working_directory).args([
+        "-c",
+        "core.quotePath=false",
+        "--no-optional-locks",
+        "log",
+        "--no-color",
+        &format!("--format={LOG_ENTRY_MARKER}%H{LOG_FIELD_SEPARATOR}%an{LOG_FIELD_SEPARATOR}%ae{LOG_FIELD_SEPARATOR}%at{LOG_FIELD_SEPARATOR}%s"),
+    ]);
+    let has_patches = rows.is_some();
+    match rows {
+        Some(rows) => {
+            // `git log -L` takes one-based, inclusive line numbers, and always prints patches.
+            command.arg(format!(
+                "-L{},{}:{}",
+                rows.start + 1,
+                rows.end.max(rows.start + 1),
+                path.display()
+            ));
+        }
+        None => {
+            // `--name-only` prints the file's path in every commit, which `--follow` tracks across renames.
+            command
+                .args(["--follow", "--name-only", "--"])
+                .arg(path.as_os_str());
+        }
+    }
+
+    let output = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|e| anyhow!("Failed to start git log process: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains(GIT_LOG_NO_COMMIT_ERROR) {
+            return Ok(Vec::new());
+        }
+        return Err(anyhow!("git log process failed: {}", stderr));
+    }
+
+    parse_git_log(&String::from_utf8_lossy(&output.stdout), path, has_patches)
+}
+
+/// Parses the entries printed by `git log`, followed either by the names of the changed files
+/// or by patches. The path of each entry is taken from those, defaulting to `path`.
+fn parse_git_log(output: &str, path: &Path, has_patches: bool) -> Result<Vec<LogEntry>> {
+    let mut entries = Vec::new();
+    let mut entry_has_path = false;
+    for line in output.lines() {
+        if let Some(line) = line.strip_prefix(LOG_ENTRY_MARKER) {
+            let mut fields = line.splitn(5, LOG_FIELD_SEPARATOR);
+            let mut next_field = |name: &str| {
+                fields
+                    .next()
+                    .with_context(|| format!("missing {name} in git log entry {line:?}"))
+            };
+            entries.push(LogEntry {
+                sha: next_field("sha")?.parse()?,
+                author_name: next_field("author name")?.to_string(),
+                author_email: next_field("author email")?.to_string(),
+                author_time: next_field("author time")?
+                    .parse()
+                    .context("failed to parse author time")?,
+                summary: next_field("summary")?.to_string(),
+                path: path.into(),
+            });
+            entry_has_path = false;
+            continue;
+        }
+
+        let Some(entry) = entries.last_mut() else {
+            continue;
+        };
+        let entry_path = if has_patches {
+            line.strip_prefix("+++ b/")
+        } else {
+            Some(line).filter(|line| !line.is_empty())
+        };
+        if let Some(entry_path) = entry_path.filter(|_| !entry_has_path) {
+            entry.path = Path::new(entry_path).into();
+            entry_has_path = true;
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_log() {
+        let output = "\
+\x1e8ae8d3b3f0b5a3e64c4a4e5e62a3cb5ad6b0a9d1\x1fJane Doe\x1fjane@example.com\x1f1700000000\x1fFix the parser
+diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -2,1 +2,1 @@
+-    old();
++    new();
+\x1e0f1e2d3c4b5a69788796a5b4c3d2e1f0a1b2c3d4\x1fJohn Roe\x1fjohn@example.com\x1f1600000000\x1fInitial commit: add \x1f separators
+";
+        let entries = parse_git_log(output, Path::new("src/main.rs"), true).unwrap();
+        assert_eq!(
+            entries,
+            [
+                LogEntry {
+                    sha: "8ae8d3b3f0b5a3e64c4a4e5e62a3cb5ad6b0a9d1".parse().unwrap(),
+                    author_name: "Jane Doe".into(),
+                    author_email: "jane@example.com".into(),
+                    author_time: 1700000000,
+                    summary: "Fix the parser".into(),
+                    path: Path::new("src/main.rs").into(),
+                },
+                LogEntry {
+                    sha: "0f1e2d3c4b5a69788796a5b4c3d2e1f0a1b2c3d4".parse().unwrap(),
+                    author_name: "John Roe".into(),
+                    author_email: "john@example.com".into(),
+                    author_time: 1600000000,
+                    summary: "Initial commit: add \x1f separators".into(),
+                    path: Path::new("src/main.rs").into(),
+                },
+            ]
+        );
+        assert!(parse_git_log("\x1enot-a-sha\x1fa\x1fb\x1f0\x1fc", Path::new("a"), false).is_err());
+    }
+
+    #[test]
+    fn test_parse_git_log_with_renames() {
+        let output = "\
+\x1e8ae8d3b3f0b5a3e64c4a4e5e62a3cb5ad6b0a9d1\x1fJane Doe\x1fjane@example.com\x1f1700000000\x1fRename the parser
+
+src/parser.rs
+\x1e0f1e2d3c4b5a69788796a5b4c3d2e1f0a1b2c3d4\x1fJohn Roe\x1fjohn@example.com\x1f1600000000\x1fAdd the parser
+
+src/parse.rs
+";
+        let paths = parse_git_log(output, Path::new("src/parser.rs"), false)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                RepoPath::from(Path::new("src/parser.rs")),
+                RepoPath::from(Path::new("src/parse.rs")),
+            ]
+        );
+    }
+}
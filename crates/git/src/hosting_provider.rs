@@ -27,6 +27,40 @@ pub struct BuildPermalinkParams<'a> {
     pub selection: Option<Range<u32>>,
 }
 
+pub struct BuildCreatePullRequestParams<'a> {
+    /// The branch, as named on the remote, that the pull request should be opened from.
+    pub branch: &'a str,
+}
+
+pub struct BuildCompareParams<'a> {
+    pub base: &'a str,
+    pub head: &'a str,
+}
+
+/// Percent-encodes a branch name for the path of a URL. The slashes separating the parts
+/// of the name are kept, as that is how hosting providers link to such branches.
+pub fn encode_branch_for_url_path(branch: &str) -> String {
+    let mut encoded = String::with_capacity(branch.len());
+    for byte in branch.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// A page of a Git hosting provider about a branch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchUrlKind {
+    /// The page for opening a pull request from the branch.
+    CreatePullRequest,
+    /// The comparison of the branch with the default branch of the repository.
+    Compare,
+}
+
 /// A Git hosting provider.
 #[async_trait]
 pub trait GitHostingProvider {
@@ -71,6 +105,26 @@ pub trait GitHostingProvider {
 
     fn parse_remote_url(&self, url: &str) -> Option<ParsedGitRemote>;
 
+    /// Returns a URL for opening a pull request from the given branch on this hosting provider,
+    /// or `None` if the provider has no such page.
+    fn build_create_pull_request_url(
+        &self,
+        _remote: &ParsedGitRemote,
+        _params: BuildCreatePullRequestParams,
+    ) -> Option<Url> {
+        None
+    }
+
+    /// Returns a URL comparing two refs on this hosting provider, or `None` if the provider
+    /// has no such page.
+    fn build_compare_url(
+        &self,
+        _remote: &ParsedGitRemote,
+        _params: BuildCompareParams,
+    ) -> Option<Url> {
+        None
+    }
+
     fn extract_pull_request(
         &self,
         _remote: &ParsedGitRemote,
@@ -157,6 +211,11 @@ impl GitHostingProviderRegistry {
             .providers
             .insert(provider.name(), provider);
     }
+
+    /// Removes the [`GitHostingProvider`] with the given name from the registry.
+    pub fn unregister_hosting_provider(&self, name: &str) {
+        self.state.write().providers.remove(name);
+    }
 }
 
 #[derive(Debug, PartialEq)]
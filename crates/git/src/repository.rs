@@ -1,5 +1,11 @@
 use crate::GitHostingProviderRegistry;
-use crate::{blame::Blame, status::GitStatus};
+use crate::{
+    blame::Blame,
+    history::{CommitFileDiff, LogEntry},
+    stash::{StashAction, StashEntry},
+    status::{GitStatus, StageStatus},
+    Oid,
+};
 use anyhow::{Context, Result};
 use collections::{HashMap, HashSet};
 use git2::BranchType;
@@ -9,12 +15,23 @@ use rope::Rope;
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
+    ops::Range,
     path::{Component, Path, PathBuf},
+    process::Stdio,
     sync::Arc,
 };
 use sum_tree::MapSeekTarget;
 use util::ResultExt;
 
+/// The message of the error returned when switching branches would overwrite uncommitted changes.
+/// It is matched by message, as that is all that is left of the errors of remote repositories.
+const DIRTY_WORKTREE_ERROR: &str = "uncommitted changes would be overwritten by checkout";
+
+/// Whether switching branches failed because it would overwrite uncommitted changes.
+pub fn is_dirty_worktree_error(error: &anyhow::Error) -> bool {
+    format!("{error:#}").contains(DIRTY_WORKTREE_ERROR)
+}
+
 #[derive(Clone, Debug, Hash, PartialEq)]
 pub struct Branch {
     pub is_head: bool,
@@ -30,15 +47,46 @@ pub trait GitRepository: Send + Sync {
     /// Note that for symlink entries, this will return the contents of the symlink, not the target.
     fn load_index_text(&self, relative_file_path: &Path) -> Option<String>;
 
+    /// Loads a git repository entry's contents in the HEAD commit.
+    /// Note that for symlink entries, this will return the contents of the symlink, not the target.
+    fn load_committed_text(&self, relative_file_path: &Path) -> Option<String>;
+
     /// Returns the URL of the remote with the given name.
     fn remote_url(&self, name: &str) -> Option<String>;
     fn branch_name(&self) -> Option<String>;
 
+    /// Returns the name of the default branch of the remote with the given name, as last fetched.
+    fn remote_default_branch(&self, remote: &str) -> Option<String>;
+
+    /// Returns the name on the given remote of the branch that the current branch tracks,
+    /// or `None` if the current branch does not track a branch of that remote.
+    fn upstream_branch_name(&self, remote: &str) -> Option<String>;
+
     /// Returns the SHA of the current HEAD.
     fn head_sha(&self) -> Option<String>;
 
     fn status(&self, path_prefixes: &[PathBuf]) -> Result<GitStatus>;
 
+    /// Returns whether the changes of the changed files are staged in the index.
+    fn stage_statuses(&self, path_prefixes: &[PathBuf]) -> Result<Vec<(RepoPath, StageStatus)>>;
+
+    /// Updates the index to match the working tree for the given paths, removing deleted files from it.
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Resets the index entries of the given paths to HEAD.
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Restores the given paths in both the index and the working tree to HEAD.
+    /// Files that are not in HEAD are removed from the index and deleted.
+    fn discard_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Sets the contents of a path in the index, or removes it from the index if `content` is `None`.
+    /// Used to stage or unstage parts of a file.
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()>;
+
+    /// Commits the staged changes with the given message.
+    fn commit(&self, message: &str) -> Result<()>;
+
     fn branches(&self) -> Result<Vec<Branch>>;
     fn change_branch(&self, _: &str) -> Result<()>;
     fn create_branch(&self, _: &str) -> Result<()>;
@@ -46,6 +94,42 @@ pub trait GitRepository: Send + Sync {
 
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame>;
 
+    /// Lists the commits that changed the given path, newest first.
+    /// If `rows` is given, only the commits that changed those rows of the file in HEAD are listed.
+    fn file_history(&self, path: &RepoPath, rows: Option<Range<u32>>) -> Result<Vec<LogEntry>>;
+
+    /// Loads the contents of a file in the given commit.
+    /// Returns `None` if the file does not exist in that commit.
+    fn load_text_at_commit(&self, sha: Oid, path: &RepoPath) -> Result<Option<String>>;
+
+    /// Returns the files changed by the given commit, relative to its first parent.
+    fn commit_diff(&self, sha: Oid) -> Result<Vec<CommitFileDiff>>;
+
+    /// Resolves a revision, such as a branch, a tag or a commit SHA, to a commit.
+    fn resolve_revision(&self, revision: &str) -> Result<Oid>;
+
+    /// Returns the best common ancestor of HEAD and the given revision.
+    fn merge_base(&self, revision: &str) -> Result<Oid>;
+
+    /// Returns the files whose contents in the working tree differ from the given commit,
+    /// including untracked files.
+    fn changed_paths_since(&self, sha: Oid) -> Result<Vec<RepoPath>>;
+
+    /// Lists the entries of the stash, newest first.
+    fn stash_list(&self) -> Result<Vec<StashEntry>>;
+
+    /// Stashes the changes of the working tree and the index, including untracked files.
+    fn stash_push(&self, message: Option<&str>) -> Result<()>;
+
+    /// Applies the given stash entry and removes it from the stash.
+    fn stash_pop(&self, sha: Oid) -> Result<()>;
+
+    /// Applies the given stash entry, keeping it in the stash.
+    fn stash_apply(&self, sha: Oid) -> Result<()>;
+
+    /// Removes the given stash entry without applying it.
+    fn stash_drop(&self, sha: Oid) -> Result<()>;
+
     /// Returns the path to the repository, typically the `.git` folder.
     fn dot_git_dir(&self) -> PathBuf;
 }
@@ -74,6 +158,14 @@ impl RealGitRepository {
             hosting_provider_registry,
         }
     }
+
+    fn working_directory(&self) -> Result<PathBuf> {
+        self.repository
+            .lock()
+            .workdir()
+            .context("failed to read git work directory")
+            .map(Path::to_path_buf)
+    }
 }
 
 // https://git-scm.com/book/en/v2/Git-Internals-Git-Objects
@@ -115,6 +207,30 @@ impl GitRepository for RealGitRepository {
         None
     }
 
+    fn load_committed_text(&self, relative_file_path: &Path) -> Option<String> {
+        fn logic(repo: &git2::Repository, relative_file_path: &Path) -> Result<Option<String>> {
+            check_path_to_repo_path_errors(relative_file_path)?;
+
+            let Ok(head) = repo.head() else {
+                return Ok(None);
+            };
+            let tree = head.peel_to_tree()?;
+            let entry = match tree.get_path(relative_file_path) {
+                Ok(entry) if entry.filemode() != GIT_MODE_SYMLINK as i32 => entry,
+                _ => return Ok(None),
+            };
+
+            let content = repo.find_blob(entry.id())?.content().to_owned();
+            Ok(Some(String::from_utf8(content)?))
+        }
+
+        match logic(&self.repository.lock(), relative_file_path) {
+            Ok(value) => return value,
+            Err(err) => log::error!("Error loading committed text: {:?}", err),
+        }
+        None
+    }
+
     fn remote_url(&self, name: &str) -> Option<String> {
         let repo = self.repository.lock();
         let remote = repo.find_remote(name).ok()?;
@@ -128,18 +244,183 @@ impl GitRepository for RealGitRepository {
         Some(branch.to_string())
     }
 
+    fn remote_default_branch(&self, remote: &str) -> Option<String> {
+        let repo = self.repository.lock();
+        let remote_prefix = format!("refs/remotes/{remote}/");
+        if let Ok(remote_head) = repo.find_reference(&format!("{remote_prefix}HEAD")) {
+            if let Some(target) = remote_head.symbolic_target() {
+                return target.strip_prefix(&remote_prefix).map(str::to_string);
+            }
+        }
+
+        // `<remote>/HEAD` is only set up by `git clone`, so fall back to the usual names.
+        ["main", "master"].into_iter().find_map(|branch| {
+            repo.find_reference(&format!("{remote_prefix}{branch}"))
+                .ok()
+                .map(|_| branch.to_string())
+        })
+    }
+
+    fn upstream_branch_name(&self, remote: &str) -> Option<String> {
+        let repo = self.repository.lock();
+        let head = repo.head().ok()?;
+        let branch = head.name()?;
+        let upstream_remote = repo.branch_upstream_remote(branch).ok()?;
+        if upstream_remote.as_str()? != remote {
+            return None;
+        }
+        let upstream_branch = repo.branch_upstream_merge(branch).ok()?;
+        upstream_branch
+            .as_str()?
+            .strip_prefix("refs/heads/")
+            .map(str::to_string)
+    }
+
     fn head_sha(&self) -> Option<String> {
         Some(self.repository.lock().head().ok()?.target()?.to_string())
     }
 
     fn status(&self, path_prefixes: &[PathBuf]) -> Result<GitStatus> {
-        let working_directory = self
-            .repository
-            .lock()
+        let working_directory = self.working_directory()?;
+        GitStatus::new(&self.git_binary_path, &working_directory, path_prefixes)
+    }
+
+    fn stage_statuses(&self, path_prefixes: &[PathBuf]) -> Result<Vec<(RepoPath, StageStatus)>> {
+        let working_directory = self.working_directory()?;
+        crate::status::stage_statuses(&self.git_binary_path, &working_directory, path_prefixes)
+    }
+
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let repo = self.repository.lock();
+        let working_directory = repo
             .workdir()
             .context("failed to read git work directory")?
             .to_path_buf();
-        GitStatus::new(&self.git_binary_path, &working_directory, path_prefixes)
+        let mut index = repo.index()?;
+        for path in paths {
+            check_path_to_repo_path_errors(path)?;
+            if working_directory.join(path).symlink_metadata().is_ok() {
+                index.add_path(path)?;
+            } else {
+                index.remove_path(path)?;
+            }
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let repo = self.repository.lock();
+        for path in paths {
+            check_path_to_repo_path_errors(path)?;
+        }
+        // Without a HEAD commit, resetting removes the paths from the index.
+        let head = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        repo.reset_default(
+            head.as_ref().map(|head| head.as_object()),
+            paths.iter().map(|path| path.as_path()),
+        )?;
+        Ok(())
+    }
+
+    fn discard_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let repo = self.repository.lock();
+        let working_directory = repo
+            .workdir()
+            .context("failed to read git work directory")?
+            .to_path_buf();
+        let head = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let head_tree = head.as_ref().map(|head| head.tree()).transpose()?;
+
+        let mut index = repo.index()?;
+        let mut committed_paths = Vec::new();
+        for path in paths {
+            check_path_to_repo_path_errors(path)?;
+            if head_tree
+                .as_ref()
+                .is_some_and(|tree| tree.get_path(path).is_ok())
+            {
+                committed_paths.push(path);
+            } else {
+                if index.get_path(path, 0).is_some() {
+                    index.remove_path(path)?;
+                }
+                let abs_path = working_directory.join(path);
+                if abs_path.symlink_metadata().is_ok() {
+                    std::fs::remove_file(&abs_path)
+                        .with_context(|| format!("failed to delete {abs_path:?}"))?;
+                }
+            }
+        }
+        index.write()?;
+
+        if let Some(head) = head.filter(|_| !committed_paths.is_empty()) {
+            repo.reset_default(
+                Some(head.as_object()),
+                committed_paths.iter().map(|path| path.as_path()),
+            )?;
+            let mut checkout = git2::build::CheckoutBuilder::new();
+            checkout.force();
+            for path in committed_paths {
+                checkout.path(path.as_path());
+            }
+            repo.checkout_head(Some(&mut checkout))?;
+        }
+        Ok(())
+    }
+
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()> {
+        const STAGE_NORMAL: i32 = 0;
+        const GIT_MODE_FILE: u32 = 0o100644;
+
+        let repo = self.repository.lock();
+        check_path_to_repo_path_errors(path)?;
+        let mut index = repo.index()?;
+        match content {
+            Some(content) => {
+                let mode = index
+                    .get_path(path, STAGE_NORMAL)
+                    .map_or(GIT_MODE_FILE, |entry| entry.mode);
+                let entry = git2::IndexEntry {
+                    ctime: git2::IndexTime::new(0, 0),
+                    mtime: git2::IndexTime::new(0, 0),
+                    dev: 0,
+                    ino: 0,
+                    mode,
+                    uid: 0,
+                    gid: 0,
+                    file_size: content.len() as u32,
+                    id: git2::Oid::zero(),
+                    flags: 0,
+                    flags_extended: 0,
+                    path: path
+                        .iter()
+                        .map(|component| component.to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/")
+                        .into_bytes(),
+                };
+                index.add_frombuffer(&entry, content.as_bytes())?;
+            }
+            None => index.remove_path(path)?,
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    fn commit(&self, message: &str) -> Result<()> {
+        let working_directory = self.working_directory()?;
+        let output = util::command::new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .args(["commit", "--quiet", "--message", message])
+            .stdin(Stdio::null())
+            .output()
+            .context("failed to start git commit process")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("git commit failed: {}", stderr.trim());
+        }
+        Ok(())
     }
 
     fn branch_exits(&self, name: &str) -> Result<bool> {
@@ -189,7 +470,14 @@ impl GitRepository for RealGitRepository {
         let revision = repo.find_branch(name, BranchType::Local)?;
         let revision = revision.get();
         let as_tree = revision.peel_to_tree()?;
-        repo.checkout_tree(as_tree.as_object(), None)?;
+        repo.checkout_tree(as_tree.as_object(), None)
+            .map_err(|error| {
+                if error.code() == git2::ErrorCode::Conflict {
+                    anyhow::anyhow!("{DIRTY_WORKTREE_ERROR}: {}", error.message())
+                } else {
+                    error.into()
+                }
+            })?;
         repo.set_head(
             revision
                 .name()
@@ -225,11 +513,137 @@ impl GitRepository for RealGitRepository {
             self.hosting_provider_registry.clone(),
         )
     }
+
+    fn file_history(&self, path: &RepoPath, rows: Option<Range<u32>>) -> Result<Vec<LogEntry>> {
+        let working_directory = self.working_directory()?;
+        crate::history::file_history(&self.git_binary_path, &working_directory, path, rows)
+    }
+
+    fn load_text_at_commit(&self, sha: Oid, path: &RepoPath) -> Result<Option<String>> {
+        check_path_to_repo_path_errors(path)?;
+        let repo = self.repository.lock();
+        let tree = repo.find_commit(sha.0)?.tree()?;
+        let entry = match tree.get_path(path) {
+            Ok(entry) => entry,
+            Err(error) if error.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let content = repo.find_blob(entry.id())?.content().to_owned();
+        Ok(Some(String::from_utf8(content)?))
+    }
+
+    fn commit_diff(&self, sha: Oid) -> Result<Vec<CommitFileDiff>> {
+        let repo = self.repository.lock();
+        let commit = repo.find_commit(sha.0)?;
+        let new_tree = commit.tree()?;
+        let old_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)?;
+
+        let load_blob = |id: git2::Oid| -> Result<Option<String>> {
+            if id.is_zero() {
+                return Ok(None);
+            }
+            let content = repo.find_blob(id)?.content().to_owned();
+            // Binary files are shown as empty.
+            Ok(Some(String::from_utf8(content).unwrap_or_default()))
+        };
+        diff.deltas()
+            .filter_map(|delta| {
+                let path = delta.new_file().path().or(delta.old_file().path())?;
+                Some((RepoPath::from(path), delta))
+            })
+            .map(|(path, delta)| {
+                Ok(CommitFileDiff {
+                    path,
+                    old_text: load_blob(delta.old_file().id())?,
+                    new_text: load_blob(delta.new_file().id())?,
+                })
+            })
+            .collect()
+    }
+
+    fn resolve_revision(&self, revision: &str) -> Result<Oid> {
+        let repo = self.repository.lock();
+        let commit = repo
+            .revparse_single(revision)
+            .with_context(|| format!("unknown revision {revision:?}"))?
+            .peel_to_commit()?;
+        Ok(Oid(commit.id()))
+    }
+
+    fn merge_base(&self, revision: &str) -> Result<Oid> {
+        let head = self.resolve_revision("HEAD")?;
+        let other = self.resolve_revision(revision)?;
+        let merge_base = self.repository.lock().merge_base(head.0, other.0)?;
+        Ok(Oid(merge_base))
+    }
+
+    fn changed_paths_since(&self, sha: Oid) -> Result<Vec<RepoPath>> {
+        let repo = self.repository.lock();
+        let tree = repo.find_commit(sha.0)?.tree()?;
+        let mut options = git2::DiffOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+        Ok(diff
+            .deltas()
+            .filter_map(|delta| {
+                let path = delta.new_file().path().or(delta.old_file().path())?;
+                Some(RepoPath::from(path))
+            })
+            .collect())
+    }
+
+    fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        let working_directory = self.working_directory()?;
+        crate::stash::stash_list(&self.git_binary_path, &working_directory)
+    }
+
+    fn stash_push(&self, message: Option<&str>) -> Result<()> {
+        let working_directory = self.working_directory()?;
+        crate::stash::stash_push(&self.git_binary_path, &working_directory, message)
+    }
+
+    fn stash_pop(&self, sha: Oid) -> Result<()> {
+        let working_directory = self.working_directory()?;
+        crate::stash::stash_entry_action(
+            &self.git_binary_path,
+            &working_directory,
+            sha,
+            StashAction::Pop,
+        )
+    }
+
+    fn stash_apply(&self, sha: Oid) -> Result<()> {
+        let working_directory = self.working_directory()?;
+        crate::stash::stash_entry_action(
+            &self.git_binary_path,
+            &working_directory,
+            sha,
+            StashAction::Apply,
+        )
+    }
+
+    fn stash_drop(&self, sha: Oid) -> Result<()> {
+        let working_directory = self.working_directory()?;
+        crate::stash::stash_entry_action(
+            &self.git_binary_path,
+            &working_directory,
+            sha,
+            StashAction::Drop,
+        )
+    }
 }
 
-#[derive(Debug, Clone)]
+/// Reads a file of the working copy, given its absolute path.
+pub type FakeWorkingCopyLoader = Arc<dyn Fn(&Path) -> Option<String> + Send + Sync>;
+
+#[derive(Clone)]
 pub struct FakeGitRepository {
     state: Arc<Mutex<FakeGitRepositoryState>>,
+    load_working_copy_file: FakeWorkingCopyLoader,
 }
 
 #[derive(Debug, Clone)]
@@ -237,26 +651,60 @@ pub struct FakeGitRepositoryState {
     pub dot_git_dir: PathBuf,
     pub event_emitter: smol::channel::Sender<PathBuf>,
     pub index_contents: HashMap<PathBuf, String>,
+    pub head_contents: HashMap<PathBuf, String>,
     pub blames: HashMap<PathBuf, Blame>,
     pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
+    pub staged_paths: HashSet<RepoPath>,
+    pub commit_messages: Vec<String>,
+    pub file_histories: HashMap<RepoPath, Vec<LogEntry>>,
+    pub commit_diffs: HashMap<Oid, Vec<CommitFileDiff>>,
+    pub revisions: HashMap<String, Oid>,
+    /// The stash entries, newest first.
+    pub stashes: Vec<FakeStash>,
     pub current_branch_name: Option<String>,
     pub branches: HashSet<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct FakeStash {
+    pub sha: Oid,
+    pub message: String,
+    pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
+}
+
 impl FakeGitRepository {
-    pub fn open(state: Arc<Mutex<FakeGitRepositoryState>>) -> Arc<dyn GitRepository> {
-        Arc::new(FakeGitRepository { state })
+    pub fn open(
+        state: Arc<Mutex<FakeGitRepositoryState>>,
+        load_working_copy_file: FakeWorkingCopyLoader,
+    ) -> Arc<dyn GitRepository> {
+        Arc::new(FakeGitRepository {
+            state,
+            load_working_copy_file,
+        })
     }
 }
 
 impl FakeGitRepositoryState {
+    fn emit_event(&self) {
+        self.event_emitter
+            .try_send(self.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+    }
+
     pub fn new(dot_git_dir: PathBuf, event_emitter: smol::channel::Sender<PathBuf>) -> Self {
         FakeGitRepositoryState {
             dot_git_dir,
             event_emitter,
             index_contents: Default::default(),
+            head_contents: Default::default(),
             blames: Default::default(),
             worktree_statuses: Default::default(),
+            staged_paths: Default::default(),
+            commit_messages: Default::default(),
+            file_histories: Default::default(),
+            commit_diffs: Default::default(),
+            revisions: Default::default(),
+            stashes: Default::default(),
             current_branch_name: Default::default(),
             branches: Default::default(),
         }
@@ -271,6 +719,11 @@ impl GitRepository for FakeGitRepository {
         state.index_contents.get(path).cloned()
     }
 
+    fn load_committed_text(&self, path: &Path) -> Option<String> {
+        let state = self.state.lock();
+        state.head_contents.get(path).cloned()
+    }
+
     fn remote_url(&self, _name: &str) -> Option<String> {
         None
     }
@@ -280,6 +733,14 @@ impl GitRepository for FakeGitRepository {
         state.current_branch_name.clone()
     }
 
+    fn remote_default_branch(&self, _remote: &str) -> Option<String> {
+        None
+    }
+
+    fn upstream_branch_name(&self, _remote: &str) -> Option<String> {
+        None
+    }
+
     fn head_sha(&self) -> Option<String> {
         None
     }
@@ -311,6 +772,104 @@ impl GitRepository for FakeGitRepository {
         })
     }
 
+    fn stage_statuses(&self, path_prefixes: &[PathBuf]) -> Result<Vec<(RepoPath, StageStatus)>> {
+        let state = self.state.lock();
+        let mut stage_statuses = state
+            .worktree_statuses
+            .keys()
+            .filter(|repo_path| {
+                path_prefixes
+                    .iter()
+                    .any(|path_prefix| repo_path.0.starts_with(path_prefix))
+            })
+            .map(|repo_path| {
+                let stage_status = if state.staged_paths.contains(repo_path) {
+                    StageStatus::Staged
+                } else {
+                    StageStatus::Unstaged
+                };
+                (repo_path.clone(), stage_status)
+            })
+            .collect::<Vec<_>>();
+        stage_statuses.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        Ok(stage_statuses)
+    }
+
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let working_directory = self
+            .state
+            .lock()
+            .dot_git_dir
+            .parent()
+            .map(Path::to_path_buf);
+        let contents = paths
+            .iter()
+            .map(|path| {
+                let abs_path = working_directory.as_ref()?.join(&path.0);
+                (self.load_working_copy_file)(&abs_path)
+            })
+            .collect::<Vec<_>>();
+
+        let mut state = self.state.lock();
+        for (path, content) in paths.iter().zip(contents) {
+            match content {
+                Some(content) => state.index_contents.insert(path.0.clone(), content),
+                None => state.index_contents.remove(&path.0),
+            };
+        }
+        state.staged_paths.extend(paths.iter().cloned());
+        state.emit_event();
+        Ok(())
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for path in paths {
+            state.staged_paths.remove(path);
+        }
+        state.emit_event();
+        Ok(())
+    }
+
+    fn discard_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for path in paths {
+            state.staged_paths.remove(path);
+            state.worktree_statuses.remove(path);
+            match state.head_contents.get(&path.0).cloned() {
+                Some(head_text) => state.index_contents.insert(path.0.clone(), head_text),
+                None => state.index_contents.remove(&path.0),
+            };
+        }
+        state.emit_event();
+        Ok(())
+    }
+
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()> {
+        let mut state = self.state.lock();
+        match content {
+            Some(content) => state.index_contents.insert(path.0.clone(), content),
+            None => state.index_contents.remove(&path.0),
+        };
+        state.emit_event();
+        Ok(())
+    }
+
+    fn commit(&self, message: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        anyhow::ensure!(!state.staged_paths.is_empty(), "nothing to commit");
+        for path in std::mem::take(&mut state.staged_paths) {
+            state.worktree_statuses.remove(&path);
+            match state.index_contents.get(&path.0).cloned() {
+                Some(index_text) => state.head_contents.insert(path.0, index_text),
+                None => state.head_contents.remove(&path.0),
+            };
+        }
+        state.commit_messages.push(message.to_string());
+        state.emit_event();
+        Ok(())
+    }
+
     fn branches(&self) -> Result<Vec<Branch>> {
         let state = self.state.lock();
         let current_branch = &state.current_branch_name;
@@ -358,6 +917,170 @@ impl GitRepository for FakeGitRepository {
             .with_context(|| format!("failed to get blame for {:?}", path))
             .cloned()
     }
+
+    fn file_history(&self, path: &RepoPath, _rows: Option<Range<u32>>) -> Result<Vec<LogEntry>> {
+        let state = self.state.lock();
+        Ok(state.file_histories.get(path).cloned().unwrap_or_default())
+    }
+
+    fn load_text_at_commit(&self, sha: Oid, path: &RepoPath) -> Result<Option<String>> {
+        let state = self.state.lock();
+        let files = state
+            .commit_diffs
+            .get(&sha)
+            .with_context(|| format!("commit {sha} not found"))?;
+        Ok(files
+            .iter()
+            .find(|file| file.path == *path)
+            .and_then(|file| file.new_text.clone()))
+    }
+
+    fn commit_diff(&self, sha: Oid) -> Result<Vec<CommitFileDiff>> {
+        let state = self.state.lock();
+        state
+            .commit_diffs
+            .get(&sha)
+            .with_context(|| format!("commit {sha} not found"))
+            .cloned()
+    }
+
+    fn resolve_revision(&self, revision: &str) -> Result<Oid> {
+        let state = self.state.lock();
+        state
+            .revisions
+            .get(revision)
+            .copied()
+            .with_context(|| format!("unknown revision {revision:?}"))
+    }
+
+    fn merge_base(&self, revision: &str) -> Result<Oid> {
+        self.resolve_revision(revision)
+    }
+
+    fn changed_paths_since(&self, sha: Oid) -> Result<Vec<RepoPath>> {
+        let state = self.state.lock();
+        let files = state
+            .commit_diffs
+            .get(&sha)
+            .with_context(|| format!("commit {sha} not found"))?;
+        Ok(files.iter().map(|file| file.path.clone()).collect())
+    }
+
+    fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        let state = self.state.lock();
+        Ok(state
+            .stashes
+            .iter()
+            .enumerate()
+            .map(|(index, stash)| StashEntry {
+                index,
+                sha: stash.sha,
+                message: stash.message.clone(),
+            })
+            .collect())
+    }
+
+    fn stash_push(&self, message: Option<&str>) -> Result<()> {
+        let mut state = self.state.lock();
+        anyhow::ensure!(
+            !state.worktree_statuses.is_empty(),
+            "No local changes to save"
+        );
+        let branch = state.current_branch_name.clone().unwrap_or_default();
+        let message = match message {
+            Some(message) => format!("On {branch}: {message}"),
+            None => format!("WIP on {branch}"),
+        };
+        let sha = Oid::from_bytes(&[state.stashes.len() as u8 + 1; 20])?;
+        let worktree_statuses = std::mem::take(&mut state.worktree_statuses);
+        state.staged_paths.clear();
+        state.stashes.insert(
+            0,
+            FakeStash {
+                sha,
+                message,
+                worktree_statuses,
+            },
+        );
+        state.emit_event();
+        Ok(())
+    }
+
+    fn stash_pop(&self, sha: Oid) -> Result<()> {
+        self.stash_apply(sha)?;
+        self.stash_drop(sha)
+    }
+
+    fn stash_apply(&self, sha: Oid) -> Result<()> {
+        let mut state = self.state.lock();
+        let stash = state
+            .stashes
+            .iter()
+            .find(|stash| stash.sha == sha)
+            .cloned()
+            .with_context(|| format!("stash entry {} no longer exists", sha.display_short()))?;
+        state.worktree_statuses.extend(stash.worktree_statuses);
+        state.emit_event();
+        Ok(())
+    }
+
+    fn stash_drop(&self, sha: Oid) -> Result<()> {
+        let mut state = self.state.lock();
+        let index = state
+            .stashes
+            .iter()
+            .position(|stash| stash.sha == sha)
+            .with_context(|| format!("stash entry {} no longer exists", sha.display_short()))?;
+        state.stashes.remove(index);
+        state.emit_event();
+        Ok(())
+    }
+}
+
+/// The text that the changes of a file are computed against.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DiffBase {
+    /// The file's staged contents, so only unstaged changes are shown.
+    #[default]
+    Index,
+    /// The file's contents in a revision, such as a branch, a tag or a commit.
+    Revision(String),
+    /// The file's contents in the merge base of HEAD and a revision, so only the changes
+    /// made since branching off that revision are shown.
+    MergeBase(String),
+}
+
+impl DiffBase {
+    /// Resolves the commit to diff against, or `None` when diffing against the index.
+    pub fn resolve(&self, repo: &dyn GitRepository) -> Result<Option<Oid>> {
+        match self {
+            DiffBase::Index => Ok(None),
+            DiffBase::Revision(revision) => repo.resolve_revision(revision).map(Some),
+            DiffBase::MergeBase(revision) => repo.merge_base(revision).map(Some),
+        }
+    }
+
+    /// Loads the text to diff the file at the given path against.
+    pub fn load_text(&self, repo: &dyn GitRepository, path: &RepoPath) -> Option<String> {
+        match self.resolve(repo).log_err()? {
+            None => repo.load_index_text(path),
+            Some(sha) => repo.load_text_at_commit(sha, path).log_err().flatten(),
+        }
+    }
+
+    pub fn is_index(&self) -> bool {
+        *self == DiffBase::Index
+    }
+}
+
+impl std::fmt::Display for DiffBase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiffBase::Index => write!(f, "Index"),
+            DiffBase::Revision(revision) => write!(f, "{revision}"),
+            DiffBase::MergeBase(revision) => write!(f, "Merge Base with {revision}"),
+        }
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
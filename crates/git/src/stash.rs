@@ -0,0 +1,148 @@
+use crate::Oid;
+use anyhow::{anyhow, Context, Result};
+use std::{path::Path, process::Stdio};
+
+/// An entry of the stash, newest first, as listed by `git stash list`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StashEntry {
+    /// The position of the entry in the stash, as in `stash@{index}`.
+    pub index: usize,
+    /// The stash commit, whose first parent is the commit the changes were stashed on.
+    pub sha: Oid,
+    pub message: String,
+}
+
+/// What to do with an entry of the stash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StashAction {
+    /// Applies the entry to the working tree and removes it from the stash.
+    Pop,
+    /// Applies the entry to the working tree, keeping it in the stash.
+    Apply,
+    /// Removes the entry from the stash without applying it.
+    Drop,
+}
+
+impl StashAction {
+    fn subcommand(&self) -> &'static str {
+        match self {
+            StashAction::Pop => "pop",
+            StashAction::Apply => "apply",
+            StashAction::Drop => "drop",
+        }
+    }
+}
+
+const STASH_FIELD_SEPARATOR: char = '\x1f';
+
+pub(crate) fn stash_list(git_binary: &Path, working_directory: &Path) -> Result<Vec<StashEntry>> {
+    let output = run_stash_command(
+        git_binary,
+        working_directory,
+        &["list", &format!("--format=%H{STASH_FIELD_SEPARATOR}%gs")],
+    )?;
+    parse_stash_list(&output)
+}
+
+/// Stashes the changes of the working tree and the index, including untracked files.
+pub(crate) fn stash_push(
+    git_binary: &Path,
+    working_directory: &Path,
+    message: Option<&str>,
+) -> Result<()> {
+    let mut args = vec!["push", "--include-untracked"];
+    if let Some(message) = message {
+        args.extend(["--message", message]);
+    }
+    run_stash_command(git_binary, working_directory, &args)?;
+    Ok(())
+}
+
+/// Pops, applies or drops the stash entry with the given commit. Entries are looked up by their commit,
+/// as their indices shift whenever the stash changes.
+pub(crate) fn stash_entry_action(
+    git_binary: &Path,
+    working_directory: &Path,
+    sha: Oid,
+    action: StashAction,
+) -> Result<()> {
+    let index = stash_list(git_binary, working_directory)?
+        .into_iter()
+        .find(|entry| entry.sha == sha)
+        .with_context(|| format!("stash entry {} no longer exists", sha.display_short()))?
+        .index;
+    run_stash_command(
+        git_binary,
+        working_directory,
+        &[action.subcommand(), &format!("stash@{{{index}}}")],
+    )?;
+    Ok(())
+}
+
+fn run_stash_command(git_binary: &Path, working_directory: &Path, args: &[&str]) -> Result<String> {
+    let output = util::command::new_std_command(git_binary)
+        .current_dir(working_directory)
+        .arg("stash")
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|e| anyhow!("Failed to start git stash process: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("git stash {} failed: {}", args[0], stderr.trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn parse_stash_list(output: &str) -> Result<Vec<StashEntry>> {
+    output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            let (sha, message) = line
+                .split_once(STASH_FIELD_SEPARATOR)
+                .with_context(|| format!("invalid git stash list entry {line:?}"))?;
+            Ok(StashEntry {
+                index,
+                sha: sha.parse()?,
+                message: message.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stash_list() {
+        let output = "\
+8ae8d3b3f0b5a3e64c4a4e5e62a3cb5ad6b0a9d1\x1fOn main: switching to feature
+0f1e2d3c4b5a69788796a5b4c3d2e1f0a1b2c3d4\x1fWIP on feature: 1234567 Fix the parser
+";
+        let entries = parse_stash_list(output).unwrap();
+        assert_eq!(
+            entries,
+            [
+                StashEntry {
+                    index: 0,
+                    sha: "8ae8d3b3f0b5a3e64c4a4e5e62a3cb5ad6b0a9d1".parse().unwrap(),
+                    message: "On main: switching to feature".into(),
+                },
+                StashEntry {
+                    index: 1,
+                    sha: "0f1e2d3c4b5a69788796a5b4c3d2e1f0a1b2c3d4".parse().unwrap(),
+                    message: "WIP on feature: 1234567 Fix the parser".into(),
+                },
+            ]
+        );
+        assert!(parse_stash_list("").unwrap().is_empty());
+        assert!(parse_stash_list("not-a-sha\x1fmessage").is_err());
+    }
+}
@@ -17,59 +17,9 @@ impl GitStatus {
         working_directory: &Path,
         path_prefixes: &[PathBuf],
     ) -> Result<Self> {
-        let child = util::command::new_std_command(git_binary)
-            .current_dir(working_directory)
-            .args([
-                "--no-optional-locks",
-                "status",
-                "--porcelain=v1",
-                "--untracked-files=all",
-                "-z",
-            ])
-            .args(path_prefixes.iter().map(|path_prefix| {
-                if *path_prefix == Path::new("") {
-                    Path::new(".")
-                } else {
-                    path_prefix
-                }
-            }))
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| anyhow!("Failed to start git status process: {}", e))?;
-
-        let output = child
-            .wait_with_output()
-            .map_err(|e| anyhow!("Failed to read git blame output: {}", e))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("git status process failed: {}", stderr));
-        }
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut entries = stdout
-            .split('\0')
-            .filter_map(|entry| {
-                if entry.is_char_boundary(3) {
-                    let (status, path) = entry.split_at(3);
-                    let status = status.trim();
-                    Some((
-                        RepoPath(PathBuf::from(path)),
-                        match status {
-                            "A" | "??" => GitFileStatus::Added,
-                            "M" => GitFileStatus::Modified,
-                            _ => return None,
-                        },
-                    ))
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
-        entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        let stdout = run_git_status(git_binary, working_directory, path_prefixes)?;
         Ok(Self {
-            entries: entries.into(),
+            entries: parse_statuses(&stdout).into(),
         })
     }
 
@@ -81,6 +31,139 @@ impl GitStatus {
     }
 }
 
+fn parse_statuses(porcelain_output: &str) -> Vec<(RepoPath, GitFileStatus)> {
+    let mut entries = porcelain_output
+        .split('\0')
+        .filter_map(|entry| {
+            if entry.is_char_boundary(3) {
+                let (status, path) = entry.split_at(3);
+                let status = status.trim();
+                Some((
+                    RepoPath(PathBuf::from(path)),
+                    match status {
+                        "A" | "??" => GitFileStatus::Added,
+                        "M" => GitFileStatus::Modified,
+                        status if is_unmerged(status) => GitFileStatus::Conflict,
+                        _ => return None,
+                    },
+                ))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+/// Whether a porcelain status code describes an unmerged path, left behind by a merge conflict.
+fn is_unmerged(status: &str) -> bool {
+    matches!(status, "DD" | "AU" | "UD" | "UA" | "DU" | "AA" | "UU")
+}
+
+/// Whether the changes of a file are staged in the git index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StageStatus {
+    /// All changes of the file are staged.
+    Staged,
+    /// None of the changes of the file are staged.
+    Unstaged,
+    /// The file has both staged and unstaged changes.
+    PartiallyStaged,
+}
+
+impl StageStatus {
+    pub fn has_staged_changes(&self) -> bool {
+        matches!(self, StageStatus::Staged | StageStatus::PartiallyStaged)
+    }
+
+    pub fn has_unstaged_changes(&self) -> bool {
+        matches!(self, StageStatus::Unstaged | StageStatus::PartiallyStaged)
+    }
+}
+
+pub(crate) fn stage_statuses(
+    git_binary: &Path,
+    working_directory: &Path,
+    path_prefixes: &[PathBuf],
+) -> Result<Vec<(RepoPath, StageStatus)>> {
+    let stdout = run_git_status(git_binary, working_directory, path_prefixes)?;
+    Ok(parse_stage_statuses(&stdout))
+}
+
+fn parse_stage_statuses(porcelain_output: &str) -> Vec<(RepoPath, StageStatus)> {
+    let mut statuses = Vec::new();
+    let mut entries = porcelain_output.split('\0');
+    while let Some(entry) = entries.next() {
+        if !entry.is_char_boundary(3) {
+            continue;
+        }
+        let (status, path) = entry.split_at(3);
+        let is_conflict = is_unmerged(status.trim_end());
+        let mut status = status.chars();
+        let (Some(index_status), Some(worktree_status)) = (status.next(), status.next()) else {
+            continue;
+        };
+        // Renames and copies are followed by the original path of the file.
+        if matches!(index_status, 'R' | 'C') {
+            entries.next();
+        }
+        // Conflicting changes have to be resolved and then staged.
+        if is_conflict {
+            statuses.push((RepoPath(PathBuf::from(path)), StageStatus::Unstaged));
+            continue;
+        }
+        let has_staged_changes = !matches!(index_status, ' ' | '?');
+        let has_unstaged_changes = worktree_status != ' ';
+        let stage_status = match (has_staged_changes, has_unstaged_changes) {
+            (true, true) => StageStatus::PartiallyStaged,
+            (true, false) => StageStatus::Staged,
+            (false, _) => StageStatus::Unstaged,
+        };
+        statuses.push((RepoPath(PathBuf::from(path)), stage_status));
+    }
+    statuses.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    statuses
+}
+
+fn run_git_status(
+    git_binary: &Path,
+    working_directory: &Path,
+    path_prefixes: &[PathBuf],
+) -> Result<String> {
+    let child = util::command::new_std_command(git_binary)
+        .current_dir(working_directory)
+        .args([
+            "--no-optional-locks",
+            "status",
+            "--porcelain=v1",
+            "--untracked-files=all",
+            "-z",
+        ])
+        .args(path_prefixes.iter().map(|path_prefix| {
+            if *path_prefix == Path::new("") {
+                Path::new(".")
+            } else {
+                path_prefix
+            }
+        }))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to start git status process: {}", e))?;
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| anyhow!("Failed to read git blame output: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("git status process failed: {}", stderr));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
 impl Default for GitStatus {
     fn default() -> Self {
         Self {
@@ -88,3 +171,43 @@ impl Default for GitStatus {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_statuses() {
+        let output = "M  modified.rs\0?? new.rs\0UU both_modified.rs\0AA both_added.rs\0DU deleted_by_us.rs\0";
+        assert_eq!(
+            parse_statuses(output),
+            vec![
+                (RepoPath("both_added.rs".into()), GitFileStatus::Conflict),
+                (RepoPath("both_modified.rs".into()), GitFileStatus::Conflict),
+                (RepoPath("deleted_by_us.rs".into()), GitFileStatus::Conflict),
+                (RepoPath("modified.rs".into()), GitFileStatus::Modified),
+                (RepoPath("new.rs".into()), GitFileStatus::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_stage_statuses() {
+        let output = "M  staged.rs\0 M unstaged.rs\0MM partially.rs\0?? new.rs\0R  renamed.rs\0original.rs\0A  added.rs\0UU conflict.rs\0";
+        assert_eq!(
+            parse_stage_statuses(output),
+            vec![
+                (RepoPath("added.rs".into()), StageStatus::Staged),
+                (RepoPath("conflict.rs".into()), StageStatus::Unstaged),
+                (RepoPath("new.rs".into()), StageStatus::Unstaged),
+                (
+                    RepoPath("partially.rs".into()),
+                    StageStatus::PartiallyStaged
+                ),
+                (RepoPath("renamed.rs".into()), StageStatus::Staged),
+                (RepoPath("staged.rs".into()), StageStatus::Staged),
+                (RepoPath("unstaged.rs".into()), StageStatus::Unstaged),
+            ]
+        );
+    }
+}
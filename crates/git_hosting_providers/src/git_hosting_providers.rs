@@ -1,22 +1,28 @@
 mod providers;
+mod settings;
 
 use std::sync::Arc;
 
+use ::settings::Settings as _;
 use git::repository::GitRepository;
 use git::GitHostingProviderRegistry;
 use gpui::AppContext;
 
 pub use crate::providers::*;
+pub use crate::settings::*;
 
 /// Initializes the Git hosting providers.
-pub fn init(cx: &AppContext) {
+pub fn init(cx: &mut AppContext) {
+    GitHostingProviderSettings::register(cx);
+
     let provider_registry = GitHostingProviderRegistry::global(cx);
     provider_registry.register_hosting_provider(Arc::new(Bitbucket));
     provider_registry.register_hosting_provider(Arc::new(Codeberg));
     provider_registry.register_hosting_provider(Arc::new(Gitee));
-    provider_registry.register_hosting_provider(Arc::new(Github));
+    provider_registry.register_hosting_provider(Arc::new(Github::new()));
     provider_registry.register_hosting_provider(Arc::new(Gitlab::new()));
     provider_registry.register_hosting_provider(Arc::new(Sourcehut));
+    settings::register_configured_providers(provider_registry, cx);
 }
 
 /// Registers additional Git hosting providers.
@@ -1,13 +1,36 @@
 mod bitbucket;
+mod bitbucket_server;
 mod codeberg;
+mod gitea;
 mod gitee;
 mod github;
 mod gitlab;
 mod sourcehut;
 
 pub use bitbucket::*;
+pub use bitbucket_server::*;
 pub use codeberg::*;
+pub use gitea::*;
 pub use gitee::*;
 pub use github::*;
 pub use gitlab::*;
 pub use sourcehut::*;
+
+use url::Url;
+
+/// Returns the path segments of a remote URL of a self-hosted instance, without the leading
+/// ones of the instance's base URL, for instances served from a subpath such as
+/// `https://example.com/gitea/`. SSH remotes usually don't include that subpath.
+fn remote_path_segments<'a>(url: &'a Url, base_url: &Url) -> Option<Vec<&'a str>> {
+    let mut path_segments = url.path_segments()?.collect::<Vec<_>>();
+    let base_path_segments = base_url
+        .path_segments()
+        .into_iter()
+        .flatten()
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    if path_segments.starts_with(&base_path_segments) {
+        path_segments.drain(..base_path_segments.len());
+    }
+    Some(path_segments)
+}
@@ -3,7 +3,8 @@ use std::str::FromStr;
 use url::Url;
 
 use git::{
-    BuildCommitPermalinkParams, BuildPermalinkParams, GitHostingProvider, ParsedGitRemote,
+    encode_branch_for_url_path, BuildCommitPermalinkParams, BuildCompareParams,
+    BuildCreatePullRequestParams, BuildPermalinkParams, GitHostingProvider, ParsedGitRemote,
     RemoteUrl,
 };
 
@@ -80,6 +81,38 @@ impl GitHostingProvider for Bitbucket {
         );
         permalink
     }
+
+    fn build_create_pull_request_url(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCreatePullRequestParams,
+    ) -> Option<Url> {
+        let BuildCreatePullRequestParams { branch } = params;
+        let ParsedGitRemote { owner, repo } = remote;
+
+        let mut url = self
+            .base_url()
+            .join(&format!("{owner}/{repo}/pull-requests/new"))
+            .ok()?;
+        url.query_pairs_mut().append_pair("source", branch);
+        Some(url)
+    }
+
+    fn build_compare_url(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCompareParams,
+    ) -> Option<Url> {
+        let BuildCompareParams { base, head } = params;
+        let ParsedGitRemote { owner, repo } = remote;
+        let base = encode_branch_for_url_path(base);
+        let head = encode_branch_for_url_path(head);
+
+        // Bitbucket separates the compared refs with a carriage return, listing the head first.
+        self.base_url()
+            .join(&format!("{owner}/{repo}/branches/compare/{head}%0D{base}"))
+            .ok()
+    }
 }
 
 #[cfg(test)]
@@ -187,4 +220,41 @@ mod tests {
             "https://bitbucket.org/zed-industries/zed/src/f00b4r/main.rs#lines-24:48";
         assert_eq!(permalink.to_string(), expected_url.to_string())
     }
+
+    #[test]
+    fn test_build_bitbucket_create_pull_request_url() {
+        let url = Bitbucket
+            .build_create_pull_request_url(
+                &ParsedGitRemote {
+                    owner: "zed-industries".into(),
+                    repo: "zed".into(),
+                },
+                BuildCreatePullRequestParams { branch: "feature" },
+            )
+            .unwrap();
+
+        let expected_url =
+            "https://bitbucket.org/zed-industries/zed/pull-requests/new?source=feature";
+        assert_eq!(url.to_string(), expected_url.to_string())
+    }
+
+    #[test]
+    fn test_build_bitbucket_compare_url() {
+        let url = Bitbucket
+            .build_compare_url(
+                &ParsedGitRemote {
+                    owner: "zed-industries".into(),
+                    repo: "zed".into(),
+                },
+                BuildCompareParams {
+                    base: "main",
+                    head: "feature",
+                },
+            )
+            .unwrap();
+
+        let expected_url =
+            "https://bitbucket.org/zed-industries/zed/branches/compare/feature%0Dmain";
+        assert_eq!(url.to_string(), expected_url.to_string())
+    }
 }
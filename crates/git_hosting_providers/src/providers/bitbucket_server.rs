@@ -0,0 +1,233 @@
+use std::str::FromStr;
+
+use url::Url;
+
+use git::{
+    BuildCommitPermalinkParams, BuildCompareParams, BuildCreatePullRequestParams,
+    BuildPermalinkParams, GitHostingProvider, ParsedGitRemote, RemoteUrl,
+};
+
+use super::remote_path_segments;
+
+/// A self-hosted Bitbucket Server or Bitbucket Data Center instance.
+///
+/// Unlike bitbucket.org, repositories live in projects, and the personal repositories of a user
+/// live in the project named after the user, prefixed with a `~`.
+#[derive(Debug)]
+pub struct BitbucketServer {
+    name: String,
+    base_url: Url,
+}
+
+impl BitbucketServer {
+    pub fn new(name: impl Into<String>, base_url: Url) -> Self {
+        Self {
+            name: name.into(),
+            base_url,
+        }
+    }
+
+    fn repository_url(&self, remote: &ParsedGitRemote, path: &str) -> Url {
+        let ParsedGitRemote { owner, repo } = remote;
+        let project = match owner.strip_prefix('~') {
+            Some(user) => format!("users/{user}"),
+            None => format!("projects/{owner}"),
+        };
+
+        self.base_url()
+            .join(&format!("{project}/repos/{repo}/{path}"))
+            .unwrap()
+    }
+}
+
+impl GitHostingProvider for BitbucketServer {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn base_url(&self) -> Url {
+        self.base_url.clone()
+    }
+
+    fn supports_avatars(&self) -> bool {
+        false
+    }
+
+    fn format_line_number(&self, line: u32) -> String {
+        format!("{line}")
+    }
+
+    fn format_line_numbers(&self, start_line: u32, end_line: u32) -> String {
+        format!("{start_line}-{end_line}")
+    }
+
+    fn parse_remote_url(&self, url: &str) -> Option<ParsedGitRemote> {
+        let url = RemoteUrl::from_str(url).ok()?;
+
+        let host = url.host_str()?;
+        if host != self.base_url.host_str()? {
+            return None;
+        }
+
+        // HTTP clone URLs are served from `/scm`, SSH ones from the root.
+        let mut path_segments = remote_path_segments(&url, &self.base_url)?
+            .into_iter()
+            .peekable();
+        path_segments.next_if_eq(&"scm");
+        let owner = path_segments.next()?;
+        let repo = path_segments.next()?.trim_end_matches(".git");
+
+        Some(ParsedGitRemote {
+            owner: owner.into(),
+            repo: repo.into(),
+        })
+    }
+
+    fn build_commit_permalink(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCommitPermalinkParams,
+    ) -> Url {
+        let BuildCommitPermalinkParams { sha } = params;
+
+        self.repository_url(remote, &format!("commits/{sha}"))
+    }
+
+    fn build_permalink(&self, remote: ParsedGitRemote, params: BuildPermalinkParams) -> Url {
+        let BuildPermalinkParams {
+            sha,
+            path,
+            selection,
+        } = params;
+
+        let mut permalink = self.repository_url(&remote, &format!("browse/{path}"));
+        permalink.query_pairs_mut().append_pair("at", sha);
+        permalink.set_fragment(
+            selection
+                .map(|selection| self.line_fragment(&selection))
+                .as_deref(),
+        );
+        permalink
+    }
+
+    fn build_create_pull_request_url(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCreatePullRequestParams,
+    ) -> Option<Url> {
+        let BuildCreatePullRequestParams { branch } = params;
+
+        let mut url = self.repository_url(remote, "pull-requests");
+        url.query_pairs_mut()
+            .append_key_only("create")
+            .append_pair("sourceBranch", &format!("refs/heads/{branch}"));
+        Some(url)
+    }
+
+    fn build_compare_url(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCompareParams,
+    ) -> Option<Url> {
+        let BuildCompareParams { base, head } = params;
+
+        let mut url = self.repository_url(remote, "compare/diff");
+        url.query_pairs_mut()
+            .append_pair("sourceBranch", &format!("refs/heads/{head}"))
+            .append_pair("targetBranch", &format!("refs/heads/{base}"));
+        Some(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn bitbucket_server() -> BitbucketServer {
+        BitbucketServer::new(
+            "Bitbucket Server",
+            Url::parse("https://bitbucket.example.com").unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_parse_remote_url_given_ssh_url() {
+        let parsed_remote = bitbucket_server()
+            .parse_remote_url("ssh://git@bitbucket.example.com:7999/zed/editor.git")
+            .unwrap();
+
+        assert_eq!(
+            parsed_remote,
+            ParsedGitRemote {
+                owner: "zed".into(),
+                repo: "editor".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_remote_url_given_https_url() {
+        let parsed_remote = bitbucket_server()
+            .parse_remote_url("https://bitbucket.example.com/scm/zed/editor.git")
+            .unwrap();
+
+        assert_eq!(
+            parsed_remote,
+            ParsedGitRemote {
+                owner: "zed".into(),
+                repo: "editor".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_build_bitbucket_server_permalink_with_multi_line_selection() {
+        let permalink = bitbucket_server().build_permalink(
+            ParsedGitRemote {
+                owner: "zed".into(),
+                repo: "editor".into(),
+            },
+            BuildPermalinkParams {
+                sha: "f00b4r",
+                path: "src/main.rs",
+                selection: Some(23..47),
+            },
+        );
+
+        let expected_url =
+            "https://bitbucket.example.com/projects/zed/repos/editor/browse/src/main.rs?at=f00b4r#24-48";
+        assert_eq!(permalink.to_string(), expected_url.to_string())
+    }
+
+    #[test]
+    fn test_build_bitbucket_server_commit_permalink_for_personal_repository() {
+        let permalink = bitbucket_server().build_commit_permalink(
+            &ParsedGitRemote {
+                owner: "~jdoe".into(),
+                repo: "dotfiles".into(),
+            },
+            BuildCommitPermalinkParams { sha: "f00b4r" },
+        );
+
+        let expected_url = "https://bitbucket.example.com/users/jdoe/repos/dotfiles/commits/f00b4r";
+        assert_eq!(permalink.to_string(), expected_url.to_string())
+    }
+
+    #[test]
+    fn test_build_bitbucket_server_create_pull_request_url() {
+        let url = bitbucket_server()
+            .build_create_pull_request_url(
+                &ParsedGitRemote {
+                    owner: "zed".into(),
+                    repo: "editor".into(),
+                },
+                BuildCreatePullRequestParams { branch: "feature" },
+            )
+            .unwrap();
+
+        let expected_url = "https://bitbucket.example.com/projects/zed/repos/editor/pull-requests?create&sourceBranch=refs%2Fheads%2Ffeature";
+        assert_eq!(url.to_string(), expected_url.to_string())
+    }
+}
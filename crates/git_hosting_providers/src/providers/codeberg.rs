@@ -9,7 +9,8 @@ use serde::Deserialize;
 use url::Url;
 
 use git::{
-    BuildCommitPermalinkParams, BuildPermalinkParams, GitHostingProvider, Oid, ParsedGitRemote,
+    encode_branch_for_url_path, BuildCommitPermalinkParams, BuildCompareParams,
+    BuildCreatePullRequestParams, BuildPermalinkParams, GitHostingProvider, Oid, ParsedGitRemote,
     RemoteUrl,
 };
 
@@ -156,6 +157,36 @@ impl GitHostingProvider for Codeberg {
         permalink
     }
 
+    fn build_create_pull_request_url(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCreatePullRequestParams,
+    ) -> Option<Url> {
+        let BuildCreatePullRequestParams { branch } = params;
+        let ParsedGitRemote { owner, repo } = remote;
+        let branch = encode_branch_for_url_path(branch);
+
+        // Comparing a lone branch compares it with the default branch, offering to open a pull request.
+        self.base_url()
+            .join(&format!("{owner}/{repo}/compare/{branch}"))
+            .ok()
+    }
+
+    fn build_compare_url(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCompareParams,
+    ) -> Option<Url> {
+        let BuildCompareParams { base, head } = params;
+        let ParsedGitRemote { owner, repo } = remote;
+        let base = encode_branch_for_url_path(base);
+        let head = encode_branch_for_url_path(head);
+
+        self.base_url()
+            .join(&format!("{owner}/{repo}/compare/{base}...{head}"))
+            .ok()
+    }
+
     async fn commit_author_avatar_url(
         &self,
         repo_owner: &str,
@@ -262,4 +293,39 @@ mod tests {
         let expected_url = "https://codeberg.org/zed-industries/zed/src/commit/faa6f979be417239b2e070dbbf6392b909224e0b/crates/editor/src/git/permalink.rs#L24-L48";
         assert_eq!(permalink.to_string(), expected_url.to_string())
     }
+
+    #[test]
+    fn test_build_codeberg_create_pull_request_url() {
+        let url = Codeberg
+            .build_create_pull_request_url(
+                &ParsedGitRemote {
+                    owner: "zed-industries".into(),
+                    repo: "zed".into(),
+                },
+                BuildCreatePullRequestParams { branch: "feature" },
+            )
+            .unwrap();
+
+        let expected_url = "https://codeberg.org/zed-industries/zed/compare/feature";
+        assert_eq!(url.to_string(), expected_url.to_string())
+    }
+
+    #[test]
+    fn test_build_codeberg_compare_url() {
+        let url = Codeberg
+            .build_compare_url(
+                &ParsedGitRemote {
+                    owner: "zed-industries".into(),
+                    repo: "zed".into(),
+                },
+                BuildCompareParams {
+                    base: "main",
+                    head: "feature",
+                },
+            )
+            .unwrap();
+
+        let expected_url = "https://codeberg.org/zed-industries/zed/compare/main...feature";
+        assert_eq!(url.to_string(), expected_url.to_string())
+    }
 }
@@ -0,0 +1,303 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use futures::AsyncReadExt;
+use http_client::{AsyncBody, HttpClient, HttpRequestExt, Request};
+use serde::Deserialize;
+use url::Url;
+
+use git::{
+    BuildCommitPermalinkParams, BuildCompareParams, BuildCreatePullRequestParams,
+    BuildPermalinkParams, GitHostingProvider, Oid, ParsedGitRemote, RemoteUrl,
+};
+
+use super::remote_path_segments;
+
+#[derive(Debug, Deserialize)]
+struct CommitDetails {
+    author: Option<User>,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    pub avatar_url: String,
+}
+
+/// A self-hosted instance of Gitea or of its fork, Forgejo.
+#[derive(Debug)]
+pub struct Gitea {
+    name: String,
+    base_url: Url,
+}
+
+impl Gitea {
+    pub fn new(name: impl Into<String>, base_url: Url) -> Self {
+        Self {
+            name: name.into(),
+            base_url,
+        }
+    }
+
+    async fn fetch_gitea_commit_author(
+        &self,
+        repo_owner: &str,
+        repo: &str,
+        commit: &str,
+        client: &Arc<dyn HttpClient>,
+    ) -> Result<Option<User>> {
+        let url = self
+            .base_url
+            .join(&format!(
+                "api/v1/repos/{repo_owner}/{repo}/git/commits/{commit}"
+            ))?
+            .to_string();
+
+        let request = Request::get(&url)
+            .header("Content-Type", "application/json")
+            .follow_redirects(http_client::RedirectPolicy::FollowAll);
+
+        let mut response = client
+            .send(request.body(AsyncBody::default())?)
+            .await
+            .with_context(|| format!("error fetching {} commit details at {:?}", self.name, url))?;
+
+        let mut body = Vec::new();
+        response.body_mut().read_to_end(&mut body).await?;
+
+        if response.status().is_client_error() {
+            let text = String::from_utf8_lossy(body.as_slice());
+            bail!(
+                "status error {}, response: {text:?}",
+                response.status().as_u16()
+            );
+        }
+
+        let body_str = std::str::from_utf8(&body)?;
+
+        serde_json::from_str::<CommitDetails>(body_str)
+            .map(|commit| commit.author)
+            .with_context(|| format!("failed to deserialize {} commit details", self.name))
+    }
+}
+
+#[async_trait]
+impl GitHostingProvider for Gitea {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn base_url(&self) -> Url {
+        self.base_url.clone()
+    }
+
+    fn supports_avatars(&self) -> bool {
+        true
+    }
+
+    fn format_line_number(&self, line: u32) -> String {
+        format!("L{line}")
+    }
+
+    fn format_line_numbers(&self, start_line: u32, end_line: u32) -> String {
+        format!("L{start_line}-L{end_line}")
+    }
+
+    fn parse_remote_url(&self, url: &str) -> Option<ParsedGitRemote> {
+        let url = RemoteUrl::from_str(url).ok()?;
+
+        let host = url.host_str()?;
+        if host != self.base_url.host_str()? {
+            return None;
+        }
+
+        let mut path_segments = remote_path_segments(&url, &self.base_url)?.into_iter();
+        let owner = path_segments.next()?;
+        let repo = path_segments.next()?.trim_end_matches(".git");
+
+        Some(ParsedGitRemote {
+            owner: owner.into(),
+            repo: repo.into(),
+        })
+    }
+
+    fn build_commit_permalink(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCommitPermalinkParams,
+    ) -> Url {
+        let BuildCommitPermalinkParams { sha } = params;
+        let ParsedGitRemote { owner, repo } = remote;
+
+        self.base_url()
+            .join(&format!("{owner}/{repo}/commit/{sha}"))
+            .unwrap()
+    }
+
+    fn build_permalink(&self, remote: ParsedGitRemote, params: BuildPermalinkParams) -> Url {
+        let ParsedGitRemote { owner, repo } = remote;
+        let BuildPermalinkParams {
+            sha,
+            path,
+            selection,
+        } = params;
+
+        let mut permalink = self
+            .base_url()
+            .join(&format!("{owner}/{repo}/src/commit/{sha}/{path}"))
+            .unwrap();
+        permalink.set_fragment(
+            selection
+                .map(|selection| self.line_fragment(&selection))
+                .as_deref(),
+        );
+        permalink
+    }
+
+    fn build_create_pull_request_url(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCreatePullRequestParams,
+    ) -> Option<Url> {
+        let BuildCreatePullRequestParams { branch } = params;
+        let ParsedGitRemote { owner, repo } = remote;
+
+        self.base_url()
+            .join(&format!("{owner}/{repo}/compare/{branch}"))
+            .ok()
+    }
+
+    fn build_compare_url(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCompareParams,
+    ) -> Option<Url> {
+        let BuildCompareParams { base, head } = params;
+        let ParsedGitRemote { owner, repo } = remote;
+
+        self.base_url()
+            .join(&format!("{owner}/{repo}/compare/{base}...{head}"))
+            .ok()
+    }
+
+    async fn commit_author_avatar_url(
+        &self,
+        repo_owner: &str,
+        repo: &str,
+        commit: Oid,
+        http_client: Arc<dyn HttpClient>,
+    ) -> Result<Option<Url>> {
+        let commit = commit.to_string();
+        let avatar_url = self
+            .fetch_gitea_commit_author(repo_owner, repo, &commit, &http_client)
+            .await?
+            .map(|author| Url::parse(&author.avatar_url))
+            .transpose()?;
+        Ok(avatar_url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn gitea() -> Gitea {
+        Gitea::new("Gitea", Url::parse("https://gitea.example.com").unwrap())
+    }
+
+    #[test]
+    fn test_parse_remote_url_given_ssh_url() {
+        let parsed_remote = gitea()
+            .parse_remote_url("git@gitea.example.com:zed-industries/zed.git")
+            .unwrap();
+
+        assert_eq!(
+            parsed_remote,
+            ParsedGitRemote {
+                owner: "zed-industries".into(),
+                repo: "zed".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_remote_url_given_https_url() {
+        let parsed_remote = gitea()
+            .parse_remote_url("https://gitea.example.com/zed-industries/zed.git")
+            .unwrap();
+
+        assert_eq!(
+            parsed_remote,
+            ParsedGitRemote {
+                owner: "zed-industries".into(),
+                repo: "zed".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_remote_url_given_instance_under_subpath() {
+        let gitea = Gitea::new("Gitea", Url::parse("https://example.com/gitea/").unwrap());
+
+        for remote_url in [
+            "https://example.com/gitea/zed-industries/zed.git",
+            "git@example.com:zed-industries/zed.git",
+        ] {
+            assert_eq!(
+                gitea.parse_remote_url(remote_url),
+                Some(ParsedGitRemote {
+                    owner: "zed-industries".into(),
+                    repo: "zed".into(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_remote_url_given_other_host() {
+        assert_eq!(
+            gitea().parse_remote_url("https://codeberg.org/zed-industries/zed.git"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_build_gitea_permalink_with_multi_line_selection() {
+        let permalink = gitea().build_permalink(
+            ParsedGitRemote {
+                owner: "zed-industries".into(),
+                repo: "zed".into(),
+            },
+            BuildPermalinkParams {
+                sha: "faa6f979be417239b2e070dbbf6392b909224e0b",
+                path: "crates/editor/src/git/permalink.rs",
+                selection: Some(23..47),
+            },
+        );
+
+        let expected_url = "https://gitea.example.com/zed-industries/zed/src/commit/faa6f979be417239b2e070dbbf6392b909224e0b/crates/editor/src/git/permalink.rs#L24-L48";
+        assert_eq!(permalink.to_string(), expected_url.to_string())
+    }
+
+    #[test]
+    fn test_build_gitea_compare_url() {
+        let url = gitea()
+            .build_compare_url(
+                &ParsedGitRemote {
+                    owner: "zed-industries".into(),
+                    repo: "zed".into(),
+                },
+                BuildCompareParams {
+                    base: "main",
+                    head: "feature",
+                },
+            )
+            .unwrap();
+
+        let expected_url = "https://gitea.example.com/zed-industries/zed/compare/main...feature";
+        assert_eq!(url.to_string(), expected_url.to_string())
+    }
+}
@@ -3,8 +3,8 @@ use std::str::FromStr;
 use url::Url;
 
 use git::{
-    BuildCommitPermalinkParams, BuildPermalinkParams, GitHostingProvider, ParsedGitRemote,
-    RemoteUrl,
+    encode_branch_for_url_path, BuildCommitPermalinkParams, BuildCompareParams,
+    BuildPermalinkParams, GitHostingProvider, ParsedGitRemote, RemoteUrl,
 };
 
 pub struct Gitee;
@@ -80,6 +80,21 @@ impl GitHostingProvider for Gitee {
         );
         permalink
     }
+
+    fn build_compare_url(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCompareParams,
+    ) -> Option<Url> {
+        let BuildCompareParams { base, head } = params;
+        let ParsedGitRemote { owner, repo } = remote;
+        let base = encode_branch_for_url_path(base);
+        let head = encode_branch_for_url_path(head);
+
+        self.base_url()
+            .join(&format!("{owner}/{repo}/compare/{base}...{head}"))
+            .ok()
+    }
 }
 
 #[cfg(test)]
@@ -171,4 +186,23 @@ mod tests {
         let expected_url = "https://gitee.com/zed-industries/zed/blob/e5fe811d7ad0fc26934edd76f891d20bdc3bb194/crates/editor/src/git/permalink.rs#L24-48";
         assert_eq!(permalink.to_string(), expected_url.to_string())
     }
+
+    #[test]
+    fn test_build_gitee_compare_url() {
+        let url = Gitee
+            .build_compare_url(
+                &ParsedGitRemote {
+                    owner: "zed-industries".into(),
+                    repo: "zed".into(),
+                },
+                BuildCompareParams {
+                    base: "main",
+                    head: "feature",
+                },
+            )
+            .unwrap();
+
+        let expected_url = "https://gitee.com/zed-industries/zed/compare/main...feature";
+        assert_eq!(url.to_string(), expected_url.to_string())
+    }
 }
@@ -10,10 +10,13 @@ use serde::Deserialize;
 use url::Url;
 
 use git::{
-    BuildCommitPermalinkParams, BuildPermalinkParams, GitHostingProvider, Oid, ParsedGitRemote,
+    encode_branch_for_url_path, BuildCommitPermalinkParams, BuildCompareParams,
+    BuildCreatePullRequestParams, BuildPermalinkParams, GitHostingProvider, Oid, ParsedGitRemote,
     PullRequest, RemoteUrl,
 };
 
+use super::remote_path_segments;
+
 fn pull_request_number_regex() -> &'static Regex {
     static PULL_REQUEST_NUMBER_REGEX: OnceLock<Regex> = OnceLock::new();
 
@@ -42,9 +45,40 @@ struct User {
     pub avatar_url: String,
 }
 
-pub struct Github;
+#[derive(Debug)]
+pub struct Github {
+    name: String,
+    base_url: Url,
+}
 
 impl Github {
+    pub fn new() -> Self {
+        Self {
+            name: "GitHub".to_string(),
+            base_url: Url::parse("https://github.com").unwrap(),
+        }
+    }
+
+    /// Returns a provider for a GitHub Enterprise Server instance.
+    pub fn new_self_hosted(name: impl Into<String>, base_url: Url) -> Self {
+        Self {
+            name: name.into(),
+            base_url,
+        }
+    }
+
+    fn is_public_instance(&self) -> bool {
+        self.base_url.host_str() == Some("github.com")
+    }
+
+    fn api_url(&self) -> Result<Url> {
+        if self.is_public_instance() {
+            Ok(Url::parse("https://api.github.com")?)
+        } else {
+            Ok(self.base_url.join("api/v3/")?)
+        }
+    }
+
     async fn fetch_github_commit_author(
         &self,
         repo_owner: &str,
@@ -52,14 +86,20 @@ impl Github {
         commit: &str,
         client: &Arc<dyn HttpClient>,
     ) -> Result<Option<User>> {
-        let url = format!("https://api.github.com/repos/{repo_owner}/{repo}/commits/{commit}");
+        let url = self
+            .api_url()?
+            .join(&format!("repos/{repo_owner}/{repo}/commits/{commit}"))?
+            .to_string();
 
         let mut request = Request::get(&url)
             .header("Content-Type", "application/json")
             .follow_redirects(http_client::RedirectPolicy::FollowAll);
 
-        if let Ok(github_token) = std::env::var("GITHUB_TOKEN") {
-            request = request.header("Authorization", format!("Bearer {}", github_token));
+        // The token is only meant for github.com, so never send it to another host.
+        if self.is_public_instance() {
+            if let Ok(github_token) = std::env::var("GITHUB_TOKEN") {
+                request = request.header("Authorization", format!("Bearer {}", github_token));
+            }
         }
 
         let mut response = client
@@ -89,11 +129,11 @@ impl Github {
 #[async_trait]
 impl GitHostingProvider for Github {
     fn name(&self) -> String {
-        "GitHub".to_string()
+        self.name.clone()
     }
 
     fn base_url(&self) -> Url {
-        Url::parse("https://github.com").unwrap()
+        self.base_url.clone()
     }
 
     fn supports_avatars(&self) -> bool {
@@ -112,11 +152,11 @@ impl GitHostingProvider for Github {
         let url = RemoteUrl::from_str(url).ok()?;
 
         let host = url.host_str()?;
-        if host != "github.com" {
+        if host != self.base_url.host_str()? {
             return None;
         }
 
-        let mut path_segments = url.path_segments()?;
+        let mut path_segments = remote_path_segments(&url, &self.base_url)?.into_iter();
         let owner = path_segments.next()?;
         let repo = path_segments.next()?.trim_end_matches(".git");
 
@@ -162,6 +202,35 @@ impl GitHostingProvider for Github {
         permalink
     }
 
+    fn build_create_pull_request_url(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCreatePullRequestParams,
+    ) -> Option<Url> {
+        let BuildCreatePullRequestParams { branch } = params;
+        let ParsedGitRemote { owner, repo } = remote;
+        let branch = encode_branch_for_url_path(branch);
+
+        self.base_url()
+            .join(&format!("{owner}/{repo}/pull/new/{branch}"))
+            .ok()
+    }
+
+    fn build_compare_url(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCompareParams,
+    ) -> Option<Url> {
+        let BuildCompareParams { base, head } = params;
+        let ParsedGitRemote { owner, repo } = remote;
+        let base = encode_branch_for_url_path(base);
+        let head = encode_branch_for_url_path(head);
+
+        self.base_url()
+            .join(&format!("{owner}/{repo}/compare/{base}...{head}"))
+            .ok()
+    }
+
     fn extract_pull_request(&self, remote: &ParsedGitRemote, message: &str) -> Option<PullRequest> {
         let line = message.lines().next()?;
         let capture = pull_request_number_regex().captures(line)?;
@@ -204,7 +273,7 @@ mod tests {
 
     #[test]
     fn test_parse_remote_url_given_ssh_url() {
-        let parsed_remote = Github
+        let parsed_remote = Github::new()
             .parse_remote_url("git@github.com:zed-industries/zed.git")
             .unwrap();
 
@@ -219,7 +288,7 @@ mod tests {
 
     #[test]
     fn test_parse_remote_url_given_https_url() {
-        let parsed_remote = Github
+        let parsed_remote = Github::new()
             .parse_remote_url("https://github.com/zed-industries/zed.git")
             .unwrap();
 
@@ -234,7 +303,7 @@ mod tests {
 
     #[test]
     fn test_parse_remote_url_given_https_url_with_username() {
-        let parsed_remote = Github
+        let parsed_remote = Github::new()
             .parse_remote_url("https://jlannister@github.com/some-org/some-repo.git")
             .unwrap();
 
@@ -247,13 +316,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_remote_url_given_self_hosted_instance_under_subpath() {
+        let github = Github::new_self_hosted(
+            "GitHub Enterprise",
+            Url::parse("https://example.com/github/").unwrap(),
+        );
+        let parsed_remote = github
+            .parse_remote_url("https://example.com/github/some-org/some-repo.git")
+            .unwrap();
+
+        assert_eq!(
+            parsed_remote,
+            ParsedGitRemote {
+                owner: "some-org".into(),
+                repo: "some-repo".into(),
+            }
+        );
+    }
+
     #[test]
     fn test_build_github_permalink_from_ssh_url() {
         let remote = ParsedGitRemote {
             owner: "zed-industries".into(),
             repo: "zed".into(),
         };
-        let permalink = Github.build_permalink(
+        let permalink = Github::new().build_permalink(
             remote,
             BuildPermalinkParams {
                 sha: "e6ebe7974deb6bb6cc0e2595c8ec31f0c71084b7",
@@ -268,7 +356,7 @@ mod tests {
 
     #[test]
     fn test_build_github_permalink() {
-        let permalink = Github.build_permalink(
+        let permalink = Github::new().build_permalink(
             ParsedGitRemote {
                 owner: "zed-industries".into(),
                 repo: "zed".into(),
@@ -286,7 +374,7 @@ mod tests {
 
     #[test]
     fn test_build_github_permalink_with_single_line_selection() {
-        let permalink = Github.build_permalink(
+        let permalink = Github::new().build_permalink(
             ParsedGitRemote {
                 owner: "zed-industries".into(),
                 repo: "zed".into(),
@@ -304,7 +392,7 @@ mod tests {
 
     #[test]
     fn test_build_github_permalink_with_multi_line_selection() {
-        let permalink = Github.build_permalink(
+        let permalink = Github::new().build_permalink(
             ParsedGitRemote {
                 owner: "zed-industries".into(),
                 repo: "zed".into(),
@@ -328,7 +416,9 @@ mod tests {
         };
 
         let message = "This does not contain a pull request";
-        assert!(Github.extract_pull_request(&remote, message).is_none());
+        assert!(Github::new()
+            .extract_pull_request(&remote, message)
+            .is_none());
 
         // Pull request number at end of first line
         let message = indoc! {r#"
@@ -343,7 +433,7 @@ mod tests {
         };
 
         assert_eq!(
-            Github
+            Github::new()
                 .extract_pull_request(&remote, &message)
                 .unwrap()
                 .url
@@ -358,6 +448,66 @@ mod tests {
             See the original PR, this is a fix.
             "#
         };
-        assert_eq!(Github.extract_pull_request(&remote, &message), None);
+        assert_eq!(Github::new().extract_pull_request(&remote, &message), None);
+    }
+
+    #[test]
+    fn test_build_github_create_pull_request_url() {
+        let remote = ParsedGitRemote {
+            owner: "zed-industries".into(),
+            repo: "zed".into(),
+        };
+        let url = Github::new()
+            .build_create_pull_request_url(
+                &remote,
+                BuildCreatePullRequestParams {
+                    branch: "fix/git-panel",
+                },
+            )
+            .unwrap();
+
+        let expected_url = "https://github.com/zed-industries/zed/pull/new/fix/git-panel";
+        assert_eq!(url.to_string(), expected_url.to_string())
+    }
+
+    #[test]
+    fn test_build_github_compare_url() {
+        let remote = ParsedGitRemote {
+            owner: "zed-industries".into(),
+            repo: "zed".into(),
+        };
+        let url = Github::new()
+            .build_compare_url(
+                &remote,
+                BuildCompareParams {
+                    base: "main",
+                    head: "fix/git-panel",
+                },
+            )
+            .unwrap();
+
+        let expected_url = "https://github.com/zed-industries/zed/compare/main...fix/git-panel";
+        assert_eq!(url.to_string(), expected_url.to_string())
+    }
+
+    #[test]
+    fn test_build_github_compare_url_with_special_characters() {
+        let remote = ParsedGitRemote {
+            owner: "zed-industries".into(),
+            repo: "zed".into(),
+        };
+        let url = Github
+            .build_compare_url(
+                &remote,
+                BuildCompareParams {
+                    base: "main",
+                    head: "feature/#1 100%",
+                },
+            )
+            .unwrap();
+
+        let expected_url =
+            "https://github.com/zed-industries/zed/compare/main...feature/%231%20100%25";
+        assert_eq!(url.to_string(), expected_url.to_string())
     }
 }
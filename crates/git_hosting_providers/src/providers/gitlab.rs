@@ -5,10 +5,13 @@ use url::Url;
 use util::maybe;
 
 use git::{
-    BuildCommitPermalinkParams, BuildPermalinkParams, GitHostingProvider, ParsedGitRemote,
+    encode_branch_for_url_path, BuildCommitPermalinkParams, BuildCompareParams,
+    BuildCreatePullRequestParams, BuildPermalinkParams, GitHostingProvider, ParsedGitRemote,
     RemoteUrl,
 };
 
+use super::remote_path_segments;
+
 #[derive(Debug)]
 pub struct Gitlab {
     name: String,
@@ -23,6 +26,14 @@ impl Gitlab {
         }
     }
 
+    /// Returns a provider for a self-hosted GitLab instance.
+    pub fn new_self_hosted(name: impl Into<String>, base_url: Url) -> Self {
+        Self {
+            name: name.into(),
+            base_url,
+        }
+    }
+
     pub fn from_remote_url(remote_url: &str) -> Result<Self> {
         let host = maybe!({
             if let Some(remote_url) = remote_url.strip_prefix("git@") {
@@ -77,7 +88,7 @@ impl GitHostingProvider for Gitlab {
             return None;
         }
 
-        let mut path_segments = url.path_segments()?.collect::<Vec<_>>();
+        let mut path_segments = remote_path_segments(&url, &self.base_url)?;
         let repo = path_segments.pop()?.trim_end_matches(".git");
         let owner = path_segments.join("/");
 
@@ -122,6 +133,38 @@ impl GitHostingProvider for Gitlab {
         );
         permalink
     }
+
+    fn build_create_pull_request_url(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCreatePullRequestParams,
+    ) -> Option<Url> {
+        let BuildCreatePullRequestParams { branch } = params;
+        let ParsedGitRemote { owner, repo } = remote;
+
+        let mut url = self
+            .base_url()
+            .join(&format!("{owner}/{repo}/-/merge_requests/new"))
+            .ok()?;
+        url.query_pairs_mut()
+            .append_pair("merge_request[source_branch]", branch);
+        Some(url)
+    }
+
+    fn build_compare_url(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCompareParams,
+    ) -> Option<Url> {
+        let BuildCompareParams { base, head } = params;
+        let ParsedGitRemote { owner, repo } = remote;
+        let base = encode_branch_for_url_path(base);
+        let head = encode_branch_for_url_path(head);
+
+        self.base_url()
+            .join(&format!("{owner}/{repo}/-/compare/{base}...{head}"))
+            .ok()
+    }
 }
 
 #[cfg(test)]
@@ -290,4 +333,43 @@ mod tests {
         let expected_url = "https://gitlab-instance.big-co.com/zed-industries/zed/-/blob/b2efec9824c45fcc90c9a7eb107a50d1772a60aa/crates/zed/src/main.rs";
         assert_eq!(permalink.to_string(), expected_url.to_string())
     }
+
+    #[test]
+    fn test_build_gitlab_create_pull_request_url() {
+        let remote = ParsedGitRemote {
+            owner: "zed-industries".into(),
+            repo: "zed".into(),
+        };
+        let url = Gitlab::new()
+            .build_create_pull_request_url(
+                &remote,
+                BuildCreatePullRequestParams {
+                    branch: "fix/git-panel",
+                },
+            )
+            .unwrap();
+
+        let expected_url = "https://gitlab.com/zed-industries/zed/-/merge_requests/new?merge_request%5Bsource_branch%5D=fix%2Fgit-panel";
+        assert_eq!(url.to_string(), expected_url.to_string())
+    }
+
+    #[test]
+    fn test_build_gitlab_compare_url() {
+        let remote = ParsedGitRemote {
+            owner: "zed-industries".into(),
+            repo: "zed".into(),
+        };
+        let url = Gitlab::new()
+            .build_compare_url(
+                &remote,
+                BuildCompareParams {
+                    base: "main",
+                    head: "fix/git-panel",
+                },
+            )
+            .unwrap();
+
+        let expected_url = "https://gitlab.com/zed-industries/zed/-/compare/main...fix/git-panel";
+        assert_eq!(url.to_string(), expected_url.to_string())
+    }
 }
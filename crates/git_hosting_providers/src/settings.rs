@@ -0,0 +1,238 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use git::{GitHostingProvider, GitHostingProviderRegistry};
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources, SettingsStore};
+use url::Url;
+
+use crate::{BitbucketServer, Gitea, Github, Gitlab};
+
+/// The software a self-hosted Git hosting provider runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum GitHostingProviderKind {
+    /// GitHub Enterprise Server.
+    Github,
+    Gitlab,
+    /// Gitea or Forgejo.
+    Gitea,
+    /// Bitbucket Server or Bitbucket Data Center.
+    BitbucketServer,
+}
+
+impl GitHostingProviderKind {
+    fn display_name(&self) -> &'static str {
+        match self {
+            GitHostingProviderKind::Github => "GitHub Enterprise",
+            GitHostingProviderKind::Gitlab => "GitLab Self-Hosted",
+            GitHostingProviderKind::Gitea => "Gitea",
+            GitHostingProviderKind::BitbucketServer => "Bitbucket Server",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct GitHostingProviderConfig {
+    /// The software the instance runs.
+    pub provider: GitHostingProviderKind,
+    /// The URL of the instance, such as `https://github.example.com`.
+    /// Remotes whose host matches the host of this URL are handled by the provider.
+    pub base_url: String,
+    /// The name to show for the provider.
+    ///
+    /// Default: the kind of the provider, followed by the host of the instance
+    pub name: Option<String>,
+}
+
+impl GitHostingProviderConfig {
+    fn build_provider(&self) -> Result<Arc<dyn GitHostingProvider + Send + Sync + 'static>> {
+        let mut base_url = Url::parse(&self.base_url)
+            .with_context(|| format!("invalid git hosting provider URL {:?}", self.base_url))?;
+        let host = base_url
+            .host_str()
+            .with_context(|| format!("git hosting provider URL {:?} has no host", self.base_url))?;
+        let name = self
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("{} ({host})", self.provider.display_name()));
+
+        // Paths are joined onto the base URL, which only keeps its last segment with a trailing slash.
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+
+        Ok(match self.provider {
+            GitHostingProviderKind::Github => Arc::new(Github::new_self_hosted(name, base_url)),
+            GitHostingProviderKind::Gitlab => Arc::new(Gitlab::new_self_hosted(name, base_url)),
+            GitHostingProviderKind::Gitea => Arc::new(Gitea::new(name, base_url)),
+            GitHostingProviderKind::BitbucketServer => {
+                Arc::new(BitbucketServer::new(name, base_url))
+            }
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GitHostingProviderSettings {
+    /// Git hosting providers running on custom domains, such as GitHub Enterprise or
+    /// self-hosted GitLab instances.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub git_hosting_providers: Vec<GitHostingProviderConfig>,
+}
+
+impl Settings for GitHostingProviderSettings {
+    const KEY: Option<&'static str> = None;
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        // Providers configured in different places add up rather than replacing each other.
+        let mut settings = Self::default();
+        for source in sources.defaults_and_customizations() {
+            settings
+                .git_hosting_providers
+                .extend(source.git_hosting_providers.iter().cloned());
+        }
+        Ok(settings)
+    }
+}
+
+/// Registers the providers configured in the settings, keeping them up to date as the settings change.
+pub(crate) fn register_configured_providers(
+    provider_registry: Arc<GitHostingProviderRegistry>,
+    cx: &mut AppContext,
+) {
+    let mut configs = Vec::new();
+    let mut registered_names = Vec::new();
+    let mut update = move |cx: &mut AppContext| {
+        let settings = GitHostingProviderSettings::get_global(cx);
+        if settings.git_hosting_providers == configs {
+            return;
+        }
+        configs = settings.git_hosting_providers.clone();
+        update_configured_providers(&provider_registry, &configs, &mut registered_names);
+    };
+
+    update(cx);
+    cx.observe_global::<SettingsStore>(update).detach();
+}
+
+/// Replaces the providers registered for the previous configuration, whose names are given,
+/// with the providers of the new one. Providers named like another registered provider,
+/// such as a built-in one, are rejected so that they don't replace it.
+fn update_configured_providers(
+    provider_registry: &GitHostingProviderRegistry,
+    configs: &[GitHostingProviderConfig],
+    registered_names: &mut Vec<String>,
+) {
+    for name in registered_names.drain(..) {
+        provider_registry.unregister_hosting_provider(&name);
+    }
+    for config in configs {
+        let provider = match config.build_provider() {
+            Ok(provider) => provider,
+            Err(error) => {
+                log::error!("failed to register git hosting provider: {error:#}");
+                continue;
+            }
+        };
+        let name = provider.name();
+        if provider_registry
+            .list_hosting_providers()
+            .iter()
+            .any(|registered| registered.name() == name)
+        {
+            log::error!(
+                "failed to register git hosting provider: a provider named {name:?} already exists"
+            );
+            continue;
+        }
+        registered_names.push(name);
+        provider_registry.register_hosting_provider(provider);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use git::ParsedGitRemote;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_build_provider_from_config() {
+        let config: GitHostingProviderConfig = serde_json::from_str(
+            r#"{ "provider": "bitbucket-server", "base_url": "https://example.com/bitbucket" }"#,
+        )
+        .unwrap();
+        let provider = config.build_provider().unwrap();
+
+        assert_eq!(provider.name(), "Bitbucket Server (example.com)");
+        assert_eq!(
+            provider.parse_remote_url("ssh://git@example.com:7999/zed/editor.git"),
+            Some(ParsedGitRemote {
+                owner: "zed".into(),
+                repo: "editor".into(),
+            })
+        );
+        assert_eq!(
+            provider
+                .build_commit_permalink(
+                    &ParsedGitRemote {
+                        owner: "zed".into(),
+                        repo: "editor".into(),
+                    },
+                    git::BuildCommitPermalinkParams { sha: "f00b4r" },
+                )
+                .to_string(),
+            "https://example.com/bitbucket/projects/zed/repos/editor/commits/f00b4r"
+        );
+        assert_eq!(
+            provider.parse_remote_url("https://example.com/bitbucket/scm/zed/editor.git"),
+            Some(ParsedGitRemote {
+                owner: "zed".into(),
+                repo: "editor".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_configured_providers_do_not_replace_built_in_ones() {
+        let registry = GitHostingProviderRegistry::new();
+        registry.register_hosting_provider(Arc::new(Github::new()));
+        let provider_names = || {
+            registry
+                .list_hosting_providers()
+                .iter()
+                .map(|provider| provider.name())
+                .collect::<Vec<_>>()
+        };
+
+        let configs: Vec<GitHostingProviderConfig> = serde_json::from_str(
+            r#"[
+                { "provider": "github", "base_url": "https://github.example.com", "name": "GitHub" },
+                { "provider": "gitea", "base_url": "https://example.com/gitea", "name": "Gitea" }
+            ]"#,
+        )
+        .unwrap();
+        let mut registered_names = Vec::new();
+        update_configured_providers(&registry, &configs, &mut registered_names);
+        assert_eq!(registered_names, ["Gitea"]);
+        assert_eq!(provider_names(), ["GitHub", "Gitea"]);
+        let github = registry
+            .list_hosting_providers()
+            .into_iter()
+            .find(|provider| provider.name() == "GitHub")
+            .unwrap();
+        assert_eq!(github.base_url().as_str(), "https://github.com/");
+
+        update_configured_providers(&registry, &[], &mut registered_names);
+        assert!(registered_names.is_empty());
+        assert_eq!(provider_names(), ["GitHub"]);
+    }
+}
@@ -0,0 +1,249 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use fuzzy::{StringMatch, StringMatchCandidate};
+use git::repository::DiffBase;
+use gpui::{
+    rems, AppContext, AsyncAppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView,
+    Model, Render, Subscription, Task, View, ViewContext, WindowContext,
+};
+use picker::{Picker, PickerDelegate};
+use project::{Project, ProjectPath};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::notifications::{DetachAndPromptErr, NotifyTaskExt};
+use workspace::{ModalView, Workspace};
+
+use crate::SelectDiffBase;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(DiffBasePicker::open);
+    })
+    .detach();
+}
+
+/// A modal for choosing the revision that the git changes in the editors are computed against.
+pub struct DiffBasePicker {
+    picker: View<Picker<DiffBasePickerDelegate>>,
+    _subscription: Subscription,
+}
+
+impl DiffBasePicker {
+    fn open(workspace: &mut Workspace, _: &SelectDiffBase, cx: &mut ViewContext<Workspace>) {
+        let project = workspace.project().clone();
+        cx.spawn(|workspace, mut cx| async move {
+            let delegate = DiffBasePickerDelegate::new(project, &cx).await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                workspace.toggle_modal(cx, |cx| DiffBasePicker::new(delegate, cx))
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to read branches", cx, |_, _| None)
+    }
+
+    fn new(delegate: DiffBasePickerDelegate, cx: &mut ViewContext<Self>) -> Self {
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl ModalView for DiffBasePicker {}
+impl EventEmitter<DismissEvent> for DiffBasePicker {}
+
+impl FocusableView for DiffBasePicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for DiffBasePicker {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+struct DiffBaseEntry {
+    diff_base: DiffBase,
+    label: String,
+    positions: Vec<usize>,
+}
+
+pub struct DiffBasePickerDelegate {
+    project: Model<Project>,
+    current: DiffBase,
+    candidates: Vec<DiffBase>,
+    matches: Vec<DiffBaseEntry>,
+    selected_index: usize,
+}
+
+impl DiffBasePickerDelegate {
+    async fn new(project: Model<Project>, cx: &AsyncAppContext) -> Result<Self> {
+        let (current, branches_request) = cx.update(|cx| {
+            let project = project.read(cx);
+            let worktree = project
+                .visible_worktrees(cx)
+                .next()
+                .context("No worktrees found")?;
+            let project_path = ProjectPath::root_path(worktree.read(cx).id());
+            anyhow::Ok((
+                project.git_diff_base(cx).clone(),
+                project.branches(project_path, cx),
+            ))
+        })??;
+        let mut branches = branches_request.await?;
+        branches.sort_by(|a, b| b.is_head.cmp(&a.is_head).then(a.name.cmp(&b.name)));
+
+        let mut candidates = vec![DiffBase::Index, DiffBase::Revision("HEAD".into())];
+        for branch in branches.iter().filter(|branch| !branch.is_head) {
+            candidates.push(DiffBase::MergeBase(branch.name.to_string()));
+        }
+        for branch in &branches {
+            candidates.push(DiffBase::Revision(branch.name.to_string()));
+        }
+
+        Ok(Self {
+            project,
+            current,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        })
+    }
+}
+
+impl PickerDelegate for DiffBasePickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        "Compare with a branch, tag or commit...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self
+            .candidates
+            .iter()
+            .enumerate()
+            .map(|(ix, diff_base)| StringMatchCandidate::new(ix, &diff_base.to_string()))
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    10000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches
+                        .into_iter()
+                        .map(|string_match| DiffBaseEntry {
+                            diff_base: delegate.candidates[string_match.candidate_id].clone(),
+                            label: string_match.string,
+                            positions: string_match.positions,
+                        })
+                        .collect();
+
+                    // Any revision git understands can be typed in, not only branches.
+                    let revision = query.trim();
+                    if !revision.is_empty()
+                        && !delegate
+                            .matches
+                            .iter()
+                            .any(|entry| entry.diff_base == DiffBase::Revision(revision.into()))
+                    {
+                        delegate.matches.push(DiffBaseEntry {
+                            diff_base: DiffBase::Revision(revision.into()),
+                            label: format!("Compare with '{revision}'"),
+                            positions: Vec::new(),
+                        });
+                        delegate.matches.push(DiffBaseEntry {
+                            diff_base: DiffBase::MergeBase(revision.into()),
+                            label: format!("Merge Base with '{revision}'"),
+                            positions: Vec::new(),
+                        });
+                    }
+
+                    delegate.selected_index = delegate
+                        .matches
+                        .iter()
+                        .position(|entry| entry.diff_base == delegate.current)
+                        .filter(|_| query.is_empty())
+                        .unwrap_or(0);
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(entry) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        self.project
+            .update(cx, |project, cx| {
+                project.set_git_diff_base(entry.diff_base.clone(), cx)
+            })
+            .detach_and_notify_err(cx);
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry = &self.matches[ix];
+        let label = match &entry.diff_base {
+            DiffBase::Index => "Index (unstaged changes)".to_string(),
+            _ => entry.label.clone(),
+        };
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(HighlightedLabel::new(label, entry.positions.clone()))
+                .when(entry.diff_base == self.current, |item| {
+                    item.end_slot(Icon::new(IconName::Check).color(Color::Accent))
+                }),
+        )
+    }
+}
@@ -1,7 +1,7 @@
 use collections::HashMap;
 use std::{
     cell::OnceCell,
-    collections::HashSet,
+    collections::{BTreeSet, HashSet},
     ffi::OsStr,
     ops::Range,
     path::{Path, PathBuf},
@@ -9,20 +9,24 @@ use std::{
     time::Duration,
 };
 
-use git::repository::GitFileStatus;
+use editor::Editor;
+use git::{repository::GitFileStatus, status::StageStatus};
 
 use util::{ResultExt, TryFutureExt};
 
 use db::kvp::KEY_VALUE_STORE;
 use gpui::*;
-use project::{Entry, EntryKind, Fs, Project, ProjectEntryId, WorktreeId};
+use project::{Entry, EntryKind, Fs, Project, ProjectEntryId, ProjectPath, WorktreeId};
 use serde::{Deserialize, Serialize};
 use settings::Settings as _;
 use ui::{
     prelude::*, Checkbox, Divider, DividerColor, ElevationIndex, Scrollbar, ScrollbarState, Tooltip,
 };
 use workspace::dock::{DockPosition, Panel, PanelEvent};
-use workspace::Workspace;
+use workspace::{
+    notifications::{NotifyResultExt, NotifyTaskExt},
+    Workspace,
+};
 
 use crate::{git_status_icon, settings::GitPanelSettings};
 use crate::{CommitAllChanges, CommitStagedChanges, DiscardAll, StageAll, UnstageAll};
@@ -53,11 +57,13 @@ pub struct GitStatusEntry {}
 struct EntryDetails {
     filename: String,
     display_name: String,
+    worktree_id: WorktreeId,
     path: Arc<Path>,
     kind: EntryKind,
     depth: usize,
     is_expanded: bool,
     status: Option<GitFileStatus>,
+    stage_status: Option<StageStatus>,
 }
 
 impl EntryDetails {
@@ -73,6 +79,7 @@ struct SerializedGitPanel {
 
 pub struct GitPanel {
     _workspace: WeakView<Workspace>,
+    commit_editor: View<Editor>,
     current_modifiers: Modifiers,
     focus_handle: FocusHandle,
     fs: Arc<dyn Fs>,
@@ -88,6 +95,10 @@ pub struct GitPanel {
     // The entries that are currently shown in the panel, aka
     // not hidden by folding or such
     visible_entries: Vec<(WorktreeId, Vec<Entry>, OnceCell<HashSet<Arc<Path>>>)>,
+    // Whether the changes of the visible files are staged, by worktree
+    stage_statuses: HashMap<WorktreeId, HashMap<Arc<Path>, StageStatus>>,
+    update_stage_statuses_task: Task<()>,
+    pending_commit: Option<Task<()>>,
     width: Option<Pixels>,
 }
 
@@ -122,6 +133,7 @@ impl GitPanel {
                     cx.notify();
                 }
                 project::Event::WorktreeUpdatedEntries(_, _)
+                | project::Event::WorktreeUpdatedGitRepositories(_)
                 | project::Event::WorktreeAdded(_)
                 | project::Event::WorktreeOrderChanged => {
                     this.update_visible_entries(None, cx);
@@ -131,15 +143,26 @@ impl GitPanel {
             })
             .detach();
 
+            let commit_editor = cx.new_view(|cx| {
+                let mut editor = Editor::multi_line(cx);
+                editor.set_placeholder_text("Add a message", cx);
+                editor
+            });
+            cx.observe(&commit_editor, |_, _, cx| cx.notify()).detach();
+
             let scroll_handle = UniformListScrollHandle::new();
 
             let mut this = Self {
                 _workspace: weak_workspace,
+                commit_editor,
                 focus_handle: cx.focus_handle(),
                 fs,
                 pending_serialization: Task::ready(None),
                 project,
                 visible_entries: Vec::new(),
+                stage_statuses: HashMap::default(),
+                update_stage_statuses_task: Task::ready(()),
+                pending_commit: None,
                 current_modifiers: cx.modifiers(),
                 expanded_dir_ids: Default::default(),
 
@@ -254,36 +277,260 @@ impl GitPanel {
 }
 
 impl GitPanel {
-    fn stage_all(&mut self, _: &StageAll, _cx: &mut ViewContext<Self>) {
-        // TODO: Implement stage all
-        println!("Stage all triggered");
+    fn stage_all(&mut self, _: &StageAll, cx: &mut ViewContext<Self>) {
+        let paths = self.changed_paths(|_| true);
+        self.update_paths(paths, Project::stage_git_paths, cx)
+            .detach_and_notify_err(cx);
     }
 
-    fn unstage_all(&mut self, _: &UnstageAll, _cx: &mut ViewContext<Self>) {
-        // TODO: Implement unstage all
-        println!("Unstage all triggered");
+    fn unstage_all(&mut self, _: &UnstageAll, cx: &mut ViewContext<Self>) {
+        let paths = self.changed_paths(|status| status.has_staged_changes());
+        self.update_paths(paths, Project::unstage_git_paths, cx)
+            .detach_and_notify_err(cx);
     }
 
-    fn discard_all(&mut self, _: &DiscardAll, _cx: &mut ViewContext<Self>) {
-        // TODO: Implement discard all
-        println!("Discard all triggered");
+    fn discard_all(&mut self, _: &DiscardAll, cx: &mut ViewContext<Self>) {
+        let paths = self.changed_paths(|_| true);
+        if paths.is_empty() {
+            return;
+        }
+
+        let answer = cx.prompt(
+            PromptLevel::Warning,
+            "Discard all changes?",
+            Some("Changed files are restored to their last committed state and new files are deleted. This cannot be undone."),
+            &["Discard", "Cancel"],
+        );
+        cx.spawn(|this, mut cx| async move {
+            if answer.await != Ok(0) {
+                return Ok(());
+            }
+            this.update(&mut cx, |this, cx| {
+                this.update_paths(paths, Project::discard_git_paths, cx)
+            })?
+            .await
+        })
+        .detach_and_notify_err(cx);
     }
 
     /// Commit all staged changes
-    fn commit_staged_changes(&mut self, _: &CommitStagedChanges, _cx: &mut ViewContext<Self>) {
-        // TODO: Implement commit all staged
-        println!("Commit staged changes triggered");
+    fn commit_staged_changes(&mut self, _: &CommitStagedChanges, cx: &mut ViewContext<Self>) {
+        self.commit(false, cx);
     }
 
     /// Commit all changes, regardless of whether they are staged or not
-    fn commit_all_changes(&mut self, _: &CommitAllChanges, _cx: &mut ViewContext<Self>) {
-        // TODO: Implement commit all changes
-        println!("Commit all changes triggered");
+    fn commit_all_changes(&mut self, _: &CommitAllChanges, cx: &mut ViewContext<Self>) {
+        self.commit(true, cx);
     }
 
-    fn all_staged(&self) -> bool {
-        // TODO: Implement all_staged
-        true
+    fn commit(&mut self, include_unstaged: bool, cx: &mut ViewContext<Self>) {
+        if !self.can_commit(include_unstaged, cx) {
+            return;
+        }
+
+        let message = self.commit_editor.read(cx).text(cx);
+        let paths = if include_unstaged {
+            self.changed_paths(|_| true)
+        } else {
+            self.changed_paths(|status| status.has_staged_changes())
+        };
+        let repositories = self.repositories_for_paths(&paths, cx);
+        let stage_task =
+            include_unstaged.then(|| self.update_paths(paths, Project::stage_git_paths, cx));
+
+        self.pending_commit = Some(cx.spawn(|this, mut cx| async move {
+            let result = async {
+                if let Some(stage_task) = stage_task {
+                    stage_task.await?;
+                }
+                let commit_tasks = this.update(&mut cx, |this, cx| {
+                    let project = this.project.read(cx);
+                    repositories
+                        .into_iter()
+                        .map(|repository| {
+                            project.commit_git_changes(repository, message.clone(), cx)
+                        })
+                        .collect::<Vec<_>>()
+                })?;
+                futures::future::try_join_all(commit_tasks).await
+            }
+            .await;
+
+            this.update(&mut cx, |this, cx| {
+                this.pending_commit = None;
+                if result.is_ok() {
+                    this.commit_editor.update(cx, |editor, cx| editor.clear(cx));
+                }
+                this.update_stage_statuses(cx);
+                cx.notify();
+            })
+            .ok();
+            result.notify_async_err(&mut cx);
+        }));
+        cx.notify();
+    }
+
+    fn can_commit(&self, include_unstaged: bool, cx: &AppContext) -> bool {
+        self.pending_commit.is_none()
+            && !self.commit_editor.read(cx).text(cx).trim().is_empty()
+            && if include_unstaged {
+                !self.changed_paths(|_| true).is_empty()
+            } else {
+                !self
+                    .changed_paths(|status| status.has_staged_changes())
+                    .is_empty()
+            }
+    }
+
+    fn toggle_staged_for_entry(&mut self, details: &EntryDetails, cx: &mut ViewContext<Self>) {
+        let paths = vec![(details.worktree_id, vec![details.path.clone()])];
+        let task = if details
+            .stage_status
+            .map_or(true, |status| status.has_unstaged_changes())
+        {
+            self.update_paths(paths, Project::stage_git_paths, cx)
+        } else {
+            self.update_paths(paths, Project::unstage_git_paths, cx)
+        };
+        task.detach_and_notify_err(cx);
+    }
+
+    /// Runs a git operation on the given paths of each worktree, then refreshes the stage statuses.
+    fn update_paths(
+        &mut self,
+        paths: Vec<(WorktreeId, Vec<Arc<Path>>)>,
+        update: fn(&Project, WorktreeId, Vec<Arc<Path>>, &AppContext) -> Task<Result<()>>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let project = self.project.read(cx);
+        let tasks = paths
+            .into_iter()
+            .map(|(worktree_id, paths)| update(project, worktree_id, paths, cx))
+            .collect::<Vec<_>>();
+        cx.spawn(|this, mut cx| async move {
+            let result = futures::future::try_join_all(tasks).await;
+            this.update(&mut cx, |this, cx| this.update_stage_statuses(cx))?;
+            result.map(drop)
+        })
+    }
+
+    /// Returns the changed files whose stage status matches the predicate, by worktree.
+    /// This includes deleted files, which only appear in the repository statuses.
+    /// Files whose stage status is not known yet are considered unstaged.
+    fn changed_paths(
+        &self,
+        predicate: impl Fn(StageStatus) -> bool,
+    ) -> Vec<(WorktreeId, Vec<Arc<Path>>)> {
+        let mut worktree_ids = self
+            .visible_entries
+            .iter()
+            .map(|(worktree_id, _, _)| *worktree_id)
+            .collect::<Vec<_>>();
+        for worktree_id in self.stage_statuses.keys() {
+            if !worktree_ids.contains(worktree_id) {
+                worktree_ids.push(*worktree_id);
+            }
+        }
+
+        worktree_ids
+            .into_iter()
+            .filter_map(|worktree_id| {
+                let stage_statuses = self.stage_statuses.get(&worktree_id);
+                let mut paths = stage_statuses
+                    .into_iter()
+                    .flatten()
+                    .filter(|(_, status)| predicate(**status))
+                    .map(|(path, _)| path.clone())
+                    .collect::<BTreeSet<_>>();
+                if let Some((_, entries, _)) = self
+                    .visible_entries
+                    .iter()
+                    .find(|(id, _, _)| *id == worktree_id)
+                {
+                    paths.extend(
+                        entries
+                            .iter()
+                            .filter(|entry| entry.is_file() && entry.git_status.is_some())
+                            .filter(|entry| {
+                                stage_statuses
+                                    .map_or(true, |statuses| !statuses.contains_key(&entry.path))
+                                    && predicate(StageStatus::Unstaged)
+                            })
+                            .map(|entry| entry.path.clone()),
+                    );
+                }
+                (!paths.is_empty()).then(|| (worktree_id, paths.into_iter().collect()))
+            })
+            .collect()
+    }
+
+    /// Returns a path in each of the repositories that contain the given paths.
+    fn repositories_for_paths(
+        &self,
+        paths: &[(WorktreeId, Vec<Arc<Path>>)],
+        cx: &AppContext,
+    ) -> Vec<ProjectPath> {
+        let project = self.project.read(cx);
+        let mut repositories = Vec::new();
+        for (worktree_id, paths) in paths {
+            let Some(worktree) = project.worktree_for_id(*worktree_id, cx) else {
+                continue;
+            };
+            let snapshot = worktree.read(cx).snapshot();
+            let mut work_directory_ids = HashSet::new();
+            for path in paths {
+                if let Some(repository) = snapshot.repository_for_path(path) {
+                    if work_directory_ids.insert(repository.work_directory_id()) {
+                        repositories.push(ProjectPath {
+                            worktree_id: *worktree_id,
+                            path: path.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        repositories
+    }
+
+    fn update_stage_statuses(&mut self, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        let tasks = project
+            .visible_worktrees(cx)
+            .filter(|worktree| worktree.read(cx).repositories().next().is_some())
+            .map(|worktree| {
+                let worktree_id = worktree.read(cx).id();
+                let task = project.git_stage_statuses(worktree_id, None, cx);
+                async move { (worktree_id, task.await) }
+            })
+            .collect::<Vec<_>>();
+
+        self.update_stage_statuses_task = cx.spawn(|this, mut cx| async move {
+            let results = futures::future::join_all(tasks).await;
+            this.update(&mut cx, |this, cx| {
+                this.stage_statuses = results
+                    .into_iter()
+                    .filter_map(|(worktree_id, statuses)| {
+                        Some((worktree_id, statuses.log_err()?.into_iter().collect()))
+                    })
+                    .collect();
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn all_staged_state(&self) -> ToggleState {
+        let has_unstaged = !self
+            .changed_paths(|status| status.has_unstaged_changes())
+            .is_empty();
+        let has_staged = !self
+            .changed_paths(|status| status.has_staged_changes())
+            .is_empty();
+        match (has_staged, has_unstaged) {
+            (true, false) => ToggleState::Selected,
+            (true, true) => ToggleState::Indeterminate,
+            (false, _) => ToggleState::Unselected,
+        }
     }
 
     fn no_entries(&self) -> bool {
@@ -362,13 +609,21 @@ impl GitPanel {
 
                     let display_name = entry.path.to_string_lossy().into_owned();
 
+                    let stage_status = self
+                        .stage_statuses
+                        .get(worktree_id)
+                        .and_then(|statuses| statuses.get(&entry.path))
+                        .copied();
+
                     let details = EntryDetails {
                         filename,
                         display_name,
+                        worktree_id: *worktree_id,
                         kind: entry.kind,
                         is_expanded,
                         path: entry.path.clone(),
                         status,
+                        stage_status,
                         depth,
                     };
                     callback(entry.id, details, cx);
@@ -424,6 +679,7 @@ impl GitPanel {
             );
         }
 
+        self.update_stage_statuses(cx);
         cx.notify();
     }
 }
@@ -455,6 +711,7 @@ impl GitPanel {
         let focus_handle = self.focus_handle(cx).clone();
 
         let changes_string = format!("{} changes", self.entry_count());
+        let all_staged_state = self.all_staged_state();
 
         h_flex()
             .h(px(32.))
@@ -464,7 +721,17 @@ impl GitPanel {
             .child(
                 h_flex()
                     .gap_2()
-                    .child(Checkbox::new("all-changes", true.into()).disabled(true))
+                    .child(
+                        Checkbox::new("all-changes", all_staged_state)
+                            .disabled(self.no_entries())
+                            .on_click(cx.listener(move |this, _, cx| {
+                                if all_staged_state == ToggleState::Selected {
+                                    this.unstage_all(&UnstageAll, cx)
+                                } else {
+                                    this.stage_all(&StageAll, cx)
+                                }
+                            })),
+                    )
                     .child(div().text_buffer(cx).text_ui_sm(cx).child(changes_string)),
             )
             .child(div().flex_grow())
@@ -484,11 +751,12 @@ impl GitPanel {
                                 )
                             })
                             .icon_size(IconSize::Small)
-                            .disabled(true),
+                            .disabled(self.no_entries())
+                            .on_click(cx.listener(|this, _, cx| this.discard_all(&DiscardAll, cx))),
                     )
-                    .child(if self.all_staged() {
+                    .child(if all_staged_state == ToggleState::Selected {
                         self.panel_button("unstage-all", "Unstage All").on_click(
-                            cx.listener(move |_, _, cx| cx.dispatch_action(Box::new(DiscardAll))),
+                            cx.listener(move |_, _, cx| cx.dispatch_action(Box::new(UnstageAll))),
                         )
                     } else {
                         self.panel_button("stage-all", "Stage All").on_click(
@@ -502,8 +770,12 @@ impl GitPanel {
         let focus_handle_1 = self.focus_handle(cx).clone();
         let focus_handle_2 = self.focus_handle(cx).clone();
 
+        let can_commit_staged = self.can_commit(false, cx);
+        let can_commit_all = self.can_commit(true, cx);
+
         let commit_staged_button = self
             .panel_button("commit-staged-changes", "Commit")
+            .disabled(!can_commit_staged)
             .tooltip(move |cx| {
                 let focus_handle = focus_handle_1.clone();
                 Tooltip::for_action_in(
@@ -519,6 +791,7 @@ impl GitPanel {
 
         let commit_all_button = self
             .panel_button("commit-all-changes", "Commit All")
+            .disabled(!can_commit_all)
             .tooltip(move |cx| {
                 let focus_handle = focus_handle_2.clone();
                 Tooltip::for_action_in(
@@ -540,10 +813,8 @@ impl GitPanel {
                 .bg(cx.theme().colors().editor_background)
                 .font_buffer(cx)
                 .text_ui_sm(cx)
-                .text_color(cx.theme().colors().text_muted)
-                .child("Add a message")
                 .gap_1()
-                .child(div().flex_grow())
+                .child(div().flex_grow().child(self.commit_editor.clone()))
                 .child(h_flex().child(div().gap_1().flex_grow()).child(
                     if self.current_modifiers.alt {
                         commit_all_button
@@ -551,8 +822,9 @@ impl GitPanel {
                         commit_staged_button
                     },
                 ))
-                .cursor(CursorStyle::OperationNotAllowed)
-                .opacity(0.5),
+                .when(self.project.read(cx).is_read_only(cx), |this| {
+                    this.cursor(CursorStyle::OperationNotAllowed).opacity(0.5)
+                }),
         )
     }
 
@@ -658,7 +930,12 @@ impl GitPanel {
     ) -> impl IntoElement {
         let id = id.to_proto() as usize;
         let checkbox_id = ElementId::Name(format!("checkbox_{}", id).into());
-        let is_staged = ToggleState::Selected;
+        let is_staged = match details.stage_status {
+            Some(StageStatus::Staged) => ToggleState::Selected,
+            Some(StageStatus::PartiallyStaged) => ToggleState::Indeterminate,
+            Some(StageStatus::Unstaged) | None => ToggleState::Unselected,
+        };
+        let is_read_only = self.project.read(cx).is_read_only(cx);
 
         h_flex()
             .id(id)
@@ -671,7 +948,14 @@ impl GitPanel {
             .font_buffer(cx)
             .text_ui_sm(cx)
             .when(!details.is_dir(), |this| {
-                this.child(Checkbox::new(checkbox_id, is_staged))
+                let details = details.clone();
+                this.child(
+                    Checkbox::new(checkbox_id, is_staged)
+                        .disabled(is_read_only)
+                        .on_click(cx.listener(move |this, _, cx| {
+                            this.toggle_staged_for_entry(&details, cx)
+                        })),
+                )
             })
             .when_some(details.status, |this, status| {
                 this.child(git_status_icon(status))
@@ -4,8 +4,10 @@ use gpui::{actions, AppContext, Hsla};
 use settings::GitPanelSettings;
 use ui::{Color, Icon, IconName, IntoElement};
 
+pub mod diff_base_picker;
 pub mod git_panel;
 mod settings;
+pub mod stash_picker;
 
 actions!(
     git_ui,
@@ -14,7 +16,10 @@ actions!(
         UnstageAll,
         DiscardAll,
         CommitStagedChanges,
-        CommitAllChanges
+        CommitAllChanges,
+        SelectDiffBase,
+        StashChanges,
+        ShowStashes
     ]
 );
 
@@ -0,0 +1,426 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use collections::HashMap;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use git::{
+    history::CommitFileDiff,
+    stash::{StashAction, StashEntry},
+    Oid,
+};
+use gpui::{
+    rems, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
+    Render, Subscription, Task, View, ViewContext, WindowContext,
+};
+use picker::{Picker, PickerDelegate};
+use project::{Project, ProjectPath};
+use similar::{ChangeTag, TextDiff};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::notifications::{DetachAndPromptErr, NotifyTaskExt};
+use workspace::{ModalView, Workspace};
+
+use crate::{ShowStashes, StashChanges};
+
+/// The maximum number of diff lines shown in the preview of a stash entry.
+const PREVIEW_LINE_LIMIT: usize = 400;
+const PREVIEW_CONTEXT_LINES: usize = 2;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(StashPicker::open);
+        workspace.register_action(|workspace, _: &StashChanges, cx| {
+            let project = workspace.project().read(cx);
+            let Some(repository) = repository_root(project, cx) else {
+                return;
+            };
+            project
+                .git_stash_push(repository, None, cx)
+                .detach_and_notify_err(cx);
+        });
+    })
+    .detach();
+}
+
+/// The path of the repository that the git actions of the workspace operate on.
+fn repository_root(project: &Project, cx: &AppContext) -> Option<ProjectPath> {
+    let worktree = project.visible_worktrees(cx).next()?;
+    Some(ProjectPath::root_path(worktree.read(cx).id()))
+}
+
+/// A modal listing the stash entries, with a preview of the changes of the selected one.
+pub struct StashPicker {
+    picker: View<Picker<StashPickerDelegate>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl StashPicker {
+    fn open(workspace: &mut Workspace, _: &ShowStashes, cx: &mut ViewContext<Workspace>) {
+        let project = workspace.project().clone();
+        let repository = repository_root(project.read(cx), cx);
+        cx.spawn(|workspace, mut cx| async move {
+            let repository = repository.context("No worktrees found")?;
+            let entries = project
+                .update(&mut cx, |project, cx| {
+                    project.git_stash_list(repository.clone(), cx)
+                })?
+                .await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let delegate = StashPickerDelegate::new(project, repository, entries);
+                workspace.toggle_modal(cx, |cx| StashPicker::new(delegate, cx))
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to read the stash", cx, |_, _| None)
+    }
+
+    fn new(delegate: StashPickerDelegate, cx: &mut ViewContext<Self>) -> Self {
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        let _subscriptions = vec![
+            cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent)),
+            cx.observe(&picker, |_, _, cx| cx.notify()),
+        ];
+        Self {
+            picker,
+            _subscriptions,
+        }
+    }
+
+    fn render_preview(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let delegate = &self.picker.read(cx).delegate;
+        let files = delegate
+            .selected_entry()
+            .and_then(|entry| delegate.diffs.get(&entry.sha));
+        let colors = cx.theme().colors();
+        let status = cx.theme().status();
+
+        let mut lines = Vec::new();
+        for file in files.into_iter().flatten() {
+            if lines.len() >= PREVIEW_LINE_LIMIT {
+                break;
+            }
+            lines.push(
+                div()
+                    .pt_2()
+                    .child(Label::new(file.path.to_string_lossy().to_string()).color(Color::Muted))
+                    .into_any_element(),
+            );
+            let old_text = file.old_text.as_deref().unwrap_or_default();
+            let new_text = file.new_text.as_deref().unwrap_or_default();
+            let diff = TextDiff::from_lines(old_text, new_text);
+            for group in diff.grouped_ops(PREVIEW_CONTEXT_LINES) {
+                for change in group.iter().flat_map(|op| diff.iter_changes(op)) {
+                    if lines.len() >= PREVIEW_LINE_LIMIT {
+                        break;
+                    }
+                    let (prefix, background) = match change.tag() {
+                        ChangeTag::Equal => (' ', None),
+                        ChangeTag::Insert => ('+', Some(status.created_background)),
+                        ChangeTag::Delete => ('-', Some(status.deleted_background)),
+                    };
+                    let text = change.value().trim_end_matches(['\r', '\n']);
+                    lines.push(
+                        div()
+                            .px_1()
+                            .font_buffer(cx)
+                            .text_ui_sm(cx)
+                            .when_some(background, |this, background| this.bg(background))
+                            .child(format!("{prefix}{text}"))
+                            .into_any_element(),
+                    );
+                }
+            }
+        }
+
+        v_flex()
+            .id("stash-preview")
+            .flex_1()
+            .min_w_0()
+            .h(rems(28.))
+            .p_2()
+            .overflow_y_scroll()
+            .border_l_1()
+            .border_color(colors.border_variant)
+            .bg(colors.editor_background)
+            .when(lines.is_empty(), |this| {
+                this.items_center()
+                    .justify_center()
+                    .child(Label::new("No changes to preview").color(Color::Muted))
+            })
+            .children(lines)
+    }
+}
+
+impl ModalView for StashPicker {}
+impl EventEmitter<DismissEvent> for StashPicker {}
+
+impl FocusableView for StashPicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for StashPicker {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .w(rems(64.))
+            .items_start()
+            .child(v_flex().w(rems(28.)).child(self.picker.clone()))
+            .child(self.render_preview(cx))
+    }
+}
+
+pub struct StashPickerDelegate {
+    project: Model<Project>,
+    repository: ProjectPath,
+    entries: Vec<StashEntry>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    diffs: HashMap<Oid, Vec<CommitFileDiff>>,
+    _load_diff_task: Option<Task<()>>,
+}
+
+impl StashPickerDelegate {
+    fn new(project: Model<Project>, repository: ProjectPath, entries: Vec<StashEntry>) -> Self {
+        Self {
+            project,
+            repository,
+            entries,
+            matches: Vec::new(),
+            selected_index: 0,
+            diffs: HashMap::default(),
+            _load_diff_task: None,
+        }
+    }
+
+    fn selected_entry(&self) -> Option<&StashEntry> {
+        let string_match = self.matches.get(self.selected_index)?;
+        self.entries.get(string_match.candidate_id)
+    }
+
+    fn load_selected_diff(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let sha = entry.sha;
+        if self.diffs.contains_key(&sha) {
+            return;
+        }
+        // A stash entry is a commit on top of the commit its changes were stashed on.
+        let diff = self
+            .project
+            .read(cx)
+            .git_commit_diff(self.repository.clone(), sha, cx);
+        self._load_diff_task = Some(cx.spawn(|picker, mut cx| async move {
+            let files = diff.await.log_err().unwrap_or_default();
+            picker
+                .update(&mut cx, |picker, cx| {
+                    picker.delegate.diffs.insert(sha, files);
+                    cx.notify();
+                })
+                .ok();
+        }));
+    }
+
+    fn run_stash_action(&mut self, action: StashAction, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let task =
+            self.project
+                .read(cx)
+                .git_stash_action(self.repository.clone(), entry.sha, action, cx);
+        cx.spawn(|picker, mut cx| async move {
+            task.await?;
+            if action == StashAction::Drop {
+                let entries = picker
+                    .update(&mut cx, |picker, cx| {
+                        let delegate = &picker.delegate;
+                        delegate
+                            .project
+                            .read(cx)
+                            .git_stash_list(delegate.repository.clone(), cx)
+                    })?
+                    .await?;
+                picker.update(&mut cx, |picker, cx| {
+                    picker.delegate.entries = entries;
+                    picker.refresh(cx);
+                })?;
+            } else {
+                picker.update(&mut cx, |_, cx| cx.emit(DismissEvent))?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to update the stash", cx, |_, _| None);
+    }
+
+    fn stash_changes(&mut self, message: Option<String>, cx: &mut ViewContext<Picker<Self>>) {
+        let task = self
+            .project
+            .read(cx)
+            .git_stash_push(self.repository.clone(), message, cx);
+        cx.spawn(|picker, mut cx| async move {
+            task.await?;
+            picker.update(&mut cx, |_, cx| cx.emit(DismissEvent))?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to stash changes", cx, |_, _| None);
+    }
+}
+
+impl PickerDelegate for StashPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        "Search stash entries...".into()
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        "No stash entries".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+        self.load_selected_diff(cx);
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(ix, entry)| StringMatchCandidate::new(ix, &entry.message))
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    10000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+
+            picker
+                .update(&mut cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    delegate.selected_index = delegate
+                        .selected_index
+                        .min(delegate.matches.len().saturating_sub(1));
+                    delegate.load_selected_diff(cx);
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let action = if secondary {
+            StashAction::Apply
+        } else {
+            StashAction::Pop
+        };
+        self.run_stash_action(action, cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let string_match = &self.matches[ix];
+        let entry = self.entries.get(string_match.candidate_id)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(
+                    Label::new(format!("stash@{{{}}}", entry.index))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(HighlightedLabel::new(
+                    entry.message.clone(),
+                    string_match.positions.clone(),
+                )),
+        )
+    }
+
+    fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        let has_selection = self.selected_entry().is_some();
+        Some(
+            h_flex()
+                .w_full()
+                .p_2()
+                .gap_1()
+                .justify_between()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Button::new("stash-changes", "Stash Changes")
+                        .label_size(LabelSize::Small)
+                        .on_click(cx.listener(|picker, _, cx| {
+                            let query = picker.query(cx);
+                            let message = Some(query.trim().to_string()).filter(|m| !m.is_empty());
+                            picker.delegate.stash_changes(message, cx);
+                        })),
+                )
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            Button::new("drop-stash", "Drop")
+                                .label_size(LabelSize::Small)
+                                .disabled(!has_selection)
+                                .on_click(cx.listener(|picker, _, cx| {
+                                    picker.delegate.run_stash_action(StashAction::Drop, cx);
+                                })),
+                        )
+                        .child(
+                            Button::new("apply-stash", "Apply")
+                                .label_size(LabelSize::Small)
+                                .disabled(!has_selection)
+                                .on_click(cx.listener(|picker, _, cx| {
+                                    picker.delegate.run_stash_action(StashAction::Apply, cx);
+                                })),
+                        )
+                        .child(
+                            Button::new("pop-stash", "Pop")
+                                .label_size(LabelSize::Small)
+                                .disabled(!has_selection)
+                                .on_click(cx.listener(|picker, _, cx| {
+                                    picker.delegate.run_stash_action(StashAction::Pop, cx);
+                                })),
+                        ),
+                )
+                .into_any_element(),
+        )
+    }
+}
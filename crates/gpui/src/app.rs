@@ -259,6 +259,7 @@ pub struct AppContext {
     // TypeId is the type of the event that the listener callback expects
     pub(crate) event_listeners: SubscriberSet<EntityId, (TypeId, Listener)>,
     pub(crate) keystroke_observers: SubscriberSet<(), KeystrokeObserver>,
+    pub(crate) keystroke_interceptors: SubscriberSet<(), KeystrokeObserver>,
     pub(crate) keyboard_layout_observers: SubscriberSet<(), Handler>,
     pub(crate) release_listeners: SubscriberSet<EntityId, ReleaseListener>,
     pub(crate) global_observers: SubscriberSet<TypeId, Handler>,
@@ -321,6 +322,7 @@ impl AppContext {
                 event_listeners: SubscriberSet::new(),
                 release_listeners: SubscriberSet::new(),
                 keystroke_observers: SubscriberSet::new(),
+                keystroke_interceptors: SubscriberSet::new(),
                 keyboard_layout_observers: SubscriberSet::new(),
                 global_observers: SubscriberSet::new(),
                 quit_observers: SubscriberSet::new(),
@@ -1168,6 +1170,29 @@ impl AppContext {
         )
     }
 
+    /// Register a callback to be invoked when a keystroke is received by the application
+    /// in any window, before it is dispatched. Unlike [`Self::observe_keystrokes`], this sees
+    /// every keystroke, including the ones that make up a multi-keystroke binding.
+    /// The event's action is always `None`, as no action has been resolved yet.
+    ///
+    /// The callback is invoked for every key-down event in every window, whether or not it is
+    /// then handled or its propagation stopped, including the keystrokes dispatched with
+    /// [`WindowContext::dispatch_keystroke`], which are marked with [`KeystrokeEvent::is_simulated`].
+    pub fn intercept_keystrokes(
+        &mut self,
+        mut f: impl FnMut(&KeystrokeEvent, &mut WindowContext) + 'static,
+    ) -> Subscription {
+        let (subscription, activate) = self.keystroke_interceptors.insert(
+            (),
+            Box::new(move |event, cx| {
+                f(event, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
     /// Register key bindings.
     pub fn bind_keys(&mut self, bindings: impl IntoIterator<Item = KeyBinding>) {
         self.keymap.borrow_mut().add_bindings(bindings);
@@ -1622,6 +1647,10 @@ pub struct KeystrokeEvent {
 
     /// The action that was resolved for the keystroke, if any
     pub action: Option<Box<dyn Action>>,
+
+    /// Whether the keystroke was dispatched by the application with
+    /// [`WindowContext::dispatch_keystroke`], rather than typed by the user
+    pub is_simulated: bool,
 }
 
 struct NullHttpClient;
@@ -401,7 +401,7 @@ impl TestAppContext {
 
     /// dispatches a single Keystroke (see also `simulate_keystrokes` and `simulate_input`)
     pub fn dispatch_keystroke(&mut self, window: AnyWindowHandle, keystroke: Keystroke) {
-        self.update_window(window, |_, cx| cx.dispatch_keystroke(keystroke))
+        self.update_window(window, |_, cx| cx.dispatch_typed_keystroke(keystroke))
             .unwrap();
     }
 
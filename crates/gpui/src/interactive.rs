@@ -465,6 +465,7 @@ impl PlatformInput {
 
 #[cfg(test)]
 mod test {
+    use std::{cell::RefCell, rc::Rc};
 
     use crate::{
         self as gpui, div, FocusHandle, InteractiveElement, IntoElement, KeyBinding, Keystroke,
@@ -535,4 +536,48 @@ mod test {
             })
             .unwrap();
     }
+
+    #[gpui::test]
+    fn test_intercept_simulated_keystrokes(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(Default::default(), |cx| {
+                cx.new_view(|cx| TestView {
+                    saw_key_down: false,
+                    saw_action: false,
+                    focus_handle: cx.focus_handle(),
+                })
+            })
+            .unwrap()
+        });
+        window
+            .update(cx, |test_view, cx| cx.focus(&test_view.focus_handle))
+            .unwrap();
+
+        let intercepted = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            let intercepted = intercepted.clone();
+            cx.intercept_keystrokes(move |event, _| {
+                intercepted
+                    .borrow_mut()
+                    .push((event.keystroke.key.clone(), event.is_simulated));
+            })
+        });
+
+        cx.dispatch_keystroke(*window, Keystroke::parse("a").unwrap());
+        window
+            .update(cx, |_, cx| {
+                cx.dispatch_keystroke(Keystroke::parse("b").unwrap())
+            })
+            .unwrap();
+        cx.dispatch_keystroke(*window, Keystroke::parse("c").unwrap());
+
+        assert_eq!(
+            *intercepted.borrow(),
+            [
+                ("a".to_string(), false),
+                ("b".to_string(), true),
+                ("c".to_string(), false),
+            ]
+        );
+    }
 }
@@ -555,6 +555,8 @@ pub struct Window {
     pending_modifier: ModifierState,
     pending_input_observers: SubscriberSet<(), AnyObserver>,
     prompt: Option<RenderablePromptHandle>,
+    /// Whether a keystroke passed to `dispatch_keystroke` is being dispatched.
+    dispatching_simulated_keystroke: bool,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -832,6 +834,7 @@ impl Window {
             pending_modifier: ModifierState::default(),
             pending_input_observers: SubscriberSet::new(),
             prompt: None,
+            dispatching_simulated_keystroke: false,
         })
     }
     fn new_focus_listener(&self, value: AnyWindowFocusListener) -> (Subscription, impl FnOnce()) {
@@ -1022,6 +1025,22 @@ impl<'a> WindowContext<'a> {
         })
     }
 
+    fn dispatch_keystroke_interceptors(&mut self, keystroke: &Keystroke) {
+        let is_simulated = self.window.dispatching_simulated_keystroke;
+        self.keystroke_interceptors
+            .clone()
+            .retain(&(), move |callback| {
+                (callback)(
+                    &KeystrokeEvent {
+                        keystroke: keystroke.clone(),
+                        action: None,
+                        is_simulated,
+                    },
+                    self,
+                )
+            });
+    }
+
     pub(crate) fn dispatch_keystroke_observers(
         &mut self,
         event: &dyn Any,
@@ -1031,6 +1050,7 @@ impl<'a> WindowContext<'a> {
             return;
         };
 
+        let is_simulated = self.window.dispatching_simulated_keystroke;
         self.keystroke_observers
             .clone()
             .retain(&(), move |callback| {
@@ -1038,6 +1058,7 @@ impl<'a> WindowContext<'a> {
                     &KeystrokeEvent {
                         keystroke: key_down_event.keystroke.clone(),
                         action: action.as_ref().map(|action| action.boxed_clone()),
+                        is_simulated,
                     },
                     self,
                 )
@@ -3043,7 +3064,17 @@ impl<'a> WindowContext<'a> {
 
     /// Dispatch a given keystroke as though the user had typed it.
     /// You can create a keystroke with Keystroke::parse("").
+    /// Keystroke observers are told that the keystroke is simulated.
     pub fn dispatch_keystroke(&mut self, keystroke: Keystroke) -> bool {
+        let was_dispatching_simulated_keystroke =
+            mem::replace(&mut self.window.dispatching_simulated_keystroke, true);
+        let handled = self.dispatch_typed_keystroke(keystroke);
+        self.window.dispatching_simulated_keystroke = was_dispatching_simulated_keystroke;
+        handled
+    }
+
+    /// Dispatch a given keystroke as if it came from the platform, as in tests.
+    pub(crate) fn dispatch_typed_keystroke(&mut self, keystroke: Keystroke) -> bool {
         let keystroke = keystroke.with_simulated_ime();
         let result = self.dispatch_event(PlatformInput::KeyDown(KeyDownEvent {
             keystroke: keystroke.clone(),
@@ -3277,6 +3308,7 @@ impl<'a> WindowContext<'a> {
         } else if let Some(key_down_event) = event.downcast_ref::<KeyDownEvent>() {
             self.window.pending_modifier.saw_keystroke = true;
             keystroke = Some(key_down_event.keystroke.clone());
+            self.dispatch_keystroke_interceptors(&key_down_event.keystroke);
         }
 
         let Some(keystroke) = keystroke else {
@@ -2805,6 +2805,46 @@ impl BufferSnapshot {
             .layers_for_range(0..self.len(), &self.text, true)
     }
 
+    /// Returns the sorted, non-overlapping ranges within the given range that are covered by
+    /// syntax highlighting captures with one of the given names, or whose names extend one of
+    /// them, such as `comment.doc` for `comment`.
+    pub fn highlight_capture_ranges(
+        &self,
+        range: Range<usize>,
+        capture_names: &[&str],
+    ) -> Vec<Range<usize>> {
+        let mut captures = self.syntax.captures(range, &self.text, |grammar| {
+            grammar.highlights_query.as_ref()
+        });
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        while let Some(capture) = captures.peek() {
+            let grammar = captures.grammars()[capture.grammar_index];
+            let name = grammar
+                .highlights_query
+                .as_ref()
+                .and_then(|query| query.capture_names().get(capture.index as usize));
+            let is_selected = name.map_or(false, |name| {
+                capture_names.iter().any(|selected| {
+                    name.strip_prefix(selected)
+                        .map_or(false, |rest| rest.is_empty() || rest.starts_with('.'))
+                })
+            });
+
+            // Captures are ordered by their start, so nested ones extend the last range.
+            if is_selected {
+                let capture_range = capture.node.byte_range();
+                match ranges.last_mut() {
+                    Some(last) if capture_range.start <= last.end => {
+                        last.end = last.end.max(capture_range.end);
+                    }
+                    _ => ranges.push(capture_range),
+                }
+            }
+            captures.advance();
+        }
+        ranges
+    }
+
     pub fn syntax_layer_at<D: ToOffset>(&self, position: D) -> Option<SyntaxLayer> {
         let offset = position.to_offset(self);
         self.syntax
@@ -3463,6 +3503,42 @@ impl BufferSnapshot {
         })
     }
 
+    /// Returns the ranges and targets of any matches of the links query that intersect the given range.
+    /// The target of a link is the text of its capture, such as a URL or a relative file path.
+    pub fn links<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> impl Iterator<Item = (Range<usize>, String)> + '_ {
+        let offset_range = range.start.to_offset(self)..range.end.to_offset(self);
+        let mut syntax_matches = self.syntax.matches(offset_range, self, |grammar| {
+            grammar.links_config.as_ref().map(|config| &config.query)
+        });
+
+        let configs = syntax_matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.links_config.as_ref())
+            .collect::<Vec<_>>();
+
+        iter::from_fn(move || {
+            while let Some(mat) = syntax_matches.peek() {
+                let link_range = configs[mat.grammar_index]
+                    .and_then(|config| {
+                        mat.captures
+                            .iter()
+                            .find(|capture| capture.index == config.link_capture_ix)
+                    })
+                    .map(|capture| capture.node.byte_range());
+                syntax_matches.advance();
+                if let Some(link_range) = link_range {
+                    let target = self.text_for_range(link_range.clone()).collect();
+                    return Some((link_range, target));
+                }
+            }
+            None
+        })
+    }
+
     pub fn injections_intersecting_range<T: ToOffset>(
         &self,
         range: Range<T>,
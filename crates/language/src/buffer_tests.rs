@@ -948,6 +948,26 @@ fn test_text_objects(cx: &mut AppContext) {
     )
 }
 
+#[gpui::test]
+fn test_links(cx: &mut AppContext) {
+    let text = indoc! {r#"
+        const A: &str = "one.txt";
+        const B: &str = "two.txt";
+    "#};
+    let language = rust_lang()
+        .with_links_query(r#"(string_literal (string_content) @link)"#)
+        .unwrap();
+
+    let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(Arc::new(language), cx));
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+
+    let links = snapshot
+        .links(Point::new(1, 0)..Point::new(1, 26))
+        .map(|(range, target)| (&text[range], target))
+        .collect::<Vec<_>>();
+    assert_eq!(links, &[("two.txt", "two.txt".to_string())]);
+}
+
 #[gpui::test]
 fn test_enclosing_bracket_ranges(cx: &mut AppContext) {
     let mut assert = |selection_text, range_markers| {
@@ -1134,6 +1154,49 @@ fn test_range_for_syntax_ancestor(cx: &mut AppContext) {
     }
 }
 
+#[gpui::test]
+fn test_highlight_capture_ranges(cx: &mut AppContext) {
+    let language = rust_lang()
+        .with_highlights_query(
+            r#"
+            (block_comment) @comment.doc
+            (string_literal) @string
+            (identifier) @variable
+            "#,
+        )
+        .unwrap();
+
+    cx.new_model(|cx| {
+        let text = r#"fn a() { /* b */ let s = "c"; /* d */ }"#;
+        let buffer = Buffer::local(text, cx).with_language(Arc::new(language), cx);
+        let snapshot = buffer.snapshot();
+
+        assert_eq!(
+            snapshot.highlight_capture_ranges(0..text.len(), &["comment"]),
+            vec![range_of(text, "/* b */"), range_of(text, "/* d */")]
+        );
+        assert_eq!(
+            snapshot.highlight_capture_ranges(0..text.len(), &["string", "comment"]),
+            vec![
+                range_of(text, "/* b */"),
+                range_of(text, "\"c\""),
+                range_of(text, "/* d */")
+            ]
+        );
+        assert_eq!(
+            snapshot.highlight_capture_ranges(0..text.len(), &["str"]),
+            Vec::<Range<usize>>::new()
+        );
+
+        buffer
+    });
+
+    fn range_of(text: &str, part: &str) -> Range<usize> {
+        let start = text.find(part).unwrap();
+        start..start + part.len()
+    }
+}
+
 #[gpui::test]
 fn test_autoindent_with_soft_tabs(cx: &mut AppContext) {
     init_settings(cx, |_| {});
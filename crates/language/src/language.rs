@@ -851,6 +851,7 @@ pub struct Grammar {
     pub text_object_config: Option<TextObjectConfig>,
     pub embedding_config: Option<EmbeddingConfig>,
     pub(crate) injection_config: Option<InjectionConfig>,
+    pub(crate) links_config: Option<LinksConfig>,
     pub(crate) override_config: Option<OverrideConfig>,
     pub(crate) highlight_map: Mutex<HighlightMap>,
 }
@@ -934,6 +935,11 @@ struct RedactionConfig {
     pub redaction_capture_ix: u32,
 }
 
+struct LinksConfig {
+    pub query: Query,
+    pub link_capture_ix: u32,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 enum RunnableCapture {
     Named(SharedString),
@@ -993,6 +999,7 @@ impl Language {
                     embedding_config: None,
                     indents_config: None,
                     injection_config: None,
+                    links_config: None,
                     override_config: None,
                     redactions_config: None,
                     runnable_config: None,
@@ -1047,6 +1054,11 @@ impl Language {
                 .with_injection_query(query.as_ref())
                 .context("Error loading injection query")?;
         }
+        if let Some(query) = queries.links {
+            self = self
+                .with_links_query(query.as_ref())
+                .context("Error loading links query")?;
+        }
         if let Some(query) = queries.overrides {
             self = self
                 .with_override_query(query.as_ref())
@@ -1400,6 +1412,25 @@ impl Language {
         Ok(self)
     }
 
+    pub fn with_links_query(mut self, source: &str) -> anyhow::Result<Self> {
+        let grammar = self
+            .grammar_mut()
+            .ok_or_else(|| anyhow!("cannot mutate grammar"))?;
+
+        let query = Query::new(&grammar.ts_language, source)?;
+        let mut link_capture_ix = None;
+        get_capture_indices(&query, &mut [("link", &mut link_capture_ix)]);
+
+        if let Some(link_capture_ix) = link_capture_ix {
+            grammar.links_config = Some(LinksConfig {
+                query,
+                link_capture_ix,
+            });
+        }
+
+        Ok(self)
+    }
+
     fn grammar_mut(&mut self) -> Option<&mut Grammar> {
         Arc::get_mut(self.grammar.as_mut()?)
     }
@@ -178,6 +178,7 @@ pub const QUERY_FILENAME_PREFIXES: &[(
     ("indents", |q| &mut q.indents),
     ("embedding", |q| &mut q.embedding),
     ("injections", |q| &mut q.injections),
+    ("links", |q| &mut q.links),
     ("overrides", |q| &mut q.overrides),
     ("redactions", |q| &mut q.redactions),
     ("runnables", |q| &mut q.runnables),
@@ -193,6 +194,7 @@ pub struct LanguageQueries {
     pub outline: Option<Cow<'static, str>>,
     pub embedding: Option<Cow<'static, str>>,
     pub injections: Option<Cow<'static, str>>,
+    pub links: Option<Cow<'static, str>>,
     pub overrides: Option<Cow<'static, str>>,
     pub redactions: Option<Cow<'static, str>>,
     pub runnables: Option<Cow<'static, str>>,
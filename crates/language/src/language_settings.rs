@@ -136,6 +136,16 @@ pub struct LanguageSettings {
     pub code_actions_on_format: HashMap<String, bool>,
     /// Whether to perform linked edits
     pub linked_edits: bool,
+    /// Whether to highlight code using the language server's semantic tokens.
+    pub semantic_tokens: bool,
+    /// Whether to show the language server's code lenses above the code they apply to.
+    pub code_lens: bool,
+    /// Whether to fold using the ranges reported by the language server,
+    /// instead of only using indentation.
+    pub lsp_folding_ranges: bool,
+    /// Whether to expand and shrink selections using the language server's selection ranges,
+    /// instead of the syntax tree.
+    pub lsp_selection_ranges: bool,
     /// Task configuration for this language.
     pub tasks: LanguageTaskConfig,
     /// Whether to pop the completions menu while typing in an editor without
@@ -380,6 +390,27 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: true
     pub linked_edits: Option<bool>,
+    /// Whether to highlight code using the semantic tokens reported by the language server,
+    /// on top of the syntax highlighting provided by Tree-sitter.
+    ///
+    /// Default: false
+    pub semantic_tokens: Option<bool>,
+    /// Whether to show the code lenses reported by the language server, such as
+    /// "Run test" or reference counts, above the code they apply to.
+    ///
+    /// Default: false
+    pub code_lens: Option<bool>,
+    /// Whether to fold using the ranges reported by the language server when it provides them,
+    /// falling back to indentation-based folding elsewhere.
+    ///
+    /// Default: true
+    pub lsp_folding_ranges: Option<bool>,
+    /// Whether `editor::SelectLargerSyntaxNode` should prefer the selection ranges reported by
+    /// the language server over the syntax tree, which helps with weak or injected grammars.
+    /// The syntax tree is still used when the server reports no larger range.
+    ///
+    /// Default: false
+    pub lsp_selection_ranges: Option<bool>,
     /// Whether indentation of pasted content should be adjusted based on the context.
     ///
     /// Default: true
@@ -1166,6 +1197,10 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.code_actions_on_format.clone(),
     );
     merge(&mut settings.linked_edits, src.linked_edits);
+    merge(&mut settings.semantic_tokens, src.semantic_tokens);
+    merge(&mut settings.code_lens, src.code_lens);
+    merge(&mut settings.lsp_folding_ranges, src.lsp_folding_ranges);
+    merge(&mut settings.lsp_selection_ranges, src.lsp_selection_ranges);
     merge(&mut settings.tasks, src.tasks.clone());
 
     merge(
@@ -1092,6 +1092,7 @@ impl SearchableItem for LspLogView {
             // LSP log is read-only.
             replacement: false,
             selection: false,
+            syntax_scope: false,
         }
     }
     fn active_match_index(
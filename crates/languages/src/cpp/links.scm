@@ -0,0 +1,2 @@
+(preproc_include
+  path: (string_literal (string_content) @link))
@@ -0,0 +1,546 @@
+use std::ops::Range;
+
+use collections::HashMap;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{
+    actions, div, px, uniform_list, AppContext, ClickEvent, EventEmitter, FocusHandle,
+    FocusableView, InteractiveElement, IntoElement, KeyContext, Model, ParentElement, Render,
+    ScrollStrategy, SharedString, Styled, Task, UniformListScrollHandle, View, ViewContext,
+    VisualContext, WeakView, WindowContext,
+};
+use language::{Buffer, Point, ToPoint as _};
+use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrev};
+use project::{CallHierarchyDirection, HierarchyItem, Location, Project, TypeHierarchyDirection};
+use settings::Settings;
+use util::ResultExt;
+use workspace::{
+    item::Item,
+    ui::{
+        h_flex, v_flex, ActiveTheme, Color, FluentBuilder, Label, LabelCommon, LabelSize, ListItem,
+        StyledTypography, Toggleable,
+    },
+    SplitDirection, Workspace,
+};
+
+use crate::{
+    outline_panel_settings::OutlinePanelSettings, CollapseSelectedEntry, ExpandSelectedEntry,
+};
+
+actions!(
+    hierarchy,
+    [
+        ShowIncomingCalls,
+        ShowOutgoingCalls,
+        ShowSubtypes,
+        ShowSupertypes
+    ]
+);
+
+pub(crate) fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|workspace, _: &ShowIncomingCalls, cx| {
+                HierarchyView::deploy(
+                    workspace,
+                    HierarchyKind::Calls(CallHierarchyDirection::Incoming),
+                    cx,
+                );
+            })
+            .register_action(|workspace, _: &ShowOutgoingCalls, cx| {
+                HierarchyView::deploy(
+                    workspace,
+                    HierarchyKind::Calls(CallHierarchyDirection::Outgoing),
+                    cx,
+                );
+            })
+            .register_action(|workspace, _: &ShowSupertypes, cx| {
+                HierarchyView::deploy(
+                    workspace,
+                    HierarchyKind::Types(TypeHierarchyDirection::Supertypes),
+                    cx,
+                );
+            })
+            .register_action(|workspace, _: &ShowSubtypes, cx| {
+                HierarchyView::deploy(
+                    workspace,
+                    HierarchyKind::Types(TypeHierarchyDirection::Subtypes),
+                    cx,
+                );
+            });
+    })
+    .detach();
+}
+
+/// Which relation between symbols a [`HierarchyView`] walks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HierarchyKind {
+    Calls(CallHierarchyDirection),
+    Types(TypeHierarchyDirection),
+}
+
+impl HierarchyKind {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Calls(CallHierarchyDirection::Incoming) => "Incoming Calls",
+            Self::Calls(CallHierarchyDirection::Outgoing) => "Outgoing Calls",
+            Self::Types(TypeHierarchyDirection::Supertypes) => "Supertypes",
+            Self::Types(TypeHierarchyDirection::Subtypes) => "Subtypes",
+        }
+    }
+
+    /// Resolves the symbol at the given position and its direct neighbours in the hierarchy.
+    fn fetch(
+        &self,
+        project: &Model<Project>,
+        buffer: &Model<Buffer>,
+        position: language::Anchor,
+        cx: &mut WindowContext,
+    ) -> Task<anyhow::Result<Vec<(HierarchyItem, Vec<HierarchyChild>)>>> {
+        match *self {
+            Self::Calls(direction) => {
+                let hierarchy = project.update(cx, |project, cx| {
+                    project.call_hierarchy(buffer, position, direction, cx)
+                });
+                cx.spawn(|_| async move {
+                    Ok(hierarchy
+                        .await?
+                        .into_iter()
+                        .map(|hierarchy| {
+                            let children = hierarchy
+                                .calls
+                                .into_iter()
+                                .map(|call| HierarchyChild {
+                                    item: call.item,
+                                    target: call.call_sites.into_iter().next(),
+                                })
+                                .collect();
+                            (hierarchy.item, children)
+                        })
+                        .collect())
+                })
+            }
+            Self::Types(direction) => {
+                let hierarchy = project.update(cx, |project, cx| {
+                    project.type_hierarchy(buffer, position, direction, cx)
+                });
+                cx.spawn(|_| async move {
+                    Ok(hierarchy
+                        .await?
+                        .into_iter()
+                        .map(|hierarchy| {
+                            let children = hierarchy
+                                .related
+                                .into_iter()
+                                .map(|item| HierarchyChild { item, target: None })
+                                .collect();
+                            (hierarchy.item, children)
+                        })
+                        .collect())
+                })
+            }
+        }
+    }
+}
+
+struct HierarchyChild {
+    item: HierarchyItem,
+    /// Where to navigate when the entry is opened, if different from the item itself.
+    target: Option<Location>,
+}
+
+struct HierarchyNode {
+    item: HierarchyItem,
+    target: Option<Location>,
+    depth: usize,
+    /// `None` until the node's neighbours were requested from the language server.
+    children: Option<Vec<usize>>,
+    expanded: bool,
+}
+
+/// A tree of symbols related to the one under the cursor, expanded lazily
+/// by querying the language server for each entry the user unfolds.
+pub struct HierarchyView {
+    kind: HierarchyKind,
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    root_name: Option<SharedString>,
+    nodes: Vec<HierarchyNode>,
+    roots: Vec<usize>,
+    visible_entries: Vec<usize>,
+    selected_entry: Option<usize>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+    loading_roots: bool,
+    fetch_roots_task: Task<()>,
+    fetch_tasks: HashMap<usize, Task<()>>,
+}
+
+impl HierarchyView {
+    fn deploy(workspace: &mut Workspace, kind: HierarchyKind, cx: &mut ViewContext<Workspace>) {
+        let Some(editor) = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+        else {
+            return;
+        };
+        let Some((buffer, position)) = editor.update(cx, |editor, cx| {
+            let head = editor.selections.newest_anchor().head();
+            editor.buffer().read(cx).text_anchor_for_position(head, cx)
+        }) else {
+            return;
+        };
+
+        let project = workspace.project().clone();
+        let workspace_handle = workspace.weak_handle();
+        let view = cx.new_view(|cx| {
+            let mut view = Self::new(kind, project, workspace_handle, cx);
+            view.fetch_roots(buffer, position, cx);
+            view
+        });
+        workspace.split_item(SplitDirection::Right, Box::new(view), cx);
+    }
+
+    pub fn new(
+        kind: HierarchyKind,
+        project: Model<Project>,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        Self {
+            kind,
+            project,
+            workspace,
+            root_name: None,
+            nodes: Vec::new(),
+            roots: Vec::new(),
+            visible_entries: Vec::new(),
+            selected_entry: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+            loading_roots: false,
+            fetch_roots_task: Task::ready(()),
+            fetch_tasks: HashMap::default(),
+        }
+    }
+
+    fn fetch_roots(
+        &mut self,
+        buffer: Model<Buffer>,
+        position: language::Anchor,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let fetch = self.kind.fetch(&self.project, &buffer, position, cx);
+        self.loading_roots = true;
+        self.fetch_roots_task = cx.spawn(|this, mut cx| async move {
+            let hierarchies = fetch.await.log_err().unwrap_or_default();
+            this.update(&mut cx, |this, cx| {
+                this.loading_roots = false;
+                this.nodes.clear();
+                this.roots.clear();
+                for (item, children) in hierarchies {
+                    this.root_name
+                        .get_or_insert_with(|| SharedString::from(item.name.clone()));
+                    let root = this.push_node(item, None, 0);
+                    this.set_children(root, children);
+                    this.roots.push(root);
+                }
+                this.selected_entry = None;
+                this.update_visible_entries(cx);
+            })
+            .ok();
+        });
+    }
+
+    fn push_node(&mut self, item: HierarchyItem, target: Option<Location>, depth: usize) -> usize {
+        self.nodes.push(HierarchyNode {
+            item,
+            target,
+            depth,
+            children: None,
+            expanded: false,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn set_children(&mut self, node_ix: usize, children: Vec<HierarchyChild>) {
+        let depth = self.nodes[node_ix].depth + 1;
+        let children = children
+            .into_iter()
+            .map(|child| self.push_node(child.item, child.target, depth))
+            .collect();
+        let node = &mut self.nodes[node_ix];
+        node.children = Some(children);
+        node.expanded = true;
+    }
+
+    fn update_visible_entries(&mut self, cx: &mut ViewContext<Self>) {
+        let mut visible_entries = Vec::with_capacity(self.visible_entries.len());
+        let mut stack = self.roots.iter().rev().copied().collect::<Vec<_>>();
+        while let Some(node_ix) = stack.pop() {
+            visible_entries.push(node_ix);
+            let node = &self.nodes[node_ix];
+            if node.expanded {
+                if let Some(children) = &node.children {
+                    stack.extend(children.iter().rev().copied());
+                }
+            }
+        }
+        self.visible_entries = visible_entries;
+        cx.notify();
+    }
+
+    fn toggle_expanded(&mut self, node_ix: usize, cx: &mut ViewContext<Self>) {
+        let expanded = self.nodes[node_ix].expanded;
+        self.set_expanded(node_ix, !expanded, cx);
+    }
+
+    fn set_expanded(&mut self, node_ix: usize, expanded: bool, cx: &mut ViewContext<Self>) {
+        let node = &mut self.nodes[node_ix];
+        if !expanded || node.children.is_some() {
+            node.expanded = expanded;
+            self.update_visible_entries(cx);
+            return;
+        }
+        if self.fetch_tasks.contains_key(&node_ix) {
+            return;
+        }
+
+        let location = node.item.location.clone();
+        let fetch = self
+            .kind
+            .fetch(&self.project, &location.buffer, location.range.start, cx);
+        let task = cx.spawn(|this, mut cx| async move {
+            let children = fetch.await.log_err().and_then(|hierarchies| {
+                hierarchies.into_iter().next().map(|(_, children)| children)
+            });
+            this.update(&mut cx, |this, cx| {
+                this.set_children(node_ix, children.unwrap_or_default());
+                this.update_visible_entries(cx);
+            })
+            .ok();
+        });
+        self.fetch_tasks.insert(node_ix, task);
+    }
+
+    fn open_entry(&mut self, node_ix: usize, cx: &mut ViewContext<Self>) {
+        let node = &self.nodes[node_ix];
+        let location = node.target.as_ref().unwrap_or(&node.item.location).clone();
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            let pane = workspace.adjacent_pane(cx);
+            let editor = workspace.open_project_item::<Editor>(
+                pane,
+                location.buffer.clone(),
+                true,
+                true,
+                cx,
+            );
+            let position = location.range.start.to_point(location.buffer.read(cx));
+            editor.update(cx, |editor, cx| {
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([position..position])
+                });
+            });
+        });
+    }
+
+    fn select_next(&mut self, _: &SelectNext, cx: &mut ViewContext<Self>) {
+        if self.visible_entries.is_empty() {
+            return;
+        }
+        let next = match self.selected_entry {
+            Some(ix) if ix + 1 < self.visible_entries.len() => ix + 1,
+            Some(_) => 0,
+            None => 0,
+        };
+        self.select_entry(next, cx);
+    }
+
+    fn select_prev(&mut self, _: &SelectPrev, cx: &mut ViewContext<Self>) {
+        if self.visible_entries.is_empty() {
+            return;
+        }
+        let prev = match self.selected_entry {
+            Some(ix) if ix > 0 => ix - 1,
+            _ => self.visible_entries.len() - 1,
+        };
+        self.select_entry(prev, cx);
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, cx: &mut ViewContext<Self>) {
+        if !self.visible_entries.is_empty() {
+            self.select_entry(0, cx);
+        }
+    }
+
+    fn select_last(&mut self, _: &SelectLast, cx: &mut ViewContext<Self>) {
+        if !self.visible_entries.is_empty() {
+            self.select_entry(self.visible_entries.len() - 1, cx);
+        }
+    }
+
+    fn select_entry(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        self.selected_entry = Some(ix);
+        self.scroll_handle
+            .scroll_to_item(ix, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    fn selected_node(&self) -> Option<usize> {
+        self.visible_entries.get(self.selected_entry?).copied()
+    }
+
+    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(node_ix) = self.selected_node() {
+            self.open_entry(node_ix, cx);
+        }
+    }
+
+    fn expand_selected_entry(&mut self, _: &ExpandSelectedEntry, cx: &mut ViewContext<Self>) {
+        if let Some(node_ix) = self.selected_node() {
+            self.set_expanded(node_ix, true, cx);
+        }
+    }
+
+    fn collapse_selected_entry(&mut self, _: &CollapseSelectedEntry, cx: &mut ViewContext<Self>) {
+        if let Some(node_ix) = self.selected_node() {
+            self.set_expanded(node_ix, false, cx);
+        }
+    }
+
+    fn render_entry(&self, visible_ix: usize, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let node_ix = self.visible_entries[visible_ix];
+        let node = &self.nodes[node_ix];
+        let settings = OutlinePanelSettings::get_global(cx);
+        let can_expand = node
+            .children
+            .as_ref()
+            .map_or(true, |children| !children.is_empty());
+
+        ListItem::new(node_ix)
+            .indent_level(node.depth)
+            .indent_step_size(px(settings.indent_size))
+            .toggle(can_expand.then_some(node.expanded))
+            .on_toggle(cx.listener(move |this, _, cx| this.toggle_expanded(node_ix, cx)))
+            .toggle_state(self.selected_entry == Some(visible_ix))
+            .child(
+                h_flex()
+                    .h_6()
+                    .gap_2()
+                    .child(Label::new(node.item.name.clone()))
+                    .when_some(node.item.detail.clone(), |this, detail| {
+                        this.child(
+                            Label::new(detail)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .single_line(),
+                        )
+                    })
+                    .child(
+                        Label::new(location_label(&node.item.location, cx))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .on_click(cx.listener(move |this, event: &ClickEvent, cx| {
+                this.selected_entry = Some(visible_ix);
+                if event.down.click_count > 1 {
+                    this.toggle_expanded(node_ix, cx);
+                } else {
+                    this.open_entry(node_ix, cx);
+                }
+                cx.notify();
+            }))
+    }
+}
+
+fn location_label(location: &Location, cx: &AppContext) -> SharedString {
+    let buffer = location.buffer.read(cx);
+    let Point { row, .. } = location.range.start.to_point(buffer);
+    let file_name = buffer
+        .file()
+        .map(|file| file.file_name(cx).to_string_lossy().into_owned())
+        .unwrap_or_else(|| "untitled".to_owned());
+    format!("{file_name}:{}", row + 1).into()
+}
+
+impl Render for HierarchyView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let mut key_context = KeyContext::new_with_defaults();
+        key_context.add("HierarchyView");
+        key_context.add("menu");
+
+        v_flex()
+            .id("hierarchy-view")
+            .key_context(key_context)
+            .track_focus(&self.focus_handle(cx))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::expand_selected_entry))
+            .on_action(cx.listener(Self::collapse_selected_entry))
+            .size_full()
+            .text_ui(cx)
+            .bg(cx.theme().colors().panel_background)
+            .map(|this| {
+                if self.visible_entries.is_empty() {
+                    let message = if self.loading_roots {
+                        "Loading…"
+                    } else {
+                        "No results"
+                    };
+                    this.child(div().p_2().child(Label::new(message).color(Color::Muted)))
+                } else {
+                    this.child(
+                        uniform_list(
+                            cx.view().clone(),
+                            "hierarchy-entries",
+                            self.visible_entries.len(),
+                            |this, range: Range<usize>, cx| {
+                                range
+                                    .map(|visible_ix| this.render_entry(visible_ix, cx))
+                                    .collect()
+                            },
+                        )
+                        .size_full()
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                }
+            })
+    }
+}
+
+impl EventEmitter<()> for HierarchyView {}
+
+impl FocusableView for HierarchyView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for HierarchyView {
+    type Event = ();
+
+    fn tab_content_text(&self, _: &WindowContext) -> Option<SharedString> {
+        Some(match &self.root_name {
+            Some(name) => format!("{}: {name}", self.kind.title()).into(),
+            None => self.kind.title().into(),
+        })
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<workspace::WorkspaceId>,
+        _: &mut ViewContext<Self>,
+    ) -> Option<View<Self>> {
+        None
+    }
+}
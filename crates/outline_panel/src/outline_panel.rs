@@ -1,3 +1,4 @@
+mod hierarchy_view;
 mod outline_panel_settings;
 
 use std::{
@@ -58,6 +59,11 @@ use workspace::{
 };
 use worktree::{Entry, ProjectEntryId, WorktreeId};
 
+pub use hierarchy_view::{
+    HierarchyKind, HierarchyView, ShowIncomingCalls, ShowOutgoingCalls, ShowSubtypes,
+    ShowSupertypes,
+};
+
 actions!(
     outline_panel,
     [
@@ -578,6 +584,7 @@ pub fn init_settings(cx: &mut AppContext) {
 pub fn init(assets: impl AssetSource, cx: &mut AppContext) {
     init_settings(cx);
     file_icons::init(assets, cx);
+    hierarchy_view::init(cx);
 
     cx.observe_new_views(|workspace: &mut Workspace, _| {
         workspace.register_action(|workspace, _: &ToggleFocus, cx| {
@@ -1,7 +1,9 @@
 use crate::{
     lsp_store::OpenLspBufferHandle,
     search::SearchQuery,
-    worktree_store::{WorktreeStore, WorktreeStoreEvent},
+    worktree_store::{
+        deserialize_diff_base, serialize_diff_base, WorktreeStore, WorktreeStoreEvent,
+    },
     ProjectItem as _, ProjectPath,
 };
 use ::git::{parse_git_remote_url, BuildPermalinkParams, GitHostingProviderRegistry};
@@ -10,7 +12,7 @@ use client::Client;
 use collections::{hash_map, HashMap, HashSet};
 use fs::Fs;
 use futures::{channel::oneshot, future::Shared, Future, FutureExt as _, StreamExt};
-use git::{blame::Blame, diff::BufferDiff};
+use git::{blame::Blame, diff::BufferDiff, repository::DiffBase};
 use gpui::{
     AppContext, AsyncAppContext, Context as _, EventEmitter, Model, ModelContext, Subscription,
     Task, WeakModel,
@@ -42,6 +44,7 @@ pub struct BufferStore {
     opened_buffers: HashMap<BufferId, OpenBuffer>,
     downstream_client: Option<(AnyProtoClient, u64)>,
     shared_buffers: HashMap<proto::PeerId, HashMap<BufferId, SharedBuffer>>,
+    diff_base: DiffBase,
 }
 
 #[derive(Hash, Eq, PartialEq, Clone)]
@@ -98,6 +101,7 @@ pub enum BufferStoreEvent {
         buffer: Model<Buffer>,
         old_file: Option<Arc<dyn language::File>>,
     },
+    DiffBaseChanged,
 }
 
 #[derive(Default, Debug)]
@@ -109,15 +113,18 @@ impl RemoteBufferStore {
     fn load_staged_text(
         &self,
         buffer_id: BufferId,
+        diff_base: &DiffBase,
         cx: &AppContext,
     ) -> Task<Result<Option<String>>> {
         let project_id = self.project_id;
         let client = self.upstream_client.clone();
+        let diff_base = serialize_diff_base(diff_base);
         cx.background_executor().spawn(async move {
             Ok(client
                 .request(proto::GetStagedText {
                     project_id,
                     buffer_id: buffer_id.to_proto(),
+                    diff_base: Some(diff_base),
                 })
                 .await?
                 .staged_text)
@@ -390,9 +397,10 @@ impl RemoteBufferStore {
 }
 
 impl LocalBufferStore {
-    fn load_staged_text(
+    fn load_diff_base_text(
         &self,
         buffer: &Model<Buffer>,
+        diff_base: &DiffBase,
         cx: &AppContext,
     ) -> Task<Result<Option<String>>> {
         let Some(file) = buffer.read(cx).file() else {
@@ -408,7 +416,9 @@ impl LocalBufferStore {
             return Task::ready(Err(anyhow!("no such worktree")));
         };
 
-        worktree.read(cx).load_staged_file(path.as_ref(), cx)
+        worktree
+            .read(cx)
+            .load_diff_base_file(path.as_ref(), diff_base, cx)
     }
 
     fn save_local_buffer(
@@ -484,7 +494,7 @@ impl LocalBufferStore {
                         Self::local_worktree_git_repos_changed(
                             this,
                             worktree.clone(),
-                            updated_repos,
+                            Some(updated_repos),
                             cx,
                         )
                     }
@@ -514,10 +524,12 @@ impl LocalBufferStore {
         }
     }
 
+    /// Reloads the diff bases of the open buffers in the given repositories,
+    /// or in every repository of the worktree when `changed_repos` is `None`.
     fn local_worktree_git_repos_changed(
         this: &mut BufferStore,
         worktree_handle: Model<Worktree>,
-        changed_repos: &UpdatedGitRepositoriesSet,
+        changed_repos: Option<&UpdatedGitRepositoriesSet>,
         cx: &mut ModelContext<BufferStore>,
     ) {
         debug_assert!(worktree_handle.read(cx).is_local());
@@ -536,9 +548,11 @@ impl LocalBufferStore {
                     if file.worktree != worktree_handle {
                         return None;
                     }
-                    changed_repos
-                        .iter()
-                        .find(|(work_dir, _)| file.path.starts_with(work_dir))?;
+                    if let Some(changed_repos) = changed_repos {
+                        changed_repos
+                            .iter()
+                            .find(|(work_dir, _)| file.path.starts_with(work_dir))?;
+                    }
                     let unstaged_changes = unstaged_changes.as_ref()?.upgrade()?;
                     let snapshot = buffer.text_snapshot();
                     Some((unstaged_changes, snapshot, file.path.clone()))
@@ -552,6 +566,7 @@ impl LocalBufferStore {
             return;
         }
 
+        let diff_base = this.diff_base.clone();
         cx.spawn(move |this, mut cx| async move {
             let snapshot =
                 worktree_handle.update(&mut cx, |tree, _| tree.as_local().unwrap().snapshot())?;
@@ -563,7 +578,8 @@ impl LocalBufferStore {
                         .filter_map(|(change_set, buffer_snapshot, path)| {
                             let (repo_entry, local_repo_entry) = snapshot.repo_for_path(&path)?;
                             let relative_path = repo_entry.relativize(&snapshot, &path).ok()?;
-                            let base_text = local_repo_entry.repo().load_index_text(&relative_path);
+                            let base_text = diff_base
+                                .load_text(local_repo_entry.repo().as_ref(), &relative_path);
                             Some((change_set, buffer_snapshot, base_text))
                         })
                         .collect::<Vec<_>>()
@@ -587,6 +603,7 @@ impl LocalBufferStore {
                                 project_id: *project_id,
                                 buffer_id: buffer_snapshot.remote_id().to_proto(),
                                 staged_text,
+                                diff_base: Some(serialize_diff_base(&diff_base)),
                             })
                             .log_err();
                     }
@@ -919,6 +936,7 @@ impl BufferStore {
             loading_buffers: Default::default(),
             loading_change_sets: Default::default(),
             worktree_store,
+            diff_base: DiffBase::default(),
         }
     }
 
@@ -943,6 +961,7 @@ impl BufferStore {
             loading_change_sets: Default::default(),
             shared_buffers: Default::default(),
             worktree_store,
+            diff_base: DiffBase::default(),
         }
     }
 
@@ -1027,8 +1046,12 @@ impl BufferStore {
             hash_map::Entry::Occupied(e) => e.get().clone(),
             hash_map::Entry::Vacant(entry) => {
                 let load = match &self.state {
-                    BufferStoreState::Local(this) => this.load_staged_text(&buffer, cx),
-                    BufferStoreState::Remote(this) => this.load_staged_text(buffer_id, cx),
+                    BufferStoreState::Local(this) => {
+                        this.load_diff_base_text(&buffer, &self.diff_base, cx)
+                    }
+                    BufferStoreState::Remote(this) => {
+                        this.load_staged_text(buffer_id, &self.diff_base, cx)
+                    }
                 };
 
                 entry
@@ -1048,6 +1071,117 @@ impl BufferStore {
             .spawn(async move { task.await.map_err(|e| anyhow!("{e}")) })
     }
 
+    /// Loads the text of the given buffer's file in the given diff base, which may differ
+    /// from the diff base that the changes of the open buffers are computed against.
+    pub fn load_base_text(
+        &self,
+        buffer: &Model<Buffer>,
+        diff_base: &DiffBase,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        match &self.state {
+            BufferStoreState::Local(this) => this.load_diff_base_text(buffer, diff_base, cx),
+            BufferStoreState::Remote(this) => {
+                this.load_staged_text(buffer.read(cx).remote_id(), diff_base, cx)
+            }
+        }
+    }
+
+    /// The text that the changes of the open buffers are computed against.
+    pub fn diff_base(&self) -> &DiffBase {
+        &self.diff_base
+    }
+
+    /// Changes the text that the changes of the open buffers are computed against,
+    /// reloading the base texts of the buffers' change sets.
+    ///
+    /// The diff base is local to each collaborator: the host keeps its own, and computes
+    /// the base texts of guests against the diff base they ask for.
+    pub fn set_diff_base(
+        &mut self,
+        diff_base: DiffBase,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        if self.diff_base == diff_base {
+            return Task::ready(Ok(()));
+        }
+        self.diff_base = diff_base.clone();
+        cx.emit(BufferStoreEvent::DiffBaseChanged);
+
+        match &self.state {
+            BufferStoreState::Local(this) => {
+                let worktrees = this
+                    .worktree_store
+                    .read(cx)
+                    .worktrees()
+                    .filter(|worktree| worktree.read(cx).is_local())
+                    .collect::<Vec<_>>();
+                for worktree in worktrees {
+                    Self::local_worktree_git_repos_changed(self, worktree, None, cx);
+                }
+                Task::ready(Ok(()))
+            }
+            BufferStoreState::Remote(_) => {
+                let reloads = self
+                    .opened_buffers
+                    .values()
+                    .filter_map(|buffer| {
+                        if let OpenBuffer::Complete {
+                            buffer,
+                            unstaged_changes,
+                        } = buffer
+                        {
+                            Some((buffer.upgrade()?, unstaged_changes.as_ref()?.upgrade()?))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .map(|(buffer, change_set)| {
+                        self.reload_remote_base_text(buffer, change_set, cx)
+                    })
+                    .collect::<Vec<_>>();
+                cx.background_executor().spawn(async move {
+                    futures::future::try_join_all(reloads).await?;
+                    Ok(())
+                })
+            }
+        }
+    }
+
+    /// Reloads the base text of a remote buffer's change set from the host,
+    /// for the current diff base.
+    fn reload_remote_base_text(
+        &self,
+        buffer: Model<Buffer>,
+        change_set: Model<BufferChangeSet>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let BufferStoreState::Remote(remote) = &self.state else {
+            return Task::ready(Ok(()));
+        };
+        let diff_base = self.diff_base.clone();
+        let load = remote.load_staged_text(buffer.read(cx).remote_id(), &diff_base, cx);
+        cx.spawn(|this, mut cx| async move {
+            let base_text = load.await?;
+            this.update(&mut cx, |this, cx| {
+                // A newer diff base was picked while this one was loading.
+                if this.diff_base != diff_base {
+                    return;
+                }
+                change_set.update(cx, |change_set, cx| {
+                    let snapshot = buffer.read(cx).text_snapshot();
+                    if let Some(base_text) = base_text {
+                        let _ = change_set.set_base_text(base_text, snapshot, cx);
+                    } else {
+                        change_set.unset_base_text(snapshot, cx);
+                    }
+                });
+            })
+        })
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn set_change_set(&mut self, buffer_id: BufferId, change_set: Model<BufferChangeSet>) {
         self.loading_change_sets
@@ -1924,13 +2058,20 @@ impl BufferStore {
         mut cx: AsyncAppContext,
     ) -> Result<proto::GetStagedTextResponse> {
         let buffer_id = BufferId::new(request.payload.buffer_id)?;
-        let change_set = this
+        let diff_base = request
+            .payload
+            .diff_base
+            .map(deserialize_diff_base)
+            .unwrap_or_default();
+        let (buffer, change_set) = this
             .update(&mut cx, |this, cx| {
                 let buffer = this.get(buffer_id)?;
-                Some(this.open_unstaged_changes(buffer, cx))
+                Some((buffer.clone(), this.open_unstaged_changes(buffer, cx)))
             })?
-            .ok_or_else(|| anyhow!("no such buffer"))?
-            .await?;
+            .ok_or_else(|| anyhow!("no such buffer"))?;
+        // The change set keeps the guest informed of changes to the repository, through
+        // `UpdateDiffBase` messages, even when it diffs against another base than the host.
+        let change_set = change_set.await?;
         this.update(&mut cx, |this, _| {
             let shared_buffers = this
                 .shared_buffers
@@ -1941,12 +2082,28 @@ impl BufferStore {
                 shared.unstaged_changes = Some(change_set.clone());
             }
         })?;
-        let staged_text = change_set.read_with(&cx, |change_set, cx| {
-            change_set
-                .base_text
-                .as_ref()
-                .map(|buffer| buffer.read(cx).text())
+        let load_diff_base_text = this.update(&mut cx, |this, cx| {
+            if this.diff_base == diff_base {
+                return None;
+            }
+            Some(match &this.state {
+                BufferStoreState::Local(local) => {
+                    local.load_diff_base_text(&buffer, &diff_base, cx)
+                }
+                BufferStoreState::Remote(remote) => {
+                    remote.load_staged_text(buffer_id, &diff_base, cx)
+                }
+            })
         })?;
+        let staged_text = match load_diff_base_text {
+            Some(load) => load.await?,
+            None => change_set.read_with(&cx, |change_set, cx| {
+                change_set
+                    .base_text
+                    .as_ref()
+                    .map(|buffer| buffer.read(cx).text())
+            })?,
+        };
         Ok(proto::GetStagedTextResponse { staged_text })
     }
 
@@ -1956,6 +2113,11 @@ impl BufferStore {
         mut cx: AsyncAppContext,
     ) -> Result<()> {
         let buffer_id = BufferId::new(request.payload.buffer_id)?;
+        let diff_base = request
+            .payload
+            .diff_base
+            .map(deserialize_diff_base)
+            .unwrap_or_default();
         let Some((buffer, change_set)) = this.update(&mut cx, |this, _| {
             if let OpenBuffer::Complete {
                 unstaged_changes,
@@ -1970,6 +2132,15 @@ impl BufferStore {
         else {
             return Ok(());
         };
+        // The host diffs against another base, so the repository changed: reload the
+        // base text for this collaborator's own diff base.
+        let reload = this.update(&mut cx, |this, cx| {
+            (this.diff_base != diff_base)
+                .then(|| this.reload_remote_base_text(buffer.clone(), change_set.clone(), cx))
+        })?;
+        if let Some(reload) = reload {
+            return reload.await;
+        }
         change_set.update(&mut cx, |change_set, cx| {
             if let Some(staged_text) = request.payload.staged_text {
                 let _ = change_set.set_base_text(staged_text, buffer.read(cx).text_snapshot(), cx);
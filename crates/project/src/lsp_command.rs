@@ -2,15 +2,17 @@ mod signature_help;
 
 use crate::{
     lsp_store::{LocalLspStore, LspStore},
-    CodeAction, CoreCompletion, DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint,
-    InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location,
-    LocationLink, MarkupContent, ProjectTransaction, ResolveState,
+    CallHierarchy, CallHierarchyCall, CallHierarchyDirection, ClientCodeLensCommand, CodeAction,
+    CodeLens, CoreCompletion, DocumentHighlight, DocumentLink, FoldingRange, HierarchyItem, Hover,
+    HoverBlock, HoverBlockKind, InlayHint, InlayHintLabel, InlayHintLabelPart,
+    InlayHintLabelPartTooltip, InlayHintTooltip, Location, LocationLink, MarkupContent,
+    ProjectTransaction, ResolveState,
 };
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use client::proto::{self, PeerId};
 use clock::Global;
-use collections::HashSet;
+use collections::{HashMap, HashSet};
 use futures::future;
 use gpui::{AppContext, AsyncAppContext, Entity, Model};
 use language::{
@@ -18,7 +20,7 @@ use language::{
     point_from_lsp, point_to_lsp,
     proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
     range_from_lsp, range_to_lsp, Anchor, Bias, Buffer, BufferSnapshot, CachedLspAdapter, CharKind,
-    OffsetRangeExt, PointUtf16, ToOffset, ToPointUtf16, Transaction, Unclipped,
+    OffsetRangeExt, Point, PointUtf16, ToOffset, ToPointUtf16, Transaction, Unclipped,
 };
 use lsp::{
     AdapterServerCapabilities, CodeActionKind, CodeActionOptions, CompletionContext,
@@ -26,8 +28,10 @@ use lsp::{
     LanguageServer, LanguageServerId, LinkedEditingRangeServerCapabilities, OneOf,
     ServerCapabilities,
 };
+use serde::Deserialize;
 use signature_help::{lsp_to_proto_signature, proto_to_lsp_signature};
-use std::{cmp::Reverse, ops::Range, path::Path, sync::Arc};
+use std::{cmp::Reverse, mem, ops::Range, path::Path, sync::Arc};
+use task::TaskTemplate;
 use text::{BufferId, LineEnding};
 
 pub use signature_help::{
@@ -135,6 +139,33 @@ pub(crate) struct GetTypeDefinition {
 pub(crate) struct GetImplementation {
     pub position: PointUtf16,
 }
+
+#[derive(Debug)]
+pub(crate) struct GetCallHierarchy {
+    pub position: PointUtf16,
+    pub direction: CallHierarchyDirection,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetTypeHierarchy {
+    pub position: PointUtf16,
+    pub direction: TypeHierarchyDirection,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetCodeLens;
+
+#[derive(Debug)]
+pub(crate) struct GetFoldingRanges;
+
+#[derive(Debug)]
+pub(crate) struct GetSelectionRanges {
+    pub positions: Vec<Anchor>,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetDocumentLinks;
+
 #[derive(Debug)]
 pub(crate) struct GetReferences {
     pub position: PointUtf16,
@@ -829,6 +860,1266 @@ impl LspCommand for GetTypeDefinition {
     }
 }
 
+#[async_trait(?Send)]
+impl LspCommand for GetCallHierarchy {
+    type Response = Vec<CallHierarchy>;
+    type LspRequest = lsp::request::CallHierarchyPrepare;
+    type ProtoRequest = proto::GetCallHierarchy;
+
+    fn status(&self) -> Option<String> {
+        match self.direction {
+            CallHierarchyDirection::Incoming => Some("Finding incoming calls...".to_owned()),
+            CallHierarchyDirection::Outgoing => Some("Finding outgoing calls...".to_owned()),
+        }
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        !matches!(
+            &capabilities.server_capabilities.call_hierarchy_provider,
+            None | Some(lsp::CallHierarchyServerCapability::Simple(false))
+        )
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyPrepareParams {
+        lsp::CallHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        items: Option<Vec<lsp::CallHierarchyItem>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchy>> {
+        let (lsp_adapter, language_server) =
+            language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+
+        let mut hierarchies = Vec::new();
+        for lsp_item in items.unwrap_or_default() {
+            // Call ranges are reported relative to the caller: for incoming calls that is
+            // the other item, for outgoing calls it is the item we have prepared.
+            let lsp_calls = match self.direction {
+                CallHierarchyDirection::Incoming => language_server
+                    .request::<lsp::request::CallHierarchyIncomingCalls>(
+                        lsp::CallHierarchyIncomingCallsParams {
+                            item: lsp_item.clone(),
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    )
+                    .await?
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|call| {
+                        let call_sites_uri = call.from.uri.clone();
+                        (call.from, call_sites_uri, call.from_ranges)
+                    })
+                    .collect::<Vec<_>>(),
+                CallHierarchyDirection::Outgoing => language_server
+                    .request::<lsp::request::CallHierarchyOutgoingCalls>(
+                        lsp::CallHierarchyOutgoingCallsParams {
+                            item: lsp_item.clone(),
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    )
+                    .await?
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|call| (call.to, lsp_item.uri.clone(), call.from_ranges))
+                    .collect::<Vec<_>>(),
+            };
+
+            let item = location_from_lsp(
+                lsp_item.uri,
+                lsp_item.selection_range,
+                &lsp_store,
+                &lsp_adapter,
+                &language_server,
+                &mut cx,
+            )
+            .await
+            .map(|location| HierarchyItem {
+                name: lsp_item.name,
+                kind: lsp_item.kind,
+                detail: lsp_item.detail,
+                location,
+            })?;
+
+            let mut calls = Vec::with_capacity(lsp_calls.len());
+            for (lsp_call_item, call_sites_uri, lsp_call_sites) in lsp_calls {
+                let location = location_from_lsp(
+                    lsp_call_item.uri,
+                    lsp_call_item.selection_range,
+                    &lsp_store,
+                    &lsp_adapter,
+                    &language_server,
+                    &mut cx,
+                )
+                .await?;
+                let mut call_sites = Vec::with_capacity(lsp_call_sites.len());
+                for lsp_call_site in lsp_call_sites {
+                    call_sites.push(
+                        location_from_lsp(
+                            call_sites_uri.clone(),
+                            lsp_call_site,
+                            &lsp_store,
+                            &lsp_adapter,
+                            &language_server,
+                            &mut cx,
+                        )
+                        .await?,
+                    );
+                }
+                calls.push(CallHierarchyCall {
+                    item: HierarchyItem {
+                        name: lsp_call_item.name,
+                        kind: lsp_call_item.kind,
+                        detail: lsp_call_item.detail,
+                        location,
+                    },
+                    call_sites,
+                });
+            }
+
+            hierarchies.push(CallHierarchy { item, calls });
+        }
+
+        Ok(hierarchies)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetCallHierarchy {
+        let direction = match self.direction {
+            CallHierarchyDirection::Incoming => proto::CallHierarchyDirection::Incoming,
+            CallHierarchyDirection::Outgoing => proto::CallHierarchyDirection::Outgoing,
+        };
+        proto::GetCallHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            version: serialize_version(&buffer.version()),
+            direction: direction.into(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetCallHierarchy,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        let direction = match message.direction() {
+            proto::CallHierarchyDirection::Incoming => CallHierarchyDirection::Incoming,
+            proto::CallHierarchyDirection::Outgoing => CallHierarchyDirection::Outgoing,
+        };
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+            direction,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchy>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetCallHierarchyResponse {
+        let hierarchies = response
+            .into_iter()
+            .map(|hierarchy| proto::CallHierarchy {
+                item: Some(hierarchy_item_to_proto(
+                    hierarchy.item,
+                    lsp_store,
+                    peer_id,
+                    cx,
+                )),
+                calls: hierarchy
+                    .calls
+                    .into_iter()
+                    .map(|call| proto::CallHierarchyCall {
+                        item: Some(hierarchy_item_to_proto(call.item, lsp_store, peer_id, cx)),
+                        call_sites: call
+                            .call_sites
+                            .into_iter()
+                            .map(|location| location_to_proto(location, lsp_store, peer_id, cx))
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        proto::GetCallHierarchyResponse { hierarchies }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetCallHierarchyResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchy>> {
+        let mut hierarchies = Vec::with_capacity(message.hierarchies.len());
+        for hierarchy in message.hierarchies {
+            let item = hierarchy_item_from_proto(
+                hierarchy
+                    .item
+                    .ok_or_else(|| anyhow!("missing hierarchy item"))?,
+                &lsp_store,
+                &mut cx,
+            )
+            .await?;
+            let mut calls = Vec::with_capacity(hierarchy.calls.len());
+            for call in hierarchy.calls {
+                let item = hierarchy_item_from_proto(
+                    call.item.ok_or_else(|| anyhow!("missing call item"))?,
+                    &lsp_store,
+                    &mut cx,
+                )
+                .await?;
+                let mut call_sites = Vec::with_capacity(call.call_sites.len());
+                for call_site in call.call_sites {
+                    call_sites.push(location_from_proto(call_site, &lsp_store, &mut cx).await?);
+                }
+                calls.push(CallHierarchyCall { item, call_sites });
+            }
+            hierarchies.push(CallHierarchy { item, calls });
+        }
+        Ok(hierarchies)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetCallHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetTypeHierarchy {
+    type Response = Vec<TypeHierarchy>;
+    type LspRequest = lsp::request::TypeHierarchyPrepare;
+    type ProtoRequest = proto::GetTypeHierarchy;
+
+    fn status(&self) -> Option<String> {
+        match self.direction {
+            TypeHierarchyDirection::Supertypes => Some("Finding supertypes...".to_owned()),
+            TypeHierarchyDirection::Subtypes => Some("Finding subtypes...".to_owned()),
+        }
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        !matches!(
+            &capabilities.server_capabilities.type_hierarchy_provider,
+            None | Some(lsp::OneOf::Left(false))
+        )
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchyPrepareParams {
+        lsp::TypeHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        items: Option<Vec<lsp::TypeHierarchyItem>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchy>> {
+        let (lsp_adapter, language_server) =
+            language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+
+        let mut hierarchies = Vec::new();
+        for lsp_item in items.unwrap_or_default() {
+            let lsp_related = match self.direction {
+                TypeHierarchyDirection::Supertypes => {
+                    language_server
+                        .request::<lsp::request::TypeHierarchySupertypes>(
+                            lsp::TypeHierarchySupertypesParams {
+                                item: lsp_item.clone(),
+                                work_done_progress_params: Default::default(),
+                                partial_result_params: Default::default(),
+                            },
+                        )
+                        .await?
+                }
+                TypeHierarchyDirection::Subtypes => {
+                    language_server
+                        .request::<lsp::request::TypeHierarchySubtypes>(
+                            lsp::TypeHierarchySubtypesParams {
+                                item: lsp_item.clone(),
+                                work_done_progress_params: Default::default(),
+                                partial_result_params: Default::default(),
+                            },
+                        )
+                        .await?
+                }
+            }
+            .unwrap_or_default();
+
+            let mut items = Vec::with_capacity(lsp_related.len() + 1);
+            for lsp_item in std::iter::once(lsp_item).chain(lsp_related) {
+                let location = location_from_lsp(
+                    lsp_item.uri,
+                    lsp_item.selection_range,
+                    &lsp_store,
+                    &lsp_adapter,
+                    &language_server,
+                    &mut cx,
+                )
+                .await?;
+                items.push(HierarchyItem {
+                    name: lsp_item.name,
+                    kind: lsp_item.kind,
+                    detail: lsp_item.detail,
+                    location,
+                });
+            }
+            let item = items.remove(0);
+            hierarchies.push(TypeHierarchy {
+                item,
+                related: items,
+            });
+        }
+
+        Ok(hierarchies)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetTypeHierarchy {
+        let direction = match self.direction {
+            TypeHierarchyDirection::Supertypes => proto::TypeHierarchyDirection::Supertypes,
+            TypeHierarchyDirection::Subtypes => proto::TypeHierarchyDirection::Subtypes,
+        };
+        proto::GetTypeHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            version: serialize_version(&buffer.version()),
+            direction: direction.into(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetTypeHierarchy,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        let direction = match message.direction() {
+            proto::TypeHierarchyDirection::Supertypes => TypeHierarchyDirection::Supertypes,
+            proto::TypeHierarchyDirection::Subtypes => TypeHierarchyDirection::Subtypes,
+        };
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+            direction,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchy>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetTypeHierarchyResponse {
+        let hierarchies = response
+            .into_iter()
+            .map(|hierarchy| proto::TypeHierarchy {
+                item: Some(hierarchy_item_to_proto(
+                    hierarchy.item,
+                    lsp_store,
+                    peer_id,
+                    cx,
+                )),
+                related: hierarchy
+                    .related
+                    .into_iter()
+                    .map(|item| hierarchy_item_to_proto(item, lsp_store, peer_id, cx))
+                    .collect(),
+            })
+            .collect();
+        proto::GetTypeHierarchyResponse { hierarchies }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetTypeHierarchyResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchy>> {
+        let mut hierarchies = Vec::with_capacity(message.hierarchies.len());
+        for hierarchy in message.hierarchies {
+            let item = hierarchy_item_from_proto(
+                hierarchy
+                    .item
+                    .ok_or_else(|| anyhow!("missing hierarchy item"))?,
+                &lsp_store,
+                &mut cx,
+            )
+            .await?;
+            let mut related = Vec::with_capacity(hierarchy.related.len());
+            for item in hierarchy.related {
+                related.push(hierarchy_item_from_proto(item, &lsp_store, &mut cx).await?);
+            }
+            hierarchies.push(TypeHierarchy { item, related });
+        }
+        Ok(hierarchies)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetTypeHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+/// Recognizes the code lens commands that language servers expect the client to implement.
+pub(crate) fn client_code_lens_command(command: &lsp::Command) -> Option<ClientCodeLensCommand> {
+    match command.command.as_str() {
+        "rust-analyzer.runSingle" => {
+            let runnable = command.arguments.as_ref()?.first()?.clone();
+            let runnable = serde_json::from_value::<RustAnalyzerRunnable>(runnable).ok()?;
+            runnable.task_template().map(ClientCodeLensCommand::Run)
+        }
+        "rust-analyzer.showReferences" | "editor.action.showReferences" => {
+            Some(ClientCodeLensCommand::ShowReferences)
+        }
+        _ => None,
+    }
+}
+
+/// A runnable of rust-analyzer, as passed to its `rust-analyzer.runSingle` command.
+#[derive(Deserialize)]
+struct RustAnalyzerRunnable {
+    label: String,
+    kind: String,
+    args: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RustAnalyzerCargoArgs {
+    workspace_root: Option<String>,
+    cwd: Option<String>,
+    override_cargo: Option<String>,
+    #[serde(default)]
+    cargo_args: Vec<String>,
+    #[serde(default)]
+    cargo_extra_args: Vec<String>,
+    #[serde(default)]
+    executable_args: Vec<String>,
+    #[serde(default)]
+    environment: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct RustAnalyzerShellArgs {
+    program: String,
+    #[serde(default)]
+    args: Vec<String>,
+    cwd: Option<String>,
+    #[serde(default)]
+    environment: HashMap<String, String>,
+}
+
+impl RustAnalyzerRunnable {
+    fn task_template(self) -> Option<TaskTemplate> {
+        let (command, args, cwd, env) = match self.kind.as_str() {
+            "cargo" => {
+                let cargo = serde_json::from_value::<RustAnalyzerCargoArgs>(self.args).ok()?;
+                let mut args = cargo.cargo_args;
+                args.extend(cargo.cargo_extra_args);
+                if !cargo.executable_args.is_empty() {
+                    args.push("--".into());
+                    args.extend(cargo.executable_args);
+                }
+                (
+                    cargo.override_cargo.unwrap_or_else(|| "cargo".into()),
+                    args,
+                    cargo.cwd.or(cargo.workspace_root),
+                    cargo.environment,
+                )
+            }
+            "shell" => {
+                let shell = serde_json::from_value::<RustAnalyzerShellArgs>(self.args).ok()?;
+                (shell.program, shell.args, shell.cwd, shell.environment)
+            }
+            _ => return None,
+        };
+        Some(TaskTemplate {
+            label: self.label,
+            command,
+            args,
+            env,
+            cwd,
+            ..TaskTemplate::default()
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetCodeLens {
+    type Response = Vec<CodeLens>;
+    type LspRequest = lsp::request::CodeLensRequest;
+    type ProtoRequest = proto::GetCodeLens;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .code_lens_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CodeLensParams {
+        lsp::CodeLensParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lenses: Option<Vec<lsp::CodeLens>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        let (_, language_server) =
+            language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+        let can_resolve = language_server
+            .capabilities()
+            .code_lens_provider
+            .as_ref()
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false);
+
+        // Servers may leave the command out of the initial response to defer computing it,
+        // in which case it has to be resolved before the lens can be displayed.
+        let lenses = future::join_all(lenses.unwrap_or_default().into_iter().map(|lens| {
+            let language_server = language_server.clone();
+            async move {
+                if lens.command.is_none() && can_resolve {
+                    language_server
+                        .request::<lsp::request::CodeLensResolve>(lens)
+                        .await
+                } else {
+                    Ok(lens)
+                }
+            }
+        }))
+        .await;
+
+        let server_commands = language_server
+            .capabilities()
+            .execute_command_provider
+            .map(|options| options.commands)
+            .unwrap_or_default();
+
+        let snapshot = buffer.update(&mut cx, |buffer, _| buffer.snapshot())?;
+        let mut code_lens = Vec::new();
+        for lens in lenses {
+            let lens = lens?;
+            let Some(command) = lens.command.as_ref() else {
+                continue;
+            };
+            // Lenses with an empty command are labels. Other lenses are only shown when
+            // their command can be run, either by the server or by the client.
+            if !command.command.is_empty()
+                && !server_commands.contains(&command.command)
+                && client_code_lens_command(command).is_none()
+            {
+                continue;
+            }
+            let range = range_from_lsp(lens.range);
+            let start = snapshot.clip_point_utf16(range.start, Bias::Left);
+            let end = snapshot.clip_point_utf16(range.end, Bias::Left);
+            code_lens.push(CodeLens {
+                server_id,
+                range: snapshot.anchor_after(start)..snapshot.anchor_before(end),
+                lsp_lens: lens,
+            });
+        }
+        Ok(code_lens)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetCodeLens {
+        proto::GetCodeLens {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetCodeLens,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        code_lens: Vec<CodeLens>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetCodeLensResponse {
+        proto::GetCodeLensResponse {
+            lenses: code_lens
+                .iter()
+                .map(LspStore::serialize_code_lens)
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetCodeLensResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .lenses
+            .into_iter()
+            .map(LspStore::deserialize_code_lens)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetCodeLens) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetFoldingRanges {
+    type Response = Vec<FoldingRange>;
+    type LspRequest = lsp::request::FoldingRangeRequest;
+    type ProtoRequest = proto::GetFoldingRanges;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match &capabilities.server_capabilities.folding_range_provider {
+            Some(lsp::FoldingRangeProviderCapability::Simple(enabled)) => *enabled,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::FoldingRangeParams {
+        lsp::FoldingRangeParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        ranges: Option<Vec<lsp::FoldingRange>>,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<FoldingRange>> {
+        let snapshot = buffer.update(&mut cx, |buffer, _| buffer.snapshot())?;
+        let max_row = snapshot.max_point().row;
+        let mut folding_ranges = ranges
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|range| {
+                // We only fold whole lines, so the range always spans from the end of its
+                // first line to the end of its last line, regardless of the reported characters.
+                let start_row = range.start_line.min(max_row);
+                let end_row = range.end_line.min(max_row);
+                if end_row <= start_row {
+                    return None;
+                }
+                let start = Point::new(start_row, snapshot.line_len(start_row));
+                let end = Point::new(end_row, snapshot.line_len(end_row));
+                Some(FoldingRange {
+                    range: snapshot.anchor_after(start)..snapshot.anchor_before(end),
+                    kind: range.kind,
+                })
+            })
+            .collect::<Vec<_>>();
+        folding_ranges.sort_by(|a, b| a.range.start.cmp(&b.range.start, &snapshot));
+        Ok(folding_ranges)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetFoldingRanges {
+        proto::GetFoldingRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetFoldingRanges,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        folding_ranges: Vec<FoldingRange>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetFoldingRangesResponse {
+        proto::GetFoldingRangesResponse {
+            ranges: folding_ranges
+                .into_iter()
+                .map(|range| proto::FoldingRange {
+                    start: Some(serialize_anchor(&range.range.start)),
+                    end: Some(serialize_anchor(&range.range.end)),
+                    kind: range.kind.map(|kind| {
+                        match kind {
+                            lsp::FoldingRangeKind::Comment => proto::FoldingRangeKind::Comment,
+                            lsp::FoldingRangeKind::Imports => proto::FoldingRangeKind::Imports,
+                            lsp::FoldingRangeKind::Region => proto::FoldingRangeKind::Region,
+                        }
+                        .into()
+                    }),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetFoldingRangesResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<FoldingRange>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .ranges
+            .into_iter()
+            .map(|range| {
+                let start = range
+                    .start
+                    .and_then(deserialize_anchor)
+                    .context("invalid folding range start")?;
+                let end = range
+                    .end
+                    .and_then(deserialize_anchor)
+                    .context("invalid folding range end")?;
+                let kind = range
+                    .kind
+                    .and_then(proto::FoldingRangeKind::from_i32)
+                    .map(|kind| match kind {
+                        proto::FoldingRangeKind::Comment => lsp::FoldingRangeKind::Comment,
+                        proto::FoldingRangeKind::Imports => lsp::FoldingRangeKind::Imports,
+                        proto::FoldingRangeKind::Region => lsp::FoldingRangeKind::Region,
+                    });
+                Ok(FoldingRange {
+                    range: start..end,
+                    kind,
+                })
+            })
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetFoldingRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSelectionRanges {
+    type Response = Vec<Vec<Range<Anchor>>>;
+    type LspRequest = lsp::request::SelectionRangeRequest;
+    type ProtoRequest = proto::GetSelectionRanges;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match &capabilities.server_capabilities.selection_range_provider {
+            Some(lsp::SelectionRangeProviderCapability::Simple(enabled)) => *enabled,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::SelectionRangeParams {
+        let snapshot = buffer.snapshot();
+        lsp::SelectionRangeParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            positions: self
+                .positions
+                .iter()
+                .map(|position| point_to_lsp(position.to_point_utf16(&snapshot)))
+                .collect(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        selection_ranges: Option<Vec<lsp::SelectionRange>>,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<Vec<Range<Anchor>>>> {
+        buffer.read_with(&cx, |buffer, _| {
+            selection_ranges
+                .unwrap_or_default()
+                .into_iter()
+                .map(|selection_range| {
+                    // Flatten the chain of parents, so that ranges go from innermost to outermost.
+                    let mut ranges = Vec::new();
+                    let mut selection_range = Some(Box::new(selection_range));
+                    while let Some(lsp::SelectionRange { range, parent }) =
+                        selection_range.map(|range| *range)
+                    {
+                        let start =
+                            buffer.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
+                        let end = buffer.clip_point_utf16(point_from_lsp(range.end), Bias::Left);
+                        ranges.push(buffer.anchor_before(start)..buffer.anchor_after(end));
+                        selection_range = parent;
+                    }
+                    ranges
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSelectionRanges {
+        proto::GetSelectionRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            positions: self.positions.iter().map(serialize_anchor).collect(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSelectionRanges,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let positions = message
+            .positions
+            .into_iter()
+            .map(|position| deserialize_anchor(position).context("invalid position"))
+            .collect::<Result<Vec<_>>>()?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_anchors(positions.iter().copied())
+            })?
+            .await?;
+        Ok(Self { positions })
+    }
+
+    fn response_to_proto(
+        response: Vec<Vec<Range<Anchor>>>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetSelectionRangesResponse {
+        proto::GetSelectionRangesResponse {
+            selection_ranges: response
+                .into_iter()
+                .map(|ranges| proto::SelectionRanges {
+                    ranges: ranges
+                        .into_iter()
+                        .map(|range| proto::AnchorRange {
+                            start: Some(serialize_anchor(&range.start)),
+                            end: Some(serialize_anchor(&range.end)),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSelectionRangesResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<Vec<Range<Anchor>>>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .selection_ranges
+            .into_iter()
+            .map(|selection_ranges| {
+                selection_ranges
+                    .ranges
+                    .into_iter()
+                    .map(|range| {
+                        let start = range
+                            .start
+                            .and_then(deserialize_anchor)
+                            .context("invalid selection range start")?;
+                        let end = range
+                            .end
+                            .and_then(deserialize_anchor)
+                            .context("invalid selection range end")?;
+                        Ok(start..end)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSelectionRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentLinks {
+    type Response = Vec<DocumentLink>;
+    type LspRequest = lsp::request::DocumentLinkRequest;
+    type ProtoRequest = proto::GetDocumentLinks;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .document_link_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentLinkParams {
+        lsp::DocumentLinkParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        links: Option<Vec<lsp::DocumentLink>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        let (_, language_server) =
+            language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+        let can_resolve = language_server
+            .capabilities()
+            .document_link_provider
+            .as_ref()
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false);
+
+        buffer.read_with(&cx, |buffer, _| {
+            links
+                .unwrap_or_default()
+                .into_iter()
+                // Links without a target are resolved when they are opened,
+                // which is only possible if the server supports it.
+                .filter(|link| link.target.is_some() || can_resolve)
+                .map(|link| {
+                    let start =
+                        buffer.clip_point_utf16(point_from_lsp(link.range.start), Bias::Left);
+                    let end = buffer.clip_point_utf16(point_from_lsp(link.range.end), Bias::Left);
+                    DocumentLink {
+                        server_id,
+                        range: buffer.anchor_before(start)..buffer.anchor_after(end),
+                        target: link.target.as_ref().map(ToString::to_string),
+                        lsp_link: link,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentLinks {
+        proto::GetDocumentLinks {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentLinks,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        links: Vec<DocumentLink>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentLinksResponse {
+        proto::GetDocumentLinksResponse {
+            links: links
+                .iter()
+                .map(LspStore::serialize_document_link)
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentLinksResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .links
+            .into_iter()
+            .map(LspStore::deserialize_document_link)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentLinks) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+async fn location_from_lsp(
+    uri: lsp::Url,
+    range: lsp::Range,
+    lsp_store: &Model<LspStore>,
+    lsp_adapter: &Arc<CachedLspAdapter>,
+    language_server: &Arc<LanguageServer>,
+    cx: &mut AsyncAppContext,
+) -> Result<Location> {
+    let target_buffer_handle = lsp_store
+        .update(cx, |lsp_store, cx| {
+            lsp_store.open_local_buffer_via_lsp(
+                uri,
+                language_server.server_id(),
+                lsp_adapter.name.clone(),
+                cx,
+            )
+        })?
+        .await?;
+
+    target_buffer_handle.clone().update(cx, |target_buffer, _| {
+        let target_start = target_buffer.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
+        let target_end = target_buffer.clip_point_utf16(point_from_lsp(range.end), Bias::Left);
+        Location {
+            buffer: target_buffer_handle,
+            range: target_buffer.anchor_after(target_start)
+                ..target_buffer.anchor_before(target_end),
+        }
+    })
+}
+
+fn location_to_proto(
+    location: Location,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> proto::Location {
+    lsp_store
+        .buffer_store()
+        .update(cx, |buffer_store, cx| {
+            buffer_store.create_buffer_for_peer(&location.buffer, peer_id, cx)
+        })
+        .detach_and_log_err(cx);
+    proto::Location {
+        start: Some(serialize_anchor(&location.range.start)),
+        end: Some(serialize_anchor(&location.range.end)),
+        buffer_id: location.buffer.read(cx).remote_id().into(),
+    }
+}
+
+async fn location_from_proto(
+    location: proto::Location,
+    lsp_store: &Model<LspStore>,
+    cx: &mut AsyncAppContext,
+) -> Result<Location> {
+    let buffer_id = BufferId::new(location.buffer_id)?;
+    let buffer = lsp_store
+        .update(cx, |lsp_store, cx| {
+            lsp_store.wait_for_remote_buffer(buffer_id, cx)
+        })?
+        .await?;
+    let start = location
+        .start
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing location start"))?;
+    let end = location
+        .end
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing location end"))?;
+    buffer
+        .update(cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+        .await?;
+    Ok(Location {
+        buffer,
+        range: start..end,
+    })
+}
+
+fn hierarchy_item_to_proto(
+    item: HierarchyItem,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> proto::HierarchyItem {
+    proto::HierarchyItem {
+        name: item.name,
+        kind: unsafe { mem::transmute::<lsp::SymbolKind, i32>(item.kind) },
+        detail: item.detail,
+        location: Some(location_to_proto(item.location, lsp_store, peer_id, cx)),
+    }
+}
+
+async fn hierarchy_item_from_proto(
+    item: proto::HierarchyItem,
+    lsp_store: &Model<LspStore>,
+    cx: &mut AsyncAppContext,
+) -> Result<HierarchyItem> {
+    let location = item
+        .location
+        .ok_or_else(|| anyhow!("missing hierarchy item location"))?;
+    Ok(HierarchyItem {
+        name: item.name,
+        kind: unsafe { mem::transmute::<i32, lsp::SymbolKind>(item.kind) },
+        detail: item.detail,
+        location: location_from_proto(location, lsp_store, cx).await?,
+    })
+}
+
 fn language_server_for_buffer(
     lsp_store: &Model<LspStore>,
     buffer: &Model<Buffer>,
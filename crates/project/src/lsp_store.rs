@@ -10,8 +10,9 @@ use crate::{
     toolchain_store::{EmptyToolchainStore, ToolchainStoreEvent},
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
     yarn::YarnPathStore,
-    CodeAction, Completion, CoreCompletion, Hover, InlayHint, ProjectItem as _, ProjectPath,
-    ProjectTransaction, ResolveState, Symbol, ToolchainStore,
+    CodeAction, CodeLens, Completion, CoreCompletion, DocumentLink, Hover, InlayHint,
+    ProjectItem as _, ProjectPath, ProjectTransaction, ResolveState, SemanticToken, Symbol,
+    ToolchainStore,
 };
 use anyhow::{anyhow, Context as _, Result};
 use async_trait::async_trait;
@@ -43,13 +44,15 @@ use language::{
     Unclipped,
 };
 use lsp::{
-    notification::DidRenameFiles, CodeActionKind, CompletionContext, DiagnosticSeverity,
-    DiagnosticTag, DidChangeWatchedFilesRegistrationOptions, Edit, FileOperationFilter,
-    FileOperationPatternKind, FileOperationRegistrationOptions, FileRename, FileSystemWatcher,
-    InsertTextFormat, LanguageServer, LanguageServerBinary, LanguageServerBinaryOptions,
-    LanguageServerId, LanguageServerName, LspRequestFuture, MessageActionItem, MessageType, OneOf,
-    RenameFilesParams, ServerHealthStatus, ServerStatus, SymbolKind, TextEdit, Url,
-    WillRenameFiles, WorkDoneProgressCancelParams, WorkspaceFolder,
+    notification::{DidCreateFiles, DidDeleteFiles, DidRenameFiles},
+    CodeActionKind, CompletionContext, CreateFilesParams, DeleteFilesParams, DiagnosticSeverity,
+    DiagnosticTag, DidChangeWatchedFilesRegistrationOptions, Edit, FileCreate, FileDelete,
+    FileOperationFilter, FileOperationPatternKind, FileOperationRegistrationOptions, FileRename,
+    FileSystemWatcher, InsertTextFormat, LanguageServer, LanguageServerBinary,
+    LanguageServerBinaryOptions, LanguageServerId, LanguageServerName, LspRequestFuture,
+    MessageActionItem, MessageType, OneOf, RenameFilesParams, ServerHealthStatus, ServerStatus,
+    SymbolKind, TextEdit, Url, WillCreateFiles, WillDeleteFiles, WillRenameFiles,
+    WorkDoneProgressCancelParams, WorkspaceFolder,
 };
 use node_runtime::read_package_installed_version;
 use parking_lot::Mutex;
@@ -151,8 +154,8 @@ pub struct LocalLspStore {
     buffers_being_formatted: HashSet<BufferId>,
     last_workspace_edits_by_language_server: HashMap<LanguageServerId, ProjectTransaction>,
     language_server_watched_paths: HashMap<LanguageServerId, LanguageServerWatchedPaths>,
-    language_server_paths_watched_for_rename:
-        HashMap<LanguageServerId, RenamePathsWatchedForServer>,
+    language_server_paths_watched_for_file_operations:
+        HashMap<LanguageServerId, FileOperationsWatchedForServer>,
     language_server_watcher_registrations:
         HashMap<LanguageServerId, HashMap<String, Vec<FileSystemWatcher>>>,
     supplementary_language_servers:
@@ -173,6 +176,23 @@ pub struct LocalLspStore {
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
     _subscription: gpui::Subscription,
     registered_buffers: HashMap<BufferId, usize>,
+    semantic_tokens: HashMap<BufferId, CachedSemanticTokens>,
+    /// The result ids of the last pulled diagnostics of each document, sent back to the
+    /// language servers so they can report unchanged diagnostics.
+    diagnostic_result_ids: HashMap<LanguageServerId, HashMap<lsp::Url, String>>,
+    document_diagnostics_tasks: HashMap<BufferId, Task<()>>,
+    workspace_diagnostics_tasks: HashMap<LanguageServerId, Task<()>>,
+    /// The pseudo language server ids under which task problem matcher results are reported,
+    /// keyed by task label, along with the paths that currently have diagnostics from that task.
+    task_diagnostics: HashMap<String, (LanguageServerId, HashSet<PathBuf>)>,
+}
+
+/// The last raw semantic tokens a language server reported for a buffer,
+/// kept so that subsequent requests can ask for a delta instead.
+struct CachedSemanticTokens {
+    server_id: LanguageServerId,
+    result_id: Option<String>,
+    data: Vec<u32>,
 }
 
 impl LocalLspStore {
@@ -819,6 +839,63 @@ impl LocalLspStore {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::SemanticTokensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            cx.emit(LspStoreEvent::RefreshSemanticTokens);
+                            this.downstream_client.as_ref().map(|(client, project_id)| {
+                                client.send(proto::RefreshSemanticTokens {
+                                    project_id: *project_id,
+                                })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::WorkspaceDiagnosticRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            this.refresh_pulled_diagnostics(server_id, cx);
+                        })?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::CodeLensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            cx.emit(LspStoreEvent::RefreshCodeLens);
+                            this.downstream_client.as_ref().map(|(client, project_id)| {
+                                client.send(proto::RefreshCodeLens {
+                                    project_id: *project_id,
+                                })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         language_server
             .on_request::<lsp::request::ShowMessageRequest, _, _>({
                 let this = this.clone();
@@ -1957,6 +2034,8 @@ impl LocalLspStore {
     ) {
         buffer.update(cx, |buffer, cx| {
             self.buffer_snapshots.remove(&buffer.remote_id());
+            self.semantic_tokens.remove(&buffer.remote_id());
+            self.document_diagnostics_tasks.remove(&buffer.remote_id());
             for (_, language_server) in self.language_servers_for_buffer(buffer, cx) {
                 language_server
                     .notify::<lsp::notification::DidCloseTextDocument>(
@@ -2778,6 +2857,8 @@ pub enum LspStoreEvent {
     },
     Notification(String),
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     DiagnosticsUpdated {
         language_server_id: LanguageServerId,
         path: ProjectPath,
@@ -2827,8 +2908,12 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_resolve_completion_documentation);
         client.add_model_request_handler(Self::handle_apply_code_action);
         client.add_model_request_handler(Self::handle_inlay_hints);
+        client.add_model_request_handler(Self::handle_semantic_tokens);
+        client.add_model_message_handler(Self::handle_refresh_semantic_tokens);
+        client.add_model_message_handler(Self::handle_refresh_code_lens);
         client.add_model_request_handler(Self::handle_get_project_symbols);
         client.add_model_request_handler(Self::handle_resolve_inlay_hint);
+        client.add_model_request_handler(Self::handle_resolve_document_link);
         client.add_model_request_handler(Self::handle_open_buffer_for_symbol);
         client.add_model_request_handler(Self::handle_refresh_inlay_hints);
         client.add_model_request_handler(Self::handle_on_type_formatting);
@@ -2842,6 +2927,12 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_lsp_command::<GetTypeDefinition>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentHighlights>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetReferences>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetCallHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetTypeHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetCodeLens>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSelectionRanges>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentLinks>);
         client.add_model_request_handler(Self::handle_lsp_command::<PrepareRename>);
         client.add_model_request_handler(Self::handle_lsp_command::<PerformRename>);
         client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
@@ -2940,11 +3031,12 @@ impl LspStore {
                 language_servers: Default::default(),
                 last_workspace_edits_by_language_server: Default::default(),
                 language_server_watched_paths: Default::default(),
-                language_server_paths_watched_for_rename: Default::default(),
+                language_server_paths_watched_for_file_operations: Default::default(),
                 language_server_watcher_registrations: Default::default(),
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
                 buffers_being_formatted: Default::default(),
                 buffer_snapshots: Default::default(),
+                semantic_tokens: Default::default(),
                 prettier_store,
                 environment,
                 http_client,
@@ -2956,6 +3048,10 @@ impl LspStore {
                     this.as_local_mut().unwrap().shutdown_language_servers(cx)
                 }),
                 registered_buffers: HashMap::default(),
+                diagnostic_result_ids: HashMap::default(),
+                document_diagnostics_tasks: HashMap::default(),
+                workspace_diagnostics_tasks: HashMap::default(),
+                task_diagnostics: HashMap::default(),
             }),
             last_formatting_failure: None,
             downstream_client: None,
@@ -3068,7 +3164,7 @@ impl LspStore {
                     }
                 }
             }
-            BufferStoreEvent::BufferDropped(_) => {}
+            BufferStoreEvent::BufferDropped(_) | BufferStoreEvent::DiffBaseChanged => {}
         }
     }
 
@@ -3155,11 +3251,18 @@ impl LspStore {
     ) {
         match event {
             language::BufferEvent::Edited { .. } => {
-                self.on_buffer_edited(buffer, cx);
+                self.on_buffer_edited(buffer.clone(), cx);
+                self.pull_document_diagnostics(buffer, true, cx);
             }
 
             language::BufferEvent::Saved => {
-                self.on_buffer_saved(buffer, cx);
+                self.on_buffer_saved(buffer.clone(), cx);
+                let server_ids = self.as_local().map_or_else(Vec::new, |local| {
+                    local.language_server_ids_for_buffer(buffer.read(cx), cx)
+                });
+                for server_id in server_ids {
+                    self.pull_workspace_diagnostics(server_id, cx);
+                }
             }
 
             _ => {}
@@ -3208,6 +3311,7 @@ impl LspStore {
             *refcount += 1;
             if *refcount == 1 {
                 local.register_buffer_with_language_servers(buffer, cx);
+                self.pull_document_diagnostics(buffer.clone(), false, cx);
             }
 
             cx.observe_release(&handle, move |this, buffer, cx| {
@@ -3801,6 +3905,54 @@ impl LspStore {
         }
     }
 
+    /// Asks the language server for the target of a link that was reported without one.
+    pub fn resolve_document_link(
+        &self,
+        link: DocumentLink,
+        buffer_handle: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<anyhow::Result<DocumentLink>> {
+        if link.target.is_some() {
+            return Task::ready(Ok(link));
+        }
+
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ResolveDocumentLink {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                link: Some(Self::serialize_document_link(&link)),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = upstream_client
+                    .request(request)
+                    .await
+                    .context("document link proto request")?;
+                Self::deserialize_document_link(
+                    response.link.context("missing resolved document link")?,
+                )
+            })
+        } else {
+            let buffer = buffer_handle.read(cx);
+            let Some((_, lang_server)) =
+                self.language_server_for_local_buffer(buffer, link.server_id, cx)
+            else {
+                return Task::ready(Ok(link));
+            };
+            let lang_server = lang_server.clone();
+            cx.spawn(move |_, _| async move {
+                let resolved_link = lang_server
+                    .request::<lsp::request::DocumentLinkResolve>(link.lsp_link.clone())
+                    .await
+                    .context("document link resolve LSP request")?;
+                Ok(DocumentLink {
+                    target: resolved_link.target.as_ref().map(ToString::to_string),
+                    lsp_link: resolved_link,
+                    ..link
+                })
+            })
+        }
+    }
+
     pub(crate) fn linked_edit(
         &mut self,
         buffer: &Model<Buffer>,
@@ -4546,6 +4698,126 @@ impl LspStore {
         }
     }
 
+    pub fn semantic_tokens(
+        &mut self,
+        buffer_handle: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<anyhow::Result<Vec<SemanticToken>>> {
+        let buffer = buffer_handle.read(cx);
+        let buffer_id = buffer.remote_id();
+
+        if let Some((client, project_id)) = self.upstream_client() {
+            let request = proto::GetSemanticTokens {
+                project_id,
+                buffer_id: buffer_id.into(),
+                version: serialize_version(&buffer.version()),
+            };
+            return cx.spawn(move |_, mut cx| async move {
+                let response = client
+                    .request(request)
+                    .await
+                    .context("semantic tokens proto request")?;
+                buffer_handle
+                    .update(&mut cx, |buffer, _| {
+                        buffer.wait_for_version(deserialize_version(&response.version))
+                    })?
+                    .await?;
+                semantic_tokens_from_proto(response)
+            });
+        }
+
+        let Some(local) = self.as_local_mut() else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let Some(language_server) = local
+            .primary_language_server_for_buffer(buffer, cx)
+            .map(|(_, server)| server.clone())
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let Some(file) = File::from_dyn(buffer.file()).and_then(File::as_local) else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let Some((legend, supports_delta)) =
+            semantic_tokens_legend(&language_server.capabilities())
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        let server_id = language_server.server_id();
+        let text_document = lsp::TextDocumentIdentifier {
+            uri: lsp::Url::from_file_path(file.abs_path(cx)).unwrap(),
+        };
+        let snapshot = buffer.text_snapshot();
+        let previous = local
+            .semantic_tokens
+            .remove(&buffer_id)
+            .filter(|cached| supports_delta && cached.server_id == server_id)
+            .and_then(|cached| Some((cached.result_id?, cached.data)));
+
+        cx.spawn(move |lsp_store, mut cx| async move {
+            let (result_id, data) = if let Some((previous_result_id, previous_data)) = previous {
+                let response = language_server
+                    .request::<lsp::request::SemanticTokensFullDeltaRequest>(
+                        lsp::SemanticTokensDeltaParams {
+                            text_document,
+                            previous_result_id,
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    )
+                    .await
+                    .context("semantic tokens delta LSP request")?;
+                match response {
+                    Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens)) => {
+                        (tokens.result_id, flatten_semantic_tokens(tokens.data))
+                    }
+                    Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)) => (
+                        delta.result_id,
+                        apply_semantic_token_edits(previous_data, delta.edits),
+                    ),
+                    Some(lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits }) => {
+                        (None, apply_semantic_token_edits(previous_data, edits))
+                    }
+                    None => (None, Vec::new()),
+                }
+            } else {
+                let response = language_server
+                    .request::<lsp::request::SemanticTokensFullRequest>(lsp::SemanticTokensParams {
+                        text_document,
+                        work_done_progress_params: Default::default(),
+                        partial_result_params: Default::default(),
+                    })
+                    .await
+                    .context("semantic tokens LSP request")?;
+                match response {
+                    Some(lsp::SemanticTokensResult::Tokens(tokens)) => {
+                        (tokens.result_id, flatten_semantic_tokens(tokens.data))
+                    }
+                    Some(lsp::SemanticTokensResult::Partial(partial)) => {
+                        (None, flatten_semantic_tokens(partial.data))
+                    }
+                    None => (None, Vec::new()),
+                }
+            };
+
+            let tokens = decode_semantic_tokens(&data, &legend, &snapshot);
+            lsp_store.update(&mut cx, |lsp_store, _| {
+                if let Some(local) = lsp_store.as_local_mut() {
+                    local.semantic_tokens.insert(
+                        buffer_id,
+                        CachedSemanticTokens {
+                            server_id,
+                            result_id,
+                            data,
+                        },
+                    );
+                }
+            })?;
+            Ok(tokens)
+        })
+    }
+
     pub fn signature_help<T: ToPointUtf16>(
         &mut self,
         buffer: &Model<Buffer>,
@@ -5327,6 +5599,38 @@ impl LspStore {
         Ok(())
     }
 
+    /// Replaces all diagnostics previously reported by the task with the given label.
+    /// Passing no diagnostics clears them.
+    pub fn update_task_diagnostics(
+        &mut self,
+        task_label: &str,
+        diagnostics: HashMap<PathBuf, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let local = self
+            .as_local_mut()
+            .context("task diagnostics can only be reported for local projects")?;
+        let languages = local.languages.clone();
+        let (server_id, reported_paths) = local
+            .task_diagnostics
+            .entry(task_label.to_string())
+            .or_insert_with(|| (languages.next_language_server_id(), HashSet::default()));
+        let server_id = *server_id;
+        let stale_paths = mem::take(reported_paths)
+            .into_iter()
+            .filter(|path| !diagnostics.contains_key(path))
+            .collect::<Vec<_>>();
+        reported_paths.extend(diagnostics.keys().cloned());
+
+        for path in stale_paths {
+            self.update_diagnostic_entries(server_id, path, None, Vec::new(), cx)?;
+        }
+        for (path, entries) in diagnostics {
+            self.update_diagnostic_entries(server_id, path, None, entries, cx)?;
+        }
+        Ok(())
+    }
+
     fn update_worktree_diagnostics(
         &mut self,
         worktree_id: WorktreeId,
@@ -6124,6 +6428,44 @@ impl LspStore {
             .map(|(key, value)| (*key, value))
     }
 
+    pub(super) fn did_create_entry(&self, worktree_id: WorktreeId, path: &Path, is_dir: bool) {
+        let Some(uri) = lsp::Url::from_file_path(path).ok().map(String::from) else {
+            return;
+        };
+        self.did_perform_file_operation::<DidCreateFiles>(
+            worktree_id,
+            &uri,
+            is_dir,
+            FileOperationsWatchedForServer::should_send_did_create,
+            CreateFilesParams {
+                files: vec![FileCreate { uri: uri.clone() }],
+            },
+        );
+    }
+
+    pub(super) fn will_create_entry(
+        this: WeakModel<Self>,
+        worktree_id: WorktreeId,
+        path: &Path,
+        is_dir: bool,
+        cx: AsyncAppContext,
+    ) -> Task<()> {
+        let Some(uri) = lsp::Url::from_file_path(path).ok().map(String::from) else {
+            return Task::ready(());
+        };
+        Self::will_perform_file_operation::<WillCreateFiles>(
+            this,
+            worktree_id,
+            uri.clone(),
+            is_dir,
+            FileOperationsWatchedForServer::should_send_will_create,
+            CreateFilesParams {
+                files: vec![FileCreate { uri }],
+            },
+            cx,
+        )
+    }
+
     pub(super) fn did_rename_entry(
         &self,
         worktree_id: WorktreeId,
@@ -6132,30 +6474,20 @@ impl LspStore {
         is_dir: bool,
     ) {
         maybe!({
-            let local_store = self.as_local()?;
-
             let old_uri = lsp::Url::from_file_path(old_path).ok().map(String::from)?;
             let new_uri = lsp::Url::from_file_path(new_path).ok().map(String::from)?;
-
-            for language_server in local_store.language_servers_for_worktree(worktree_id) {
-                let Some(filter) = local_store
-                    .language_server_paths_watched_for_rename
-                    .get(&language_server.server_id())
-                else {
-                    continue;
-                };
-
-                if filter.should_send_did_rename(&old_uri, is_dir) {
-                    language_server
-                        .notify::<DidRenameFiles>(RenameFilesParams {
-                            files: vec![FileRename {
-                                old_uri: old_uri.clone(),
-                                new_uri: new_uri.clone(),
-                            }],
-                        })
-                        .log_err();
-                }
-            }
+            self.did_perform_file_operation::<DidRenameFiles>(
+                worktree_id,
+                &old_uri,
+                is_dir,
+                FileOperationsWatchedForServer::should_send_did_rename,
+                RenameFilesParams {
+                    files: vec![FileRename {
+                        old_uri: old_uri.clone(),
+                        new_uri,
+                    }],
+                },
+            );
             Some(())
         });
     }
@@ -6170,15 +6502,112 @@ impl LspStore {
     ) -> Task<()> {
         let old_uri = lsp::Url::from_file_path(old_path).ok().map(String::from);
         let new_uri = lsp::Url::from_file_path(new_path).ok().map(String::from);
+        let (Some(old_uri), Some(new_uri)) = (old_uri, new_uri) else {
+            return Task::ready(());
+        };
+        Self::will_perform_file_operation::<WillRenameFiles>(
+            this,
+            worktree_id,
+            old_uri.clone(),
+            is_dir,
+            FileOperationsWatchedForServer::should_send_will_rename,
+            RenameFilesParams {
+                files: vec![FileRename { old_uri, new_uri }],
+            },
+            cx,
+        )
+    }
+
+    pub(super) fn did_delete_entry(&self, worktree_id: WorktreeId, path: &Path, is_dir: bool) {
+        let Some(uri) = lsp::Url::from_file_path(path).ok().map(String::from) else {
+            return;
+        };
+        self.did_perform_file_operation::<DidDeleteFiles>(
+            worktree_id,
+            &uri,
+            is_dir,
+            FileOperationsWatchedForServer::should_send_did_delete,
+            DeleteFilesParams {
+                files: vec![FileDelete { uri: uri.clone() }],
+            },
+        );
+    }
+
+    pub(super) fn will_delete_entry(
+        this: WeakModel<Self>,
+        worktree_id: WorktreeId,
+        path: &Path,
+        is_dir: bool,
+        cx: AsyncAppContext,
+    ) -> Task<()> {
+        let Some(uri) = lsp::Url::from_file_path(path).ok().map(String::from) else {
+            return Task::ready(());
+        };
+        Self::will_perform_file_operation::<WillDeleteFiles>(
+            this,
+            worktree_id,
+            uri.clone(),
+            is_dir,
+            FileOperationsWatchedForServer::should_send_will_delete,
+            DeleteFilesParams {
+                files: vec![FileDelete { uri }],
+            },
+            cx,
+        )
+    }
+
+    /// Notifies every language server in the worktree that registered interest in
+    /// the path that a file operation has been performed on it.
+    fn did_perform_file_operation<N>(
+        &self,
+        worktree_id: WorktreeId,
+        uri: &str,
+        is_dir: bool,
+        should_send: fn(&FileOperationsWatchedForServer, &str, bool) -> bool,
+        params: N::Params,
+    ) where
+        N: lsp::notification::Notification,
+        N::Params: Clone,
+    {
+        let Some(local_store) = self.as_local() else {
+            return;
+        };
+        for language_server in local_store.language_servers_for_worktree(worktree_id) {
+            let Some(filter) = local_store
+                .language_server_paths_watched_for_file_operations
+                .get(&language_server.server_id())
+            else {
+                continue;
+            };
+
+            if should_send(filter, uri, is_dir) {
+                language_server.notify::<N>(params.clone()).log_err();
+            }
+        }
+    }
+
+    /// Asks every language server in the worktree that registered interest in the path
+    /// for the edits it wants to make before a file operation is performed, and applies them.
+    fn will_perform_file_operation<R>(
+        this: WeakModel<Self>,
+        worktree_id: WorktreeId,
+        uri: String,
+        is_dir: bool,
+        should_send: fn(&FileOperationsWatchedForServer, &str, bool) -> bool,
+        params: R::Params,
+        cx: AsyncAppContext,
+    ) -> Task<()>
+    where
+        R: lsp::request::Request<Result = Option<lsp::WorkspaceEdit>>,
+        R::Params: Clone,
+    {
         cx.spawn(move |mut cx| async move {
             let mut tasks = vec![];
             this.update(&mut cx, |this, cx| {
                 let local_store = this.as_local()?;
-                let old_uri = old_uri?;
-                let new_uri = new_uri?;
                 for language_server in local_store.language_servers_for_worktree(worktree_id) {
                     let Some(filter) = local_store
-                        .language_server_paths_watched_for_rename
+                        .language_server_paths_watched_for_file_operations
                         .get(&language_server.server_id())
                     else {
                         continue;
@@ -6188,16 +6617,13 @@ impl LspStore {
                     else {
                         continue;
                     };
-                    if filter.should_send_will_rename(&old_uri, is_dir) {
+                    if should_send(filter, &uri, is_dir) {
                         let apply_edit = cx.spawn({
-                            let old_uri = old_uri.clone();
-                            let new_uri = new_uri.clone();
+                            let params = params.clone();
                             let language_server = language_server.clone();
                             |this, mut cx| async move {
                                 let edit = language_server
-                                    .request::<WillRenameFiles>(RenameFilesParams {
-                                        files: vec![FileRename { old_uri, new_uri }],
-                                    })
+                                    .request::<R>(params)
                                     .log_err()
                                     .await
                                     .flatten()?;
@@ -6615,6 +7041,52 @@ impl LspStore {
         })
     }
 
+    async fn handle_refresh_semantic_tokens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshSemanticTokens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(LspStoreEvent::RefreshSemanticTokens);
+        })
+    }
+
+    async fn handle_refresh_code_lens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshCodeLens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(LspStoreEvent::RefreshCodeLens);
+        })
+    }
+
+    async fn handle_semantic_tokens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetSemanticTokens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetSemanticTokensResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&envelope.payload.version))
+            })?
+            .await
+            .with_context(|| format!("waiting for version for buffer {}", buffer.entity_id()))?;
+
+        let tokens = this
+            .update(&mut cx, |lsp_store, cx| {
+                lsp_store.semantic_tokens(buffer.clone(), cx)
+            })?
+            .await
+            .context("semantic tokens fetch")?;
+        let version = buffer.update(&mut cx, |buffer, _| buffer.version())?;
+        Ok(semantic_tokens_to_proto(tokens, &version))
+    }
+
     async fn handle_resolve_inlay_hint(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::ResolveInlayHint>,
@@ -6646,6 +7118,30 @@ impl LspStore {
         })
     }
 
+    async fn handle_resolve_document_link(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ResolveDocumentLink>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ResolveDocumentLinkResponse> {
+        let link = envelope
+            .payload
+            .link
+            .context("invalid resolve document link message: missing the link")?;
+        let link = Self::deserialize_document_link(link)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        let resolved_link = this
+            .update(&mut cx, |this, cx| {
+                this.resolve_document_link(link, buffer, cx)
+            })?
+            .await?;
+        Ok(proto::ResolveDocumentLinkResponse {
+            link: Some(Self::serialize_document_link(&resolved_link)),
+        })
+    }
+
     async fn handle_open_buffer_for_symbol(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::OpenBufferForSymbol>,
@@ -7096,6 +7592,8 @@ impl LspStore {
             });
         }
         local.language_server_watched_paths.remove(&server_id);
+        local.diagnostic_result_ids.remove(&server_id);
+        local.workspace_diagnostics_tasks.remove(&server_id);
         let server_state = local.language_servers.remove(&server_id);
         cx.notify();
         cx.emit(LspStoreEvent::LanguageServerRemoved(server_id));
@@ -7199,6 +7697,270 @@ impl LspStore {
         .detach();
     }
 
+    /// Requests the diagnostics of a buffer from every language server that uses the pull model
+    /// (`textDocument/diagnostic`), replacing any request still in flight for that buffer.
+    pub fn pull_document_diagnostics(
+        &mut self,
+        buffer: Model<Buffer>,
+        debounce: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        const DEBOUNCE: Duration = Duration::from_millis(250);
+
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        let buffer_id = buffer.read(cx).remote_id();
+        if !local.registered_buffers.contains_key(&buffer_id)
+            || !local
+                .language_servers_for_buffer(buffer.read(cx), cx)
+                .any(|(_, server)| pull_diagnostic_options(server).is_some())
+        {
+            return;
+        }
+        let task = cx.spawn(move |this, mut cx| async move {
+            if debounce {
+                cx.background_executor().timer(DEBOUNCE).await;
+            }
+
+            let Some((uri, requests)) = this
+                .update(&mut cx, |this, cx| {
+                    let local = this.as_local()?;
+                    let buffer = buffer.read(cx);
+                    let file = File::from_dyn(buffer.file())?;
+                    let uri = lsp::Url::from_file_path(file.as_local()?.abs_path(cx)).ok()?;
+                    let requests = local
+                        .language_servers_for_buffer(buffer, cx)
+                        .filter_map(|(_, server)| {
+                            let options = pull_diagnostic_options(server)?;
+                            let server_id = server.server_id();
+                            let version = local
+                                .buffer_snapshots
+                                .get(&buffer_id)
+                                .and_then(|snapshots| snapshots.get(&server_id))
+                                .and_then(|snapshots| snapshots.last())
+                                .map(|snapshot| snapshot.version);
+                            let previous_result_id = local
+                                .diagnostic_result_ids
+                                .get(&server_id)
+                                .and_then(|result_ids| result_ids.get(&uri))
+                                .cloned();
+                            let request = server
+                                .request::<lsp::request::DocumentDiagnosticRequest>(
+                                    lsp::DocumentDiagnosticParams {
+                                        text_document: lsp::TextDocumentIdentifier::new(
+                                            uri.clone(),
+                                        ),
+                                        identifier: options.identifier,
+                                        previous_result_id,
+                                        work_done_progress_params: Default::default(),
+                                        partial_result_params: Default::default(),
+                                    },
+                                );
+                            Some(async move { (server_id, version, request.await) })
+                        })
+                        .collect::<Vec<_>>();
+                    Some((uri, requests))
+                })
+                .ok()
+                .flatten()
+            else {
+                return;
+            };
+
+            for (server_id, version, response) in join_all(requests).await {
+                let Some(response) = response
+                    .with_context(|| format!("pulling diagnostics from server {server_id}"))
+                    .log_err()
+                else {
+                    continue;
+                };
+                this.update(&mut cx, |this, cx| {
+                    let related_documents = match response {
+                        lsp::DocumentDiagnosticReportResult::Report(
+                            lsp::DocumentDiagnosticReport::Full(report),
+                        ) => {
+                            this.apply_pulled_diagnostics(
+                                server_id,
+                                uri.clone(),
+                                version,
+                                report.full_document_diagnostic_report.into(),
+                                cx,
+                            )
+                            .log_err();
+                            report.related_documents
+                        }
+                        lsp::DocumentDiagnosticReportResult::Report(
+                            lsp::DocumentDiagnosticReport::Unchanged(report),
+                        ) => {
+                            this.apply_pulled_diagnostics(
+                                server_id,
+                                uri.clone(),
+                                version,
+                                report.unchanged_document_diagnostic_report.into(),
+                                cx,
+                            )
+                            .log_err();
+                            report.related_documents
+                        }
+                        lsp::DocumentDiagnosticReportResult::Partial(report) => {
+                            report.related_documents
+                        }
+                    };
+                    for (related_uri, report) in related_documents.unwrap_or_default() {
+                        this.apply_pulled_diagnostics(server_id, related_uri, None, report, cx)
+                            .log_err();
+                    }
+                })
+                .ok();
+            }
+        });
+        local.document_diagnostics_tasks.insert(buffer_id, task);
+    }
+
+    /// Requests the diagnostics of the whole workspace from a language server that supports it
+    /// (`workspace/diagnostic`), sending the result ids of the documents it previously reported.
+    pub fn pull_workspace_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        let Some(LanguageServerState::Running { server, .. }) =
+            local.language_servers.get(&server_id)
+        else {
+            return;
+        };
+        let Some(options) = pull_diagnostic_options(server) else {
+            return;
+        };
+        if !options.workspace_diagnostics {
+            return;
+        }
+
+        let previous_result_ids = local
+            .diagnostic_result_ids
+            .get(&server_id)
+            .into_iter()
+            .flatten()
+            .map(|(uri, result_id)| lsp::PreviousResultId {
+                uri: uri.clone(),
+                value: result_id.clone(),
+            })
+            .collect();
+        let request = server.request::<lsp::request::WorkspaceDiagnosticRequest>(
+            lsp::WorkspaceDiagnosticParams {
+                identifier: options.identifier,
+                previous_result_ids,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        );
+        let task = cx.spawn(move |this, mut cx| async move {
+            let Some(response) = request
+                .await
+                .with_context(|| format!("pulling workspace diagnostics from server {server_id}"))
+                .log_err()
+            else {
+                return;
+            };
+            let items = match response {
+                lsp::WorkspaceDiagnosticReportResult::Report(report) => report.items,
+                lsp::WorkspaceDiagnosticReportResult::Partial(report) => report.items,
+            };
+            this.update(&mut cx, |this, cx| {
+                for item in items {
+                    let (uri, version, report) = match item {
+                        lsp::WorkspaceDocumentDiagnosticReport::Full(report) => (
+                            report.uri,
+                            report.version,
+                            report.full_document_diagnostic_report.into(),
+                        ),
+                        lsp::WorkspaceDocumentDiagnosticReport::Unchanged(report) => (
+                            report.uri,
+                            report.version,
+                            report.unchanged_document_diagnostic_report.into(),
+                        ),
+                    };
+                    let version = version.and_then(|version| i32::try_from(version).ok());
+                    this.apply_pulled_diagnostics(server_id, uri, version, report, cx)
+                        .log_err();
+                }
+            })
+            .ok();
+        });
+        local.workspace_diagnostics_tasks.insert(server_id, task);
+    }
+
+    /// Re-pulls the diagnostics of every open buffer and of the workspace,
+    /// as requested by `workspace/diagnostic/refresh`.
+    fn refresh_pulled_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(local) = self.as_local() else {
+            return;
+        };
+        let buffers = local
+            .registered_buffers
+            .keys()
+            .filter_map(|buffer_id| self.buffer_store.read(cx).get(*buffer_id))
+            .filter(|buffer| {
+                local
+                    .language_server_ids_for_buffer(buffer.read(cx), cx)
+                    .contains(&server_id)
+            })
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.pull_document_diagnostics(buffer, false, cx);
+        }
+        self.pull_workspace_diagnostics(server_id, cx);
+    }
+
+    fn apply_pulled_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        uri: lsp::Url,
+        version: Option<i32>,
+        report: lsp::DocumentDiagnosticReportKind,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let local = self
+            .as_local_mut()
+            .context("pulled diagnostics on a remote project")?;
+        let result_ids = local.diagnostic_result_ids.entry(server_id).or_default();
+        match report {
+            lsp::DocumentDiagnosticReportKind::Full(report) => {
+                match report.result_id {
+                    Some(result_id) => result_ids.insert(uri.clone(), result_id),
+                    None => result_ids.remove(&uri),
+                };
+                let adapter = self
+                    .language_server_adapter_for_id(server_id)
+                    .with_context(|| format!("no adapter for language server {server_id}"))?;
+                let mut params = lsp::PublishDiagnosticsParams {
+                    uri,
+                    diagnostics: report.items,
+                    version,
+                };
+                adapter.process_diagnostics(&mut params);
+                self.update_diagnostics(
+                    server_id,
+                    params,
+                    &adapter.disk_based_diagnostic_sources,
+                    cx,
+                )
+            }
+            lsp::DocumentDiagnosticReportKind::Unchanged(report) => {
+                result_ids.insert(uri, report.result_id);
+                Ok(())
+            }
+        }
+    }
+
     pub fn update_diagnostics(
         &mut self,
         language_server_id: LanguageServerId,
@@ -7364,14 +8126,10 @@ impl LspStore {
             .as_ref()
             .and_then(|ws| ws.file_operations.as_ref())
         {
-            let did_rename_caps = file_ops_caps.did_rename.as_ref();
-            let will_rename_caps = file_ops_caps.will_rename.as_ref();
-            if did_rename_caps.or(will_rename_caps).is_some() {
-                let watcher = RenamePathsWatchedForServer::default()
-                    .with_did_rename_patterns(did_rename_caps)
-                    .with_will_rename_patterns(will_rename_caps);
+            let watcher = FileOperationsWatchedForServer::new(file_ops_caps);
+            if !watcher.is_empty() {
                 local
-                    .language_server_paths_watched_for_rename
+                    .language_server_paths_watched_for_file_operations
                     .insert(server_id, watcher);
             }
         }
@@ -7407,6 +8165,7 @@ impl LspStore {
         }
 
         // Tell the language server about every open buffer in the worktree that matches the language.
+        let mut registered_buffers = Vec::new();
         self.buffer_store.clone().update(cx, |buffer_store, cx| {
             for buffer_handle in buffer_store.buffers() {
                 let buffer = buffer_handle.read(cx);
@@ -7437,6 +8196,7 @@ impl LspStore {
                 let local = self.as_local_mut().unwrap();
 
                 if local.registered_buffers.contains_key(&buffer.remote_id()) {
+                    registered_buffers.push(buffer_handle.clone());
                     let versions = local
                         .buffer_snapshots
                         .entry(buffer.remote_id())
@@ -7487,6 +8247,11 @@ impl LspStore {
             }
         });
 
+        for buffer in registered_buffers {
+            self.pull_document_diagnostics(buffer, false, cx);
+        }
+        self.pull_workspace_diagnostics(server_id, cx);
+
         cx.notify();
     }
 
@@ -7828,6 +8593,60 @@ impl LspStore {
         })
     }
 
+    pub(crate) fn serialize_code_lens(lens: &CodeLens) -> proto::CodeLens {
+        proto::CodeLens {
+            server_id: lens.server_id.0 as u64,
+            start: Some(serialize_anchor(&lens.range.start)),
+            end: Some(serialize_anchor(&lens.range.end)),
+            lsp_lens: serde_json::to_vec(&lens.lsp_lens).unwrap(),
+        }
+    }
+
+    pub(crate) fn deserialize_code_lens(lens: proto::CodeLens) -> Result<CodeLens> {
+        let start = lens
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = lens
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        let lsp_lens = serde_json::from_slice(&lens.lsp_lens)?;
+        Ok(CodeLens {
+            server_id: LanguageServerId(lens.server_id as usize),
+            range: start..end,
+            lsp_lens,
+        })
+    }
+
+    pub(crate) fn serialize_document_link(link: &DocumentLink) -> proto::DocumentLink {
+        proto::DocumentLink {
+            start: Some(serialize_anchor(&link.range.start)),
+            end: Some(serialize_anchor(&link.range.end)),
+            target: link.target.clone(),
+            server_id: link.server_id.0 as u64,
+            lsp_link: serde_json::to_vec(&link.lsp_link).unwrap(),
+        }
+    }
+
+    pub(crate) fn deserialize_document_link(link: proto::DocumentLink) -> Result<DocumentLink> {
+        let start = link
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = link
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        let lsp_link = serde_json::from_slice(&link.lsp_link)?;
+        Ok(DocumentLink {
+            server_id: LanguageServerId(link.server_id as usize),
+            range: start..end,
+            target: link.target,
+            lsp_link,
+        })
+    }
+
     fn update_last_formatting_failure<T>(&mut self, formatting_result: &anyhow::Result<T>) {
         match &formatting_result {
             Ok(_) => self.last_formatting_failure = None,
@@ -7910,49 +8729,67 @@ pub enum LanguageServerToQuery {
     Other(LanguageServerId),
 }
 
-#[derive(Default)]
-struct RenamePathsWatchedForServer {
-    did_rename: Vec<RenameActionPredicate>,
-    will_rename: Vec<RenameActionPredicate>,
+struct FileOperationsWatchedForServer {
+    did_create: Vec<FileOperationPredicate>,
+    will_create: Vec<FileOperationPredicate>,
+    did_rename: Vec<FileOperationPredicate>,
+    will_rename: Vec<FileOperationPredicate>,
+    did_delete: Vec<FileOperationPredicate>,
+    will_delete: Vec<FileOperationPredicate>,
 }
 
-impl RenamePathsWatchedForServer {
-    fn with_did_rename_patterns(
-        mut self,
-        did_rename: Option<&FileOperationRegistrationOptions>,
-    ) -> Self {
-        if let Some(did_rename) = did_rename {
-            self.did_rename = did_rename
-                .filters
-                .iter()
+impl FileOperationsWatchedForServer {
+    fn new(capabilities: &lsp::WorkspaceFileOperationsServerCapabilities) -> Self {
+        fn predicates(
+            options: Option<&FileOperationRegistrationOptions>,
+        ) -> Vec<FileOperationPredicate> {
+            options
+                .into_iter()
+                .flat_map(|options| &options.filters)
                 .filter_map(|filter| filter.try_into().log_err())
-                .collect();
+                .collect()
         }
-        self
-    }
-    fn with_will_rename_patterns(
-        mut self,
-        will_rename: Option<&FileOperationRegistrationOptions>,
-    ) -> Self {
-        if let Some(will_rename) = will_rename {
-            self.will_rename = will_rename
-                .filters
-                .iter()
-                .filter_map(|filter| filter.try_into().log_err())
-                .collect();
+
+        Self {
+            did_create: predicates(capabilities.did_create.as_ref()),
+            will_create: predicates(capabilities.will_create.as_ref()),
+            did_rename: predicates(capabilities.did_rename.as_ref()),
+            will_rename: predicates(capabilities.will_rename.as_ref()),
+            did_delete: predicates(capabilities.did_delete.as_ref()),
+            will_delete: predicates(capabilities.will_delete.as_ref()),
         }
-        self
     }
 
+    fn is_empty(&self) -> bool {
+        self.did_create.is_empty()
+            && self.will_create.is_empty()
+            && self.did_rename.is_empty()
+            && self.will_rename.is_empty()
+            && self.did_delete.is_empty()
+            && self.will_delete.is_empty()
+    }
+
+    fn should_send_did_create(&self, path: &str, is_dir: bool) -> bool {
+        self.did_create.iter().any(|pred| pred.eval(path, is_dir))
+    }
+    fn should_send_will_create(&self, path: &str, is_dir: bool) -> bool {
+        self.will_create.iter().any(|pred| pred.eval(path, is_dir))
+    }
     fn should_send_did_rename(&self, path: &str, is_dir: bool) -> bool {
         self.did_rename.iter().any(|pred| pred.eval(path, is_dir))
     }
     fn should_send_will_rename(&self, path: &str, is_dir: bool) -> bool {
         self.will_rename.iter().any(|pred| pred.eval(path, is_dir))
     }
+    fn should_send_did_delete(&self, path: &str, is_dir: bool) -> bool {
+        self.did_delete.iter().any(|pred| pred.eval(path, is_dir))
+    }
+    fn should_send_will_delete(&self, path: &str, is_dir: bool) -> bool {
+        self.will_delete.iter().any(|pred| pred.eval(path, is_dir))
+    }
 }
 
-impl TryFrom<&FileOperationFilter> for RenameActionPredicate {
+impl TryFrom<&FileOperationFilter> for FileOperationPredicate {
     type Error = globset::Error;
     fn try_from(ops: &FileOperationFilter) -> Result<Self, globset::Error> {
         Ok(Self {
@@ -7969,12 +8806,12 @@ impl TryFrom<&FileOperationFilter> for RenameActionPredicate {
         })
     }
 }
-struct RenameActionPredicate {
+struct FileOperationPredicate {
     glob: GlobMatcher,
     kind: Option<FileOperationPatternKind>,
 }
 
-impl RenameActionPredicate {
+impl FileOperationPredicate {
     // Returns true if language server should be notified
     fn eval(&self, path: &str, is_dir: bool) -> bool {
         self.kind.as_ref().map_or(true, |kind| {
@@ -8623,6 +9460,216 @@ fn include_text(server: &lsp::LanguageServer) -> Option<bool> {
     }
 }
 
+/// Returns the pull diagnostics options of a language server,
+/// or `None` if it only publishes its diagnostics.
+fn pull_diagnostic_options(server: &LanguageServer) -> Option<lsp::DiagnosticOptions> {
+    match server.capabilities().diagnostic_provider? {
+        lsp::DiagnosticServerCapabilities::Options(options) => Some(options),
+        lsp::DiagnosticServerCapabilities::RegistrationOptions(options) => {
+            Some(options.diagnostic_options)
+        }
+    }
+}
+
+/// Returns the legend of a server's semantic tokens, and whether it can
+/// report them as a delta against a previous response.
+fn semantic_tokens_legend(
+    capabilities: &lsp::ServerCapabilities,
+) -> Option<(lsp::SemanticTokensLegend, bool)> {
+    let options = match capabilities.semantic_tokens_provider.as_ref()? {
+        lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => options,
+        lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+            &options.semantic_tokens_options
+        }
+    };
+    let supports_delta = match options.full.as_ref()? {
+        lsp::SemanticTokensFullOptions::Bool(supported) => {
+            if !supported {
+                return None;
+            }
+            false
+        }
+        lsp::SemanticTokensFullOptions::Delta { delta } => delta.unwrap_or(false),
+    };
+    Some((options.legend.clone(), supports_delta))
+}
+
+fn flatten_semantic_tokens(tokens: Vec<lsp::SemanticToken>) -> Vec<u32> {
+    tokens
+        .into_iter()
+        .flat_map(|token| {
+            [
+                token.delta_line,
+                token.delta_start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        })
+        .collect()
+}
+
+/// Semantic token edits index into the flat integer encoding of the tokens,
+/// where every token takes up five integers.
+fn apply_semantic_token_edits(
+    mut data: Vec<u32>,
+    mut edits: Vec<lsp::SemanticTokensEdit>,
+) -> Vec<u32> {
+    edits.sort_by_key(|edit| edit.start);
+    for edit in edits.into_iter().rev() {
+        let start = (edit.start as usize).min(data.len());
+        let end = (start + edit.delete_count as usize).min(data.len());
+        data.splice(
+            start..end,
+            flatten_semantic_tokens(edit.data.unwrap_or_default()),
+        );
+    }
+    data
+}
+
+fn decode_semantic_tokens(
+    data: &[u32],
+    legend: &lsp::SemanticTokensLegend,
+    snapshot: &TextBufferSnapshot,
+) -> Vec<SemanticToken> {
+    let token_types = legend
+        .token_types
+        .iter()
+        .map(|token_type| Arc::<str>::from(token_type.as_str()))
+        .collect::<Vec<_>>();
+    let token_modifiers = legend
+        .token_modifiers
+        .iter()
+        .map(|modifier| Arc::<str>::from(modifier.as_str()))
+        .collect::<Vec<_>>();
+
+    let mut tokens = Vec::with_capacity(data.len() / 5);
+    let mut line = 0;
+    let mut start = 0;
+    for token in data.chunks_exact(5) {
+        let &[delta_line, delta_start, length, token_type, modifiers] = token else {
+            continue;
+        };
+        if delta_line == 0 {
+            start += delta_start;
+        } else {
+            line += delta_line;
+            start = delta_start;
+        }
+        let Some(token_type) = token_types.get(token_type as usize) else {
+            continue;
+        };
+
+        let start_point =
+            snapshot.clip_point_utf16(Unclipped(PointUtf16::new(line, start)), Bias::Left);
+        let end_point = snapshot.clip_point_utf16(
+            Unclipped(PointUtf16::new(line, start.saturating_add(length))),
+            Bias::Left,
+        );
+        tokens.push(SemanticToken {
+            range: snapshot.anchor_after(start_point)..snapshot.anchor_before(end_point),
+            token_type: token_type.clone(),
+            modifiers: token_modifiers
+                .iter()
+                .enumerate()
+                .filter(|(ix, _)| *ix < 32 && modifiers & (1 << ix) != 0)
+                .map(|(_, modifier)| modifier.clone())
+                .collect(),
+        });
+    }
+    tokens
+}
+
+fn semantic_tokens_to_proto(
+    tokens: Vec<SemanticToken>,
+    version: &clock::Global,
+) -> proto::GetSemanticTokensResponse {
+    let mut token_types = Vec::<Arc<str>>::new();
+    let mut token_modifiers = Vec::<Arc<str>>::new();
+    let tokens = tokens
+        .into_iter()
+        .map(|token| {
+            let token_type = match token_types.iter().position(|t| *t == token.token_type) {
+                Some(ix) => ix,
+                None => {
+                    token_types.push(token.token_type.clone());
+                    token_types.len() - 1
+                }
+            };
+            let mut modifiers = 0;
+            for modifier in &token.modifiers {
+                let ix = match token_modifiers.iter().position(|m| m == modifier) {
+                    Some(ix) => ix,
+                    None => {
+                        token_modifiers.push(modifier.clone());
+                        token_modifiers.len() - 1
+                    }
+                };
+                if ix < 32 {
+                    modifiers |= 1 << ix;
+                }
+            }
+            proto::SemanticToken {
+                start: Some(serialize_anchor(&token.range.start)),
+                end: Some(serialize_anchor(&token.range.end)),
+                token_type: token_type as u32,
+                token_modifiers: modifiers,
+            }
+        })
+        .collect();
+
+    proto::GetSemanticTokensResponse {
+        token_types: token_types.iter().map(|t| t.to_string()).collect(),
+        token_modifiers: token_modifiers.iter().map(|m| m.to_string()).collect(),
+        tokens,
+        version: serialize_version(version),
+    }
+}
+
+fn semantic_tokens_from_proto(
+    response: proto::GetSemanticTokensResponse,
+) -> Result<Vec<SemanticToken>> {
+    let token_types = response
+        .token_types
+        .into_iter()
+        .map(Arc::<str>::from)
+        .collect::<Vec<_>>();
+    let token_modifiers = response
+        .token_modifiers
+        .into_iter()
+        .map(Arc::<str>::from)
+        .collect::<Vec<_>>();
+    response
+        .tokens
+        .into_iter()
+        .map(|token| {
+            let start = token
+                .start
+                .and_then(deserialize_anchor)
+                .context("invalid semantic token start")?;
+            let end = token
+                .end
+                .and_then(deserialize_anchor)
+                .context("invalid semantic token end")?;
+            let token_type = token_types
+                .get(token.token_type as usize)
+                .context("invalid semantic token type")?
+                .clone();
+            let modifiers = token_modifiers
+                .iter()
+                .enumerate()
+                .filter(|(ix, _)| *ix < 32 && token.token_modifiers & (1 << ix) != 0)
+                .map(|(_, modifier)| modifier.clone())
+                .collect();
+            Ok(SemanticToken {
+                range: start..end,
+                token_type,
+                modifiers,
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 #[test]
 fn test_glob_literal_prefix() {
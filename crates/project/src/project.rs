@@ -48,8 +48,8 @@ use itertools::Itertools;
 use language::{
     language_settings::InlayHintKind, proto::split_operations, Buffer, BufferEvent,
     CachedLspAdapter, Capability, CodeLabel, Documentation, File as _, Language, LanguageName,
-    LanguageRegistry, PointUtf16, ToOffset, ToPointUtf16, Toolchain, ToolchainList, Transaction,
-    Unclipped,
+    LanguageRegistry, ParseStatus, PointUtf16, ToOffset, ToPointUtf16, Toolchain, ToolchainList,
+    Transaction, Unclipped,
 };
 use lsp::{
     CodeActionKind, CompletionContext, CompletionItemKind, DocumentHighlightKind, LanguageServer,
@@ -81,6 +81,7 @@ use std::{
     sync::Arc,
     time::Duration,
 };
+use task::TaskTemplate;
 use task_store::TaskStore;
 use terminals::Terminals;
 use text::{Anchor, BufferId};
@@ -247,6 +248,7 @@ pub enum Event {
     WorktreeRemoved(WorktreeId),
     WorktreeUpdatedEntries(WorktreeId, UpdatedEntriesSet),
     WorktreeUpdatedGitRepositories(WorktreeId),
+    GitDiffBaseChanged,
     DiskBasedDiagnosticsStarted {
         language_server_id: LanguageServerId,
     },
@@ -272,6 +274,8 @@ pub enum Event {
     Reshared,
     Rejoined,
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
 }
@@ -316,6 +320,16 @@ pub struct InlayHint {
     pub resolve_state: ResolveState,
 }
 
+/// A range of a buffer classified by a language server's semantic tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticToken {
+    pub range: Range<language::Anchor>,
+    /// The token type from the server's legend, e.g. `function` or `enumMember`.
+    pub token_type: Arc<str>,
+    /// The token modifiers from the server's legend, e.g. `declaration` or `mutable`.
+    pub modifiers: Vec<Arc<str>>,
+}
+
 /// The user's intent behind a given completion confirmation
 #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 pub enum CompletionIntent {
@@ -393,6 +407,56 @@ pub struct CodeAction {
     pub lsp_action: lsp::CodeAction,
 }
 
+/// A code lens provided by a language server: a command shown above the code it applies to.
+#[derive(Clone, Debug)]
+pub struct CodeLens {
+    /// The id of the language server that produced this code lens.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer this code lens applies to.
+    pub range: Range<Anchor>,
+    /// The raw code lens provided by the language server.
+    pub lsp_lens: lsp::CodeLens,
+}
+
+impl CodeLens {
+    /// The command of this code lens when the language server expects the client to run it,
+    /// rather than running it itself via `workspace/executeCommand`.
+    pub fn client_command(&self) -> Option<ClientCodeLensCommand> {
+        client_code_lens_command(self.lsp_lens.command.as_ref()?)
+    }
+}
+
+/// A code lens command implemented by the client, such as rust-analyzer's "Run" lenses.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClientCodeLensCommand {
+    /// Runs a program, such as a test or a binary, as a task.
+    Run(TaskTemplate),
+    /// Lists the references to the symbol the code lens applies to.
+    ShowReferences,
+}
+
+/// A foldable region of a buffer, as reported by a language server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FoldingRange {
+    /// The range to fold, from the end of its first line to the end of its last line.
+    pub range: Range<Anchor>,
+    /// The kind of the region (e.g. comments or imports), if the server specified one.
+    pub kind: Option<lsp::FoldingRangeKind>,
+}
+
+/// A link in a buffer, as reported by a language server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentLink {
+    /// The id of the language server that reported this link.
+    pub server_id: LanguageServerId,
+    pub range: Range<Anchor>,
+    /// The URI the link points to, such as a web page or a file.
+    /// `None` until the link is resolved, for servers that compute the targets lazily.
+    pub target: Option<String>,
+    /// The raw link reported by the language server, sent back to it to resolve the target.
+    pub lsp_link: lsp::DocumentLink,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResolveState {
     Resolved,
@@ -446,6 +510,49 @@ pub struct LocationLink {
     pub target: Location,
 }
 
+/// A symbol returned by one of the language server's hierarchy requests.
+#[derive(Debug, Clone)]
+pub struct HierarchyItem {
+    pub name: String,
+    pub kind: lsp::SymbolKind,
+    pub detail: Option<String>,
+    /// The range that should be revealed when the item is picked, e.g. the name of a function.
+    pub location: Location,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallHierarchyDirection {
+    Incoming,
+    Outgoing,
+}
+
+#[derive(Debug, Clone)]
+pub struct CallHierarchyCall {
+    pub item: HierarchyItem,
+    /// Where the call happens: inside the caller for incoming calls,
+    /// inside the hierarchy item itself for outgoing calls.
+    pub call_sites: Vec<Location>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CallHierarchy {
+    pub item: HierarchyItem,
+    pub calls: Vec<CallHierarchyCall>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeHierarchyDirection {
+    Supertypes,
+    Subtypes,
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeHierarchy {
+    pub item: HierarchyItem,
+    /// Direct supertypes or subtypes of the item, depending on the requested direction.
+    pub related: Vec<HierarchyItem>,
+}
+
 #[derive(Debug)]
 pub struct DocumentHighlight {
     pub range: Range<language::Anchor>,
@@ -1471,8 +1578,32 @@ impl Project {
                 "No worktree for path {project_path:?}"
             ))));
         };
-        worktree.update(cx, |worktree, cx| {
-            worktree.create_entry(project_path.path, is_directory, cx)
+        let worktree_id = project_path.worktree_id;
+        let path = project_path.path;
+        let lsp_store = self.lsp_store().downgrade();
+        cx.spawn(|_, mut cx| async move {
+            let abs_path = worktree.update(&mut cx, |this, _| this.abs_path().join(&path))?;
+            LspStore::will_create_entry(
+                lsp_store.clone(),
+                worktree_id,
+                &abs_path,
+                is_directory,
+                cx.clone(),
+            )
+            .await;
+
+            let entry = worktree
+                .update(&mut cx, |worktree, cx| {
+                    worktree.create_entry(path, is_directory, cx)
+                })?
+                .await?;
+
+            lsp_store
+                .update(&mut cx, |this, _| {
+                    this.did_create_entry(worktree_id, &abs_path, is_directory);
+                })
+                .ok();
+            Ok(entry)
         })
     }
 
@@ -1545,11 +1676,40 @@ impl Project {
         trash: bool,
         cx: &mut ModelContext<Self>,
     ) -> Option<Task<Result<()>>> {
-        let worktree = self.worktree_for_entry(entry_id, cx)?;
-        cx.emit(Event::DeletedEntry(worktree.read(cx).id(), entry_id));
-        worktree.update(cx, |worktree, cx| {
-            worktree.delete_entry(entry_id, trash, cx)
-        })
+        let (worktree, path, is_dir) = self
+            .worktree_store
+            .read(cx)
+            .worktree_and_entry_for_id(entry_id, cx)
+            .map(|(worktree, entry)| (worktree, entry.path.clone(), entry.is_dir()))?;
+        let worktree_id = worktree.read(cx).id();
+        let abs_path = worktree.read(cx).abs_path().join(&path);
+        cx.emit(Event::DeletedEntry(worktree_id, entry_id));
+
+        let lsp_store = self.lsp_store().downgrade();
+        Some(cx.spawn(|_, mut cx| async move {
+            LspStore::will_delete_entry(
+                lsp_store.clone(),
+                worktree_id,
+                &abs_path,
+                is_dir,
+                cx.clone(),
+            )
+            .await;
+
+            worktree
+                .update(&mut cx, |worktree, cx| {
+                    worktree.delete_entry(entry_id, trash, cx)
+                })?
+                .ok_or_else(|| anyhow!("No entry {entry_id:?} in worktree"))?
+                .await?;
+
+            lsp_store
+                .update(&mut cx, |this, _| {
+                    this.did_delete_entry(worktree_id, &abs_path, is_dir);
+                })
+                .ok();
+            Ok(())
+        }))
     }
 
     pub fn expand_entry(
@@ -2139,6 +2299,7 @@ impl Project {
                         .log_err();
                 }
             }
+            BufferStoreEvent::DiffBaseChanged => cx.emit(Event::GitDiffBaseChanged),
         }
     }
 
@@ -2191,6 +2352,8 @@ impl Project {
                 };
             }
             LspStoreEvent::RefreshInlayHints => cx.emit(Event::RefreshInlayHints),
+            LspStoreEvent::RefreshSemanticTokens => cx.emit(Event::RefreshSemanticTokens),
+            LspStoreEvent::RefreshCodeLens => cx.emit(Event::RefreshCodeLens),
             LspStoreEvent::LanguageServerPrompt(prompt) => {
                 cx.emit(Event::LanguageServerPrompt(prompt.clone()))
             }
@@ -2739,6 +2902,44 @@ impl Project {
         )
     }
 
+    pub fn call_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: T,
+        direction: CallHierarchyDirection,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchy>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetCallHierarchy {
+                position,
+                direction,
+            },
+            cx,
+        )
+    }
+
+    pub fn type_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: T,
+        direction: TypeHierarchyDirection,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchy>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetTypeHierarchy {
+                position,
+                direction,
+            },
+            cx,
+        )
+    }
+
     fn document_highlights_impl(
         &mut self,
         buffer: &Model<Buffer>,
@@ -2925,6 +3126,101 @@ impl Project {
         })
     }
 
+    pub fn code_lens(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        self.request_lsp(
+            buffer_handle.clone(),
+            LanguageServerToQuery::Primary,
+            GetCodeLens,
+            cx,
+        )
+    }
+
+    /// Runs the command of a code lens via `workspace/executeCommand`,
+    /// returning the edits the language server applied while running it.
+    ///
+    /// Commands that are run by the client, see [`CodeLens::client_command`],
+    /// must be handled by the caller instead.
+    pub fn apply_code_lens(
+        &self,
+        buffer_handle: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        if lens.client_command().is_some() {
+            return Task::ready(Err(anyhow!("code lens command must be run by the client")));
+        }
+        let Some(command) = lens.lsp_lens.command else {
+            return Task::ready(Ok(ProjectTransaction::default()));
+        };
+        let action = CodeAction {
+            server_id: lens.server_id,
+            range: lens.range,
+            lsp_action: lsp::CodeAction {
+                title: command.title.clone(),
+                command: Some(command),
+                ..Default::default()
+            },
+        };
+        self.apply_code_action(buffer_handle, action, true, cx)
+    }
+
+    pub fn folding_ranges(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<FoldingRange>>> {
+        self.request_lsp(
+            buffer_handle.clone(),
+            LanguageServerToQuery::Primary,
+            GetFoldingRanges,
+            cx,
+        )
+    }
+
+    /// Returns the language server's selection ranges for each of the given positions,
+    /// ordered from the innermost range to the outermost.
+    pub fn selection_ranges(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        positions: Vec<Anchor>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<Vec<Range<Anchor>>>>> {
+        self.request_lsp(
+            buffer_handle.clone(),
+            LanguageServerToQuery::Primary,
+            GetSelectionRanges { positions },
+            cx,
+        )
+    }
+
+    pub fn document_links(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<DocumentLink>>> {
+        self.request_lsp(
+            buffer_handle.clone(),
+            LanguageServerToQuery::Primary,
+            GetDocumentLinks,
+            cx,
+        )
+    }
+
+    pub fn resolve_document_link(
+        &self,
+        link: DocumentLink,
+        buffer_handle: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<anyhow::Result<DocumentLink>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.resolve_document_link(link, buffer_handle, cx)
+        })
+    }
+
     fn prepare_rename_impl(
         &mut self,
         buffer: Model<Buffer>,
@@ -3018,6 +3314,16 @@ impl Project {
         })
     }
 
+    pub fn semantic_tokens(
+        &mut self,
+        buffer_handle: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<anyhow::Result<Vec<SemanticToken>>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.semantic_tokens(buffer_handle, cx)
+        })
+    }
+
     pub fn search(
         &mut self,
         query: SearchQuery,
@@ -3031,7 +3337,7 @@ impl Project {
             self.find_search_candidate_buffers(&query, MAX_SEARCH_RESULT_FILES + 1, cx)
         };
 
-        cx.spawn(|_, cx| async move {
+        cx.spawn(|this, mut cx| async move {
             let mut range_count = 0;
             let mut buffer_count = 0;
             let mut limit_reached = false;
@@ -3043,6 +3349,16 @@ impl Project {
             // opened buffer, we will spawn a background task that retrieves all the
             // ranges in the buffer matched by the query.
             'outer: while let Some(matching_buffer_chunk) = chunks.next().await {
+                if query.uses_syntax() {
+                    let syntax_tasks = this.update(&mut cx, |this, cx| {
+                        matching_buffer_chunk
+                            .iter()
+                            .map(|buffer| this.wait_for_syntax_tree(buffer, cx))
+                            .collect::<Vec<_>>()
+                    })?;
+                    futures::future::join_all(syntax_tasks).await;
+                }
+
                 let mut chunk_results = Vec::new();
                 for buffer in matching_buffer_chunk {
                     let buffer = buffer.clone();
@@ -3065,6 +3381,11 @@ impl Project {
                 let chunk_results = futures::future::join_all(chunk_results).await;
                 for result in chunk_results {
                     if let Some((buffer, ranges)) = result.log_err() {
+                        // Files can't be ruled out by their text alone when matching syntax,
+                        // so most candidates don't contain any match.
+                        if ranges.is_empty() && query.uses_syntax() {
+                            continue;
+                        }
                         range_count += ranges.len();
                         buffer_count += 1;
                         result_tx
@@ -3091,6 +3412,51 @@ impl Project {
         result_rx
     }
 
+    /// Resolves once the buffer's language is loaded and its syntax tree is up to date,
+    /// which buffers opened for searching may not be yet.
+    fn wait_for_syntax_tree(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<()> {
+        let buffer_ref = buffer.read(cx);
+        let load_language = if buffer_ref.language().is_none() {
+            buffer_ref
+                .file()
+                .and_then(|file| {
+                    self.languages
+                        .language_for_file(file, Some(buffer_ref.as_rope()), cx)
+                })
+                .map(|available_language| self.languages.load_language(&available_language))
+        } else {
+            None
+        };
+
+        let buffer = buffer.clone();
+        cx.spawn(|this, mut cx| async move {
+            if let Some(load_language) = load_language {
+                if let Ok(Ok(language)) = load_language.await {
+                    this.update(&mut cx, |this, cx| {
+                        this.lsp_store.update(cx, |lsp_store, cx| {
+                            lsp_store.set_language_for_buffer(&buffer, language, cx)
+                        })
+                    })
+                    .ok();
+                }
+            }
+
+            let Ok(mut parse_status) = buffer.read_with(&cx, |buffer, _| buffer.parse_status())
+            else {
+                return;
+            };
+            while *parse_status.borrow() != ParseStatus::Idle {
+                if parse_status.changed().await.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
     fn find_search_candidate_buffers(
         &mut self,
         query: &SearchQuery,
@@ -3587,6 +3953,199 @@ impl Project {
             .update_or_create_branch(repository, new_branch, cx)
     }
 
+    pub fn git_stage_statuses(
+        &self,
+        worktree_id: WorktreeId,
+        paths: Option<Vec<Arc<Path>>>,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<(Arc<Path>, git::status::StageStatus)>>> {
+        self.worktree_store()
+            .read(cx)
+            .git_stage_statuses(worktree_id, paths, cx)
+    }
+
+    pub fn stage_git_paths(
+        &self,
+        worktree_id: WorktreeId,
+        paths: Vec<Arc<Path>>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .stage_git_paths(worktree_id, paths, cx)
+    }
+
+    pub fn unstage_git_paths(
+        &self,
+        worktree_id: WorktreeId,
+        paths: Vec<Arc<Path>>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .unstage_git_paths(worktree_id, paths, cx)
+    }
+
+    pub fn discard_git_paths(
+        &self,
+        worktree_id: WorktreeId,
+        paths: Vec<Arc<Path>>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .discard_git_paths(worktree_id, paths, cx)
+    }
+
+    pub fn set_git_index_text(
+        &self,
+        project_path: ProjectPath,
+        text: Option<String>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .set_git_index_text(project_path, text, cx)
+    }
+
+    pub fn unstage_git_rows(
+        &self,
+        project_path: ProjectPath,
+        rows: Vec<Range<u32>>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .unstage_git_rows(project_path, rows, cx)
+    }
+
+    pub fn commit_git_changes(
+        &self,
+        repository: ProjectPath,
+        message: String,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .commit_git_changes(repository, message, cx)
+    }
+
+    pub fn git_file_history(
+        &self,
+        project_path: ProjectPath,
+        rows: Option<Range<u32>>,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<git::history::LogEntry>>> {
+        self.worktree_store()
+            .read(cx)
+            .git_file_history(project_path, rows, cx)
+    }
+
+    pub fn git_file_at_commit(
+        &self,
+        repository: ProjectPath,
+        path: git::repository::RepoPath,
+        sha: git::Oid,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        self.worktree_store()
+            .read(cx)
+            .git_file_at_commit(repository, path, sha, cx)
+    }
+
+    pub fn git_commit_diff(
+        &self,
+        repository: ProjectPath,
+        sha: git::Oid,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<git::history::CommitFileDiff>>> {
+        self.worktree_store()
+            .read(cx)
+            .git_commit_diff(repository, sha, cx)
+    }
+
+    pub fn git_stash_list(
+        &self,
+        repository: ProjectPath,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<git::stash::StashEntry>>> {
+        self.worktree_store()
+            .read(cx)
+            .git_stash_list(repository, cx)
+    }
+
+    pub fn git_stash_push(
+        &self,
+        repository: ProjectPath,
+        message: Option<String>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .git_stash_push(repository, message, cx)
+    }
+
+    pub fn git_stash_action(
+        &self,
+        repository: ProjectPath,
+        sha: git::Oid,
+        action: git::stash::StashAction,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .git_stash_action(repository, sha, action, cx)
+    }
+
+    pub fn git_branch_url(
+        &self,
+        repository: ProjectPath,
+        kind: git::BranchUrlKind,
+        cx: &AppContext,
+    ) -> Task<Result<url::Url>> {
+        self.worktree_store()
+            .read(cx)
+            .git_branch_url(repository, kind, cx)
+    }
+
+    /// The text that the git changes of buffers are computed against.
+    pub fn git_diff_base<'a>(&self, cx: &'a AppContext) -> &'a git::repository::DiffBase {
+        self.buffer_store.read(cx).diff_base()
+    }
+
+    pub fn set_git_diff_base(
+        &mut self,
+        diff_base: git::repository::DiffBase,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        self.buffer_store.update(cx, |buffer_store, cx| {
+            buffer_store.set_diff_base(diff_base, cx)
+        })
+    }
+
+    /// Loads the text of the given buffer's file in the given diff base, such as its text in HEAD.
+    pub fn load_git_base_text(
+        &self,
+        buffer: &Model<Buffer>,
+        diff_base: &git::repository::DiffBase,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        self.buffer_store
+            .read(cx)
+            .load_base_text(buffer, diff_base, cx)
+    }
+
+    pub fn git_changed_paths(
+        &self,
+        worktree_id: WorktreeId,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<Arc<Path>>>> {
+        let diff_base = self.git_diff_base(cx).clone();
+        self.worktree_store()
+            .read(cx)
+            .git_changed_paths(worktree_id, diff_base, cx)
+    }
+
     pub fn blame_buffer(
         &self,
         buffer: &Model<Buffer>,
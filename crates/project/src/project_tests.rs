@@ -1,7 +1,13 @@
-use crate::{Event, *};
+use crate::{search::SyntaxScope, Event, *};
 use fs::FakeFs;
 use futures::{future, StreamExt};
-use git::diff::assert_hunks;
+use git::{
+    diff::assert_hunks,
+    history::{CommitFileDiff, LogEntry},
+    repository::{DiffBase, GitFileStatus},
+    stash::StashAction,
+    status::StageStatus,
+};
 use gpui::{AppContext, SemanticVersion, UpdateGlobal};
 use http_client::Url;
 use language::{
@@ -11,8 +17,9 @@ use language::{
     OffsetRangeExt, Point, ToPoint,
 };
 use lsp::{
-    notification::DidRenameFiles, DiagnosticSeverity, DocumentChanges, FileOperationFilter,
-    NumberOrString, TextDocumentEdit, WillRenameFiles,
+    notification::{DidCreateFiles, DidDeleteFiles, DidRenameFiles},
+    DiagnosticSeverity, DocumentChanges, FileOperationFilter, NumberOrString, TextDocumentEdit,
+    WillCreateFiles, WillDeleteFiles, WillRenameFiles,
 };
 use parking_lot::Mutex;
 use pretty_assertions::{assert_eq, assert_matches};
@@ -22,7 +29,7 @@ use std::os;
 use std::{str::FromStr, sync::OnceLock};
 
 use std::{mem, num::NonZeroU32, ops::Range, task::Poll};
-use task::{ResolvedTask, TaskContext};
+use task::{ResolvedTask, TaskContext, TaskTemplate};
 use unindent::Unindent as _;
 use util::{assert_set_eq, paths::PathMatcher, test::temp_tree, TryFutureExt as _};
 
@@ -1207,6 +1214,189 @@ async fn test_omitted_diagnostics(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_pull_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "let a = b;",
+            "b.rs": "let c = d;",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let document_result_ids = Arc::new(Mutex::new(Vec::new()));
+    let workspace_result_ids = Arc::new(Mutex::new(Vec::new()));
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        workspace_diagnostics: true,
+                        ..Default::default()
+                    },
+                )),
+                ..Default::default()
+            },
+            initializer: Some(Box::new({
+                let document_result_ids = document_result_ids.clone();
+                let workspace_result_ids = workspace_result_ids.clone();
+                move |fake_server| {
+                    let document_result_ids = document_result_ids.clone();
+                    fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(
+                        move |params, _| {
+                            assert_eq!(
+                                params.text_document.uri,
+                                lsp::Url::from_file_path("/dir/a.rs").unwrap()
+                            );
+                            document_result_ids
+                                .lock()
+                                .push(params.previous_result_id.clone());
+                            async move {
+                                let report = if params.previous_result_id.is_some() {
+                                    lsp::DocumentDiagnosticReport::Unchanged(
+                                        lsp::RelatedUnchangedDocumentDiagnosticReport {
+                                            related_documents: None,
+                                            unchanged_document_diagnostic_report:
+                                                lsp::UnchangedDocumentDiagnosticReport {
+                                                    result_id: "a1".into(),
+                                                },
+                                        },
+                                    )
+                                } else {
+                                    lsp::DocumentDiagnosticReport::Full(
+                                        lsp::RelatedFullDocumentDiagnosticReport {
+                                            related_documents: None,
+                                            full_document_diagnostic_report:
+                                                lsp::FullDocumentDiagnosticReport {
+                                                    result_id: Some("a1".into()),
+                                                    items: vec![lsp::Diagnostic {
+                                                        range: lsp::Range::new(
+                                                            lsp::Position::new(0, 8),
+                                                            lsp::Position::new(0, 9),
+                                                        ),
+                                                        severity: Some(
+                                                            lsp::DiagnosticSeverity::ERROR,
+                                                        ),
+                                                        message: "undefined variable 'b'".into(),
+                                                        ..Default::default()
+                                                    }],
+                                                },
+                                        },
+                                    )
+                                };
+                                Ok(lsp::DocumentDiagnosticReportResult::Report(report))
+                            }
+                        },
+                    );
+
+                    let workspace_result_ids = workspace_result_ids.clone();
+                    fake_server.handle_request::<lsp::request::WorkspaceDiagnosticRequest, _, _>(
+                        move |params, _| {
+                            let mut previous_result_ids = params
+                                .previous_result_ids
+                                .into_iter()
+                                .map(|result_id| {
+                                    (result_id.uri.path().to_string(), result_id.value)
+                                })
+                                .collect::<Vec<_>>();
+                            previous_result_ids.sort();
+                            workspace_result_ids.lock().push(previous_result_ids);
+                            async move {
+                                Ok(lsp::WorkspaceDiagnosticReportResult::Report(
+                                    lsp::WorkspaceDiagnosticReport {
+                                        items: vec![lsp::WorkspaceDocumentDiagnosticReport::Full(
+                                            lsp::WorkspaceFullDocumentDiagnosticReport {
+                                                uri: lsp::Url::from_file_path("/dir/b.rs").unwrap(),
+                                                version: None,
+                                                full_document_diagnostic_report:
+                                                    lsp::FullDocumentDiagnosticReport {
+                                                        result_id: Some("b1".into()),
+                                                        items: vec![lsp::Diagnostic {
+                                                            range: lsp::Range::new(
+                                                                lsp::Position::new(0, 8),
+                                                                lsp::Position::new(0, 9),
+                                                            ),
+                                                            severity: Some(
+                                                                lsp::DiagnosticSeverity::ERROR,
+                                                            ),
+                                                            message: "undefined variable 'd'"
+                                                                .into(),
+                                                            ..Default::default()
+                                                        }],
+                                                    },
+                                            },
+                                        )],
+                                    },
+                                ))
+                            }
+                        },
+                    );
+                }
+            })),
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    buffer.update(cx, |buffer, _| {
+        let chunks = chunks_with_diagnostics(buffer, 0..buffer.len());
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|(s, d)| (s.as_str(), *d))
+                .collect::<Vec<_>>(),
+            &[
+                ("let a = ", None),
+                ("b", Some(DiagnosticSeverity::ERROR)),
+                (";", None),
+            ],
+        );
+    });
+    project.update(cx, |project, cx| {
+        assert_eq!(project.diagnostic_summary(false, cx).error_count, 2);
+    });
+    assert_eq!(*document_result_ids.lock(), vec![None]);
+
+    // When the server asks for a refresh, the previous result ids are sent back to it.
+    fake_server
+        .request::<lsp::request::WorkspaceDiagnosticRefresh>(())
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    assert_eq!(
+        *document_result_ids.lock(),
+        vec![None, Some("a1".to_string())]
+    );
+    assert_eq!(
+        workspace_result_ids.lock().last().unwrap(),
+        &vec![
+            ("/dir/a.rs".to_string(), "a1".to_string()),
+            ("/dir/b.rs".to_string(), "b1".to_string()),
+        ]
+    );
+    project.update(cx, |project, cx| {
+        assert_eq!(project.diagnostic_summary(false, cx).error_count, 2);
+    });
+}
+
 #[gpui::test]
 async fn test_disk_based_diagnostics_progress(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2633,14 +2823,15 @@ async fn test_definition(cx: &mut gpui::TestAppContext) {
 }
 
 #[gpui::test]
-async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
+async fn test_call_hierarchy(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "a.ts": "",
+            "a.rs": "fn a() { b() }",
+            "b.rs": "fn b() {}",
         }),
     )
     .await;
@@ -2648,15 +2839,12 @@ async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
 
     let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-    language_registry.add(typescript_lang());
-    let mut fake_language_servers = language_registry.register_fake_lsp(
-        "TypeScript",
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
         FakeLspAdapter {
             capabilities: lsp::ServerCapabilities {
-                completion_provider: Some(lsp::CompletionOptions {
-                    trigger_characters: Some(vec![":".to_string()]),
-                    ..Default::default()
-                }),
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
                 ..Default::default()
             },
             ..Default::default()
@@ -2664,75 +2852,101 @@ async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
     );
 
     let (buffer, _handle) = project
-        .update(cx, |p, cx| p.open_local_buffer_with_lsp("/dir/a.ts", cx))
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/b.rs", cx)
+        })
         .await
         .unwrap();
 
-    let fake_server = fake_language_servers.next().await.unwrap();
-
-    let text = "let a = b.fqn";
-    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
-    let completions = project.update(cx, |project, cx| {
-        project.completions(&buffer, text.len(), DEFAULT_COMPLETION_CONTEXT, cx)
+    let fake_server = fake_servers.next().await.unwrap();
+    let item = |name: &str, path: &str, start: u32, end: u32| lsp::CallHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path(path).unwrap(),
+        range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, end)),
+        selection_range: lsp::Range::new(lsp::Position::new(0, start), lsp::Position::new(0, 4)),
+        data: None,
+    };
+    let b_item = item("b", "/dir/b.rs", 3, 9);
+    let a_item = item("a", "/dir/a.rs", 3, 14);
+    fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>({
+        let b_item = b_item.clone();
+        move |params, _| {
+            let b_item = b_item.clone();
+            async move {
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(0, 3)
+                );
+                Ok(Some(vec![b_item]))
+            }
+        }
     });
+    fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        move |params, _| {
+            let a_item = a_item.clone();
+            async move {
+                assert_eq!(params.item.name, "b");
+                Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                    from: a_item,
+                    from_ranges: vec![lsp::Range::new(
+                        lsp::Position::new(0, 9),
+                        lsp::Position::new(0, 10),
+                    )],
+                }]))
+            }
+        },
+    );
 
-    fake_server
-        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
-            Ok(Some(lsp::CompletionResponse::Array(vec![
-                lsp::CompletionItem {
-                    label: "fullyQualifiedName?".into(),
-                    insert_text: Some("fullyQualifiedName".into()),
-                    ..Default::default()
-                },
-            ])))
+    let hierarchies = project
+        .update(cx, |project, cx| {
+            project.call_hierarchy(&buffer, 3, CallHierarchyDirection::Incoming, cx)
         })
-        .next()
-        .await;
-    let completions = completions.await.unwrap();
-    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
-    assert_eq!(completions.len(), 1);
-    assert_eq!(completions[0].new_text, "fullyQualifiedName");
-    assert_eq!(
-        completions[0].old_range.to_offset(&snapshot),
-        text.len() - 3..text.len()
-    );
+        .await
+        .unwrap();
 
-    let text = "let a = \"atoms/cmp\"";
-    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
-    let completions = project.update(cx, |project, cx| {
-        project.completions(&buffer, text.len() - 1, DEFAULT_COMPLETION_CONTEXT, cx)
+    assert_eq!(hierarchies.len(), 1);
+    let hierarchy = &hierarchies[0];
+    cx.update(|cx| {
+        assert_eq!(hierarchy.item.name, "b");
+        assert_eq!(
+            hierarchy
+                .item
+                .location
+                .range
+                .to_offset(hierarchy.item.location.buffer.read(cx)),
+            3..4
+        );
+        assert_eq!(hierarchy.calls.len(), 1);
+        let call = &hierarchy.calls[0];
+        assert_eq!(call.item.name, "a");
+        let caller_buffer = call.item.location.buffer.read(cx);
+        assert_eq!(
+            caller_buffer.file().unwrap().path().as_ref(),
+            Path::new("a.rs")
+        );
+        assert_eq!(
+            call.call_sites
+                .iter()
+                .map(|site| site.range.to_offset(site.buffer.read(cx)))
+                .collect::<Vec<_>>(),
+            vec![9..10]
+        );
     });
-
-    fake_server
-        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
-            Ok(Some(lsp::CompletionResponse::Array(vec![
-                lsp::CompletionItem {
-                    label: "component".into(),
-                    ..Default::default()
-                },
-            ])))
-        })
-        .next()
-        .await;
-    let completions = completions.await.unwrap();
-    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
-    assert_eq!(completions.len(), 1);
-    assert_eq!(completions[0].new_text, "component");
-    assert_eq!(
-        completions[0].old_range.to_offset(&snapshot),
-        text.len() - 4..text.len() - 1
-    );
 }
 
 #[gpui::test]
-async fn test_completions_with_carriage_returns(cx: &mut gpui::TestAppContext) {
+async fn test_type_hierarchy(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "a.ts": "",
+            "a.rs": "trait A {}",
+            "b.rs": "trait B: A {}",
         }),
     )
     .await;
@@ -2740,60 +2954,142 @@ async fn test_completions_with_carriage_returns(cx: &mut gpui::TestAppContext) {
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
 
     let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-    language_registry.add(typescript_lang());
-    let mut fake_language_servers = language_registry.register_fake_lsp(
-        "TypeScript",
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
         FakeLspAdapter {
             capabilities: lsp::ServerCapabilities {
-                completion_provider: Some(lsp::CompletionOptions {
-                    trigger_characters: Some(vec![":".to_string()]),
-                    ..Default::default()
-                }),
+                type_hierarchy_provider: Some(lsp::OneOf::Left(true)),
                 ..Default::default()
             },
             ..Default::default()
         },
     );
 
-    let (buffer, _handle) = project
-        .update(cx, |p, cx| p.open_local_buffer_with_lsp("/dir/a.ts", cx))
+    let (a_buffer, _a_handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+    let (b_buffer, _b_handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/b.rs", cx)
+        })
         .await
         .unwrap();
 
-    let fake_server = fake_language_servers.next().await.unwrap();
-
-    let text = "let a = b.fqn";
-    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
-    let completions = project.update(cx, |project, cx| {
-        project.completions(&buffer, text.len(), DEFAULT_COMPLETION_CONTEXT, cx)
+    let fake_server = fake_servers.next().await.unwrap();
+    let item = |name: &str, path: &str, end: u32| lsp::TypeHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::INTERFACE,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path(path).unwrap(),
+        range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, end)),
+        selection_range: lsp::Range::new(lsp::Position::new(0, 6), lsp::Position::new(0, 7)),
+        data: None,
+    };
+    let a_item = item("A", "/dir/a.rs", 10);
+    let b_item = item("B", "/dir/b.rs", 13);
+    fake_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>({
+        let a_item = a_item.clone();
+        let b_item = b_item.clone();
+        move |params, _| {
+            let a_item = a_item.clone();
+            let b_item = b_item.clone();
+            async move {
+                let uri = params.text_document_position_params.text_document.uri;
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(0, 6)
+                );
+                if uri == a_item.uri {
+                    Ok(Some(vec![a_item]))
+                } else {
+                    Ok(Some(vec![b_item]))
+                }
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>({
+        let a_item = a_item.clone();
+        move |params, _| {
+            let a_item = a_item.clone();
+            async move {
+                assert_eq!(params.item.name, "B");
+                Ok(Some(vec![a_item]))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>(move |params, _| {
+        let b_item = b_item.clone();
+        async move {
+            assert_eq!(params.item.name, "A");
+            Ok(Some(vec![b_item]))
+        }
     });
 
-    fake_server
-        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
-            Ok(Some(lsp::CompletionResponse::Array(vec![
-                lsp::CompletionItem {
-                    label: "fullyQualifiedName?".into(),
-                    insert_text: Some("fully\rQualified\r\nName".into()),
-                    ..Default::default()
-                },
-            ])))
+    let supertypes = project
+        .update(cx, |project, cx| {
+            project.type_hierarchy(&b_buffer, 6, TypeHierarchyDirection::Supertypes, cx)
         })
-        .next()
-        .await;
-    let completions = completions.await.unwrap();
-    assert_eq!(completions.len(), 1);
-    assert_eq!(completions[0].new_text, "fully\nQualified\nName");
+        .await
+        .unwrap();
+    let subtypes = project
+        .update(cx, |project, cx| {
+            project.type_hierarchy(&a_buffer, 6, TypeHierarchyDirection::Subtypes, cx)
+        })
+        .await
+        .unwrap();
+
+    cx.update(|cx| {
+        let summarize = |hierarchies: &[TypeHierarchy]| {
+            hierarchies
+                .iter()
+                .map(|hierarchy| {
+                    let related = hierarchy
+                        .related
+                        .iter()
+                        .map(|item| {
+                            let buffer = item.location.buffer.read(cx);
+                            (
+                                item.name.clone(),
+                                buffer.file().unwrap().path().to_path_buf(),
+                                item.location.range.to_offset(buffer),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    (hierarchy.item.name.clone(), related)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            summarize(&supertypes),
+            [(
+                "B".to_string(),
+                vec![("A".to_string(), PathBuf::from("a.rs"), 6..7)]
+            )]
+        );
+        assert_eq!(
+            summarize(&subtypes),
+            [(
+                "A".to_string(),
+                vec![("B".to_string(), PathBuf::from("b.rs"), 6..7)]
+            )]
+        );
+    });
 }
 
-#[gpui::test(iterations = 10)]
-async fn test_apply_code_actions_with_commands(cx: &mut gpui::TestAppContext) {
+#[gpui::test]
+async fn test_code_lens(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "a.ts": "a",
+            "a.rs": "fn a() {}\n\n#[test]\nfn b() {}",
         }),
     )
     .await;
@@ -2801,19 +3097,578 @@ async fn test_apply_code_actions_with_commands(cx: &mut gpui::TestAppContext) {
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
 
     let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-    language_registry.add(typescript_lang());
-    let mut fake_language_servers = language_registry.register_fake_lsp(
-        "TypeScript",
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
         FakeLspAdapter {
             capabilities: lsp::ServerCapabilities {
-                code_action_provider: Some(lsp::CodeActionProviderCapability::Options(
-                    lsp::CodeActionOptions {
-                        resolve_provider: Some(true),
-                        ..lsp::CodeActionOptions::default()
-                    },
-                )),
-                ..lsp::ServerCapabilities::default()
-            },
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                execute_command_provider: Some(lsp::ExecuteCommandOptions {
+                    commands: vec!["references".into()],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _| async move {
+        Ok(Some(vec![
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4)),
+                command: Some(lsp::Command {
+                    title: "1 reference".into(),
+                    command: "references".into(),
+                    arguments: None,
+                }),
+                data: None,
+            },
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(3, 3), lsp::Position::new(3, 4)),
+                command: None,
+                data: Some(json!("run")),
+            },
+            // Neither the server nor the client can run this command.
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(3, 3), lsp::Position::new(3, 4)),
+                command: Some(lsp::Command {
+                    title: "Debug".into(),
+                    command: "debug".into(),
+                    arguments: None,
+                }),
+                data: None,
+            },
+        ]))
+    });
+    fake_server.handle_request::<lsp::request::CodeLensResolve, _, _>(|lens, _| async move {
+        assert_eq!(lens.data, Some(json!("run")));
+        Ok(lsp::CodeLens {
+            command: Some(lsp::Command {
+                title: "Run test".into(),
+                command: "rust-analyzer.runSingle".into(),
+                arguments: Some(vec![json!({
+                    "label": "test b",
+                    "kind": "cargo",
+                    "args": {
+                        "workspaceRoot": "/dir",
+                        "cargoArgs": ["test", "--lib"],
+                        "executableArgs": ["b", "--exact"],
+                    },
+                })]),
+            }),
+            ..lens
+        })
+    });
+
+    let code_lens = project
+        .update(cx, |project, cx| project.code_lens(&buffer, cx))
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            code_lens
+                .iter()
+                .map(|lens| (
+                    lens.range.to_offset(buffer),
+                    lens.lsp_lens.command.as_ref().unwrap().title.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            vec![(3..4, "1 reference"), (22..23, "Run test")]
+        );
+    });
+    assert_eq!(code_lens[0].client_command(), None);
+    assert_eq!(
+        code_lens[1].client_command(),
+        Some(ClientCodeLensCommand::Run(TaskTemplate {
+            label: "test b".into(),
+            command: "cargo".into(),
+            args: vec![
+                "test".into(),
+                "--lib".into(),
+                "--".into(),
+                "b".into(),
+                "--exact".into()
+            ],
+            cwd: Some("/dir".into()),
+            ..TaskTemplate::default()
+        }))
+    );
+}
+
+#[gpui::test]
+async fn test_folding_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "use a;\nuse b;\n\n// one\n// two\nfn c() {\n    d();\n}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::FoldingRangeRequest, _, _>(|_, _| async move {
+        Ok(Some(vec![
+            lsp::FoldingRange {
+                start_line: 5,
+                start_character: Some(8),
+                end_line: 6,
+                ..Default::default()
+            },
+            lsp::FoldingRange {
+                start_line: 0,
+                end_line: 1,
+                kind: Some(lsp::FoldingRangeKind::Imports),
+                ..Default::default()
+            },
+            lsp::FoldingRange {
+                start_line: 3,
+                end_line: 4,
+                kind: Some(lsp::FoldingRangeKind::Comment),
+                ..Default::default()
+            },
+            // Single-line ranges can't be folded.
+            lsp::FoldingRange {
+                start_line: 7,
+                end_line: 7,
+                ..Default::default()
+            },
+        ]))
+    });
+
+    let folding_ranges = project
+        .update(cx, |project, cx| project.folding_ranges(&buffer, cx))
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            folding_ranges
+                .iter()
+                .map(|range| (range.range.to_offset(buffer), range.kind.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (6..13, Some(lsp::FoldingRangeKind::Imports)),
+                (21..28, Some(lsp::FoldingRangeKind::Comment)),
+                (37..46, None),
+            ]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_selection_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn a() { b(c) }",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                selection_range_provider: Some(lsp::SelectionRangeProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::SelectionRangeRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(params.positions, vec![lsp::Position::new(0, 11)]);
+            let range = |start, end| {
+                lsp::Range::new(lsp::Position::new(0, start), lsp::Position::new(0, end))
+            };
+            Ok(Some(vec![lsp::SelectionRange {
+                range: range(11, 12),
+                parent: Some(Box::new(lsp::SelectionRange {
+                    range: range(9, 13),
+                    parent: Some(Box::new(lsp::SelectionRange {
+                        range: range(0, 15),
+                        parent: None,
+                    })),
+                })),
+            }]))
+        },
+    );
+
+    let position = buffer.read_with(cx, |buffer, _| buffer.anchor_after(11));
+    let selection_ranges = project
+        .update(cx, |project, cx| {
+            project.selection_ranges(&buffer, vec![position], cx)
+        })
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            selection_ranges
+                .iter()
+                .map(|ranges| ranges
+                    .iter()
+                    .map(|range| range.to_offset(buffer))
+                    .collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            vec![vec![11..12, 9..13, 0..15]]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_semantic_tokens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn a() {}\nfn b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        lsp::SemanticTokensOptions {
+                            legend: lsp::SemanticTokensLegend {
+                                token_types: vec![lsp::SemanticTokenType::FUNCTION],
+                                token_modifiers: vec![lsp::SemanticTokenModifier::DECLARATION],
+                            },
+                            full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let token = |delta_line, delta_start, token_modifiers_bitset| lsp::SemanticToken {
+        delta_line,
+        delta_start,
+        length: 1,
+        token_type: 0,
+        token_modifiers_bitset,
+    };
+    fake_server.handle_request::<lsp::request::SemanticTokensFullRequest, _, _>(
+        move |_, _| async move {
+            Ok(Some(lsp::SemanticTokensResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: Some("1".into()),
+                    data: vec![token(0, 3, 1), token(1, 3, 0)],
+                },
+            )))
+        },
+    );
+    fake_server.handle_request::<lsp::request::SemanticTokensFullDeltaRequest, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.previous_result_id, "1");
+            Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+                lsp::SemanticTokensDelta {
+                    result_id: Some("2".into()),
+                    edits: vec![lsp::SemanticTokensEdit {
+                        start: 5,
+                        delete_count: 5,
+                        data: Some(vec![token(1, 3, 1)]),
+                    }],
+                },
+            )))
+        },
+    );
+
+    let tokens = project
+        .update(cx, |project, cx| {
+            project.semantic_tokens(buffer.clone(), cx)
+        })
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|token| (
+                    token.range.to_offset(buffer),
+                    token.token_type.to_string(),
+                    token.modifiers.len()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (3..4, "function".to_string(), 1),
+                (13..14, "function".to_string(), 0)
+            ]
+        );
+    });
+
+    // The second request only receives the edits since the previous response.
+    let tokens = project
+        .update(cx, |project, cx| {
+            project.semantic_tokens(buffer.clone(), cx)
+        })
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|token| (token.range.to_offset(buffer), token.modifiers.len()))
+                .collect::<Vec<_>>(),
+            vec![(3..4, 1), (13..14, 1)]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                completion_provider: Some(lsp::CompletionOptions {
+                    trigger_characters: Some(vec![":".to_string()]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |p, cx| p.open_local_buffer_with_lsp("/dir/a.ts", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    let text = "let a = b.fqn";
+    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+    let completions = project.update(cx, |project, cx| {
+        project.completions(&buffer, text.len(), DEFAULT_COMPLETION_CONTEXT, cx)
+    });
+
+    fake_server
+        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
+            Ok(Some(lsp::CompletionResponse::Array(vec![
+                lsp::CompletionItem {
+                    label: "fullyQualifiedName?".into(),
+                    insert_text: Some("fullyQualifiedName".into()),
+                    ..Default::default()
+                },
+            ])))
+        })
+        .next()
+        .await;
+    let completions = completions.await.unwrap();
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0].new_text, "fullyQualifiedName");
+    assert_eq!(
+        completions[0].old_range.to_offset(&snapshot),
+        text.len() - 3..text.len()
+    );
+
+    let text = "let a = \"atoms/cmp\"";
+    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+    let completions = project.update(cx, |project, cx| {
+        project.completions(&buffer, text.len() - 1, DEFAULT_COMPLETION_CONTEXT, cx)
+    });
+
+    fake_server
+        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
+            Ok(Some(lsp::CompletionResponse::Array(vec![
+                lsp::CompletionItem {
+                    label: "component".into(),
+                    ..Default::default()
+                },
+            ])))
+        })
+        .next()
+        .await;
+    let completions = completions.await.unwrap();
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0].new_text, "component");
+    assert_eq!(
+        completions[0].old_range.to_offset(&snapshot),
+        text.len() - 4..text.len() - 1
+    );
+}
+
+#[gpui::test]
+async fn test_completions_with_carriage_returns(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                completion_provider: Some(lsp::CompletionOptions {
+                    trigger_characters: Some(vec![":".to_string()]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |p, cx| p.open_local_buffer_with_lsp("/dir/a.ts", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    let text = "let a = b.fqn";
+    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+    let completions = project.update(cx, |project, cx| {
+        project.completions(&buffer, text.len(), DEFAULT_COMPLETION_CONTEXT, cx)
+    });
+
+    fake_server
+        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
+            Ok(Some(lsp::CompletionResponse::Array(vec![
+                lsp::CompletionItem {
+                    label: "fullyQualifiedName?".into(),
+                    insert_text: Some("fully\rQualified\r\nName".into()),
+                    ..Default::default()
+                },
+            ])))
+        })
+        .next()
+        .await;
+    let completions = completions.await.unwrap();
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0].new_text, "fully\nQualified\nName");
+}
+
+#[gpui::test(iterations = 10)]
+async fn test_apply_code_actions_with_commands(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "a",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_action_provider: Some(lsp::CodeActionProviderCapability::Options(
+                    lsp::CodeActionOptions {
+                        resolve_provider: Some(true),
+                        ..lsp::CodeActionOptions::default()
+                    },
+                )),
+                ..lsp::ServerCapabilities::default()
+            },
             ..FakeLspAdapter::default()
         },
     );
@@ -4078,6 +4933,131 @@ async fn test_lsp_rename_notifications(cx: &mut gpui::TestAppContext) {
     assert_eq!(resolved_workspace_edit.get(), Some(&expected_edit));
 }
 
+#[gpui::test]
+async fn test_lsp_create_and_delete_notifications(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let watched_paths = lsp::FileOperationRegistrationOptions {
+        filters: vec![FileOperationFilter {
+            scheme: Some("file".to_owned()),
+            pattern: lsp::FileOperationPattern {
+                glob: "**/*.rs".to_owned(),
+                matches: Some(lsp::FileOperationPatternKind::File),
+                options: None,
+            },
+        }],
+    };
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                workspace: Some(lsp::WorkspaceServerCapabilities {
+                    workspace_folders: None,
+                    file_operations: Some(lsp::WorkspaceFileOperationsServerCapabilities {
+                        did_create: Some(watched_paths.clone()),
+                        will_create: Some(watched_paths.clone()),
+                        did_delete: Some(watched_paths.clone()),
+                        will_delete: Some(watched_paths),
+                        ..Default::default()
+                    }),
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/one.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let response = project.update(cx, |project, cx| {
+        let worktree_id = project.worktrees(cx).next().unwrap().read(cx).id();
+        project.create_entry((worktree_id, "two.rs"), false, cx)
+    });
+    fake_server
+        .handle_request::<WillCreateFiles, _, _>(|params, _| async move {
+            assert_eq!(params.files.len(), 1);
+            assert_eq!(params.files[0].uri, "file:///dir/two.rs");
+            Ok(Some(lsp::WorkspaceEdit {
+                changes: Some(
+                    [(
+                        Url::from_str("file:///dir/one.rs").unwrap(),
+                        vec![lsp::TextEdit::new(
+                            lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 0)),
+                            "mod two;\n".to_owned(),
+                        )],
+                    )]
+                    .into_iter()
+                    .collect(),
+                ),
+                ..Default::default()
+            }))
+        })
+        .next()
+        .await
+        .unwrap();
+    response.await.unwrap();
+    fake_server
+        .handle_notification::<DidCreateFiles, _>(|params, _| {
+            assert_eq!(params.files.len(), 1);
+            assert_eq!(params.files[0].uri, "file:///dir/two.rs");
+        })
+        .next()
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "mod two;\nconst ONE: usize = 1;");
+    });
+
+    let response = project
+        .update(cx, |project, cx| {
+            let worktree = project.worktrees(cx).next().unwrap();
+            let entry = worktree.read(cx).entry_for_path("two.rs").unwrap();
+            project.delete_entry(entry.id, false, cx)
+        })
+        .unwrap();
+    fake_server
+        .handle_request::<WillDeleteFiles, _, _>(|params, _| async move {
+            assert_eq!(params.files.len(), 1);
+            assert_eq!(params.files[0].uri, "file:///dir/two.rs");
+            Ok(None)
+        })
+        .next()
+        .await
+        .unwrap();
+    response.await.unwrap();
+    fake_server
+        .handle_notification::<DidDeleteFiles, _>(|params, _| {
+            assert_eq!(params.files.len(), 1);
+            assert_eq!(params.files[0].uri, "file:///dir/two.rs");
+        })
+        .next()
+        .await
+        .unwrap();
+    assert!(fs
+        .metadata(Path::new("/dir/two.rs"))
+        .await
+        .unwrap()
+        .is_none());
+}
+
 #[gpui::test]
 async fn test_rename(cx: &mut gpui::TestAppContext) {
     // hi
@@ -4275,14 +5255,163 @@ async fn test_search(cx: &mut gpui::TestAppContext) {
                 None,
             )
             .unwrap(),
-            cx
-        )
-        .await
-        .unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("dir/two.rs".to_string(), vec![6..9]),
+            ("dir/three.rs".to_string(), vec![37..40]),
+            ("dir/four.rs".to_string(), vec![25..28, 36..39])
+        ])
+    );
+}
+
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "main.rs": "fn main() {\n    let a = foo().unwrap();\n    let b = bar(1).unwrap();\n    baz.unwrap_or(2);\n}\n",
+            "lib.rs": "// Never call unwrap() here.\nfn lib() {}\n",
+            "notes.txt": "foo().unwrap()",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    let query = SearchQuery::structural(
+        "$A.unwrap()",
+        false,
+        Default::default(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([("dir/main.rs".to_string(), vec![24..38, 52..67])])
+    );
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    let query = query.with_replacement("$A.expect(\"$A failed\")".to_string());
+    assert_eq!(
+        query.replacement_for_range(&snapshot, 52..67).as_deref(),
+        Some("bar(1).expect(\"bar(1) failed\")")
+    );
+    assert_eq!(
+        query.replacement_for_range(&snapshot, 24..38).as_deref(),
+        Some("foo().expect(\"foo() failed\")")
+    );
+
+    // Edits that keep the offsets of a match must not reuse the text it captured before.
+    buffer.update(cx, |buffer, cx| buffer.edit([(24..27, "qux")], None, cx));
+    cx.executor().run_until_parked();
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    assert_eq!(
+        query.replacement_for_range(&snapshot, 24..38).as_deref(),
+        Some("qux().expect(\"qux() failed\")")
+    );
+
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::structural(
+                r#"(call_expression function: (identifier) @function (#eq? @function "bar")) @match"#,
+                false,
+                Default::default(),
+                Default::default(),
+                None,
+            )
+            .unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([("dir/main.rs".to_string(), vec![52..58])])
+    );
+}
+
+#[gpui::test]
+async fn test_search_in_syntax_scope(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "main.rs": "// TODO: fix\nfn todo() { let s = \"TODO\"; }\n",
+            "notes.txt": "todo",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(Arc::new(
+        Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_highlights_query(
+            r#"
+            (line_comment) @comment
+            (string_literal) @string
+            "#,
+        )
+        .unwrap(),
+    ));
+
+    let query = |text: &str, case_sensitive: bool, syntax_scope: SyntaxScope| {
+        SearchQuery::text(
+            text,
+            false,
+            case_sensitive,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .unwrap()
+        .with_syntax_scope(syntax_scope)
+    };
+
+    assert_eq!(
+        search(&project, query("TODO", true, SyntaxScope::Comments), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([("dir/main.rs".to_string(), vec![3..7])])
+    );
+    assert_eq!(
+        search(&project, query("TODO", true, SyntaxScope::Strings), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([("dir/main.rs".to_string(), vec![34..38])])
+    );
+    assert_eq!(
+        search(&project, query("todo", false, SyntaxScope::Code), cx)
+            .await
+            .unwrap(),
         HashMap::from_iter([
-            ("dir/two.rs".to_string(), vec![6..9]),
-            ("dir/three.rs".to_string(), vec![37..40]),
-            ("dir/four.rs".to_string(), vec![25..28, 36..39])
+            ("dir/main.rs".to_string(), vec![16..20]),
+            ("dir/notes.txt".to_string(), vec![0..4]),
         ])
     );
 }
@@ -5484,172 +6613,674 @@ async fn test_reordering_worktrees(cx: &mut gpui::TestAppContext) {
         assert_eq!(third.abs_path().to_str().unwrap(), "/dir/b.rs");
     });
 
-    // move the third worktree to before the second
-    // [a, c, b] -> [a, b, c]
+    // move the third worktree to before the second
+    // [a, c, b] -> [a, b, c]
+    project
+        .update(cx, |project, cx| {
+            let third = worktree_c.read(cx);
+            let second = worktree_b.read(cx);
+            project.move_worktree(third.id(), second.id(), cx)
+        })
+        .expect("moving third before second");
+
+    // check the state after moving
+    project.update(cx, |project, cx| {
+        let worktrees = project.visible_worktrees(cx).collect::<Vec<_>>();
+        assert_eq!(worktrees.len(), 3);
+
+        let first = worktrees[0].read(cx);
+        let second = worktrees[1].read(cx);
+        let third = worktrees[2].read(cx);
+
+        // check they are now in the right order
+        assert_eq!(first.abs_path().to_str().unwrap(), "/dir/a.rs");
+        assert_eq!(second.abs_path().to_str().unwrap(), "/dir/b.rs");
+        assert_eq!(third.abs_path().to_str().unwrap(), "/dir/c.rs");
+    });
+
+    // move the first worktree to after the third
+    // [a, b, c] -> [b, c, a]
+    project
+        .update(cx, |project, cx| {
+            let first = worktree_a.read(cx);
+            let third = worktree_c.read(cx);
+            project.move_worktree(first.id(), third.id(), cx)
+        })
+        .expect("moving first after third");
+
+    // check the state after moving
+    project.update(cx, |project, cx| {
+        let worktrees = project.visible_worktrees(cx).collect::<Vec<_>>();
+        assert_eq!(worktrees.len(), 3);
+
+        let first = worktrees[0].read(cx);
+        let second = worktrees[1].read(cx);
+        let third = worktrees[2].read(cx);
+
+        // check they are now in the right order
+        assert_eq!(first.abs_path().to_str().unwrap(), "/dir/b.rs");
+        assert_eq!(second.abs_path().to_str().unwrap(), "/dir/c.rs");
+        assert_eq!(third.abs_path().to_str().unwrap(), "/dir/a.rs");
+    });
+
+    // move the third worktree to before the first
+    // [b, c, a] -> [a, b, c]
+    project
+        .update(cx, |project, cx| {
+            let third = worktree_a.read(cx);
+            let first = worktree_b.read(cx);
+            project.move_worktree(third.id(), first.id(), cx)
+        })
+        .expect("moving third before first");
+
+    // check the state after moving
+    project.update(cx, |project, cx| {
+        let worktrees = project.visible_worktrees(cx).collect::<Vec<_>>();
+        assert_eq!(worktrees.len(), 3);
+
+        let first = worktrees[0].read(cx);
+        let second = worktrees[1].read(cx);
+        let third = worktrees[2].read(cx);
+
+        // check they are now in the right order
+        assert_eq!(first.abs_path().to_str().unwrap(), "/dir/a.rs");
+        assert_eq!(second.abs_path().to_str().unwrap(), "/dir/b.rs");
+        assert_eq!(third.abs_path().to_str().unwrap(), "/dir/c.rs");
+    });
+}
+
+#[gpui::test]
+async fn test_unstaged_changes_for_buffer(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let staged_contents = r#"
+        fn main() {
+            println!("hello world");
+        }
+    "#
+    .unindent();
+    let file_contents = r#"
+        // print goodbye
+        fn main() {
+            println!("goodbye world");
+        }
+    "#
+    .unindent();
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+           "src": {
+               "main.rs": file_contents,
+           }
+        }),
+    )
+    .await;
+
+    fs.set_index_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("src/main.rs"), staged_contents)],
+    );
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/src/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    let unstaged_changes = project
+        .update(cx, |project, cx| {
+            project.open_unstaged_changes(buffer.clone(), cx)
+        })
+        .await
+        .unwrap();
+
+    cx.run_until_parked();
+    unstaged_changes.update(cx, |unstaged_changes, cx| {
+        let snapshot = buffer.read(cx).snapshot();
+        assert_hunks(
+            unstaged_changes.diff_hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &snapshot),
+            &snapshot,
+            &unstaged_changes.base_text.as_ref().unwrap().read(cx).text(),
+            &[
+                (0..1, "", "// print goodbye\n"),
+                (
+                    2..3,
+                    "    println!(\"hello world\");\n",
+                    "    println!(\"goodbye world\");\n",
+                ),
+            ],
+        );
+    });
+
+    let staged_contents = r#"
+        // print goodbye
+        fn main() {
+        }
+    "#
+    .unindent();
+
+    fs.set_index_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("src/main.rs"), staged_contents)],
+    );
+
+    cx.run_until_parked();
+    unstaged_changes.update(cx, |unstaged_changes, cx| {
+        let snapshot = buffer.read(cx).snapshot();
+        assert_hunks(
+            unstaged_changes.diff_hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &snapshot),
+            &snapshot,
+            &unstaged_changes.base_text.as_ref().unwrap().read(cx).text(),
+            &[(2..3, "", "    println!(\"goodbye world\");\n")],
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_stage_unstage_and_commit_git_changes(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let committed_contents = "one\ntwo\nthree\n".to_string();
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "a.txt": "one\nTWO\nthree\nfour\n",
+            "b.txt": "new file\n",
+        }),
+    )
+    .await;
+    fs.set_head_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("a.txt"), committed_contents.clone())],
+    );
+    fs.set_index_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("a.txt"), committed_contents.clone())],
+    );
+    fs.set_status_for_repo_via_working_copy_change(
+        Path::new("/dir/.git"),
+        &[
+            (Path::new("a.txt"), GitFileStatus::Modified),
+            (Path::new("b.txt"), GitFileStatus::Added),
+        ],
+    );
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+    cx.run_until_parked();
+
+    let paths: Vec<Arc<Path>> = vec![Path::new("a.txt").into(), Path::new("b.txt").into()];
+    let stage_statuses = |cx: &mut gpui::TestAppContext| {
+        let paths = paths.clone();
+        project.update(cx, |project, cx| {
+            project.git_stage_statuses(worktree_id, Some(paths), cx)
+        })
+    };
+
+    let mut statuses = stage_statuses(cx).await.unwrap();
+    statuses.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        statuses,
+        [
+            (Path::new("a.txt").into(), StageStatus::Unstaged),
+            (Path::new("b.txt").into(), StageStatus::Unstaged),
+        ]
+    );
+
+    project
+        .update(cx, |project, cx| {
+            project.stage_git_paths(worktree_id, paths.clone(), cx)
+        })
+        .await
+        .unwrap();
+    let mut statuses = stage_statuses(cx).await.unwrap();
+    statuses.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        statuses,
+        [
+            (Path::new("a.txt").into(), StageStatus::Staged),
+            (Path::new("b.txt").into(), StageStatus::Staged),
+        ]
+    );
+
+    project
+        .update(cx, |project, cx| {
+            project.unstage_git_paths(worktree_id, vec![Path::new("b.txt").into()], cx)
+        })
+        .await
+        .unwrap();
+    let mut statuses = stage_statuses(cx).await.unwrap();
+    statuses.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        statuses,
+        [
+            (Path::new("a.txt").into(), StageStatus::Staged),
+            (Path::new("b.txt").into(), StageStatus::Unstaged),
+        ]
+    );
+
+    // Stage both hunks of a.txt, then unstage the second one.
+    let a_path = ProjectPath {
+        worktree_id,
+        path: Path::new("a.txt").into(),
+    };
+    project
+        .update(cx, |project, cx| {
+            project.set_git_index_text(
+                a_path.clone(),
+                Some("one\nTWO\nthree\nfour\n".to_string()),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.unstage_git_rows(a_path.clone(), vec![3..4], cx)
+        })
+        .await
+        .unwrap();
+    let mut index_contents = None;
+    fs.with_git_state(Path::new("/dir/.git"), false, |state| {
+        index_contents = state.index_contents.get(Path::new("a.txt")).cloned();
+    });
+    assert_eq!(index_contents.as_deref(), Some("one\nTWO\nthree\n"));
+
+    project
+        .update(cx, |project, cx| {
+            project.commit_git_changes(a_path.clone(), "Update a.txt".to_string(), cx)
+        })
+        .await
+        .unwrap();
+    let mut head_contents = None;
+    let mut commit_messages = Vec::new();
+    fs.with_git_state(Path::new("/dir/.git"), false, |state| {
+        head_contents = state.head_contents.get(Path::new("a.txt")).cloned();
+        commit_messages = state.commit_messages.clone();
+    });
+    assert_eq!(head_contents.as_deref(), Some("one\nTWO\nthree\n"));
+    assert_eq!(commit_messages, ["Update a.txt"]);
+
+    let mut statuses = stage_statuses(cx).await.unwrap();
+    statuses.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        statuses,
+        [(Path::new("b.txt").into(), StageStatus::Unstaged)]
+    );
     project
         .update(cx, |project, cx| {
-            let third = worktree_c.read(cx);
-            let second = worktree_b.read(cx);
-            project.move_worktree(third.id(), second.id(), cx)
+            project.commit_git_changes(a_path.clone(), "Nothing staged".to_string(), cx)
         })
-        .expect("moving third before second");
+        .await
+        .unwrap_err();
+}
 
-    // check the state after moving
-    project.update(cx, |project, cx| {
-        let worktrees = project.visible_worktrees(cx).collect::<Vec<_>>();
-        assert_eq!(worktrees.len(), 3);
+#[gpui::test]
+async fn test_stage_and_commit_deleted_git_files(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
 
-        let first = worktrees[0].read(cx);
-        let second = worktrees[1].read(cx);
-        let third = worktrees[2].read(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "a.txt": "one\nTWO\n",
+        }),
+    )
+    .await;
+    let committed_files = [
+        (Path::new("a.txt"), "one\ntwo\n".to_string()),
+        (Path::new("b.txt"), "deleted\n".to_string()),
+    ];
+    fs.set_head_for_repo(Path::new("/dir/.git"), &committed_files);
+    fs.set_index_for_repo(Path::new("/dir/.git"), &committed_files);
+    fs.set_status_for_repo_via_working_copy_change(
+        Path::new("/dir/.git"),
+        &[
+            (Path::new("a.txt"), GitFileStatus::Modified),
+            (Path::new("b.txt"), GitFileStatus::Modified),
+        ],
+    );
 
-        // check they are now in the right order
-        assert_eq!(first.abs_path().to_str().unwrap(), "/dir/a.rs");
-        assert_eq!(second.abs_path().to_str().unwrap(), "/dir/b.rs");
-        assert_eq!(third.abs_path().to_str().unwrap(), "/dir/c.rs");
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
     });
+    cx.run_until_parked();
 
-    // move the first worktree to after the third
-    // [a, b, c] -> [b, c, a]
+    // The deleted file has no worktree entry, but is still listed as a changed path.
+    let mut statuses = project
+        .update(cx, |project, cx| {
+            project.git_stage_statuses(worktree_id, None, cx)
+        })
+        .await
+        .unwrap();
+    statuses.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        statuses,
+        [
+            (Path::new("a.txt").into(), StageStatus::Unstaged),
+            (Path::new("b.txt").into(), StageStatus::Unstaged),
+        ]
+    );
+
+    let paths = statuses.into_iter().map(|(path, _)| path).collect();
     project
         .update(cx, |project, cx| {
-            let first = worktree_a.read(cx);
-            let third = worktree_c.read(cx);
-            project.move_worktree(first.id(), third.id(), cx)
+            project.stage_git_paths(worktree_id, paths, cx)
         })
-        .expect("moving first after third");
+        .await
+        .unwrap();
+    let mut index_contents = HashMap::default();
+    fs.with_git_state(Path::new("/dir/.git"), false, |state| {
+        index_contents = state.index_contents.clone();
+    });
+    assert_eq!(
+        index_contents,
+        HashMap::from_iter([(PathBuf::from("a.txt"), "one\nTWO\n".to_string())])
+    );
 
-    // check the state after moving
-    project.update(cx, |project, cx| {
-        let worktrees = project.visible_worktrees(cx).collect::<Vec<_>>();
-        assert_eq!(worktrees.len(), 3);
+    project
+        .update(cx, |project, cx| {
+            project.commit_git_changes(
+                ProjectPath {
+                    worktree_id,
+                    path: Path::new("a.txt").into(),
+                },
+                "Delete b.txt".to_string(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    let mut head_contents = HashMap::default();
+    fs.with_git_state(Path::new("/dir/.git"), false, |state| {
+        head_contents = state.head_contents.clone();
+    });
+    assert_eq!(head_contents, index_contents);
+}
 
-        let first = worktrees[0].read(cx);
-        let second = worktrees[1].read(cx);
-        let third = worktrees[2].read(cx);
+#[gpui::test]
+async fn test_git_file_history(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
 
-        // check they are now in the right order
-        assert_eq!(first.abs_path().to_str().unwrap(), "/dir/b.rs");
-        assert_eq!(second.abs_path().to_str().unwrap(), "/dir/c.rs");
-        assert_eq!(third.abs_path().to_str().unwrap(), "/dir/a.rs");
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "src": {
+                "a.txt": "one\ntwo\nthree\n",
+            },
+        }),
+    )
+    .await;
+
+    let sha = git::Oid::from_str("a1b2c3d4e5f60718293a4b5c6d7e8f9012345678").unwrap();
+    let entry = LogEntry {
+        sha,
+        author_name: "Someone".into(),
+        author_email: "someone@example.com".into(),
+        author_time: 1_700_000_000,
+        summary: "Add three".into(),
+        path: Path::new("src/a.txt").into(),
+    };
+    let renamed_sha = git::Oid::from_str("0f1e2d3c4b5a69788796a5b4c3d2e1f0a1b2c3d4").unwrap();
+    let renamed_entry = LogEntry {
+        sha: renamed_sha,
+        author_name: "Someone".into(),
+        author_email: "someone@example.com".into(),
+        author_time: 1_600_000_000,
+        summary: "Add one".into(),
+        path: Path::new("a.txt").into(),
+    };
+    fs.set_file_history_for_repo(
+        Path::new("/dir/.git"),
+        Path::new("src/a.txt"),
+        vec![entry.clone(), renamed_entry.clone()],
+    );
+    fs.set_commit_diff_for_repo(
+        Path::new("/dir/.git"),
+        sha,
+        vec![CommitFileDiff {
+            path: Path::new("src/a.txt").into(),
+            old_text: Some("one\ntwo\n".into()),
+            new_text: Some("one\ntwo\nthree\n".into()),
+        }],
+    );
+    fs.set_commit_diff_for_repo(
+        Path::new("/dir/.git"),
+        renamed_sha,
+        vec![CommitFileDiff {
+            path: Path::new("a.txt").into(),
+            old_text: None,
+            new_text: Some("one\n".into()),
+        }],
+    );
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
     });
+    cx.run_until_parked();
+    let project_path = ProjectPath {
+        worktree_id,
+        path: Path::new("src/a.txt").into(),
+    };
 
-    // move the third worktree to before the first
-    // [b, c, a] -> [a, b, c]
-    project
+    let history = project
         .update(cx, |project, cx| {
-            let third = worktree_a.read(cx);
-            let first = worktree_b.read(cx);
-            project.move_worktree(third.id(), first.id(), cx)
+            project.git_file_history(project_path.clone(), Some(1..2), cx)
         })
-        .expect("moving third before first");
+        .await
+        .unwrap();
+    assert_eq!(history, [entry.clone(), renamed_entry.clone()]);
 
-    // check the state after moving
-    project.update(cx, |project, cx| {
-        let worktrees = project.visible_worktrees(cx).collect::<Vec<_>>();
-        assert_eq!(worktrees.len(), 3);
+    let text = project
+        .update(cx, |project, cx| {
+            project.git_file_at_commit(project_path.clone(), entry.path, sha, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(text.as_deref(), Some("one\ntwo\nthree\n"));
 
-        let first = worktrees[0].read(cx);
-        let second = worktrees[1].read(cx);
-        let third = worktrees[2].read(cx);
+    // Commits from before a rename are loaded with the file's path at the time.
+    let text = project
+        .update(cx, |project, cx| {
+            project.git_file_at_commit(project_path.clone(), renamed_entry.path, renamed_sha, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(text.as_deref(), Some("one\n"));
 
-        // check they are now in the right order
-        assert_eq!(first.abs_path().to_str().unwrap(), "/dir/a.rs");
-        assert_eq!(second.abs_path().to_str().unwrap(), "/dir/b.rs");
-        assert_eq!(third.abs_path().to_str().unwrap(), "/dir/c.rs");
-    });
+    let files = project
+        .update(cx, |project, cx| {
+            project.git_commit_diff(project_path.clone(), sha, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].old_text.as_deref(), Some("one\ntwo\n"));
 }
 
 #[gpui::test]
-async fn test_unstaged_changes_for_buffer(cx: &mut gpui::TestAppContext) {
+async fn test_git_diff_base(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
-    let staged_contents = r#"
-        fn main() {
-            println!("hello world");
-        }
-    "#
-    .unindent();
-    let file_contents = r#"
-        // print goodbye
-        fn main() {
-            println!("goodbye world");
-        }
-    "#
-    .unindent();
-
     let fs = FakeFs::new(cx.background_executor.clone());
     fs.insert_tree(
         "/dir",
         json!({
             ".git": {},
-           "src": {
-               "main.rs": file_contents,
-           }
+            "src": {
+                "a.txt": "one\ntwo\nthree\n",
+                "b.txt": "unchanged\n",
+            },
         }),
     )
     .await;
 
+    let sha = git::Oid::from_str("a1b2c3d4e5f60718293a4b5c6d7e8f9012345678").unwrap();
     fs.set_index_for_repo(
         Path::new("/dir/.git"),
-        &[(Path::new("src/main.rs"), staged_contents)],
+        &[(Path::new("src/a.txt"), "one\ntwo\nthree\n".into())],
+    );
+    fs.set_revision_for_repo(Path::new("/dir/.git"), "main", sha);
+    fs.set_commit_diff_for_repo(
+        Path::new("/dir/.git"),
+        sha,
+        vec![CommitFileDiff {
+            path: Path::new("src/a.txt").into(),
+            old_text: None,
+            new_text: Some("one\n".into()),
+        }],
     );
 
     let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
     let buffer = project
         .update(cx, |project, cx| {
-            project.open_local_buffer("/dir/src/main.rs", cx)
+            project.open_local_buffer("/dir/src/a.txt", cx)
         })
         .await
         .unwrap();
-    let unstaged_changes = project
+    let change_set = project
         .update(cx, |project, cx| {
             project.open_unstaged_changes(buffer.clone(), cx)
         })
         .await
         .unwrap();
+    cx.run_until_parked();
+    change_set.update(cx, |change_set, cx| {
+        let base_text = change_set.base_text.as_ref().unwrap().read(cx).text();
+        assert_eq!(base_text, "one\ntwo\nthree\n");
+    });
 
+    project
+        .update(cx, |project, cx| {
+            project.set_git_diff_base(DiffBase::MergeBase("main".into()), cx)
+        })
+        .await
+        .unwrap();
     cx.run_until_parked();
-    unstaged_changes.update(cx, |unstaged_changes, cx| {
+    change_set.update(cx, |change_set, cx| {
+        let base_text = change_set.base_text.as_ref().unwrap().read(cx).text();
+        assert_eq!(base_text, "one\n");
         let snapshot = buffer.read(cx).snapshot();
         assert_hunks(
-            unstaged_changes.diff_hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &snapshot),
+            change_set.diff_hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &snapshot),
             &snapshot,
-            &unstaged_changes.base_text.as_ref().unwrap().read(cx).text(),
-            &[
-                (0..1, "", "// print goodbye\n"),
-                (
-                    2..3,
-                    "    println!(\"hello world\");\n",
-                    "    println!(\"goodbye world\");\n",
-                ),
-            ],
+            &base_text,
+            &[(1..3, "", "two\nthree\n")],
         );
     });
 
-    let staged_contents = r#"
-        // print goodbye
-        fn main() {
-        }
-    "#
-    .unindent();
+    let changed_paths = project
+        .update(cx, |project, cx| project.git_changed_paths(worktree_id, cx))
+        .await
+        .unwrap();
+    assert_eq!(changed_paths, [Arc::from(Path::new("src/a.txt"))]);
 
-    fs.set_index_for_repo(
+    project
+        .update(cx, |project, cx| {
+            project.set_git_diff_base(DiffBase::Index, cx)
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+    change_set.update(cx, |change_set, cx| {
+        let base_text = change_set.base_text.as_ref().unwrap().read(cx).text();
+        assert_eq!(base_text, "one\ntwo\nthree\n");
+    });
+}
+
+#[gpui::test]
+async fn test_git_stash(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "a.txt": "changed\n",
+        }),
+    )
+    .await;
+    fs.set_status_for_repo_via_git_operation(
         Path::new("/dir/.git"),
-        &[(Path::new("src/main.rs"), staged_contents)],
+        &[(Path::new("a.txt"), GitFileStatus::Modified)],
     );
 
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
     cx.run_until_parked();
-    unstaged_changes.update(cx, |unstaged_changes, cx| {
-        let snapshot = buffer.read(cx).snapshot();
-        assert_hunks(
-            unstaged_changes.diff_hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &snapshot),
-            &snapshot,
-            &unstaged_changes.base_text.as_ref().unwrap().read(cx).text(),
-            &[(2..3, "", "    println!(\"goodbye world\");\n")],
-        );
+    let repository = ProjectPath::root_path(worktree_id);
+
+    project
+        .update(cx, |project, cx| {
+            project.git_stash_push(repository.clone(), Some("work in progress".into()), cx)
+        })
+        .await
+        .unwrap();
+    let stashes = project
+        .update(cx, |project, cx| {
+            project.git_stash_list(repository.clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(stashes.len(), 1);
+    assert_eq!(stashes[0].index, 0);
+    assert!(stashes[0].message.ends_with("work in progress"));
+    let sha = stashes[0].sha;
+
+    // Without changes, there is nothing to stash.
+    assert!(project
+        .update(cx, |project, cx| {
+            project.git_stash_push(repository.clone(), None, cx)
+        })
+        .await
+        .is_err());
+
+    project
+        .update(cx, |project, cx| {
+            project.git_stash_action(repository.clone(), sha, StashAction::Pop, cx)
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+    let stashes = project
+        .update(cx, |project, cx| {
+            project.git_stash_list(repository.clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert!(stashes.is_empty());
+    project.update(cx, |project, cx| {
+        let worktree = project.worktree_for_id(worktree_id, cx).unwrap();
+        let entry = worktree.read(cx).entry_for_path("a.txt").unwrap();
+        assert_eq!(entry.git_status, Some(GitFileStatus::Modified));
     });
+
+    assert!(project
+        .update(cx, |project, cx| {
+            project.git_stash_action(repository.clone(), sha, StashAction::Drop, cx)
+        })
+        .await
+        .is_err());
 }
 
 async fn search(
@@ -4,6 +4,7 @@ use client::proto;
 use fancy_regex::{Captures, Regex, RegexBuilder};
 use gpui::Model;
 use language::{Buffer, BufferSnapshot, CharKind};
+use serde::Deserialize;
 use smol::future::yield_now;
 use std::{
     borrow::Cow,
@@ -15,6 +16,10 @@ use std::{
 use text::Anchor;
 use util::paths::PathMatcher;
 
+mod structural;
+
+pub use structural::StructuralPattern;
+
 static TEXT_REPLACEMENT_SPECIAL_CHARACTERS_REGEX: OnceLock<Regex> = OnceLock::new();
 
 pub enum SearchResult {
@@ -32,12 +37,83 @@ pub enum SearchInputKind {
     Exclude,
 }
 
+/// The kinds of syntax that search matches are restricted to, based on the syntax highlighting
+/// of the searched buffers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyntaxScope {
+    #[default]
+    Anywhere,
+    /// Only matches inside of comments.
+    Comments,
+    /// Only matches inside of string literals.
+    Strings,
+    /// Only matches outside of comments and string literals.
+    Code,
+}
+
+impl SyntaxScope {
+    pub fn from_proto(scope: proto::search_query::SyntaxScope) -> Self {
+        match scope {
+            proto::search_query::SyntaxScope::Anywhere => Self::Anywhere,
+            proto::search_query::SyntaxScope::Comments => Self::Comments,
+            proto::search_query::SyntaxScope::Strings => Self::Strings,
+            proto::search_query::SyntaxScope::Code => Self::Code,
+        }
+    }
+
+    pub fn to_proto(self) -> proto::search_query::SyntaxScope {
+        match self {
+            Self::Anywhere => proto::search_query::SyntaxScope::Anywhere,
+            Self::Comments => proto::search_query::SyntaxScope::Comments,
+            Self::Strings => proto::search_query::SyntaxScope::Strings,
+            Self::Code => proto::search_query::SyntaxScope::Code,
+        }
+    }
+
+    /// Keeps the matches, given relative to `range_offset`, that lie within this scope.
+    fn filter_matches(
+        self,
+        buffer: &BufferSnapshot,
+        range_offset: usize,
+        matches: Vec<Range<usize>>,
+    ) -> Vec<Range<usize>> {
+        let capture_names: &[&str] = match self {
+            Self::Anywhere => return matches,
+            Self::Comments => &["comment"],
+            Self::Strings => &["string"],
+            Self::Code => &["comment", "string"],
+        };
+        let (Some(first), Some(last)) = (matches.first(), matches.last()) else {
+            return matches;
+        };
+
+        let search_range = range_offset + first.start..range_offset + last.end;
+        let scope_ranges = buffer.highlight_capture_ranges(search_range, capture_names);
+        matches
+            .into_iter()
+            .filter(|mat| {
+                let start = range_offset + mat.start;
+                let end = range_offset + mat.end;
+                let ix = scope_ranges.partition_point(|range| range.end <= start);
+                let scope_range = scope_ranges.get(ix);
+                if self == Self::Code {
+                    scope_range.map_or(true, |range| range.start >= end)
+                } else {
+                    scope_range.map_or(false, |range| range.start <= start && end <= range.end)
+                }
+            })
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SearchInputs {
     query: Arc<str>,
     files_to_include: PathMatcher,
     files_to_exclude: PathMatcher,
     buffers: Option<Vec<Model<Buffer>>>,
+    syntax_scope: SyntaxScope,
 }
 
 impl SearchInputs {
@@ -53,6 +129,9 @@ impl SearchInputs {
     pub fn buffers(&self) -> &Option<Vec<Model<Buffer>>> {
         &self.buffers
     }
+    pub fn syntax_scope(&self) -> SyntaxScope {
+        self.syntax_scope
+    }
 }
 #[derive(Clone, Debug)]
 pub enum SearchQuery {
@@ -74,6 +153,13 @@ pub enum SearchQuery {
         include_ignored: bool,
         inner: SearchInputs,
     },
+
+    Structural {
+        pattern: StructuralPattern,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 static WORD_MATCH_TEST: LazyLock<Regex> = LazyLock::new(|| {
@@ -101,6 +187,7 @@ impl SearchQuery {
             files_to_exclude,
             files_to_include,
             buffers,
+            syntax_scope: SyntaxScope::Anywhere,
         };
         Ok(Self::Text {
             search: Arc::new(search),
@@ -148,6 +235,7 @@ impl SearchQuery {
             files_to_exclude,
             files_to_include,
             buffers,
+            syntax_scope: SyntaxScope::Anywhere,
         };
         Ok(Self::Regex {
             regex,
@@ -160,8 +248,44 @@ impl SearchQuery {
         })
     }
 
+    /// Creates a query matching syntax nodes, as described in [`StructuralPattern`].
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        buffers: Option<Vec<Model<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let pattern = StructuralPattern::new(&query);
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            buffers,
+            syntax_scope: SyntaxScope::Anywhere,
+        };
+        Ok(Self::Structural {
+            pattern,
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     pub fn from_proto(message: proto::SearchQuery) -> Result<Self> {
-        if message.regex {
+        let syntax_scope = proto::search_query::SyntaxScope::from_i32(message.syntax_scope)
+            .map(SyntaxScope::from_proto)
+            .unwrap_or_default();
+        let query = if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                deserialize_path_matches(&message.files_to_include)?,
+                deserialize_path_matches(&message.files_to_exclude)?,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -181,7 +305,8 @@ impl SearchQuery {
                 deserialize_path_matches(&message.files_to_exclude)?,
                 None, // search opened only don't need search remote
             )
-        }
+        }?;
+        Ok(query.with_syntax_scope(syntax_scope))
     }
 
     pub fn with_replacement(mut self, new_replacement: String) -> Self {
@@ -193,6 +318,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -200,10 +329,23 @@ impl SearchQuery {
         }
     }
 
+    pub fn with_syntax_scope(mut self, syntax_scope: SyntaxScope) -> Self {
+        match self {
+            Self::Text { ref mut inner, .. }
+            | Self::Regex { ref mut inner, .. }
+            | Self::Structural { ref mut inner, .. } => {
+                inner.syntax_scope = syntax_scope;
+                self
+            }
+        }
+    }
+
     pub fn to_proto(&self) -> proto::SearchQuery {
         proto::SearchQuery {
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
+            syntax_scope: self.syntax_scope().to_proto() as i32,
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
@@ -247,14 +389,19 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            Self::Structural { pattern, .. } => {
+                let mut text = String::new();
+                BufReader::new(stream).read_to_string(&mut text)?;
+                Ok(pattern.detect(&text))
+            }
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
@@ -280,7 +427,27 @@ impl SearchQuery {
                     None
                 }
             }
+            // The replacement of a structural match depends on the syntax it matched.
+            SearchQuery::Structural { .. } => None,
+        }
+    }
+
+    /// Returns the replacement for the match found by [`Self::search`] in the given range of `buffer`.
+    pub fn replacement_for_range(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        if let SearchQuery::Structural {
+            pattern,
+            replacement,
+            ..
+        } = self
+        {
+            return pattern.replacement_for(buffer, range, replacement.as_ref()?);
         }
+        let text = buffer.text_for_range(range).collect::<String>();
+        self.replacement_for(&text).map(Cow::into_owned)
     }
 
     pub async fn search(
@@ -294,6 +461,18 @@ impl SearchQuery {
             return Default::default();
         }
 
+        if let Self::Structural { pattern, .. } = self {
+            let range = subrange.unwrap_or(0..buffer.len());
+            let matches = pattern
+                .search(buffer, range.clone())
+                .into_iter()
+                .map(|mat| mat.start - range.start..mat.end - range.start)
+                .collect();
+            return self
+                .syntax_scope()
+                .filter_matches(buffer, range.start, matches);
+        }
+
         let range_offset = subrange.as_ref().map(|r| r.start).unwrap_or(0);
         let rope = if let Some(range) = subrange {
             buffer.as_rope().slice(range)
@@ -377,7 +556,8 @@ impl SearchQuery {
             }
         }
 
-        matches
+        self.syntax_scope()
+            .filter_matches(buffer, range_offset, matches)
     }
 
     pub fn is_empty(&self) -> bool {
@@ -392,6 +572,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -399,6 +580,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -410,6 +592,9 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
@@ -417,6 +602,19 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
+    pub fn syntax_scope(&self) -> SyntaxScope {
+        self.as_inner().syntax_scope()
+    }
+
+    /// Whether matching this query depends on the syntax trees of the searched buffers.
+    pub fn uses_syntax(&self) -> bool {
+        self.is_structural() || self.syntax_scope() != SyntaxScope::Anywhere
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -454,7 +652,9 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
         }
     }
 }
@@ -0,0 +1,474 @@
+use std::{
+    fmt,
+    ops::Range,
+    sync::{Arc, LazyLock},
+};
+
+use collections::HashMap;
+use language::{
+    with_parser, with_query_cursor, BufferId, BufferSnapshot, Grammar, GrammarId, Node, Tree,
+};
+use parking_lot::Mutex;
+use regex::{Captures, Regex};
+use tree_sitter::Query;
+
+/// Stands in for a `$NAME` metavariable when parsing a template.
+const METAVARIABLE_PREFIX: &str = "zed_metavar_";
+/// Stands in for a `$$$NAME` metavariable when parsing a template.
+const MULTI_METAVARIABLE_PREFIX: &str = "zed_metavars_";
+/// A metavariable that matches anything without binding the matched text.
+const WILDCARD: &str = "_";
+
+static METAVARIABLE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$(\$\$)?([A-Z_][A-Z0-9_]*)").unwrap());
+static REPLACEMENT_VARIABLE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$(?:\$\$)?([A-Za-z_][A-Za-z0-9_]*)").unwrap());
+static WORD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap());
+
+/// A syntax-aware search pattern, matched against the syntax trees of buffers.
+///
+/// The pattern is either a code template, parsed in the language of the searched buffer, where
+/// `$NAME` stands for any syntax node and `$$$NAME` for any sequence of sibling nodes, such as
+/// `$A.unwrap()`, or a tree-sitter query, such as `(call_expression function: (identifier) @name)`.
+/// A capture named `@match` selects the matched range of a query, which otherwise spans all of its
+/// captures.
+///
+/// Replacements refer to metavariables and captures as `$NAME`.
+#[derive(Clone)]
+pub struct StructuralPattern {
+    source: Arc<str>,
+    /// The words that a file must contain to possibly match the pattern.
+    required_words: Arc<[String]>,
+    /// The pattern compiled for each grammar it was matched against, `None` when the pattern
+    /// is not valid in that grammar.
+    compiled: Arc<Mutex<HashMap<GrammarId, Option<Arc<CompiledPattern>>>>>,
+    /// The text bound to each metavariable by the current matches, keyed by the buffer and range
+    /// of the match, for expanding replacements, along with the buffer version they were found in.
+    match_captures: Arc<Mutex<HashMap<(BufferId, Range<usize>), MatchCaptures>>>,
+}
+
+struct MatchCaptures {
+    version: clock::Global,
+    captures: HashMap<String, String>,
+}
+
+enum CompiledPattern {
+    Template {
+        tree: Tree,
+        text: String,
+        range: Range<usize>,
+    },
+    Query(Query),
+}
+
+impl fmt::Debug for StructuralPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StructuralPattern")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StructuralPattern {
+    pub fn new(source: &str) -> Self {
+        let required_words = if is_query_like(source) {
+            Vec::new()
+        } else {
+            let literal_text = METAVARIABLE_REGEX.replace_all(source, " ");
+            let mut words = WORD_REGEX
+                .find_iter(&literal_text)
+                .map(|word| word.as_str().to_string())
+                .collect::<Vec<_>>();
+            words.sort();
+            words.dedup();
+            words
+        };
+
+        Self {
+            source: source.into(),
+            required_words: required_words.into(),
+            compiled: Default::default(),
+            match_captures: Default::default(),
+        }
+    }
+
+    /// Returns whether a file with the given contents may contain a match.
+    pub(crate) fn detect(&self, text: &str) -> bool {
+        self.required_words
+            .iter()
+            .all(|word| text.contains(word.as_str()))
+    }
+
+    /// Returns the ranges of the syntax nodes matching the pattern within the given range.
+    pub(crate) fn search(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<Range<usize>> {
+        // Forget the matches of the previous search of this range, which are found again below.
+        self.match_captures
+            .lock()
+            .retain(|(buffer_id, match_range), _| {
+                *buffer_id != buffer.remote_id()
+                    || match_range.start < range.start
+                    || match_range.end > range.end
+            });
+
+        let mut matches = Vec::new();
+        let mut buffer_text = None;
+        for layer in buffer.syntax_layers() {
+            let Some(grammar) = layer.language.grammar() else {
+                continue;
+            };
+            let Some(pattern) = self.compiled(grammar) else {
+                continue;
+            };
+
+            match pattern.as_ref() {
+                CompiledPattern::Template {
+                    tree,
+                    text,
+                    range: pattern_range,
+                } => {
+                    let Some(pattern_node) = tree
+                        .root_node()
+                        .descendant_for_byte_range(pattern_range.start, pattern_range.end)
+                    else {
+                        continue;
+                    };
+                    let matcher = TemplateMatcher {
+                        pattern_text: text,
+                        buffer,
+                    };
+                    matcher.find_matches(pattern_node, layer.node(), &range, |range, captures| {
+                        self.record_match(buffer, range, captures, &mut matches)
+                    });
+                }
+                CompiledPattern::Query(query) => {
+                    let buffer_text = buffer_text.get_or_insert_with(|| buffer.text());
+                    let match_capture_ix = query.capture_index_for_name("match");
+                    with_query_cursor(|cursor| {
+                        cursor.set_byte_range(range.clone());
+                        for query_match in
+                            cursor.matches(query, layer.node(), buffer_text.as_bytes())
+                        {
+                            let match_range = match match_capture_ix {
+                                Some(ix) => query_match
+                                    .captures
+                                    .iter()
+                                    .find(|capture| capture.index == ix)
+                                    .map(|capture| capture.node.byte_range()),
+                                None => query_match
+                                    .captures
+                                    .iter()
+                                    .map(|capture| capture.node.byte_range())
+                                    .reduce(|a, b| a.start.min(b.start)..a.end.max(b.end)),
+                            };
+                            let Some(match_range) = match_range else {
+                                continue;
+                            };
+                            if match_range.start < range.start || match_range.end > range.end {
+                                continue;
+                            }
+
+                            let captures = query_match
+                                .captures
+                                .iter()
+                                .map(|capture| {
+                                    (
+                                        query.capture_names()[capture.index as usize].to_string(),
+                                        capture.node.byte_range(),
+                                    )
+                                })
+                                .collect::<Vec<_>>();
+                            self.record_match(buffer, match_range, captures, &mut matches);
+                        }
+                    });
+                }
+            }
+        }
+
+        // Matches in different layers or of different query patterns may overlap,
+        // and only one of them can be replaced.
+        matches.sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+        let mut last_end = 0;
+        matches.retain(|range| {
+            let keep = range.start >= last_end;
+            if keep {
+                last_end = range.end;
+            }
+            keep
+        });
+        matches
+    }
+
+    /// Expands the metavariables of `replacement` with the text they matched in the given range
+    /// of `buffer`, which must be the range of a match found by [`Self::search`].
+    pub(crate) fn replacement_for(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+        replacement: &str,
+    ) -> Option<String> {
+        let key = (buffer.remote_id(), range.clone());
+        // The buffer may have been edited since it was searched, moving the match or changing
+        // the text it captured.
+        let is_current = self
+            .match_captures
+            .lock()
+            .get(&key)
+            .map_or(false, |entry| entry.version == *buffer.version());
+        if !is_current {
+            self.search(buffer, range);
+        }
+        let match_captures = self.match_captures.lock();
+        let captures = &match_captures.get(&key)?.captures;
+        Some(
+            REPLACEMENT_VARIABLE_REGEX
+                .replace_all(replacement, |variable: &Captures| {
+                    captures
+                        .get(&variable[1])
+                        .cloned()
+                        .unwrap_or_else(|| variable[0].to_string())
+                })
+                .into_owned(),
+        )
+    }
+
+    fn record_match(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+        captures: Vec<(String, Range<usize>)>,
+        matches: &mut Vec<Range<usize>>,
+    ) {
+        let captures = captures
+            .into_iter()
+            .map(|(name, range)| (name, buffer.text_for_range(range).collect()))
+            .collect();
+        self.match_captures.lock().insert(
+            (buffer.remote_id(), range.clone()),
+            MatchCaptures {
+                version: buffer.version().clone(),
+                captures,
+            },
+        );
+        matches.push(range);
+    }
+
+    fn compiled(&self, grammar: &Grammar) -> Option<Arc<CompiledPattern>> {
+        self.compiled
+            .lock()
+            .entry(grammar.id())
+            .or_insert_with(|| self.compile(grammar).map(Arc::new))
+            .clone()
+    }
+
+    fn compile(&self, grammar: &Grammar) -> Option<CompiledPattern> {
+        let source = self.source.trim();
+        if is_query_like(source) {
+            if let Ok(query) = Query::new(&grammar.ts_language, source) {
+                return Some(CompiledPattern::Query(query));
+            }
+        }
+
+        let text = METAVARIABLE_REGEX
+            .replace_all(source, |metavariable: &Captures| {
+                let prefix = if metavariable.get(1).is_some() {
+                    MULTI_METAVARIABLE_PREFIX
+                } else {
+                    METAVARIABLE_PREFIX
+                };
+                format!("{prefix}{}", &metavariable[2])
+            })
+            .into_owned();
+
+        // Expressions are often not valid on their own at the top level of a file,
+        // so also try to parse the template as a statement.
+        for suffix in ["", ";"] {
+            let full_text = format!("{text}{suffix}");
+            let Some(tree) = with_parser(|parser| {
+                parser.set_language(&grammar.ts_language).ok()?;
+                parser.parse(&full_text, None)
+            }) else {
+                continue;
+            };
+
+            // The template is matched by the innermost node spanning all of it, skipping the
+            // nodes, such as statements or the root of the file, that merely wrap it.
+            let Some(node) = tree.root_node().descendant_for_byte_range(0, text.len()) else {
+                continue;
+            };
+            if node.has_error() || node.byte_range() != (0..text.len()) {
+                continue;
+            }
+            let range = node.byte_range();
+            return Some(CompiledPattern::Template {
+                tree,
+                text: full_text,
+                range,
+            });
+        }
+        None
+    }
+}
+
+fn is_query_like(source: &str) -> bool {
+    source.trim_start().starts_with(['(', '['])
+}
+
+struct TemplateMatcher<'a> {
+    pattern_text: &'a str,
+    buffer: &'a BufferSnapshot,
+}
+
+type Bindings<'a> = Vec<(&'a str, Range<usize>)>;
+
+impl<'a> TemplateMatcher<'a> {
+    /// Calls `on_match` with the outermost nodes under `root` that match `pattern`
+    /// and lie within `range`, along with the ranges bound to the metavariables.
+    fn find_matches(
+        &self,
+        pattern: Node,
+        root: Node,
+        range: &Range<usize>,
+        mut on_match: impl FnMut(Range<usize>, Vec<(String, Range<usize>)>),
+    ) {
+        let mut cursor = root.walk();
+        loop {
+            let node = cursor.node();
+            let node_range = node.byte_range();
+            let mut descend = node_range.start <= range.end && node_range.end >= range.start;
+            if descend
+                && node.is_named()
+                && node_range.start >= range.start
+                && node_range.end <= range.end
+            {
+                let mut bindings = Bindings::new();
+                if self.match_node(pattern, node, &mut bindings) {
+                    let captures = bindings
+                        .into_iter()
+                        .map(|(name, range)| (name.to_string(), range))
+                        .collect();
+                    on_match(node_range, captures);
+                    descend = false;
+                }
+            }
+
+            if descend && cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn match_node(&self, pattern: Node, node: Node, bindings: &mut Bindings<'a>) -> bool {
+        let pattern_text = &self.pattern_text[pattern.byte_range()];
+        if let Some(name) = metavariable_name(pattern_text) {
+            return self.bind(name, node.byte_range(), bindings);
+        }
+        if pattern.kind_id() != node.kind_id() {
+            return false;
+        }
+
+        let pattern_children = significant_children(pattern);
+        let children = significant_children(node);
+        if pattern_children.is_empty() {
+            return children.is_empty()
+                && node.byte_range().len() == pattern_text.len()
+                && self.text(node.byte_range()) == pattern_text;
+        }
+        self.match_sequence(&pattern_children, &children, bindings)
+    }
+
+    fn match_sequence(
+        &self,
+        patterns: &[Node],
+        nodes: &[Node],
+        bindings: &mut Bindings<'a>,
+    ) -> bool {
+        let Some((pattern, remaining_patterns)) = patterns.split_first() else {
+            return nodes.is_empty();
+        };
+
+        let pattern_text = &self.pattern_text[pattern.byte_range()];
+        if let Some(name) = multi_metavariable_name(pattern_text) {
+            // Bind as few nodes as possible, so that later patterns get a chance to match.
+            for count in 0..=nodes.len() {
+                let bound_nodes = &nodes[..count];
+                let range = match (bound_nodes.first(), bound_nodes.last()) {
+                    (Some(first), Some(last)) => first.start_byte()..last.end_byte(),
+                    _ => 0..0,
+                };
+                let binding_count = bindings.len();
+                if self.bind(name, range, bindings)
+                    && self.match_sequence(remaining_patterns, &nodes[count..], bindings)
+                {
+                    return true;
+                }
+                bindings.truncate(binding_count);
+            }
+            return false;
+        }
+
+        let Some((node, remaining_nodes)) = nodes.split_first() else {
+            return false;
+        };
+        let binding_count = bindings.len();
+        if self.match_node(*pattern, *node, bindings)
+            && self.match_sequence(remaining_patterns, remaining_nodes, bindings)
+        {
+            return true;
+        }
+        bindings.truncate(binding_count);
+        false
+    }
+
+    /// Binds a metavariable to a range, unless it is already bound to a different text.
+    fn bind(&self, name: &'a str, range: Range<usize>, bindings: &mut Bindings<'a>) -> bool {
+        if name == WILDCARD {
+            return true;
+        }
+        if let Some((_, bound_range)) = bindings.iter().find(|(bound, _)| *bound == name) {
+            return self.text(bound_range.clone()) == self.text(range);
+        }
+        bindings.push((name, range));
+        true
+    }
+
+    fn text(&self, range: Range<usize>) -> String {
+        self.buffer.text_for_range(range).collect()
+    }
+}
+
+/// The name of the metavariable that a template node stands for, when the node consists of a
+/// single metavariable. Nodes that merely start with one, such as `$A.unwrap()`, are not
+/// metavariables.
+fn metavariable_name(text: &str) -> Option<&str> {
+    multi_metavariable_name(text).or_else(|| {
+        text.strip_prefix(METAVARIABLE_PREFIX)
+            .filter(|name| is_metavariable_name(name))
+    })
+}
+
+fn multi_metavariable_name(text: &str) -> Option<&str> {
+    text.strip_prefix(MULTI_METAVARIABLE_PREFIX)
+        .filter(|name| is_metavariable_name(name))
+}
+
+fn is_metavariable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit() || byte == b'_')
+}
+
+/// The children of a node that take part in matching, leaving out comments and the nodes
+/// inserted by the parser to recover from errors.
+fn significant_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra() && !child.is_missing())
+        .collect()
+}
@@ -15,7 +15,8 @@ use itertools::Itertools;
 use language::{ContextProvider, File, Language, LanguageToolchainStore, Location};
 use settings::{parse_json_with_comments, SettingsLocation};
 use task::{
-    ResolvedTask, TaskContext, TaskId, TaskTemplate, TaskTemplates, TaskVariables, VariableName,
+    ResolvedTask, SpawnInTerminal, TaskContext, TaskId, TaskTemplate, TaskTemplates, TaskVariables,
+    VariableName,
 };
 use text::{Point, ToPoint};
 use util::{post_inc, NumericPrefixWithSuffix, ResultExt as _};
@@ -242,6 +243,37 @@ impl Inventory {
         self.last_scheduled_tasks.retain(|(_, task)| &task.id != id);
     }
 
+    /// Resolves the tasks the given task depends on, recursively, with the context the task was resolved with.
+    /// Dependencies are looked up by their labels, among the tasks defined next to the task first,
+    /// then among the other worktree tasks and the global ones.
+    pub fn resolve_dependencies(
+        &self,
+        task_source_kind: &TaskSourceKind,
+        resolved_task: &mut ResolvedTask,
+    ) -> Result<()> {
+        let worktree = match task_source_kind {
+            TaskSourceKind::Worktree { id, .. } => Some(*id),
+            _ => None,
+        };
+        let templates = self
+            .worktree_templates_from_settings(worktree)
+            .chain(self.global_templates_from_settings())
+            .sorted_by_key(|(kind, _)| kind != task_source_kind)
+            .collect::<Vec<_>>();
+        let task_context = resolved_task.task_context().clone();
+        let mut labels_being_resolved = vec![resolved_task.original_task().label.clone()];
+        let dependencies = resolve_dependencies(
+            resolved_task.original_task(),
+            &templates,
+            &task_context,
+            &mut labels_being_resolved,
+        )?;
+        if let Some(spawn_in_terminal) = resolved_task.resolved.as_mut() {
+            spawn_in_terminal.dependencies = dependencies;
+        }
+        Ok(())
+    }
+
     fn global_templates_from_settings(
         &self,
     ) -> impl '_ + Iterator<Item = (TaskSourceKind, TaskTemplate)> {
@@ -328,6 +360,37 @@ impl Inventory {
     }
 }
 
+fn resolve_dependencies(
+    template: &TaskTemplate,
+    templates: &[(TaskSourceKind, TaskTemplate)],
+    task_context: &TaskContext,
+    labels_being_resolved: &mut Vec<String>,
+) -> Result<Vec<SpawnInTerminal>> {
+    template
+        .depends_on
+        .iter()
+        .map(|label| {
+            anyhow::ensure!(
+                !labels_being_resolved.contains(label),
+                "task {label:?} depends on itself"
+            );
+            let (kind, dependency) = templates
+                .iter()
+                .find(|(_, template)| &template.label == label)
+                .with_context(|| format!("no task with label {label:?} found"))?;
+            let mut spawn_in_terminal = dependency
+                .resolve_task(&kind.to_id_base(), task_context)
+                .and_then(|resolved_task| resolved_task.resolved)
+                .with_context(|| format!("failed to resolve task {label:?}"))?;
+            labels_being_resolved.push(label.clone());
+            spawn_in_terminal.dependencies =
+                resolve_dependencies(dependency, templates, task_context, labels_being_resolved)?;
+            labels_being_resolved.pop();
+            Ok(spawn_in_terminal)
+        })
+        .collect()
+}
+
 fn task_lru_comparator(
     (kind_a, task_a, lru_score_a): &(TaskSourceKind, ResolvedTask, u32),
     (kind_b, task_b, lru_score_b): &(TaskSourceKind, ResolvedTask, u32),
@@ -851,6 +914,91 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_resolve_task_dependencies(cx: &mut TestAppContext) {
+        init_test(cx);
+        let inventory = cx.update(Inventory::new);
+        let worktree_id = WorktreeId::from_usize(1);
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .update_file_based_tasks(
+                    None,
+                    Some(
+                        &json!([
+                            { "label": "lint", "command": "global lint" },
+                            { "label": "cycle", "depends_on": ["cycle"] },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+            inventory
+                .update_file_based_tasks(
+                    Some(SettingsLocation {
+                        worktree_id,
+                        path: Path::new(".zed"),
+                    }),
+                    Some(
+                        &json!([
+                            { "label": "lint", "command": "cargo clippy" },
+                            { "label": "build", "command": "cargo build", "depends_on": ["lint"] },
+                            {
+                                "label": "build and test",
+                                "command": "cargo test",
+                                "depends_on": ["build", "missing"],
+                            },
+                            { "label": "check", "depends_on": ["build", "lint"], "depends_order": "parallel" },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+        });
+
+        let resolve = |label: &str, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, _| {
+                let (task_source_kind, template) = inventory
+                    .worktree_templates_from_settings(Some(worktree_id))
+                    .chain(inventory.global_templates_from_settings())
+                    .find(|(_, template)| template.label == label)
+                    .unwrap();
+                let mut resolved_task = template
+                    .resolve_task(&task_source_kind.to_id_base(), &TaskContext::default())
+                    .unwrap();
+                inventory
+                    .resolve_dependencies(&task_source_kind, &mut resolved_task)
+                    .map(|()| resolved_task.resolved.unwrap())
+            })
+        };
+
+        let check = resolve("check", cx).unwrap();
+        assert_eq!(check.depends_order, task::DependsOrder::Parallel);
+        let dependencies = check
+            .dependencies
+            .iter()
+            .map(|dependency| dependency.command.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dependencies,
+            ["cargo build", "cargo clippy"],
+            "Tasks from the same source should be preferred over global ones"
+        );
+        assert_eq!(check.dependencies[0].dependencies.len(), 1);
+        assert_eq!(
+            check.dependencies[0].dependencies[0].command,
+            "cargo clippy"
+        );
+
+        assert!(
+            resolve("build and test", cx).is_err(),
+            "Unknown dependencies should fail the resolution"
+        );
+        assert!(
+            resolve("cycle", cx).is_err(),
+            "Cyclic dependencies should fail the resolution"
+        );
+    }
+
     fn init_test(_cx: &mut TestAppContext) {
         if std::env::var("RUST_LOG").is_ok() {
             env_logger::try_init().ok();
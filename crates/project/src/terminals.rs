@@ -1,9 +1,13 @@
 use crate::Project;
 use anyhow::{Context as _, Result};
 use collections::HashMap;
+use futures::{
+    channel::mpsc::{unbounded, UnboundedReceiver},
+    StreamExt as _,
+};
 use gpui::{AnyWindowHandle, AppContext, Context, Entity, Model, ModelContext, Task, WeakModel};
 use itertools::Itertools;
-use language::LanguageName;
+use language::{Diagnostic, DiagnosticEntry, LanguageName, PointUtf16, Unclipped};
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
@@ -13,7 +17,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use task::{Shell, SpawnInTerminal};
+use task::{Problem, ProblemMatcher, ProblemSeverity, Shell, SpawnInTerminal};
 use terminal::{
     terminal_settings::{self, TerminalSettings, VenvSettings},
     TaskState, TaskStatus, Terminal, TerminalBuilder,
@@ -83,6 +87,18 @@ impl Project {
         kind: TerminalKind,
         window: AnyWindowHandle,
         cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<Terminal>>> {
+        self.create_terminal_with_scrollback(kind, None, window, cx)
+    }
+
+    /// Creates a terminal that shows the given scrollback, saved from a previous terminal,
+    /// above the prompt of its shell.
+    pub fn create_terminal_with_scrollback(
+        &mut self,
+        kind: TerminalKind,
+        restored_scrollback: Option<String>,
+        window: AnyWindowHandle,
+        cx: &mut ModelContext<Self>,
     ) -> Task<Result<Model<Terminal>>> {
         let path: Option<Arc<Path>> = match &kind {
             TerminalKind::Shell(path) => path.as_ref().map(|path| Arc::from(path.as_ref())),
@@ -117,7 +133,13 @@ impl Project {
                 None
             };
             project.update(&mut cx, |project, cx| {
-                project.create_terminal_with_venv(kind, python_venv_directory, window, cx)
+                project.create_terminal_with_venv(
+                    kind,
+                    python_venv_directory,
+                    restored_scrollback,
+                    window,
+                    cx,
+                )
             })?
         })
     }
@@ -126,6 +148,7 @@ impl Project {
         &mut self,
         kind: TerminalKind,
         python_venv_directory: Option<PathBuf>,
+        restored_scrollback: Option<String>,
         window: AnyWindowHandle,
         cx: &mut ModelContext<Self>,
     ) -> Result<Model<Terminal>> {
@@ -172,6 +195,8 @@ impl Project {
         };
 
         let mut python_venv_activate_command = None;
+        let mut task_problem_matcher = None;
+        let mut output_lines_tx = None;
 
         let (spawn_task, shell) = match kind {
             TerminalKind::Shell(_) => {
@@ -207,6 +232,12 @@ impl Project {
                 }
             }
             TerminalKind::Task(spawn_task) => {
+                if let Some(problem_matcher) = spawn_task.problem_matcher.clone() {
+                    let (lines_tx, lines_rx) = unbounded();
+                    output_lines_tx = Some(lines_tx);
+                    task_problem_matcher =
+                        Some((spawn_task.full_label.clone(), problem_matcher, lines_rx));
+                }
                 let task_state = Some(TaskState {
                     id: spawn_task.id,
                     full_label: spawn_task.full_label,
@@ -216,6 +247,7 @@ impl Project {
                     status: TaskStatus::Running,
                     show_summary: spawn_task.show_summary,
                     show_command: spawn_task.show_command,
+                    last_command_exit_code: None,
                     completion_rx,
                 });
 
@@ -267,6 +299,7 @@ impl Project {
                 }
             }
         };
+        let task_cwd = local_path.clone();
         TerminalBuilder::new(
             local_path.map(|path| path.to_path_buf()),
             python_venv_directory,
@@ -276,9 +309,11 @@ impl Project {
             settings.cursor_shape.unwrap_or_default(),
             settings.alternate_scroll,
             settings.max_scroll_history_lines,
+            restored_scrollback,
             ssh_details.is_some(),
             window,
             completion_tx,
+            output_lines_tx,
             cx,
         )
         .map(|builder| {
@@ -305,10 +340,69 @@ impl Project {
             if let Some(activate_command) = python_venv_activate_command {
                 this.activate_python_virtual_environment(activate_command, &terminal_handle, cx);
             }
+            if let Some((task_label, problem_matcher, output_lines)) = task_problem_matcher {
+                this.report_task_problems(task_label, problem_matcher, output_lines, task_cwd, cx);
+            }
             terminal_handle
         })
     }
 
+    /// Clears the diagnostics reported by the previous run of the task, matches the lines
+    /// of the task's output as they are printed, and publishes the problems once it finishes.
+    fn report_task_problems(
+        &mut self,
+        task_label: String,
+        problem_matcher: ProblemMatcher,
+        mut output_lines: UnboundedReceiver<String>,
+        cwd: Option<Arc<Path>>,
+        cx: &mut ModelContext<Project>,
+    ) {
+        if !self.is_local() {
+            return;
+        }
+
+        let lsp_store = self.lsp_store.clone();
+        lsp_store
+            .update(cx, |lsp_store, cx| {
+                lsp_store.update_task_diagnostics(&task_label, HashMap::default(), cx)
+            })
+            .log_err();
+
+        // The output lines end when the task's process exits and the terminal closes its PTY.
+        let problems = cx.background_executor().spawn(async move {
+            let mut matcher = problem_matcher.line_matcher()?;
+            let mut problems = Vec::new();
+            while let Some(line) = output_lines.next().await {
+                problems.extend(matcher.push_line(line));
+            }
+            problems.extend(matcher.finish());
+            anyhow::Ok(problems)
+        });
+        cx.spawn(|_, mut cx| async move {
+            let problems = problems.await?;
+
+            let mut diagnostics = HashMap::<PathBuf, Vec<_>>::default();
+            for (group_id, problem) in problems.into_iter().enumerate() {
+                let path = match &cwd {
+                    Some(cwd) => cwd.join(&problem.path),
+                    None => PathBuf::from(&problem.path),
+                };
+                if path.is_relative() {
+                    continue;
+                }
+                diagnostics
+                    .entry(path)
+                    .or_default()
+                    .push(problem_diagnostic_entry(problem, &task_label, group_id));
+            }
+
+            lsp_store.update(&mut cx, |lsp_store, cx| {
+                lsp_store.update_task_diagnostics(&task_label, diagnostics, cx)
+            })?
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn python_venv_directory(
         &self,
         abs_path: Arc<Path>,
@@ -558,3 +652,39 @@ mod tests {
         assert_eq!(env.get("OTHER").unwrap(), "aaa");
     }
 }
+
+fn problem_diagnostic_entry(
+    problem: Problem,
+    task_label: &str,
+    group_id: usize,
+) -> DiagnosticEntry<Unclipped<PointUtf16>> {
+    let start = PointUtf16::new(
+        problem.line.saturating_sub(1),
+        problem.column.unwrap_or(1).saturating_sub(1),
+    );
+    let end = match (problem.end_line, problem.end_column) {
+        (Some(line), Some(column)) => {
+            PointUtf16::new(line.saturating_sub(1), column.saturating_sub(1))
+        }
+        (None, Some(column)) => PointUtf16::new(start.row, column.saturating_sub(1)),
+        _ => start,
+    };
+    let severity = match problem.severity {
+        ProblemSeverity::Error => lsp::DiagnosticSeverity::ERROR,
+        ProblemSeverity::Warning => lsp::DiagnosticSeverity::WARNING,
+        ProblemSeverity::Info => lsp::DiagnosticSeverity::INFORMATION,
+        ProblemSeverity::Hint => lsp::DiagnosticSeverity::HINT,
+    };
+    DiagnosticEntry {
+        range: Unclipped(start)..Unclipped(end.max(start)),
+        diagnostic: Diagnostic {
+            source: Some(task_label.to_string()),
+            code: problem.code,
+            severity,
+            message: problem.message,
+            group_id,
+            is_primary: true,
+            ..Diagnostic::default()
+        },
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    ops::Range,
     path::{Path, PathBuf},
     sync::{atomic::AtomicUsize, Arc},
 };
@@ -10,6 +11,15 @@ use futures::{
     future::{BoxFuture, Shared},
     FutureExt, SinkExt,
 };
+use git::{
+    history::{CommitFileDiff, LogEntry},
+    parse_git_remote_url,
+    repository::{DiffBase, GitRepository, RepoPath},
+    stash::{StashAction, StashEntry},
+    status::StageStatus,
+    BranchUrlKind, BuildCompareParams, BuildCreatePullRequestParams, GitHostingProviderRegistry,
+    Oid,
+};
 use gpui::{
     AppContext, AsyncAppContext, EntityId, EventEmitter, Model, ModelContext, Task, WeakModel,
 };
@@ -23,8 +33,9 @@ use smol::{
     stream::StreamExt,
 };
 use text::ReplicaId;
+use url::Url;
 use util::{paths::SanitizedPath, ResultExt};
-use worktree::{Entry, ProjectEntryId, Worktree, WorktreeId, WorktreeSettings};
+use worktree::{Entry, LocalWorktree, ProjectEntryId, Worktree, WorktreeId, WorktreeSettings};
 
 use crate::{search::SearchQuery, LspStore, ProjectPath};
 
@@ -74,6 +85,21 @@ impl WorktreeStore {
         client.add_model_request_handler(Self::handle_expand_project_entry);
         client.add_model_request_handler(Self::handle_git_branches);
         client.add_model_request_handler(Self::handle_update_branch);
+        client.add_model_request_handler(Self::handle_git_stage_statuses);
+        client.add_model_request_handler(Self::handle_stage_git_paths);
+        client.add_model_request_handler(Self::handle_unstage_git_paths);
+        client.add_model_request_handler(Self::handle_discard_git_paths);
+        client.add_model_request_handler(Self::handle_set_git_index_text);
+        client.add_model_request_handler(Self::handle_unstage_git_rows);
+        client.add_model_request_handler(Self::handle_commit_git_changes);
+        client.add_model_request_handler(Self::handle_git_file_history);
+        client.add_model_request_handler(Self::handle_git_changed_paths);
+        client.add_model_request_handler(Self::handle_git_file_at_commit);
+        client.add_model_request_handler(Self::handle_git_commit_diff);
+        client.add_model_request_handler(Self::handle_git_stash_list);
+        client.add_model_request_handler(Self::handle_git_stash_push);
+        client.add_model_request_handler(Self::handle_git_stash_action);
+        client.add_model_request_handler(Self::handle_git_branch_url);
     }
 
     pub fn local(retain_worktrees: bool, fs: Arc<dyn Fs>) -> Self {
@@ -984,182 +1010,1403 @@ impl WorktreeStore {
         }
     }
 
-    async fn filter_paths(
-        fs: &Arc<dyn Fs>,
-        mut input: Receiver<MatchingEntry>,
-        query: &SearchQuery,
-    ) -> Result<()> {
-        while let Some(mut entry) = input.next().await {
-            let abs_path = entry.worktree_path.join(&entry.path.path);
-            let Some(file) = fs.open_sync(&abs_path).await.log_err() else {
-                continue;
-            };
-            if query.detect(file).unwrap_or(false) {
-                entry.respond.send(entry.path).await?
+    /// Returns whether the changes of the given paths are staged, for the paths that have changes.
+    /// When no paths are given, returns every changed path in the worktree's repositories,
+    /// including deleted files, which have no worktree entry.
+    pub fn git_stage_statuses(
+        &self,
+        worktree_id: WorktreeId,
+        paths: Option<Vec<Arc<Path>>>,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<(Arc<Path>, StageStatus)>>> {
+        let Some(worktree) = self.worktree_for_id(worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for id {worktree_id:?}")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let Some(paths) = paths else {
+                    let snapshot = local_worktree.snapshot();
+                    let repositories = snapshot
+                        .repositories()
+                        .filter_map(|(_, repo_entry)| {
+                            let local_repo = local_worktree.get_local_repo(repo_entry)?;
+                            Some((repo_entry.clone(), local_repo.repo().clone()))
+                        })
+                        .collect::<Vec<_>>();
+
+                    return cx.background_executor().spawn(async move {
+                        let mut statuses = Vec::new();
+                        for (repo_entry, repo) in repositories {
+                            for (repo_path, status) in repo.stage_statuses(&[PathBuf::new()])? {
+                                if let Some(path) = repo_entry.unrelativize(&snapshot, &repo_path) {
+                                    statuses.push((path, status));
+                                }
+                            }
+                        }
+                        Ok(statuses)
+                    });
+                };
+
+                let repositories = match local_repositories_for_paths(local_worktree, &paths) {
+                    Ok(repositories) => repositories,
+                    Err(error) => return Task::ready(Err(error)),
+                };
+
+                cx.background_executor().spawn(async move {
+                    let mut statuses = Vec::new();
+                    for (repo, paths) in repositories {
+                        let paths = paths.into_iter().collect::<HashMap<_, _>>();
+                        for (repo_path, status) in repo.stage_statuses(&[PathBuf::new()])? {
+                            if let Some(path) = paths.get(&repo_path) {
+                                statuses.push((path.clone(), status));
+                            }
+                        }
+                    }
+                    Ok(statuses)
+                })
             }
-        }
+            Worktree::Remote(remote_worktree) => {
+                let request = remote_worktree.client().request(proto::GitStageStatuses {
+                    project_id: remote_worktree.project_id(),
+                    worktree_id: worktree_id.to_proto(),
+                    all_changed_paths: paths.is_none(),
+                    paths: paths
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|path| path.to_string_lossy().to_string())
+                        .collect(),
+                });
 
-        Ok(())
-    }
+                cx.background_executor().spawn(async move {
+                    let response = request.await?;
 
-    pub async fn handle_create_project_entry(
-        this: Model<Self>,
-        envelope: TypedEnvelope<proto::CreateProjectEntry>,
-        mut cx: AsyncAppContext,
-    ) -> Result<proto::ProjectEntryResponse> {
-        let worktree = this.update(&mut cx, |this, cx| {
-            let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
-            this.worktree_for_id(worktree_id, cx)
-                .ok_or_else(|| anyhow!("worktree not found"))
-        })??;
-        Worktree::handle_create_entry(worktree, envelope.payload, cx).await
+                    let statuses = response
+                        .statuses
+                        .into_iter()
+                        .map(|status| {
+                            let stage_status = match status.status() {
+                                proto::git_stage_status::Status::Staged => StageStatus::Staged,
+                                proto::git_stage_status::Status::Unstaged => StageStatus::Unstaged,
+                                proto::git_stage_status::Status::PartiallyStaged => {
+                                    StageStatus::PartiallyStaged
+                                }
+                            };
+                            (Arc::from(Path::new(&status.path)), stage_status)
+                        })
+                        .collect();
+
+                    Ok(statuses)
+                })
+            }
+        }
     }
 
-    pub async fn handle_rename_project_entry(
-        this: Model<super::Project>,
-        envelope: TypedEnvelope<proto::RenameProjectEntry>,
-        mut cx: AsyncAppContext,
-    ) -> Result<proto::ProjectEntryResponse> {
-        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
-        let (worktree_id, worktree, old_path, is_dir) = this
-            .update(&mut cx, |this, cx| {
-                this.worktree_store
-                    .read(cx)
-                    .worktree_and_entry_for_id(entry_id, cx)
-                    .map(|(worktree, entry)| {
-                        (
-                            worktree.read(cx).id(),
-                            worktree,
-                            entry.path.clone(),
-                            entry.is_dir(),
-                        )
-                    })
-            })?
-            .ok_or_else(|| anyhow!("worktree not found"))?;
-        let (old_abs_path, new_abs_path) = {
-            let root_path = worktree.update(&mut cx, |this, _| this.abs_path())?;
-            (
-                root_path.join(&old_path),
-                root_path.join(&envelope.payload.new_path),
-            )
-        };
-        let lsp_store = this
-            .update(&mut cx, |this, _| this.lsp_store())?
-            .downgrade();
-        LspStore::will_rename_entry(
-            lsp_store,
+    /// Stages the changes of the given paths, including deletions.
+    pub fn stage_git_paths(
+        &self,
+        worktree_id: WorktreeId,
+        paths: Vec<Arc<Path>>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.update_git_paths(
             worktree_id,
-            &old_abs_path,
-            &new_abs_path,
-            is_dir,
-            cx.clone(),
-        )
-        .await;
-        let response = Worktree::handle_rename_entry(worktree, envelope.payload, cx.clone()).await;
-        this.update(&mut cx, |this, cx| {
-            this.lsp_store().read(cx).did_rename_entry(
+            paths,
+            |repo, paths| repo.stage_paths(paths),
+            |project_id, worktree_id, paths| proto::StageGitPaths {
+                project_id,
                 worktree_id,
-                &old_abs_path,
-                &new_abs_path,
-                is_dir,
-            );
-        })
-        .ok();
-        response
+                paths,
+            },
+            cx,
+        )
     }
 
-    pub async fn handle_copy_project_entry(
-        this: Model<Self>,
-        envelope: TypedEnvelope<proto::CopyProjectEntry>,
-        mut cx: AsyncAppContext,
-    ) -> Result<proto::ProjectEntryResponse> {
-        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
-        let worktree = this.update(&mut cx, |this, cx| {
-            this.worktree_for_entry(entry_id, cx)
-                .ok_or_else(|| anyhow!("worktree not found"))
-        })??;
-        Worktree::handle_copy_entry(worktree, envelope.payload, cx).await
+    /// Resets the index entries of the given paths to the HEAD commit.
+    pub fn unstage_git_paths(
+        &self,
+        worktree_id: WorktreeId,
+        paths: Vec<Arc<Path>>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.update_git_paths(
+            worktree_id,
+            paths,
+            |repo, paths| repo.unstage_paths(paths),
+            |project_id, worktree_id, paths| proto::UnstageGitPaths {
+                project_id,
+                worktree_id,
+                paths,
+            },
+            cx,
+        )
     }
 
-    pub async fn handle_delete_project_entry(
-        this: Model<Self>,
-        envelope: TypedEnvelope<proto::DeleteProjectEntry>,
-        mut cx: AsyncAppContext,
-    ) -> Result<proto::ProjectEntryResponse> {
-        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
-        let worktree = this.update(&mut cx, |this, cx| {
-            this.worktree_for_entry(entry_id, cx)
-                .ok_or_else(|| anyhow!("worktree not found"))
-        })??;
-        Worktree::handle_delete_entry(worktree, envelope.payload, cx).await
+    /// Restores the given paths to the HEAD commit, deleting the files that are not committed.
+    pub fn discard_git_paths(
+        &self,
+        worktree_id: WorktreeId,
+        paths: Vec<Arc<Path>>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.update_git_paths(
+            worktree_id,
+            paths,
+            |repo, paths| repo.discard_paths(paths),
+            |project_id, worktree_id, paths| proto::DiscardGitPaths {
+                project_id,
+                worktree_id,
+                paths,
+            },
+            cx,
+        )
     }
 
-    pub async fn handle_expand_project_entry(
-        this: Model<Self>,
-        envelope: TypedEnvelope<proto::ExpandProjectEntry>,
-        mut cx: AsyncAppContext,
-    ) -> Result<proto::ExpandProjectEntryResponse> {
-        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
-        let worktree = this
-            .update(&mut cx, |this, cx| this.worktree_for_entry(entry_id, cx))?
-            .ok_or_else(|| anyhow!("invalid request"))?;
-        Worktree::handle_expand_entry(worktree, envelope.payload, cx).await
+    fn update_git_paths<T: proto::RequestMessage<Response = proto::Ack>>(
+        &self,
+        worktree_id: WorktreeId,
+        paths: Vec<Arc<Path>>,
+        local_update: fn(&dyn GitRepository, &[RepoPath]) -> Result<()>,
+        remote_request: fn(u64, u64, Vec<String>) -> T,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let Some(worktree) = self.worktree_for_id(worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for id {worktree_id:?}")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let repositories = match local_repositories_for_paths(local_worktree, &paths) {
+                    Ok(repositories) => repositories,
+                    Err(error) => return Task::ready(Err(error)),
+                };
+
+                cx.background_executor().spawn(async move {
+                    for (repo, paths) in repositories {
+                        let repo_paths = paths
+                            .into_iter()
+                            .map(|(repo_path, _)| repo_path)
+                            .collect::<Vec<_>>();
+                        local_update(repo.as_ref(), &repo_paths)?;
+                    }
+                    Ok(())
+                })
+            }
+            Worktree::Remote(remote_worktree) => {
+                let request = remote_worktree.client().request(remote_request(
+                    remote_worktree.project_id(),
+                    worktree_id.to_proto(),
+                    paths
+                        .iter()
+                        .map(|path| path.to_string_lossy().to_string())
+                        .collect(),
+                ));
+
+                cx.background_executor().spawn(async move {
+                    request.await?;
+                    Ok(())
+                })
+            }
+        }
     }
 
-    pub async fn handle_git_branches(
-        this: Model<Self>,
-        branches: TypedEnvelope<proto::GitBranches>,
-        cx: AsyncAppContext,
-    ) -> Result<proto::GitBranchesResponse> {
-        let project_path = branches
-            .payload
-            .repository
-            .clone()
-            .context("Invalid GitBranches call")?;
-        let project_path = ProjectPath {
-            worktree_id: WorktreeId::from_proto(project_path.worktree_id),
-            path: Path::new(&project_path.path).into(),
+    /// Sets the staged contents of a file, or removes it from the index if `text` is `None`.
+    pub fn set_git_index_text(
+        &self,
+        project_path: ProjectPath,
+        text: Option<String>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let Some(worktree) = self.worktree_for_id(project_path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for ProjectPath")));
         };
 
-        let branches = this
-            .read_with(&cx, |this, cx| this.branches(project_path, cx))?
-            .await?;
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let (repo, repo_path) =
+                    match local_repository_for_path(local_worktree, &project_path.path) {
+                        Ok(repository) => repository,
+                        Err(error) => return Task::ready(Err(error)),
+                    };
 
-        Ok(proto::GitBranchesResponse {
-            branches: branches
-                .into_iter()
-                .map(|branch| proto::Branch {
-                    is_head: branch.is_head,
-                    name: branch.name.to_string(),
-                    unix_timestamp: branch.unix_timestamp.map(|timestamp| timestamp as u64),
+                cx.background_executor()
+                    .spawn(async move { repo.set_index_text(&repo_path, text) })
+            }
+            Worktree::Remote(remote_worktree) => {
+                let request = remote_worktree.client().request(proto::SetGitIndexText {
+                    project_id: remote_worktree.project_id(),
+                    path: Some(proto::ProjectPath {
+                        worktree_id: project_path.worktree_id.to_proto(),
+                        path: project_path.path.to_string_lossy().to_string(),
+                    }),
+                    text,
+                });
+
+                cx.background_executor().spawn(async move {
+                    request.await?;
+                    Ok(())
                 })
-                .collect(),
-        })
+            }
+        }
     }
 
-    pub async fn handle_update_branch(
-        this: Model<Self>,
-        update_branch: TypedEnvelope<proto::UpdateGitBranch>,
-        cx: AsyncAppContext,
-    ) -> Result<proto::Ack> {
-        let project_path = update_branch
-            .payload
-            .repository
-            .clone()
-            .context("Invalid GitBranches call")?;
-        let project_path = ProjectPath {
-            worktree_id: WorktreeId::from_proto(project_path.worktree_id),
-            path: Path::new(&project_path.path).into(),
+    /// Unstages the changes of the given rows of a file's staged text, by reverting them
+    /// to their contents in the HEAD commit.
+    pub fn unstage_git_rows(
+        &self,
+        project_path: ProjectPath,
+        rows: Vec<Range<u32>>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let Some(worktree) = self.worktree_for_id(project_path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for ProjectPath")));
         };
-        let new_branch = update_branch.payload.branch_name;
 
-        this.read_with(&cx, |this, cx| {
-            this.update_or_create_branch(project_path, new_branch, cx)
-        })?
-        .await?;
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let (repo, repo_path) =
+                    match local_repository_for_path(local_worktree, &project_path.path) {
+                        Ok(repository) => repository,
+                        Err(error) => return Task::ready(Err(error)),
+                    };
 
-        Ok(proto::Ack {})
+                cx.background_executor().spawn(async move {
+                    let Some(staged_text) = repo.load_index_text(&repo_path) else {
+                        return Ok(());
+                    };
+                    let committed_text = repo.load_committed_text(&repo_path);
+                    let new_staged_text = git::diff::revert_rows(
+                        committed_text.as_deref().unwrap_or_default(),
+                        &staged_text,
+                        &rows,
+                    );
+                    if committed_text.is_none() && new_staged_text.is_empty() {
+                        repo.set_index_text(&repo_path, None)
+                    } else {
+                        repo.set_index_text(&repo_path, Some(new_staged_text))
+                    }
+                })
+            }
+            Worktree::Remote(remote_worktree) => {
+                let request = remote_worktree.client().request(proto::UnstageGitRows {
+                    project_id: remote_worktree.project_id(),
+                    path: Some(proto::ProjectPath {
+                        worktree_id: project_path.worktree_id.to_proto(),
+                        path: project_path.path.to_string_lossy().to_string(),
+                    }),
+                    rows: rows
+                        .into_iter()
+                        .map(|rows| proto::Range {
+                            start: rows.start as u64,
+                            end: rows.end as u64,
+                        })
+                        .collect(),
+                });
+
+                cx.background_executor().spawn(async move {
+                    request.await?;
+                    Ok(())
+                })
+            }
+        }
+    }
+
+    /// Commits the staged changes of the repository containing the given path.
+    pub fn commit_git_changes(
+        &self,
+        repository: ProjectPath,
+        message: String,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let Some(worktree) = self.worktree_for_id(repository.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for ProjectPath")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let (repo, _) = match local_repository_for_path(local_worktree, &repository.path) {
+                    Ok(repository) => repository,
+                    Err(error) => return Task::ready(Err(error)),
+                };
+
+                cx.background_executor()
+                    .spawn(async move { repo.commit(&message) })
+            }
+            Worktree::Remote(remote_worktree) => {
+                let request = remote_worktree.client().request(proto::CommitGitChanges {
+                    project_id: remote_worktree.project_id(),
+                    repository: Some(proto::ProjectPath {
+                        worktree_id: repository.worktree_id.to_proto(),
+                        path: repository.path.to_string_lossy().to_string(),
+                    }),
+                    message,
+                });
+
+                cx.background_executor().spawn(async move {
+                    request.await?;
+                    Ok(())
+                })
+            }
+        }
+    }
+
+    /// Lists the commits that changed a file, or the given rows of it, newest first.
+    pub fn git_file_history(
+        &self,
+        project_path: ProjectPath,
+        rows: Option<Range<u32>>,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<LogEntry>>> {
+        let Some(worktree) = self.worktree_for_id(project_path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for ProjectPath")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let (repo, repo_path) =
+                    match local_repository_for_path(local_worktree, &project_path.path) {
+                        Ok(repository) => repository,
+                        Err(error) => return Task::ready(Err(error)),
+                    };
+
+                cx.background_executor()
+                    .spawn(async move { repo.file_history(&repo_path, rows) })
+            }
+            Worktree::Remote(remote_worktree) => {
+                let request = remote_worktree.client().request(proto::GitFileHistory {
+                    project_id: remote_worktree.project_id(),
+                    path: Some(proto::ProjectPath {
+                        worktree_id: project_path.worktree_id.to_proto(),
+                        path: project_path.path.to_string_lossy().to_string(),
+                    }),
+                    rows: rows.map(|rows| proto::Range {
+                        start: rows.start as u64,
+                        end: rows.end as u64,
+                    }),
+                });
+
+                cx.background_executor().spawn(async move {
+                    let response = request.await?;
+
+                    response
+                        .entries
+                        .into_iter()
+                        .map(|entry| {
+                            anyhow::Ok(LogEntry {
+                                sha: entry.sha.parse()?,
+                                author_name: entry.author_name,
+                                author_email: entry.author_email,
+                                author_time: entry.author_time,
+                                summary: entry.summary,
+                                path: RepoPath::new(PathBuf::from(entry.path)),
+                            })
+                        })
+                        .collect()
+                })
+            }
+        }
+    }
+
+    /// Loads the contents of a file in the given commit of the repository containing the given path.
+    /// The file's path is relative to the repository, as in the [`LogEntry`] of the commit.
+    pub fn git_file_at_commit(
+        &self,
+        repository: ProjectPath,
+        path: RepoPath,
+        sha: Oid,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        let Some(worktree) = self.worktree_for_id(repository.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for ProjectPath")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let (repo, _) = match local_repository_for_path(local_worktree, &repository.path) {
+                    Ok(repository) => repository,
+                    Err(error) => return Task::ready(Err(error)),
+                };
+
+                cx.background_executor()
+                    .spawn(async move { repo.load_text_at_commit(sha, &path) })
+            }
+            Worktree::Remote(remote_worktree) => {
+                let request = remote_worktree.client().request(proto::GitFileAtCommit {
+                    project_id: remote_worktree.project_id(),
+                    repository: Some(proto::ProjectPath {
+                        worktree_id: repository.worktree_id.to_proto(),
+                        path: repository.path.to_string_lossy().to_string(),
+                    }),
+                    sha: sha.to_string(),
+                    path: path.to_string_lossy().to_string(),
+                });
+
+                cx.background_executor()
+                    .spawn(async move { Ok(request.await?.text) })
+            }
+        }
+    }
+
+    /// Returns the files changed by a commit of the repository containing the given path.
+    pub fn git_commit_diff(
+        &self,
+        repository: ProjectPath,
+        sha: Oid,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<CommitFileDiff>>> {
+        let Some(worktree) = self.worktree_for_id(repository.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for ProjectPath")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let (repo, _) = match local_repository_for_path(local_worktree, &repository.path) {
+                    Ok(repository) => repository,
+                    Err(error) => return Task::ready(Err(error)),
+                };
+
+                cx.background_executor()
+                    .spawn(async move { repo.commit_diff(sha) })
+            }
+            Worktree::Remote(remote_worktree) => {
+                let request = remote_worktree.client().request(proto::GitCommitDiff {
+                    project_id: remote_worktree.project_id(),
+                    repository: Some(proto::ProjectPath {
+                        worktree_id: repository.worktree_id.to_proto(),
+                        path: repository.path.to_string_lossy().to_string(),
+                    }),
+                    sha: sha.to_string(),
+                });
+
+                cx.background_executor().spawn(async move {
+                    let response = request.await?;
+
+                    let files = response
+                        .files
+                        .into_iter()
+                        .map(|file| CommitFileDiff {
+                            path: RepoPath::new(PathBuf::from(file.path)),
+                            old_text: file.old_text,
+                            new_text: file.new_text,
+                        })
+                        .collect();
+
+                    Ok(files)
+                })
+            }
+        }
+    }
+
+    /// Lists the files of a worktree whose contents differ from the given diff base,
+    /// across all of the worktree's repositories.
+    pub fn git_changed_paths(
+        &self,
+        worktree_id: WorktreeId,
+        diff_base: DiffBase,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<Arc<Path>>>> {
+        let Some(worktree) = self.worktree_for_id(worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for WorktreeId")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let snapshot = local_worktree.snapshot();
+                let repositories = snapshot
+                    .repositories()
+                    .filter_map(|(_, repo_entry)| {
+                        let local_repo = local_worktree.get_local_repo(repo_entry)?;
+                        Some((repo_entry.clone(), local_repo.repo().clone()))
+                    })
+                    .collect::<Vec<_>>();
+
+                cx.background_executor().spawn(async move {
+                    let mut changed_paths = Vec::new();
+                    for (repo_entry, repo) in repositories {
+                        let repo_paths = match diff_base.resolve(repo.as_ref())? {
+                            Some(sha) => repo.changed_paths_since(sha)?,
+                            None => repo
+                                .status(&[PathBuf::new()])?
+                                .entries
+                                .iter()
+                                .map(|(repo_path, _)| repo_path.clone())
+                                .collect(),
+                        };
+                        changed_paths.extend(
+                            repo_paths
+                                .iter()
+                                .filter_map(|path| repo_entry.unrelativize(&snapshot, path)),
+                        );
+                    }
+                    Ok(changed_paths)
+                })
+            }
+            Worktree::Remote(remote_worktree) => {
+                let request = remote_worktree.client().request(proto::GitChangedPaths {
+                    project_id: remote_worktree.project_id(),
+                    worktree_id: worktree_id.to_proto(),
+                    diff_base: Some(serialize_diff_base(&diff_base)),
+                });
+
+                cx.background_executor()
+                    .spawn(async move { Ok(paths_from_proto(request.await?.paths)) })
+            }
+        }
+    }
+
+    /// Lists the stash entries of the repository containing the given path, newest first.
+    pub fn git_stash_list(
+        &self,
+        repository: ProjectPath,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<StashEntry>>> {
+        let Some(worktree) = self.worktree_for_id(repository.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for ProjectPath")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let (repo, _) = match local_repository_for_path(local_worktree, &repository.path) {
+                    Ok(repository) => repository,
+                    Err(error) => return Task::ready(Err(error)),
+                };
+
+                cx.background_executor()
+                    .spawn(async move { repo.stash_list() })
+            }
+            Worktree::Remote(remote_worktree) => {
+                let request = remote_worktree.client().request(proto::GitStashList {
+                    project_id: remote_worktree.project_id(),
+                    repository: Some(proto::ProjectPath {
+                        worktree_id: repository.worktree_id.to_proto(),
+                        path: repository.path.to_string_lossy().to_string(),
+                    }),
+                });
+
+                cx.background_executor().spawn(async move {
+                    let response = request.await?;
+
+                    response
+                        .entries
+                        .into_iter()
+                        .map(|entry| {
+                            anyhow::Ok(StashEntry {
+                                index: entry.index as usize,
+                                sha: entry.sha.parse()?,
+                                message: entry.message,
+                            })
+                        })
+                        .collect()
+                })
+            }
+        }
+    }
+
+    /// Stashes the changes of the repository containing the given path.
+    pub fn git_stash_push(
+        &self,
+        repository: ProjectPath,
+        message: Option<String>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let Some(worktree) = self.worktree_for_id(repository.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for ProjectPath")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let (repo, _) = match local_repository_for_path(local_worktree, &repository.path) {
+                    Ok(repository) => repository,
+                    Err(error) => return Task::ready(Err(error)),
+                };
+
+                cx.background_executor()
+                    .spawn(async move { repo.stash_push(message.as_deref()) })
+            }
+            Worktree::Remote(remote_worktree) => {
+                let request = remote_worktree.client().request(proto::GitStashPush {
+                    project_id: remote_worktree.project_id(),
+                    repository: Some(proto::ProjectPath {
+                        worktree_id: repository.worktree_id.to_proto(),
+                        path: repository.path.to_string_lossy().to_string(),
+                    }),
+                    message,
+                });
+
+                cx.background_executor().spawn(async move {
+                    request.await?;
+                    Ok(())
+                })
+            }
+        }
+    }
+
+    /// Pops, applies or drops the stash entry with the given commit, of the repository containing the given path.
+    pub fn git_stash_action(
+        &self,
+        repository: ProjectPath,
+        sha: Oid,
+        action: StashAction,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let Some(worktree) = self.worktree_for_id(repository.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for ProjectPath")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let (repo, _) = match local_repository_for_path(local_worktree, &repository.path) {
+                    Ok(repository) => repository,
+                    Err(error) => return Task::ready(Err(error)),
+                };
+
+                cx.background_executor().spawn(async move {
+                    match action {
+                        StashAction::Pop => repo.stash_pop(sha),
+                        StashAction::Apply => repo.stash_apply(sha),
+                        StashAction::Drop => repo.stash_drop(sha),
+                    }
+                })
+            }
+            Worktree::Remote(remote_worktree) => {
+                let action = match action {
+                    StashAction::Pop => proto::git_stash_action::Action::Pop,
+                    StashAction::Apply => proto::git_stash_action::Action::Apply,
+                    StashAction::Drop => proto::git_stash_action::Action::Drop,
+                };
+                let request = remote_worktree.client().request(proto::GitStashAction {
+                    project_id: remote_worktree.project_id(),
+                    repository: Some(proto::ProjectPath {
+                        worktree_id: repository.worktree_id.to_proto(),
+                        path: repository.path.to_string_lossy().to_string(),
+                    }),
+                    sha: sha.to_string(),
+                    action: action as i32,
+                });
+
+                cx.background_executor().spawn(async move {
+                    request.await?;
+                    Ok(())
+                })
+            }
+        }
+    }
+
+    /// Returns the URL of the page of the `origin` remote's hosting provider for the current branch
+    /// of the repository containing the given path.
+    pub fn git_branch_url(
+        &self,
+        repository: ProjectPath,
+        kind: BranchUrlKind,
+        cx: &AppContext,
+    ) -> Task<Result<Url>> {
+        let Some(worktree) = self.worktree_for_id(repository.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("No worktree found for ProjectPath")));
+        };
+
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let (repo, _) = match local_repository_for_path(local_worktree, &repository.path) {
+                    Ok(repository) => repository,
+                    Err(error) => return Task::ready(Err(error)),
+                };
+                let provider_registry =
+                    GitHostingProviderRegistry::try_global(cx).unwrap_or_default();
+
+                cx.background_executor().spawn(async move {
+                    const REMOTE_NAME: &str = "origin";
+                    let origin_url = repo
+                        .remote_url(REMOTE_NAME)
+                        .ok_or_else(|| anyhow!("remote \"{REMOTE_NAME}\" not found"))?;
+                    // Branches that were not pushed yet are expected to be pushed under their local name.
+                    let branch = match repo.upstream_branch_name(REMOTE_NAME) {
+                        Some(branch) => branch,
+                        None => repo
+                            .branch_name()
+                            .filter(|branch| branch != "HEAD")
+                            .context("HEAD is not on a branch")?,
+                    };
+
+                    let (provider, remote) =
+                        parse_git_remote_url(provider_registry, &origin_url)
+                            .ok_or_else(|| anyhow!("failed to parse Git remote URL"))?;
+
+                    match kind {
+                        BranchUrlKind::CreatePullRequest => provider
+                            .build_create_pull_request_url(
+                                &remote,
+                                BuildCreatePullRequestParams { branch: &branch },
+                            )
+                            .with_context(|| {
+                                format!("{} does not support pull requests", provider.name())
+                            }),
+                        BranchUrlKind::Compare => {
+                            let base = repo
+                                .remote_default_branch(REMOTE_NAME)
+                                .unwrap_or_else(|| "main".to_string());
+                            provider
+                                .build_compare_url(
+                                    &remote,
+                                    BuildCompareParams {
+                                        base: &base,
+                                        head: &branch,
+                                    },
+                                )
+                                .with_context(|| {
+                                    format!("{} does not support comparing refs", provider.name())
+                                })
+                        }
+                    }
+                })
+            }
+            Worktree::Remote(remote_worktree) => {
+                let kind = match kind {
+                    BranchUrlKind::CreatePullRequest => {
+                        proto::git_branch_url::Kind::CreatePullRequest
+                    }
+                    BranchUrlKind::Compare => proto::git_branch_url::Kind::Compare,
+                };
+                let request = remote_worktree.client().request(proto::GitBranchUrl {
+                    project_id: remote_worktree.project_id(),
+                    repository: Some(proto::ProjectPath {
+                        worktree_id: repository.worktree_id.to_proto(),
+                        path: repository.path.to_string_lossy().to_string(),
+                    }),
+                    kind: kind as i32,
+                });
+
+                cx.background_executor().spawn(async move {
+                    let response = request.await?;
+                    Url::parse(&response.url).context("failed to parse branch URL")
+                })
+            }
+        }
+    }
+
+    async fn filter_paths(
+        fs: &Arc<dyn Fs>,
+        mut input: Receiver<MatchingEntry>,
+        query: &SearchQuery,
+    ) -> Result<()> {
+        while let Some(mut entry) = input.next().await {
+            let abs_path = entry.worktree_path.join(&entry.path.path);
+            let Some(file) = fs.open_sync(&abs_path).await.log_err() else {
+                continue;
+            };
+            if query.detect(file).unwrap_or(false) {
+                entry.respond.send(entry.path).await?
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn handle_create_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::CreateProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let worktree = this.update(&mut cx, |this, cx| {
+            let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+            this.worktree_for_id(worktree_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))
+        })??;
+        Worktree::handle_create_entry(worktree, envelope.payload, cx).await
+    }
+
+    pub async fn handle_rename_project_entry(
+        this: Model<super::Project>,
+        envelope: TypedEnvelope<proto::RenameProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
+        let (worktree_id, worktree, old_path, is_dir) = this
+            .update(&mut cx, |this, cx| {
+                this.worktree_store
+                    .read(cx)
+                    .worktree_and_entry_for_id(entry_id, cx)
+                    .map(|(worktree, entry)| {
+                        (
+                            worktree.read(cx).id(),
+                            worktree,
+                            entry.path.clone(),
+                            entry.is_dir(),
+                        )
+                    })
+            })?
+            .ok_or_else(|| anyhow!("worktree not found"))?;
+        let (old_abs_path, new_abs_path) = {
+            let root_path = worktree.update(&mut cx, |this, _| this.abs_path())?;
+            (
+                root_path.join(&old_path),
+                root_path.join(&envelope.payload.new_path),
+            )
+        };
+        let lsp_store = this
+            .update(&mut cx, |this, _| this.lsp_store())?
+            .downgrade();
+        LspStore::will_rename_entry(
+            lsp_store,
+            worktree_id,
+            &old_abs_path,
+            &new_abs_path,
+            is_dir,
+            cx.clone(),
+        )
+        .await;
+        let response = Worktree::handle_rename_entry(worktree, envelope.payload, cx.clone()).await;
+        this.update(&mut cx, |this, cx| {
+            this.lsp_store().read(cx).did_rename_entry(
+                worktree_id,
+                &old_abs_path,
+                &new_abs_path,
+                is_dir,
+            );
+        })
+        .ok();
+        response
+    }
+
+    pub async fn handle_copy_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::CopyProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
+        let worktree = this.update(&mut cx, |this, cx| {
+            this.worktree_for_entry(entry_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))
+        })??;
+        Worktree::handle_copy_entry(worktree, envelope.payload, cx).await
+    }
+
+    pub async fn handle_delete_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::DeleteProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
+        let worktree = this.update(&mut cx, |this, cx| {
+            this.worktree_for_entry(entry_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))
+        })??;
+        Worktree::handle_delete_entry(worktree, envelope.payload, cx).await
+    }
+
+    pub async fn handle_expand_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ExpandProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ExpandProjectEntryResponse> {
+        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
+        let worktree = this
+            .update(&mut cx, |this, cx| this.worktree_for_entry(entry_id, cx))?
+            .ok_or_else(|| anyhow!("invalid request"))?;
+        Worktree::handle_expand_entry(worktree, envelope.payload, cx).await
+    }
+
+    pub async fn handle_git_branches(
+        this: Model<Self>,
+        branches: TypedEnvelope<proto::GitBranches>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::GitBranchesResponse> {
+        let project_path = branches
+            .payload
+            .repository
+            .clone()
+            .context("Invalid GitBranches call")?;
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_proto(project_path.worktree_id),
+            path: Path::new(&project_path.path).into(),
+        };
+
+        let branches = this
+            .read_with(&cx, |this, cx| this.branches(project_path, cx))?
+            .await?;
+
+        Ok(proto::GitBranchesResponse {
+            branches: branches
+                .into_iter()
+                .map(|branch| proto::Branch {
+                    is_head: branch.is_head,
+                    name: branch.name.to_string(),
+                    unix_timestamp: branch.unix_timestamp.map(|timestamp| timestamp as u64),
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn handle_update_branch(
+        this: Model<Self>,
+        update_branch: TypedEnvelope<proto::UpdateGitBranch>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let project_path = update_branch
+            .payload
+            .repository
+            .clone()
+            .context("Invalid GitBranches call")?;
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_proto(project_path.worktree_id),
+            path: Path::new(&project_path.path).into(),
+        };
+        let new_branch = update_branch.payload.branch_name;
+
+        this.read_with(&cx, |this, cx| {
+            this.update_or_create_branch(project_path, new_branch, cx)
+        })?
+        .await?;
+
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_stage_statuses(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStageStatuses>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::GitStageStatusesResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let paths =
+            (!envelope.payload.all_changed_paths).then(|| paths_from_proto(envelope.payload.paths));
+
+        let statuses = this
+            .read_with(&cx, |this, cx| {
+                this.git_stage_statuses(worktree_id, paths, cx)
+            })?
+            .await?;
+
+        Ok(proto::GitStageStatusesResponse {
+            statuses: statuses
+                .into_iter()
+                .map(|(path, status)| proto::GitStageStatus {
+                    path: path.to_string_lossy().to_string(),
+                    status: match status {
+                        StageStatus::Staged => proto::git_stage_status::Status::Staged,
+                        StageStatus::Unstaged => proto::git_stage_status::Status::Unstaged,
+                        StageStatus::PartiallyStaged => {
+                            proto::git_stage_status::Status::PartiallyStaged
+                        }
+                    } as i32,
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn handle_stage_git_paths(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::StageGitPaths>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let paths = paths_from_proto(envelope.payload.paths);
+        this.read_with(&cx, |this, cx| this.stage_git_paths(worktree_id, paths, cx))?
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_unstage_git_paths(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UnstageGitPaths>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let paths = paths_from_proto(envelope.payload.paths);
+        this.read_with(&cx, |this, cx| {
+            this.unstage_git_paths(worktree_id, paths, cx)
+        })?
+        .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_discard_git_paths(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::DiscardGitPaths>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let paths = paths_from_proto(envelope.payload.paths);
+        this.read_with(&cx, |this, cx| {
+            this.discard_git_paths(worktree_id, paths, cx)
+        })?
+        .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_set_git_index_text(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::SetGitIndexText>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let project_path = envelope
+            .payload
+            .path
+            .clone()
+            .context("Invalid SetGitIndexText call")?;
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_proto(project_path.worktree_id),
+            path: Path::new(&project_path.path).into(),
+        };
+        let text = envelope.payload.text;
+
+        this.read_with(&cx, |this, cx| {
+            this.set_git_index_text(project_path, text, cx)
+        })?
+        .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_unstage_git_rows(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UnstageGitRows>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let project_path = envelope
+            .payload
+            .path
+            .clone()
+            .context("Invalid UnstageGitRows call")?;
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_proto(project_path.worktree_id),
+            path: Path::new(&project_path.path).into(),
+        };
+        let rows = envelope
+            .payload
+            .rows
+            .into_iter()
+            .map(|rows| rows.start as u32..rows.end as u32)
+            .collect();
+
+        this.read_with(&cx, |this, cx| {
+            this.unstage_git_rows(project_path, rows, cx)
+        })?
+        .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_commit_git_changes(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::CommitGitChanges>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let project_path = envelope
+            .payload
+            .repository
+            .clone()
+            .context("Invalid CommitGitChanges call")?;
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_proto(project_path.worktree_id),
+            path: Path::new(&project_path.path).into(),
+        };
+        let message = envelope.payload.message;
+
+        this.read_with(&cx, |this, cx| {
+            this.commit_git_changes(project_path, message, cx)
+        })?
+        .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_file_history(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitFileHistory>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::GitFileHistoryResponse> {
+        let project_path = envelope
+            .payload
+            .path
+            .clone()
+            .context("Invalid GitFileHistory call")?;
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_proto(project_path.worktree_id),
+            path: Path::new(&project_path.path).into(),
+        };
+        let rows = envelope
+            .payload
+            .rows
+            .map(|rows| rows.start as u32..rows.end as u32);
+
+        let entries = this
+            .read_with(&cx, |this, cx| {
+                this.git_file_history(project_path, rows, cx)
+            })?
+            .await?;
+
+        Ok(proto::GitFileHistoryResponse {
+            entries: entries
+                .into_iter()
+                .map(|entry| proto::GitLogEntry {
+                    sha: entry.sha.to_string(),
+                    author_name: entry.author_name,
+                    author_email: entry.author_email,
+                    author_time: entry.author_time,
+                    summary: entry.summary,
+                    path: entry.path.to_string_lossy().to_string(),
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn handle_git_file_at_commit(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitFileAtCommit>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::GitFileAtCommitResponse> {
+        let repository = envelope
+            .payload
+            .repository
+            .clone()
+            .context("Invalid GitFileAtCommit call")?;
+        let repository = ProjectPath {
+            worktree_id: WorktreeId::from_proto(repository.worktree_id),
+            path: Path::new(&repository.path).into(),
+        };
+        let sha = envelope.payload.sha.parse()?;
+        let path = RepoPath::new(PathBuf::from(envelope.payload.path));
+
+        let text = this
+            .read_with(&cx, |this, cx| {
+                this.git_file_at_commit(repository, path, sha, cx)
+            })?
+            .await?;
+
+        Ok(proto::GitFileAtCommitResponse { text })
+    }
+
+    pub async fn handle_git_commit_diff(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitCommitDiff>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::GitCommitDiffResponse> {
+        let project_path = envelope
+            .payload
+            .repository
+            .clone()
+            .context("Invalid GitCommitDiff call")?;
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_proto(project_path.worktree_id),
+            path: Path::new(&project_path.path).into(),
+        };
+        let sha = envelope.payload.sha.parse()?;
+
+        let files = this
+            .read_with(&cx, |this, cx| this.git_commit_diff(project_path, sha, cx))?
+            .await?;
+
+        Ok(proto::GitCommitDiffResponse {
+            files: files
+                .into_iter()
+                .map(|file| proto::GitCommitFileDiff {
+                    path: file.path.to_string_lossy().to_string(),
+                    old_text: file.old_text,
+                    new_text: file.new_text,
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn handle_git_stash_list(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStashList>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::GitStashListResponse> {
+        let project_path = envelope
+            .payload
+            .repository
+            .clone()
+            .context("Invalid GitStashList call")?;
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_proto(project_path.worktree_id),
+            path: Path::new(&project_path.path).into(),
+        };
+
+        let entries = this
+            .read_with(&cx, |this, cx| this.git_stash_list(project_path, cx))?
+            .await?;
+
+        Ok(proto::GitStashListResponse {
+            entries: entries
+                .into_iter()
+                .map(|entry| proto::GitStashEntry {
+                    index: entry.index as u64,
+                    sha: entry.sha.to_string(),
+                    message: entry.message,
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn handle_git_stash_push(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStashPush>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let project_path = envelope
+            .payload
+            .repository
+            .clone()
+            .context("Invalid GitStashPush call")?;
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_proto(project_path.worktree_id),
+            path: Path::new(&project_path.path).into(),
+        };
+        let message = envelope.payload.message;
+
+        this.read_with(&cx, |this, cx| {
+            this.git_stash_push(project_path, message, cx)
+        })?
+        .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_stash_action(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStashAction>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let project_path = envelope
+            .payload
+            .repository
+            .clone()
+            .context("Invalid GitStashAction call")?;
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_proto(project_path.worktree_id),
+            path: Path::new(&project_path.path).into(),
+        };
+        let sha = envelope.payload.sha.parse()?;
+        let action = match proto::git_stash_action::Action::from_i32(envelope.payload.action) {
+            Some(proto::git_stash_action::Action::Pop) => StashAction::Pop,
+            Some(proto::git_stash_action::Action::Apply) => StashAction::Apply,
+            Some(proto::git_stash_action::Action::Drop) => StashAction::Drop,
+            None => return Err(anyhow!("Invalid GitStashAction action")),
+        };
+
+        this.read_with(&cx, |this, cx| {
+            this.git_stash_action(project_path, sha, action, cx)
+        })?
+        .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_branch_url(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitBranchUrl>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::GitBranchUrlResponse> {
+        let project_path = envelope
+            .payload
+            .repository
+            .clone()
+            .context("Invalid GitBranchUrl call")?;
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_proto(project_path.worktree_id),
+            path: Path::new(&project_path.path).into(),
+        };
+        let kind = match proto::git_branch_url::Kind::from_i32(envelope.payload.kind) {
+            Some(proto::git_branch_url::Kind::CreatePullRequest) => {
+                BranchUrlKind::CreatePullRequest
+            }
+            Some(proto::git_branch_url::Kind::Compare) => BranchUrlKind::Compare,
+            None => return Err(anyhow!("Invalid GitBranchUrl kind")),
+        };
+
+        let url = this
+            .read_with(&cx, |this, cx| this.git_branch_url(project_path, kind, cx))?
+            .await?;
+        Ok(proto::GitBranchUrlResponse {
+            url: url.to_string(),
+        })
+    }
+
+    pub async fn handle_git_changed_paths(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitChangedPaths>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::GitChangedPathsResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let diff_base = envelope
+            .payload
+            .diff_base
+            .map(deserialize_diff_base)
+            .unwrap_or_default();
+
+        let paths = this
+            .read_with(&cx, |this, cx| {
+                this.git_changed_paths(worktree_id, diff_base, cx)
+            })?
+            .await?;
+
+        Ok(proto::GitChangedPathsResponse {
+            paths: paths
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect(),
+        })
+    }
+}
+
+pub(crate) fn serialize_diff_base(diff_base: &DiffBase) -> proto::GitDiffBase {
+    let (kind, revision) = match diff_base {
+        DiffBase::Index => (proto::git_diff_base::Kind::Index, String::new()),
+        DiffBase::Revision(revision) => (proto::git_diff_base::Kind::Revision, revision.clone()),
+        DiffBase::MergeBase(revision) => (proto::git_diff_base::Kind::MergeBase, revision.clone()),
+    };
+    proto::GitDiffBase {
+        kind: kind as i32,
+        revision,
+    }
+}
+
+pub(crate) fn deserialize_diff_base(diff_base: proto::GitDiffBase) -> DiffBase {
+    match proto::git_diff_base::Kind::from_i32(diff_base.kind) {
+        Some(proto::git_diff_base::Kind::Revision) => DiffBase::Revision(diff_base.revision),
+        Some(proto::git_diff_base::Kind::MergeBase) => DiffBase::MergeBase(diff_base.revision),
+        Some(proto::git_diff_base::Kind::Index) | None => DiffBase::Index,
+    }
+}
+
+fn paths_from_proto(paths: Vec<String>) -> Vec<Arc<Path>> {
+    paths
+        .into_iter()
+        .map(|path| Arc::from(PathBuf::from(path)))
+        .collect()
+}
+
+/// Returns the git repository containing the given worktree path, and the path relative to it.
+fn local_repository_for_path(
+    local_worktree: &LocalWorktree,
+    path: &Path,
+) -> Result<(Arc<dyn GitRepository>, RepoPath)> {
+    let (repo_entry, local_repo) = local_worktree.repo_for_path(path).with_context(|| {
+        format!(
+            "No repository found for {path:?} in worktree {}",
+            local_worktree.abs_path().to_string_lossy()
+        )
+    })?;
+    let repo_path = repo_entry.relativize(local_worktree, path)?;
+    Ok((local_repo.repo().clone(), repo_path))
+}
+
+/// Groups the given worktree paths by the git repository that contains them.
+fn local_repositories_for_paths(
+    local_worktree: &LocalWorktree,
+    paths: &[Arc<Path>],
+) -> Result<Vec<(Arc<dyn GitRepository>, Vec<(RepoPath, Arc<Path>)>)>> {
+    let mut repositories = HashMap::<ProjectEntryId, (Arc<dyn GitRepository>, Vec<_>)>::default();
+    for path in paths {
+        let (repo_entry, local_repo) = local_worktree.repo_for_path(path).with_context(|| {
+            format!(
+                "No repository found for {path:?} in worktree {}",
+                local_worktree.abs_path().to_string_lossy()
+            )
+        })?;
+        let repo_path = repo_entry.relativize(local_worktree, path)?;
+        repositories
+            .entry(repo_entry.work_directory_id())
+            .or_insert_with(|| (local_repo.repo().clone(), Vec::new()))
+            .1
+            .push((repo_path, path.clone()));
     }
+    Ok(repositories.into_values().collect())
 }
 
 #[derive(Clone, Debug)]
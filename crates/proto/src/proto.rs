@@ -186,8 +186,21 @@ messages!(
     (GetChannelMessages, Background),
     (GetChannelMessagesById, Background),
     (GetChannelMessagesResponse, Background),
+    (GetCallHierarchy, Background),
+    (GetCallHierarchyResponse, Background),
     (GetCodeActions, Background),
     (GetCodeActionsResponse, Background),
+    (GetCodeLens, Background),
+    (GetCodeLensResponse, Background),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
+    (GetSelectionRanges, Background),
+    (GetSelectionRangesResponse, Background),
+    (GetDocumentLinks, Background),
+    (GetDocumentLinksResponse, Background),
+    (ResolveDocumentLink, Background),
+    (ResolveDocumentLinkResponse, Background),
+    (RefreshCodeLens, Foreground),
     (GetCompletions, Background),
     (GetCompletionsResponse, Background),
     (GetDefinition, Background),
@@ -212,6 +225,11 @@ messages!(
     (GetSupermavenApiKeyResponse, Background),
     (GetTypeDefinition, Background),
     (GetTypeDefinitionResponse, Background),
+    (GetTypeHierarchy, Background),
+    (GetTypeHierarchyResponse, Background),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensResponse, Background),
+    (RefreshSemanticTokens, Foreground),
     (GetImplementation, Background),
     (GetImplementationResponse, Background),
     (GetLlmToken, Background),
@@ -360,6 +378,28 @@ messages!(
     (GitBranches, Background),
     (GitBranchesResponse, Background),
     (UpdateGitBranch, Background),
+    (GitStageStatuses, Background),
+    (GitStageStatusesResponse, Background),
+    (StageGitPaths, Background),
+    (UnstageGitPaths, Background),
+    (DiscardGitPaths, Background),
+    (SetGitIndexText, Background),
+    (UnstageGitRows, Background),
+    (CommitGitChanges, Background),
+    (GitFileHistory, Background),
+    (GitFileHistoryResponse, Background),
+    (GitFileAtCommit, Background),
+    (GitFileAtCommitResponse, Background),
+    (GitCommitDiff, Background),
+    (GitCommitDiffResponse, Background),
+    (GitChangedPaths, Background),
+    (GitChangedPathsResponse, Background),
+    (GitStashList, Background),
+    (GitStashListResponse, Background),
+    (GitStashPush, Background),
+    (GitStashAction, Background),
+    (GitBranchUrl, Background),
+    (GitBranchUrlResponse, Background),
     (ListToolchains, Foreground),
     (ListToolchainsResponse, Foreground),
     (ActivateToolchain, Foreground),
@@ -401,7 +441,13 @@ request_messages!(
     (GetChannelMembers, GetChannelMembersResponse),
     (GetChannelMessages, GetChannelMessagesResponse),
     (GetChannelMessagesById, GetChannelMessagesResponse),
+    (GetCallHierarchy, GetCallHierarchyResponse),
     (GetCodeActions, GetCodeActionsResponse),
+    (GetCodeLens, GetCodeLensResponse),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetSelectionRanges, GetSelectionRangesResponse),
+    (GetDocumentLinks, GetDocumentLinksResponse),
+    (ResolveDocumentLink, ResolveDocumentLinkResponse),
     (GetCompletions, GetCompletionsResponse),
     (GetDefinition, GetDefinitionResponse),
     (GetDeclaration, GetDeclarationResponse),
@@ -417,6 +463,8 @@ request_messages!(
     (GetStagedText, GetStagedTextResponse),
     (GetSupermavenApiKey, GetSupermavenApiKeyResponse),
     (GetTypeDefinition, GetTypeDefinitionResponse),
+    (GetTypeHierarchy, GetTypeHierarchyResponse),
+    (GetSemanticTokens, GetSemanticTokensResponse),
     (LinkedEditingRange, LinkedEditingRangeResponse),
     (ListRemoteDirectory, ListRemoteDirectoryResponse),
     (GetUsers, UsersResponse),
@@ -492,6 +540,21 @@ request_messages!(
     (LanguageServerPromptRequest, LanguageServerPromptResponse),
     (GitBranches, GitBranchesResponse),
     (UpdateGitBranch, Ack),
+    (GitStageStatuses, GitStageStatusesResponse),
+    (StageGitPaths, Ack),
+    (UnstageGitPaths, Ack),
+    (DiscardGitPaths, Ack),
+    (SetGitIndexText, Ack),
+    (UnstageGitRows, Ack),
+    (CommitGitChanges, Ack),
+    (GitFileHistory, GitFileHistoryResponse),
+    (GitFileAtCommit, GitFileAtCommitResponse),
+    (GitCommitDiff, GitCommitDiffResponse),
+    (GitChangedPaths, GitChangedPathsResponse),
+    (GitStashList, GitStashListResponse),
+    (GitStashPush, Ack),
+    (GitStashAction, Ack),
+    (GitBranchUrl, GitBranchUrlResponse),
     (ListToolchains, ListToolchainsResponse),
     (ActivateToolchain, Ack),
     (ActiveToolchain, ActiveToolchainResponse),
@@ -520,7 +583,13 @@ entity_messages!(
     ExpandProjectEntry,
     FindSearchCandidates,
     FormatBuffers,
+    GetCallHierarchy,
     GetCodeActions,
+    GetCodeLens,
+    GetFoldingRanges,
+    GetSelectionRanges,
+    GetDocumentLinks,
+    ResolveDocumentLink,
     GetCompletions,
     GetDefinition,
     GetDeclaration,
@@ -532,6 +601,8 @@ entity_messages!(
     GetSignatureHelp,
     GetStagedText,
     GetTypeDefinition,
+    GetTypeHierarchy,
+    GetSemanticTokens,
     InlayHints,
     JoinProject,
     LeaveProject,
@@ -546,6 +617,8 @@ entity_messages!(
     PerformRename,
     PrepareRename,
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     ReloadBuffers,
     RemoveProjectCollaborator,
     RenameProjectEntry,
@@ -581,6 +654,21 @@ entity_messages!(
     LanguageServerPromptRequest,
     GitBranches,
     UpdateGitBranch,
+    GitStageStatuses,
+    StageGitPaths,
+    UnstageGitPaths,
+    DiscardGitPaths,
+    SetGitIndexText,
+    UnstageGitRows,
+    CommitGitChanges,
+    GitFileHistory,
+    GitFileAtCommit,
+    GitCommitDiff,
+    GitChangedPaths,
+    GitStashList,
+    GitStashPush,
+    GitStashAction,
+    GitBranchUrl,
     ListToolchains,
     ActivateToolchain,
     ActiveToolchain,
@@ -1,9 +1,10 @@
 mod registrar;
 
 use crate::{
-    search_bar::render_nav_button, FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll,
-    ReplaceNext, SearchOptions, SelectAllMatches, SelectNextMatch, SelectPrevMatch,
-    ToggleCaseSensitive, ToggleRegex, ToggleReplace, ToggleSelection, ToggleWholeWord,
+    search_bar::{render_nav_button, render_syntax_scope_menu},
+    FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOptions,
+    SelectAllMatches, SelectNextMatch, SelectPrevMatch, SelectSyntaxScope, ToggleCaseSensitive,
+    ToggleRegex, ToggleReplace, ToggleSelection, ToggleWholeWord,
 };
 use any_vec::AnyVec;
 use collections::HashMap;
@@ -19,7 +20,7 @@ use gpui::{
     VisualContext as _, WindowContext,
 };
 use project::{
-    search::SearchQuery,
+    search::{SearchQuery, SyntaxScope},
     search_history::{SearchHistory, SearchHistoryCursor},
 };
 use serde::Deserialize;
@@ -90,6 +91,7 @@ pub struct BufferSearchBar {
     search_options: SearchOptions,
     default_options: SearchOptions,
     configured_options: SearchOptions,
+    syntax_scope: SyntaxScope,
     query_contains_error: bool,
     dismissed: bool,
     search_history: SearchHistory,
@@ -252,6 +254,20 @@ impl Render for BufferSearchBar {
                                 cx.listener(|this, _, cx| this.toggle_regex(&ToggleRegex, cx)),
                             )
                         }))
+                        .children(supported_options.syntax_scope.then(|| {
+                            let search_bar = cx.view().downgrade();
+                            render_syntax_scope_menu(
+                                "buffer-search-syntax-scope",
+                                self.syntax_scope,
+                                move |scope, cx| {
+                                    search_bar
+                                        .update(cx, |search_bar, cx| {
+                                            search_bar.select_syntax_scope(scope, cx)
+                                        })
+                                        .ok();
+                                },
+                            )
+                        }))
                     }),
             )
             .child(
@@ -535,6 +551,11 @@ impl BufferSearchBar {
                 this.toggle_regex(action, cx);
             }
         }));
+        registrar.register_handler(ForDeployed(|this, action: &SelectSyntaxScope, cx| {
+            if this.supported_options().syntax_scope {
+                this.select_syntax_scope(action.scope, cx);
+            }
+        }));
         registrar.register_handler(ForDeployed(|this, action: &ToggleSelection, cx| {
             if this.supported_options().selection {
                 this.toggle_selection(action, cx);
@@ -593,6 +614,7 @@ impl BufferSearchBar {
             default_options: search_options,
             configured_options: search_options,
             search_options,
+            syntax_scope: SyntaxScope::Anywhere,
             pending_search: None,
             query_contains_error: false,
             dismissed: true,
@@ -982,6 +1004,12 @@ impl BufferSearchBar {
         self.toggle_search_option(SearchOptions::REGEX, cx)
     }
 
+    pub fn select_syntax_scope(&mut self, scope: SyntaxScope, cx: &mut ViewContext<Self>) {
+        self.syntax_scope = scope;
+        drop(self.update_matches(false, cx));
+        cx.notify();
+    }
+
     fn clear_active_searchable_item_matches(&mut self, cx: &mut WindowContext) {
         if let Some(active_searchable_item) = self.active_searchable_item.as_ref() {
             self.active_match_index = None;
@@ -1044,7 +1072,9 @@ impl BufferSearchBar {
                             Default::default(),
                             None,
                         ) {
-                            Ok(query) => query.with_replacement(self.replacement(cx)),
+                            Ok(query) => query
+                                .with_replacement(self.replacement(cx))
+                                .with_syntax_scope(self.syntax_scope),
                             Err(_) => {
                                 self.query_contains_error = true;
                                 self.clear_active_searchable_item_matches(cx);
@@ -1062,7 +1092,9 @@ impl BufferSearchBar {
                             Default::default(),
                             None,
                         ) {
-                            Ok(query) => query.with_replacement(self.replacement(cx)),
+                            Ok(query) => query
+                                .with_replacement(self.replacement(cx))
+                                .with_syntax_scope(self.syntax_scope),
                             Err(_) => {
                                 self.query_contains_error = true;
                                 self.clear_active_searchable_item_matches(cx);
@@ -0,0 +1,395 @@
+use anyhow::Result;
+use db::sqlez::bindable::{Bind, Column, StaticColumnCount};
+use db::sqlez::statement::Statement;
+use project::search::{SearchQuery, SyntaxScope};
+
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+
+use workspace::{WorkspaceDb, WorkspaceId};
+
+use crate::SearchOptions;
+
+/// The number of past searches kept for every workspace.
+const MAX_SEARCH_HISTORY_LEN: usize = 500;
+
+/// The inputs of a project search, as stored in the workspace database.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SerializedSearch {
+    pub(crate) query: String,
+    pub(crate) files_to_include: String,
+    pub(crate) files_to_exclude: String,
+    pub(crate) replacement: Option<String>,
+    pub(crate) options: SearchOptions,
+    pub(crate) syntax_scope: SyntaxScope,
+}
+
+impl SerializedSearch {
+    pub(crate) fn new(query: &SearchQuery, replacement: Option<String>) -> Self {
+        Self {
+            query: query.as_str().to_string(),
+            files_to_include: query.as_inner().files_to_include().sources().join(","),
+            files_to_exclude: query.as_inner().files_to_exclude().sources().join(","),
+            replacement: replacement.filter(|replacement| !replacement.is_empty()),
+            options: SearchOptions::from_query(query),
+            syntax_scope: query.syntax_scope(),
+        }
+    }
+}
+
+impl StaticColumnCount for SerializedSearch {
+    fn column_count() -> usize {
+        10
+    }
+}
+
+impl Bind for SerializedSearch {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        let start_index = statement.bind(&self.query, start_index)?;
+        let start_index = statement.bind(&self.files_to_include, start_index)?;
+        let start_index = statement.bind(&self.files_to_exclude, start_index)?;
+        let start_index = statement.bind(&self.replacement, start_index)?;
+        let start_index = statement.bind(
+            &self.options.contains(SearchOptions::WHOLE_WORD),
+            start_index,
+        )?;
+        let start_index = statement.bind(
+            &self.options.contains(SearchOptions::CASE_SENSITIVE),
+            start_index,
+        )?;
+        let start_index = statement.bind(
+            &self.options.contains(SearchOptions::INCLUDE_IGNORED),
+            start_index,
+        )?;
+        let start_index =
+            statement.bind(&self.options.contains(SearchOptions::REGEX), start_index)?;
+        let start_index = statement.bind(
+            &self.options.contains(SearchOptions::STRUCTURAL),
+            start_index,
+        )?;
+        let syntax_scope: i32 = match self.syntax_scope {
+            SyntaxScope::Anywhere => 0,
+            SyntaxScope::Comments => 1,
+            SyntaxScope::Strings => 2,
+            SyntaxScope::Code => 3,
+        };
+        statement.bind(&syntax_scope, start_index)
+    }
+}
+
+impl Column for SerializedSearch {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (query, start_index): (String, i32) = Column::column(statement, start_index)?;
+        let (files_to_include, start_index): (String, i32) =
+            Column::column(statement, start_index)?;
+        let (files_to_exclude, start_index): (String, i32) =
+            Column::column(statement, start_index)?;
+        let (replacement, start_index): (Option<String>, i32) =
+            Column::column(statement, start_index)?;
+
+        let mut options = SearchOptions::NONE;
+        let mut start_index = start_index;
+        for option in [
+            SearchOptions::WHOLE_WORD,
+            SearchOptions::CASE_SENSITIVE,
+            SearchOptions::INCLUDE_IGNORED,
+            SearchOptions::REGEX,
+            SearchOptions::STRUCTURAL,
+        ] {
+            let (enabled, next_index): (bool, i32) = Column::column(statement, start_index)?;
+            options.set(option, enabled);
+            start_index = next_index;
+        }
+
+        let (syntax_scope, start_index): (i32, i32) = Column::column(statement, start_index)?;
+        let syntax_scope = match syntax_scope {
+            1 => SyntaxScope::Comments,
+            2 => SyntaxScope::Strings,
+            3 => SyntaxScope::Code,
+            _ => SyntaxScope::Anywhere,
+        };
+
+        let search = Self {
+            query,
+            files_to_include,
+            files_to_exclude,
+            replacement,
+            options,
+            syntax_scope,
+        };
+        Ok((search, start_index))
+    }
+}
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // search_history(
+    //   id: usize,
+    //   workspace_id: usize,
+    //   query: String,
+    //   files_to_include: String,
+    //   files_to_exclude: String,
+    //   replacement: Option<String>,
+    //   whole_word: bool,
+    //   case_sensitive: bool,
+    //   include_ignored: bool,
+    //   regex: bool,
+    //   structural: bool,
+    //   syntax_scope: i32,
+    // )
+    //
+    // saved_searches(
+    //   workspace_id: usize,
+    //   name: String,
+    //   ... the search columns of search_history
+    // )
+    pub static ref SEARCH_DB: SearchDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE search_history(
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                workspace_id INTEGER NOT NULL,
+                query TEXT NOT NULL,
+                files_to_include TEXT NOT NULL,
+                files_to_exclude TEXT NOT NULL,
+                replacement TEXT,
+                whole_word INTEGER NOT NULL,
+                case_sensitive INTEGER NOT NULL,
+                include_ignored INTEGER NOT NULL,
+                regex INTEGER NOT NULL,
+                structural INTEGER NOT NULL,
+                syntax_scope INTEGER NOT NULL,
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+
+            CREATE TABLE saved_searches(
+                workspace_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                query TEXT NOT NULL,
+                files_to_include TEXT NOT NULL,
+                files_to_exclude TEXT NOT NULL,
+                replacement TEXT,
+                whole_word INTEGER NOT NULL,
+                case_sensitive INTEGER NOT NULL,
+                include_ignored INTEGER NOT NULL,
+                regex INTEGER NOT NULL,
+                structural INTEGER NOT NULL,
+                syntax_scope INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, name),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        )];
+);
+
+impl SearchDb {
+    /// Appends a search to the history of the workspace, moving an earlier
+    /// search with the same query to the end and dropping the oldest entries.
+    pub(crate) async fn add_to_history(
+        &self,
+        workspace_id: WorkspaceId,
+        search: SerializedSearch,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec_bound::<(WorkspaceId, String)>(sql!(
+                DELETE FROM search_history WHERE workspace_id = ? AND query = ?
+            ))?((workspace_id, search.query.clone()))?;
+            conn.exec_bound::<(WorkspaceId, SerializedSearch)>(sql!(
+                INSERT INTO search_history(
+                    workspace_id,
+                    query,
+                    files_to_include,
+                    files_to_exclude,
+                    replacement,
+                    whole_word,
+                    case_sensitive,
+                    include_ignored,
+                    regex,
+                    structural,
+                    syntax_scope
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ))?((workspace_id, search))?;
+            conn.exec_bound::<(WorkspaceId, i64)>(sql!(
+                DELETE FROM search_history
+                WHERE workspace_id = ?1 AND id NOT IN (
+                    SELECT id FROM search_history
+                    WHERE workspace_id = ?1
+                    ORDER BY id DESC
+                    LIMIT ?2
+                )
+            ))?((workspace_id, MAX_SEARCH_HISTORY_LEN as i64))
+        })
+        .await
+    }
+
+    query! {
+        pub(crate) fn search_history(workspace_id: WorkspaceId) -> Result<Vec<SerializedSearch>> {
+            SELECT
+                query,
+                files_to_include,
+                files_to_exclude,
+                replacement,
+                whole_word,
+                case_sensitive,
+                include_ignored,
+                regex,
+                structural,
+                syntax_scope
+            FROM search_history
+            WHERE workspace_id = ?
+            ORDER BY id ASC
+        }
+    }
+
+    query! {
+        pub(crate) async fn save_search(workspace_id: WorkspaceId, name: String, search: SerializedSearch) -> Result<()> {
+            INSERT OR REPLACE INTO saved_searches(
+                workspace_id,
+                name,
+                query,
+                files_to_include,
+                files_to_exclude,
+                replacement,
+                whole_word,
+                case_sensitive,
+                include_ignored,
+                regex,
+                structural,
+                syntax_scope
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        }
+    }
+
+    query! {
+        pub(crate) fn saved_searches(workspace_id: WorkspaceId) -> Result<Vec<(String, SerializedSearch)>> {
+            SELECT
+                name,
+                query,
+                files_to_include,
+                files_to_exclude,
+                replacement,
+                whole_word,
+                case_sensitive,
+                include_ignored,
+                regex,
+                structural,
+                syntax_scope
+            FROM saved_searches
+            WHERE workspace_id = ?
+            ORDER BY name ASC
+        }
+    }
+
+    query! {
+        pub(crate) fn saved_search(workspace_id: WorkspaceId, name: String) -> Result<Option<SerializedSearch>> {
+            SELECT
+                query,
+                files_to_include,
+                files_to_exclude,
+                replacement,
+                whole_word,
+                case_sensitive,
+                include_ignored,
+                regex,
+                structural,
+                syntax_scope
+            FROM saved_searches
+            WHERE workspace_id = ? AND name = ?
+        }
+    }
+
+    query! {
+        pub(crate) async fn delete_saved_search(workspace_id: WorkspaceId, name: String) -> Result<()> {
+            DELETE FROM saved_searches
+            WHERE workspace_id = ? AND name = ?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialized_search(query: &str) -> SerializedSearch {
+        SerializedSearch {
+            query: query.to_string(),
+            files_to_include: "crates/**/*.rs".to_string(),
+            files_to_exclude: String::new(),
+            replacement: None,
+            options: SearchOptions::CASE_SENSITIVE | SearchOptions::REGEX,
+            syntax_scope: SyntaxScope::Comments,
+        }
+    }
+
+    #[gpui::test]
+    async fn test_search_history() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+
+        SEARCH_DB
+            .add_to_history(workspace_id, serialized_search("one"))
+            .await
+            .unwrap();
+        SEARCH_DB
+            .add_to_history(workspace_id, serialized_search("two"))
+            .await
+            .unwrap();
+        // Searching for a query again moves it to the end of the history.
+        SEARCH_DB
+            .add_to_history(workspace_id, serialized_search("one"))
+            .await
+            .unwrap();
+
+        let history = SEARCH_DB.search_history(workspace_id).unwrap();
+        assert_eq!(
+            history,
+            vec![serialized_search("two"), serialized_search("one")]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_saved_searches() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+
+        let mut todos = serialized_search("TODO|FIXME");
+        SEARCH_DB
+            .save_search(workspace_id, "todos".to_string(), todos.clone())
+            .await
+            .unwrap();
+        SEARCH_DB
+            .save_search(workspace_id, "bugs".to_string(), serialized_search("bug"))
+            .await
+            .unwrap();
+
+        // Saving under an existing name overwrites the search.
+        todos.replacement = Some("DONE".to_string());
+        todos.syntax_scope = SyntaxScope::Anywhere;
+        SEARCH_DB
+            .save_search(workspace_id, "todos".to_string(), todos.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            SEARCH_DB.saved_searches(workspace_id).unwrap(),
+            vec![
+                ("bugs".to_string(), serialized_search("bug")),
+                ("todos".to_string(), todos.clone()),
+            ]
+        );
+        assert_eq!(
+            SEARCH_DB
+                .saved_search(workspace_id, "todos".to_string())
+                .unwrap(),
+            Some(todos)
+        );
+
+        SEARCH_DB
+            .delete_saved_search(workspace_id, "bugs".to_string())
+            .await
+            .unwrap();
+        assert_eq!(SEARCH_DB.saved_searches(workspace_id).unwrap().len(), 1);
+    }
+}
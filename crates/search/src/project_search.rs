@@ -1,7 +1,11 @@
 use crate::{
-    buffer_search::Deploy, BufferSearchBar, FocusSearch, NextHistoryQuery, PreviousHistoryQuery,
-    ReplaceAll, ReplaceNext, SearchOptions, SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive,
-    ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleWholeWord,
+    buffer_search::Deploy,
+    persistence::{SerializedSearch, SEARCH_DB},
+    saved_searches::SavedSearches,
+    search_bar::render_syntax_scope_menu,
+    BufferSearchBar, FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext,
+    SearchOptions, SelectNextMatch, SelectPrevMatch, SelectSyntaxScope, ToggleCaseSensitive,
+    ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleStructural, ToggleWholeWord,
 };
 use collections::{HashMap, HashSet};
 use editor::{
@@ -10,19 +14,20 @@ use editor::{
 };
 use futures::StreamExt;
 use gpui::{
-    actions, div, Action, AnyElement, AnyView, AppContext, Axis, Context as _, EntityId,
-    EventEmitter, FocusHandle, FocusableView, Global, Hsla, InteractiveElement, IntoElement,
-    KeyContext, Model, ModelContext, ParentElement, Point, Render, SharedString, Styled,
-    Subscription, Task, TextStyle, UpdateGlobal, View, ViewContext, VisualContext, WeakModel,
-    WeakView, WindowContext,
+    actions, div, impl_actions, Action, AnyElement, AnyView, AppContext, Axis, Context as _,
+    EntityId, EventEmitter, FocusHandle, FocusableView, Global, Hsla, InteractiveElement,
+    IntoElement, KeyContext, Model, ModelContext, ParentElement, Point, Render, SharedString,
+    Styled, Subscription, Task, TextStyle, UpdateGlobal, View, ViewContext, VisualContext,
+    WeakModel, WeakView, WindowContext,
 };
 use language::Buffer;
 use menu::Confirm;
 use project::{
-    search::{SearchInputKind, SearchQuery},
+    search::{SearchInputKind, SearchQuery, SyntaxScope},
     search_history::SearchHistoryCursor,
     Project, ProjectPath,
 };
+use serde::Deserialize;
 use settings::Settings;
 use std::{
     any::{Any, TypeId},
@@ -38,6 +43,7 @@ use ui::{
 use util::paths::PathMatcher;
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle},
+    notifications::DetachAndPromptErr,
     searchable::{Direction, SearchableItem, SearchableItemHandle},
     DeploySearch, ItemNavHistory, NewSearch, ToolbarItemEvent, ToolbarItemLocation,
     ToolbarItemView, Workspace, WorkspaceId,
@@ -45,17 +51,64 @@ use workspace::{
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        SaveSearch,
+        OpenSavedSearches,
+    ]
 );
 
+/// Runs the search saved in the current project under the given name.
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct RunSavedSearch {
+    pub name: String,
+}
+
+impl_actions!(project_search, [RunSavedSearch]);
+
 #[derive(Default)]
 struct ActiveSettings(HashMap<WeakModel<Project>, ProjectSearchSettings>);
 
 impl Global for ActiveSettings {}
 
+/// The last search run with each query of a project's search history, to restore its options
+/// and replacement along with the query when navigating the history.
+#[derive(Default)]
+struct SearchHistoryEntries(HashMap<WeakModel<Project>, HashMap<String, SerializedSearch>>);
+
+impl Global for SearchHistoryEntries {}
+
+impl SearchHistoryEntries {
+    fn add(project: &Model<Project>, search: SerializedSearch, cx: &mut AppContext) {
+        Self::update_global(cx, |entries, _| {
+            entries
+                .0
+                .entry(project.downgrade())
+                .or_default()
+                .insert(search.query.clone(), search);
+        });
+    }
+
+    fn get<'a>(
+        project: &Model<Project>,
+        query: &str,
+        cx: &'a AppContext,
+    ) -> Option<&'a SerializedSearch> {
+        cx.global::<Self>().0.get(&project.downgrade())?.get(query)
+    }
+}
+
 pub fn init(cx: &mut AppContext) {
     cx.set_global(ActiveSettings::default());
-    cx.observe_new_views(|workspace: &mut Workspace, _cx| {
+    cx.set_global(SearchHistoryEntries::default());
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        restore_search_history(workspace, cx);
+        workspace.register_action(SavedSearches::open);
+        workspace.register_action(SavedSearches::save);
+        workspace.register_action(ProjectSearchView::run_saved_search);
         register_workspace_action(workspace, move |search_bar, _: &Deploy, cx| {
             search_bar.focus_search(cx);
         });
@@ -74,6 +127,15 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, cx);
         });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleStructural, cx| {
+            search_bar.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, action: &SelectSyntaxScope, cx| {
+                search_bar.select_syntax_scope(action.scope, cx);
+            },
+        );
         register_workspace_action(workspace, move |search_bar, action: &ToggleReplace, cx| {
             search_bar.toggle_replace(action, cx)
         });
@@ -116,6 +178,49 @@ pub fn init(cx: &mut AppContext) {
     .detach();
 }
 
+/// Loads the searches persisted for the workspace into the search history of its project,
+/// unless that history has already been populated in this session.
+fn restore_search_history(workspace: &Workspace, cx: &mut ViewContext<Workspace>) {
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    let project = workspace.project().clone();
+    if !project
+        .read(cx)
+        .search_history(SearchInputKind::Query)
+        .is_empty()
+    {
+        return;
+    }
+    cx.spawn(|_, mut cx| async move {
+        let history = cx
+            .background_executor()
+            .spawn(async move { SEARCH_DB.search_history(workspace_id) })
+            .await?;
+        project.update(&mut cx, |project, cx| {
+            if !project.search_history(SearchInputKind::Query).is_empty() {
+                return;
+            }
+            let project_handle = cx.handle();
+            for search in history {
+                for (kind, text) in [
+                    (SearchInputKind::Query, &search.query),
+                    (SearchInputKind::Include, &search.files_to_include),
+                    (SearchInputKind::Exclude, &search.files_to_exclude),
+                ] {
+                    if !text.is_empty() {
+                        project
+                            .search_history_mut(kind)
+                            .add(&mut SearchHistoryCursor::default(), text.clone());
+                    }
+                }
+                SearchHistoryEntries::add(&project_handle, search, cx);
+            }
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
 fn is_contains_uppercase(str: &str) -> bool {
     str.chars().any(|c| c.is_uppercase())
 }
@@ -144,12 +249,14 @@ enum InputPanel {
 
 pub struct ProjectSearchView {
     workspace: WeakView<Workspace>,
+    workspace_id: Option<WorkspaceId>,
     focus_handle: FocusHandle,
     model: Model<ProjectSearch>,
     query_editor: View<Editor>,
     replacement_editor: View<Editor>,
     results_editor: View<Editor>,
     search_options: SearchOptions,
+    syntax_scope: SyntaxScope,
     panels_with_errors: HashSet<InputPanel>,
     active_match_index: Option<usize>,
     search_id: usize,
@@ -509,6 +616,7 @@ impl Item for ProjectSearchView {
     }
 
     fn added_to_workspace(&mut self, workspace: &mut Workspace, cx: &mut ViewContext<Self>) {
+        self.workspace_id = workspace.database_id();
         self.results_editor
             .update(cx, |editor, cx| editor.added_to_workspace(workspace, cx));
     }
@@ -575,6 +683,14 @@ impl ProjectSearchView {
 
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) {
         self.search_options.toggle(option);
+        // A query is either a regular expression or a syntax pattern, never both.
+        if self.search_options.contains(option) {
+            if option == SearchOptions::REGEX {
+                self.search_options.remove(SearchOptions::STRUCTURAL);
+            } else if option == SearchOptions::STRUCTURAL {
+                self.search_options.remove(SearchOptions::REGEX);
+            }
+        }
         ActiveSettings::update_global(cx, |settings, cx| {
             settings.0.insert(
                 self.model.read(cx).project.downgrade(),
@@ -646,6 +762,7 @@ impl ProjectSearchView {
         let excerpts;
         let mut replacement_text = None;
         let mut query_text = String::new();
+        let mut syntax_scope = SyntaxScope::Anywhere;
         let mut subscriptions = Vec::new();
 
         // Read in settings if available
@@ -665,6 +782,7 @@ impl ProjectSearchView {
                 query_text = active_query.as_str().to_string();
                 replacement_text = active_query.replacement().map(ToOwned::to_owned);
                 options = SearchOptions::from_query(active_query);
+                syntax_scope = active_query.syntax_scope();
             }
         }
         subscriptions.push(cx.observe(&model, |this, _, cx| this.model_changed(cx)));
@@ -757,6 +875,7 @@ impl ProjectSearchView {
         // Check if Worktrees have all been previously indexed
         let mut this = ProjectSearchView {
             workspace,
+            workspace_id: None,
             focus_handle,
             replacement_editor,
             search_id: model.read(cx).search_id,
@@ -764,6 +883,7 @@ impl ProjectSearchView {
             query_editor,
             results_editor,
             search_options: options,
+            syntax_scope,
             panels_with_errors: HashSet::default(),
             active_match_index: None,
             included_files_editor,
@@ -821,19 +941,28 @@ impl ProjectSearchView {
             .active_item(cx)
             .and_then(|item| item.downcast::<ProjectSearchView>())
         {
-            let new_query = search_view.update(cx, |search_view, cx| {
+            let new_search = search_view.update(cx, |search_view, cx| {
                 let new_query = search_view.build_search_query(cx);
+                let replacement = search_view.current_replacement(cx);
                 if new_query.is_some() {
                     if let Some(old_query) = search_view.model.read(cx).active_query.clone() {
                         search_view.query_editor.update(cx, |editor, cx| {
                             editor.set_text(old_query.as_str(), cx);
                         });
                         search_view.search_options = SearchOptions::from_query(&old_query);
+                        search_view.syntax_scope = old_query.syntax_scope();
                     }
                 }
-                new_query
+                new_query.map(|new_query| (new_query, replacement))
             });
-            if let Some(new_query) = new_query {
+            if let Some((new_query, replacement)) = new_search {
+                let search = SerializedSearch::new(&new_query, replacement);
+                SearchHistoryEntries::add(workspace.project(), search.clone(), cx);
+                if let Some(workspace_id) = workspace.database_id() {
+                    cx.background_executor()
+                        .spawn(SEARCH_DB.add_to_history(workspace_id, search))
+                        .detach_and_log_err(cx);
+                }
                 let model = cx.new_model(|cx| {
                     let mut model = ProjectSearch::new(workspace.project().clone(), cx);
                     model.search(new_query, cx);
@@ -913,10 +1042,114 @@ impl ProjectSearchView {
 
     fn search(&mut self, cx: &mut ViewContext<Self>) {
         if let Some(query) = self.build_search_query(cx) {
+            let search = SerializedSearch::new(&query, self.current_replacement(cx));
+            let project = self.model.read(cx).project.clone();
+            SearchHistoryEntries::add(&project, search.clone(), cx);
+            if let Some(workspace_id) = self.workspace_id {
+                cx.background_executor()
+                    .spawn(SEARCH_DB.add_to_history(workspace_id, search))
+                    .detach_and_log_err(cx);
+            }
             self.model.update(cx, |model, cx| model.search(query, cx));
         }
     }
 
+    /// The replacement text, if replacing is enabled.
+    fn current_replacement(&self, cx: &AppContext) -> Option<String> {
+        self.replace_enabled.then(|| self.replacement(cx))
+    }
+
+    /// The search currently entered in the view, if it is valid.
+    pub(crate) fn serialized_search(
+        &mut self,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<SerializedSearch> {
+        let query = self.build_search_query(cx)?;
+        Some(SerializedSearch::new(&query, self.current_replacement(cx)))
+    }
+
+    /// Fills the inputs of the view with a persisted search and runs it.
+    pub(crate) fn run_serialized_search(
+        &mut self,
+        search: &SerializedSearch,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.search_options = search.options;
+        self.syntax_scope = search.syntax_scope;
+        self.set_search_editor(SearchInputKind::Query, &search.query, cx);
+        self.set_search_editor(SearchInputKind::Include, &search.files_to_include, cx);
+        self.set_search_editor(SearchInputKind::Exclude, &search.files_to_exclude, cx);
+        if !search.files_to_include.is_empty() || !search.files_to_exclude.is_empty() {
+            self.filters_enabled = true;
+        }
+        self.replace_enabled = search.replacement.is_some();
+        self.replacement_editor.update(cx, |editor, cx| {
+            editor.set_text(search.replacement.clone().unwrap_or_default(), cx)
+        });
+        self.search(cx);
+        cx.notify();
+    }
+
+    /// Runs a persisted search in the project search of the active pane,
+    /// or in a new one if there is none or `new_tab` is set.
+    pub(crate) fn open_serialized_search(
+        workspace: &mut Workspace,
+        search: &SerializedSearch,
+        new_tab: bool,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items()
+            .find_map(|item| item.downcast::<ProjectSearchView>())
+            .filter(|_| !new_tab);
+
+        let search_view = if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, cx);
+            existing
+        } else {
+            let weak_workspace = cx.view().downgrade();
+            let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+            let view = cx.new_view(|cx| ProjectSearchView::new(weak_workspace, model, cx, None));
+            workspace.add_item_to_active_pane(Box::new(view.clone()), None, true, cx);
+            view
+        };
+
+        search_view.update(cx, |search_view, cx| {
+            search_view.run_serialized_search(search, cx);
+            search_view.focus_results_editor(cx);
+        });
+    }
+
+    fn run_saved_search(
+        workspace: &mut Workspace,
+        action: &RunSavedSearch,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(workspace_id) = workspace.database_id() else {
+            return;
+        };
+        let name = action.name.clone();
+        cx.spawn(|workspace, mut cx| async move {
+            let search = cx
+                .background_executor()
+                .spawn({
+                    let name = name.clone();
+                    async move { SEARCH_DB.saved_search(workspace_id, name) }
+                })
+                .await?;
+            workspace.update(&mut cx, |workspace, cx| match search {
+                Some(search) => Self::open_serialized_search(workspace, &search, false, cx),
+                None => {
+                    let message = format!("No saved search named \"{name}\"");
+                    workspace.show_error(&anyhow::anyhow!(message), cx);
+                }
+            })
+        })
+        .detach_and_prompt_err("Failed to read the saved search", cx, |_, _| None);
+    }
+
     pub fn search_query_text(&self, cx: &WindowContext) -> String {
         self.query_editor.read(cx).text(cx)
     }
@@ -965,7 +1198,32 @@ impl ProjectSearchView {
                 }
             };
 
-        let query = if self.search_options.contains(SearchOptions::REGEX) {
+        let query = if self.search_options.contains(SearchOptions::STRUCTURAL) {
+            match SearchQuery::structural(
+                text,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+                open_buffers,
+            ) {
+                Ok(query) => {
+                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
+                    if should_unmark_error {
+                        cx.notify();
+                    }
+
+                    Some(query)
+                }
+                Err(_e) => {
+                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
+                    if should_mark_error {
+                        cx.notify();
+                    }
+
+                    None
+                }
+            }
+        } else if self.search_options.contains(SearchOptions::REGEX) {
             match SearchQuery::regex(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
@@ -1026,7 +1284,7 @@ impl ProjectSearchView {
         if query.as_ref().is_some_and(|query| query.is_empty()) {
             return None;
         }
-        query
+        query.map(|query| query.with_syntax_scope(self.syntax_scope))
     }
 
     fn open_buffers(&self, cx: &mut ViewContext<Self>) -> Vec<Model<Buffer>> {
@@ -1109,6 +1367,26 @@ impl ProjectSearchView {
         editor.update(cx, |included_editor, cx| included_editor.set_text(text, cx));
     }
 
+    /// Fills an input with an entry of its search history. Queries also restore the options
+    /// and replacement they were last searched with.
+    fn set_history_entry(&mut self, kind: SearchInputKind, text: &str, cx: &mut ViewContext<Self>) {
+        self.set_search_editor(kind, text, cx);
+        if kind != SearchInputKind::Query {
+            return;
+        }
+        let project = self.model.read(cx).project.clone();
+        let Some(search) = SearchHistoryEntries::get(&project, text, cx).cloned() else {
+            return;
+        };
+        self.search_options = search.options;
+        self.syntax_scope = search.syntax_scope;
+        self.replace_enabled = search.replacement.is_some();
+        self.replacement_editor.update(cx, |editor, cx| {
+            editor.set_text(search.replacement.unwrap_or_default(), cx)
+        });
+        cx.notify();
+    }
+
     fn focus_results_editor(&mut self, cx: &mut ViewContext<Self>) {
         self.query_editor.update(cx, |query_editor, cx| {
             let cursor = query_editor.selections.newest_anchor().head();
@@ -1202,6 +1480,18 @@ impl ProjectSearchView {
                     .key_binding(KeyBinding::for_action_in(&ToggleRegex, &focus_handle, cx))
                     .on_click(|_event, cx| cx.dispatch_action(ToggleRegex.boxed_clone())),
             )
+            .child(
+                Button::new("structural", "Match syntax structure")
+                    .icon(IconName::Code)
+                    .icon_position(IconPosition::Start)
+                    .icon_size(IconSize::Small)
+                    .key_binding(KeyBinding::for_action_in(
+                        &ToggleStructural,
+                        &focus_handle,
+                        cx,
+                    ))
+                    .on_click(|_event, cx| cx.dispatch_action(ToggleStructural.boxed_clone())),
+            )
             .child(
                 Button::new("match-case", "Match case")
                     .icon(IconName::CaseSensitive)
@@ -1410,6 +1700,22 @@ impl ProjectSearchBar {
         }
     }
 
+    fn select_syntax_scope(&mut self, scope: SyntaxScope, cx: &mut ViewContext<Self>) -> bool {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.syntax_scope = scope;
+                if search_view.model.read(cx).active_query.is_some() {
+                    search_view.search(cx);
+                }
+            });
+
+            cx.notify();
+            true
+        } else {
+            false
+        }
+    }
+
     fn toggle_opened_only(&mut self, cx: &mut ViewContext<Self>) -> bool {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.update(cx, |search_view, cx| {
@@ -1481,7 +1787,7 @@ impl ProjectSearchBar {
                                 String::new()
                             }
                         });
-                        search_view.set_search_editor(kind, &new_query, cx);
+                        search_view.set_history_entry(kind, &new_query, cx);
                     }
                 }
             });
@@ -1513,7 +1819,7 @@ impl ProjectSearchBar {
                                 .current(search_view.model.read(cx).cursor(kind))
                                 .map(str::to_string)
                             {
-                                search_view.set_search_editor(kind, &new_query, cx);
+                                search_view.set_history_entry(kind, &new_query, cx);
                                 return;
                             }
                         }
@@ -1527,7 +1833,7 @@ impl ProjectSearchBar {
                                     .map(str::to_string)
                             })
                         }) {
-                            search_view.set_search_editor(kind, &new_query, cx);
+                            search_view.set_history_entry(kind, &new_query, cx);
                         }
                     }
                 }
@@ -1631,7 +1937,28 @@ impl Render for ProjectSearchBar {
                         cx.listener(|this, _, cx| {
                             this.toggle_search_option(SearchOptions::REGEX, cx);
                         }),
-                    )),
+                    ))
+                    .child(SearchOptions::STRUCTURAL.as_button(
+                        self.is_option_enabled(SearchOptions::STRUCTURAL, cx),
+                        focus_handle.clone(),
+                        cx.listener(|this, _, cx| {
+                            this.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+                        }),
+                    ))
+                    .child({
+                        let search_bar = cx.view().downgrade();
+                        render_syntax_scope_menu(
+                            "project-search-syntax-scope",
+                            search.syntax_scope,
+                            move |scope, cx| {
+                                search_bar
+                                    .update(cx, |search_bar, cx| {
+                                        search_bar.select_syntax_scope(scope, cx);
+                                    })
+                                    .ok();
+                            },
+                        )
+                    }),
             );
 
         let mode_column = h_flex()
@@ -3167,6 +3494,116 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_search_query_history_restores_options(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+
+        window
+            .update(cx, {
+                let search_bar = search_bar.clone();
+                move |workspace, cx| {
+                    workspace.panes()[0].update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                }
+            })
+            .unwrap();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .unwrap()
+        });
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.search_options = SearchOptions::REGEX;
+                    search_view.replace_enabled = true;
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("O.E", cx));
+                    search_view
+                        .replacement_editor
+                        .update(cx, |replacement_editor, cx| {
+                            replacement_editor.set_text("1", cx)
+                        });
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.search_options = SearchOptions::CASE_SENSITIVE;
+                    search_view.replace_enabled = false;
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        // Going back in the history restores the options and replacement of each query.
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.focus_search(cx);
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "O.E");
+                    assert_eq!(search_view.search_options, SearchOptions::REGEX);
+                    assert!(search_view.replace_enabled);
+                    assert_eq!(search_view.replacement(cx), "1");
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.focus_search(cx);
+                    search_bar.next_history_query(&NextHistoryQuery, cx);
+                });
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                    assert!(!search_view.replace_enabled);
+                });
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     async fn test_search_query_history_with_multiple_views(cx: &mut TestAppContext) {
         init_test(cx);
@@ -0,0 +1,345 @@
+use std::sync::Arc;
+
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    rems, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render,
+    Subscription, Task, View, ViewContext, WeakView, WindowContext,
+};
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::notifications::DetachAndPromptErr;
+use workspace::{ModalView, Workspace, WorkspaceId};
+
+use crate::{
+    persistence::{SerializedSearch, SEARCH_DB},
+    project_search::{OpenSavedSearches, SaveSearch},
+    ProjectSearchView,
+};
+
+/// A modal listing the searches saved in the workspace.
+///
+/// When opened to save a search, the text typed into the picker names it,
+/// and confirming an existing entry overwrites that saved search instead.
+pub struct SavedSearches {
+    picker: View<Picker<SavedSearchesDelegate>>,
+    _subscription: Subscription,
+}
+
+impl SavedSearches {
+    pub(crate) fn open(
+        workspace: &mut Workspace,
+        _: &OpenSavedSearches,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        Self::toggle(workspace, None, cx);
+    }
+
+    pub(crate) fn save(workspace: &mut Workspace, _: &SaveSearch, cx: &mut ViewContext<Workspace>) {
+        let Some(search_view) = workspace
+            .active_item(cx)
+            .and_then(|item| item.downcast::<ProjectSearchView>())
+        else {
+            return;
+        };
+        if let Some(search) =
+            search_view.update(cx, |search_view, cx| search_view.serialized_search(cx))
+        {
+            Self::toggle(workspace, Some(search), cx);
+        }
+    }
+
+    fn toggle(
+        workspace: &mut Workspace,
+        search_to_save: Option<SerializedSearch>,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(workspace_id) = workspace.database_id() else {
+            return;
+        };
+        cx.spawn(|workspace, mut cx| async move {
+            let searches = cx
+                .background_executor()
+                .spawn(async move { SEARCH_DB.saved_searches(workspace_id) })
+                .await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let delegate = SavedSearchesDelegate::new(
+                    cx.view().downgrade(),
+                    workspace_id,
+                    searches,
+                    search_to_save,
+                );
+                workspace.toggle_modal(cx, |cx| SavedSearches::new(delegate, cx))
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to read the saved searches", cx, |_, _| None);
+    }
+
+    fn new(delegate: SavedSearchesDelegate, cx: &mut ViewContext<Self>) -> Self {
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl ModalView for SavedSearches {}
+impl EventEmitter<DismissEvent> for SavedSearches {}
+
+impl FocusableView for SavedSearches {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for SavedSearches {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+pub struct SavedSearchesDelegate {
+    workspace: WeakView<Workspace>,
+    workspace_id: WorkspaceId,
+    searches: Vec<(String, SerializedSearch)>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    search_to_save: Option<SerializedSearch>,
+    query: String,
+}
+
+impl SavedSearchesDelegate {
+    fn new(
+        workspace: WeakView<Workspace>,
+        workspace_id: WorkspaceId,
+        searches: Vec<(String, SerializedSearch)>,
+        search_to_save: Option<SerializedSearch>,
+    ) -> Self {
+        Self {
+            workspace,
+            workspace_id,
+            searches,
+            matches: Vec::new(),
+            selected_index: 0,
+            search_to_save,
+            query: String::new(),
+        }
+    }
+
+    /// The name typed into the picker, when saving under a name that is not taken yet.
+    /// It is listed as the first entry, before the matching saved searches.
+    fn new_name(&self) -> Option<&str> {
+        self.search_to_save.as_ref()?;
+        let name = self.query.trim();
+        let is_new = !name.is_empty() && !self.searches.iter().any(|(saved, _)| saved == name);
+        is_new.then_some(name)
+    }
+
+    fn selected_search(&self) -> Option<&(String, SerializedSearch)> {
+        let ix = self
+            .selected_index
+            .checked_sub(self.new_name().is_some() as usize)?;
+        let string_match = self.matches.get(ix)?;
+        self.searches.get(string_match.candidate_id)
+    }
+
+    fn save(&mut self, search: SerializedSearch, cx: &mut ViewContext<Picker<Self>>) {
+        let name = match self.new_name() {
+            Some(name) => name.to_string(),
+            None => match self.selected_search() {
+                Some((name, _)) => name.clone(),
+                None => return,
+            },
+        };
+        let workspace_id = self.workspace_id;
+        cx.spawn(|picker, mut cx| async move {
+            SEARCH_DB.save_search(workspace_id, name, search).await?;
+            picker.update(&mut cx, |_, cx| cx.emit(DismissEvent))?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to save the search", cx, |_, _| None);
+    }
+
+    fn delete_selected(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        let Some((name, _)) = self.selected_search() else {
+            return;
+        };
+        let name = name.clone();
+        let workspace_id = self.workspace_id;
+        cx.spawn(|picker, mut cx| async move {
+            SEARCH_DB
+                .delete_saved_search(workspace_id, name.clone())
+                .await?;
+            picker.update(&mut cx, |picker, cx| {
+                picker.delegate.searches.retain(|(saved, _)| *saved != name);
+                picker.refresh(cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to delete the saved search", cx, |_, _| None);
+    }
+}
+
+impl PickerDelegate for SavedSearchesDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        if self.search_to_save.is_some() {
+            "Name the search, or pick one to overwrite...".into()
+        } else {
+            "Search saved searches...".into()
+        }
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        "No saved searches".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len() + self.new_name().is_some() as usize
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        self.query = query.clone();
+        let candidates = self
+            .searches
+            .iter()
+            .enumerate()
+            .map(|(ix, (name, _))| StringMatchCandidate::new(ix, name))
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    10000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    delegate.selected_index = delegate
+                        .selected_index
+                        .min(delegate.match_count().saturating_sub(1));
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(search) = self.search_to_save.clone() {
+            self.save(search, cx);
+            return;
+        }
+        let Some((_, search)) = self.selected_search() else {
+            return;
+        };
+        let search = search.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::open_serialized_search(workspace, &search, secondary, cx)
+            })
+            .log_err();
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let item = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(selected);
+
+        let ix = match self.new_name() {
+            Some(name) if ix == 0 => {
+                return Some(item.child(Label::new(format!("Save as \"{name}\""))));
+            }
+            Some(_) => ix - 1,
+            None => ix,
+        };
+        let string_match = self.matches.get(ix)?;
+        let (name, search) = self.searches.get(string_match.candidate_id)?;
+        Some(
+            item.child(
+                v_flex()
+                    .child(HighlightedLabel::new(
+                        name.clone(),
+                        string_match.positions.clone(),
+                    ))
+                    .child(
+                        Label::new(search.query.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .single_line(),
+                    ),
+            ),
+        )
+    }
+
+    fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        let has_selection = self.selected_search().is_some();
+        Some(
+            h_flex()
+                .w_full()
+                .p_2()
+                .gap_1()
+                .justify_end()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Button::new("delete-saved-search", "Delete")
+                        .label_size(LabelSize::Small)
+                        .disabled(!has_selection)
+                        .on_click(cx.listener(|picker, _, cx| {
+                            picker.delegate.delete_selected(cx);
+                        })),
+                )
+                .when(self.search_to_save.is_none(), |this| {
+                    this.child(
+                        Button::new("open-saved-search-in-new-tab", "Open in New Tab")
+                            .label_size(LabelSize::Small)
+                            .disabled(!has_selection)
+                            .on_click(cx.listener(|picker, _, cx| {
+                                picker.delegate.confirm(true, cx);
+                            })),
+                    )
+                })
+                .into_any_element(),
+        )
+    }
+}
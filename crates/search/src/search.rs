@@ -1,16 +1,19 @@
 use bitflags::bitflags;
 pub use buffer_search::BufferSearchBar;
 use editor::SearchSettings;
-use gpui::{actions, Action, AppContext, FocusHandle, IntoElement};
-use project::search::SearchQuery;
+use gpui::{actions, impl_actions, Action, AppContext, FocusHandle, IntoElement};
+use project::search::{SearchQuery, SyntaxScope};
 pub use project_search::ProjectSearchView;
+use serde::Deserialize;
 use ui::{prelude::*, Tooltip};
 use ui::{ButtonStyle, IconButton, IconButtonShape};
 use workspace::notifications::NotificationId;
 use workspace::{Toast, Workspace};
 
 pub mod buffer_search;
+mod persistence;
 pub mod project_search;
+mod saved_searches;
 pub(crate) mod search_bar;
 
 pub fn init(cx: &mut AppContext) {
@@ -27,6 +30,7 @@ actions!(
         ToggleCaseSensitive,
         ToggleIncludeIgnored,
         ToggleRegex,
+        ToggleStructural,
         ToggleReplace,
         ToggleSelection,
         SelectNextMatch,
@@ -39,6 +43,14 @@ actions!(
     ]
 );
 
+/// Restricts search matches to comments, string literals or code.
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct SelectSyntaxScope {
+    pub scope: SyntaxScope,
+}
+
+impl_actions!(search, [SelectSyntaxScope]);
+
 bitflags! {
     #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
     pub struct SearchOptions: u8 {
@@ -47,6 +59,7 @@ bitflags! {
         const CASE_SENSITIVE = 0b010;
         const INCLUDE_IGNORED = 0b100;
         const REGEX = 0b1000;
+        const STRUCTURAL = 0b10000;
     }
 }
 
@@ -57,6 +70,7 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => "Match Case Sensitively",
             SearchOptions::INCLUDE_IGNORED => "Also search files ignored by configuration",
             SearchOptions::REGEX => "Use Regular Expressions",
+            SearchOptions::STRUCTURAL => "Match Syntax Structure",
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -67,6 +81,7 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => ui::IconName::CaseSensitive,
             SearchOptions::INCLUDE_IGNORED => ui::IconName::Sliders,
             SearchOptions::REGEX => ui::IconName::Regex,
+            SearchOptions::STRUCTURAL => ui::IconName::Code,
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -77,6 +92,7 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => Box::new(ToggleCaseSensitive),
             SearchOptions::INCLUDE_IGNORED => Box::new(ToggleIncludeIgnored),
             SearchOptions::REGEX => Box::new(ToggleRegex),
+            SearchOptions::STRUCTURAL => Box::new(ToggleStructural),
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -91,6 +107,7 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::STRUCTURAL, query.is_structural());
         options
     }
 
@@ -1,7 +1,12 @@
+use std::rc::Rc;
+
 use gpui::{Action, FocusHandle, IntoElement};
-use ui::{prelude::*, Tooltip};
+use project::search::SyntaxScope;
+use ui::{prelude::*, ContextMenu, PopoverMenu, Tooltip};
 use ui::{IconButton, IconButtonShape};
 
+use crate::SelectSyntaxScope;
+
 pub(super) fn render_nav_button(
     icon: ui::IconName,
     active: bool,
@@ -18,3 +23,55 @@ pub(super) fn render_nav_button(
     .tooltip(move |cx| Tooltip::for_action_in(tooltip, action, &focus_handle, cx))
     .disabled(!active)
 }
+
+/// Renders a menu for restricting matches to comments, string literals or code.
+pub(super) fn render_syntax_scope_menu(
+    id: &'static str,
+    selected_scope: SyntaxScope,
+    on_select: impl Fn(SyntaxScope, &mut WindowContext) + 'static,
+) -> impl IntoElement {
+    let on_select = Rc::new(on_select);
+    PopoverMenu::new(id)
+        .trigger(
+            IconButton::new(
+                SharedString::from(format!("{id}-trigger")),
+                IconName::ListTree,
+            )
+            .style(ButtonStyle::Subtle)
+            .shape(IconButtonShape::Square)
+            .toggle_state(selected_scope != SyntaxScope::Anywhere)
+            .tooltip(move |cx| {
+                Tooltip::text(format!("Search {}", syntax_scope_label(selected_scope)), cx)
+            }),
+        )
+        .menu(move |cx| {
+            let on_select = on_select.clone();
+            Some(ContextMenu::build(cx, move |mut menu, _| {
+                for scope in [
+                    SyntaxScope::Anywhere,
+                    SyntaxScope::Comments,
+                    SyntaxScope::Strings,
+                    SyntaxScope::Code,
+                ] {
+                    let on_select = on_select.clone();
+                    menu = menu.toggleable_entry(
+                        syntax_scope_label(scope),
+                        scope == selected_scope,
+                        IconPosition::Start,
+                        Some(SelectSyntaxScope { scope }.boxed_clone()),
+                        move |cx| on_select(scope, cx),
+                    );
+                }
+                menu
+            }))
+        })
+}
+
+fn syntax_scope_label(scope: SyntaxScope) -> &'static str {
+    match scope {
+        SyntaxScope::Anywhere => "Anywhere",
+        SyntaxScope::Comments => "Only in Comments",
+        SyntaxScope::Strings => "Only in Strings",
+        SyntaxScope::Code => "Only in Code",
+    }
+}
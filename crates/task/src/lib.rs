@@ -1,6 +1,7 @@
 //! Baseline interface of Tasks in Zed: all tasks in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+mod problem_matcher;
 pub mod static_source;
 mod task_template;
 mod vscode_format;
@@ -13,7 +14,11 @@ use std::borrow::Cow;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-pub use task_template::{HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
+pub use problem_matcher::{
+    BuiltInProblemMatcher, CustomProblemMatcher, Problem, ProblemLineMatcher, ProblemMatcher,
+    ProblemPattern, ProblemSeverity,
+};
+pub use task_template::{DependsOrder, HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
 pub use vscode_format::VsCodeTaskFile;
 pub use zed_actions::RevealTarget;
 
@@ -58,6 +63,12 @@ pub struct SpawnInTerminal {
     pub show_summary: bool,
     /// Whether to show the command line in the task output.
     pub show_command: bool,
+    /// How to find problems in the task output, to show them as diagnostics.
+    pub problem_matcher: Option<ProblemMatcher>,
+    /// Tasks to run before this one, resolved with the same context.
+    pub dependencies: Vec<SpawnInTerminal>,
+    /// How to run the `dependencies`.
+    pub depends_order: DependsOrder,
 }
 
 /// A final form of the [`TaskTemplate`], that got resolved with a particualar [`TaskContext`] and now is ready to spawn the actual task.
@@ -71,6 +82,8 @@ pub struct ResolvedTask {
     pub id: TaskId,
     /// A template the task got resolved from.
     original_task: TaskTemplate,
+    /// A context the task got resolved with, to resolve its dependencies with later.
+    task_context: TaskContext,
     /// Full, unshortened label of the task after all resolutions are made.
     pub resolved_label: String,
     /// Variables that were substituted during the task template resolution.
@@ -86,6 +99,11 @@ impl ResolvedTask {
         &self.original_task
     }
 
+    /// A context the task got resolved with.
+    pub fn task_context(&self) -> &TaskContext {
+        &self.task_context
+    }
+
     /// Variables that were substituted during the task template resolution.
     pub fn substituted_variables(&self) -> &HashSet<VariableName> {
         &self.substituted_variables
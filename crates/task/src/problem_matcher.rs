@@ -0,0 +1,536 @@
+use std::collections::VecDeque;
+
+use anyhow::Context;
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes how to find problems (errors, warnings, etc.) in the output of a task,
+/// so that they can be shown as diagnostics.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcher {
+    /// One of the matchers for the output of commonly used tools.
+    BuiltIn(BuiltInProblemMatcher),
+    /// A matcher that looks for problems with user-provided regular expressions.
+    Custom(CustomProblemMatcher),
+}
+
+/// Problem matchers for the output of commonly used tools.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltInProblemMatcher {
+    /// Errors and warnings reported by `rustc` and `cargo`.
+    Rustc,
+    /// Errors reported by the TypeScript compiler.
+    Tsc,
+    /// Problems reported by ESLint's default, "stylish", formatter.
+    Eslint,
+    /// Errors and warnings reported by `gcc` and `clang`.
+    Gcc,
+    /// Errors reported by the `go` tool.
+    Go,
+}
+
+/// A problem matcher based on regular expressions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CustomProblemMatcher {
+    /// The patterns to match against consecutive lines of the task output.
+    /// A problem is reported once every pattern has matched, combining the groups captured by each of them.
+    pub pattern: Vec<ProblemPattern>,
+}
+
+/// A regular expression that captures (parts of) a problem from a single line of the task output.
+/// The fields name the indices of the capture groups with the corresponding parts of the problem.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression to match against a line of the task output.
+    pub regexp: String,
+    /// The group with the path of the file with the problem, relative to the task's working directory.
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// The group with the 1-based line of the problem.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// The group with the 1-based column of the problem.
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// The group with the 1-based line where the problem ends.
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// The group with the 1-based column where the problem ends.
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    /// The group with the severity of the problem, e.g. `error` or `warning`.
+    /// Problems without a severity are reported as errors.
+    #[serde(default)]
+    pub severity: Option<usize>,
+    /// The group with the code of the problem.
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// The group with the message of the problem.
+    #[serde(default)]
+    pub message: Option<usize>,
+    /// Whether the pattern may match several lines in a row, reporting a problem for each of them.
+    /// Only the last pattern of a matcher can loop.
+    #[serde(default)]
+    pub r#loop: bool,
+}
+
+/// A problem found in the task output.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Problem {
+    /// The path of the file with the problem, as it was printed by the task.
+    pub path: String,
+    /// The 1-based line of the problem.
+    pub line: u32,
+    /// The 1-based column of the problem.
+    pub column: Option<u32>,
+    /// The 1-based line where the problem ends.
+    pub end_line: Option<u32>,
+    /// The 1-based column where the problem ends.
+    pub end_column: Option<u32>,
+    /// The severity of the problem.
+    pub severity: ProblemSeverity,
+    /// The code of the problem.
+    pub code: Option<String>,
+    /// The message of the problem.
+    pub message: String,
+}
+
+/// The severity of a [`Problem`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProblemSeverity {
+    /// An error.
+    #[default]
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message.
+    Info,
+    /// A hint, e.g. a `note` emitted by a compiler.
+    Hint,
+}
+
+impl ProblemSeverity {
+    fn parse(severity: &str) -> Self {
+        match severity.to_ascii_lowercase().as_str() {
+            "warning" | "warn" => Self::Warning,
+            "info" | "information" => Self::Info,
+            "hint" | "note" | "help" => Self::Hint,
+            _ => Self::Error,
+        }
+    }
+}
+
+impl ProblemMatcher {
+    /// Finds all problems in the given lines of the task output.
+    pub fn match_lines(&self, lines: &[String]) -> anyhow::Result<Vec<Problem>> {
+        let mut matcher = self.line_matcher()?;
+        let mut problems = Vec::new();
+        for line in lines {
+            problems.extend(matcher.push_line(line.clone()));
+        }
+        problems.extend(matcher.finish());
+        Ok(problems)
+    }
+
+    /// Creates a matcher that finds problems in the task output while it is being printed.
+    pub fn line_matcher(&self) -> anyhow::Result<ProblemLineMatcher> {
+        let patterns = match self {
+            Self::BuiltIn(matcher) => matcher.patterns(),
+            Self::Custom(matcher) => matcher.pattern.clone(),
+        };
+        let regexes = patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(&pattern.regexp)
+                    .with_context(|| format!("invalid problem pattern `{}`", pattern.regexp))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(ProblemLineMatcher {
+            patterns,
+            regexes,
+            pending_lines: VecDeque::new(),
+        })
+    }
+}
+
+/// Finds problems in the task output one line at a time, keeping only the lines
+/// that may still turn out to be a part of a multi-line problem.
+pub struct ProblemLineMatcher {
+    patterns: Vec<ProblemPattern>,
+    regexes: Vec<Regex>,
+    pending_lines: VecDeque<String>,
+}
+
+impl ProblemLineMatcher {
+    /// Adds the next line of the task output, returning the problems it completes.
+    pub fn push_line(&mut self, line: String) -> Vec<Problem> {
+        if self.patterns.is_empty() {
+            return Vec::new();
+        }
+        self.pending_lines.push_back(line);
+        self.match_pending_lines(false)
+    }
+
+    /// Returns the problems completed by the end of the task output.
+    pub fn finish(&mut self) -> Vec<Problem> {
+        self.match_pending_lines(true)
+    }
+
+    fn match_pending_lines(&mut self, is_complete: bool) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        while !self.pending_lines.is_empty() {
+            let Some((matched_lines, matched_problems)) = self.match_first_lines(is_complete)
+            else {
+                break;
+            };
+            problems.extend(matched_problems);
+            self.pending_lines.drain(..matched_lines);
+        }
+        problems
+    }
+
+    /// Matches the patterns starting at the first pending line, returning how many lines
+    /// can be discarded, or `None` if more lines are needed to tell.
+    fn match_first_lines(&self, is_complete: bool) -> Option<(usize, Vec<Problem>)> {
+        let last_pattern = self.patterns.last()?;
+        let not_matched = Some((1, Vec::new()));
+
+        let mut problem = Problem::default();
+        let mut end = 0;
+        for (ix, (pattern, regex)) in self.patterns.iter().zip(&self.regexes).enumerate() {
+            if pattern.r#loop && ix + 1 == self.patterns.len() {
+                break;
+            }
+            let Some(line) = self.pending_lines.get(end) else {
+                return if is_complete { not_matched } else { None };
+            };
+            let Some(captures) = regex.captures(line) else {
+                return not_matched;
+            };
+            pattern.apply(&captures, &mut problem);
+            end += 1;
+        }
+
+        if !last_pattern.r#loop {
+            return Some((end, problem.validate().into_iter().collect()));
+        }
+
+        let regex = self.regexes.last().unwrap();
+        let looped_start = end;
+        let mut problems = Vec::new();
+        loop {
+            let Some(line) = self.pending_lines.get(end) else {
+                if is_complete {
+                    break;
+                }
+                return None;
+            };
+            let Some(captures) = regex.captures(line) else {
+                break;
+            };
+            let mut looped_problem = problem.clone();
+            last_pattern.apply(&captures, &mut looped_problem);
+            problems.extend(looped_problem.validate());
+            end += 1;
+        }
+        if end > looped_start {
+            Some((end, problems))
+        } else {
+            not_matched
+        }
+    }
+}
+
+impl BuiltInProblemMatcher {
+    fn patterns(&self) -> Vec<ProblemPattern> {
+        match self {
+            Self::Rustc => vec![
+                ProblemPattern {
+                    regexp: r"^(warning|error)(?:\[(\w+)\])?: (.*)$".to_string(),
+                    severity: Some(1),
+                    code: Some(2),
+                    message: Some(3),
+                    ..Default::default()
+                },
+                ProblemPattern {
+                    regexp: r"^\s*--> (.+?):(\d+):(\d+)$".to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    ..Default::default()
+                },
+            ],
+            Self::Tsc => vec![ProblemPattern {
+                regexp: r"^([^\s].*)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+(TS\d+)\s*:\s*(.*)$".to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                code: Some(5),
+                message: Some(6),
+                ..Default::default()
+            }],
+            Self::Eslint => vec![
+                ProblemPattern {
+                    regexp: r"^([^\s].*)$".to_string(),
+                    file: Some(1),
+                    ..Default::default()
+                },
+                ProblemPattern {
+                    regexp: r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.*?)(?:\s\s+(\S+))?$"
+                        .to_string(),
+                    line: Some(1),
+                    column: Some(2),
+                    severity: Some(3),
+                    message: Some(4),
+                    code: Some(5),
+                    r#loop: true,
+                    ..Default::default()
+                },
+            ],
+            Self::Gcc => vec![ProblemPattern {
+                regexp: r"^(.+?):(\d+):(\d+):\s+(?:fatal\s+)?(warning|error|note):\s+(.*)$"
+                    .to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                ..Default::default()
+            }],
+            Self::Go => vec![ProblemPattern {
+                regexp: r"^\s*([^\s:]+\.go):(\d+)(?::(\d+))?:\s+(.*)$".to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                message: Some(4),
+                ..Default::default()
+            }],
+        }
+    }
+}
+
+impl ProblemPattern {
+    fn apply(&self, captures: &Captures, problem: &mut Problem) {
+        let group = |index: Option<usize>| {
+            index
+                .and_then(|index| captures.get(index))
+                .map(|group| group.as_str().trim())
+                .filter(|group| !group.is_empty())
+        };
+        let number = |index: Option<usize>| group(index).and_then(|group| group.parse().ok());
+
+        if let Some(path) = group(self.file) {
+            problem.path = path.to_string();
+        }
+        if let Some(line) = number(self.line) {
+            problem.line = line;
+        }
+        if let Some(column) = number(self.column) {
+            problem.column = Some(column);
+        }
+        if let Some(end_line) = number(self.end_line) {
+            problem.end_line = Some(end_line);
+        }
+        if let Some(end_column) = number(self.end_column) {
+            problem.end_column = Some(end_column);
+        }
+        if let Some(severity) = group(self.severity) {
+            problem.severity = ProblemSeverity::parse(severity);
+        }
+        if let Some(code) = group(self.code) {
+            problem.code = Some(code.to_string());
+        }
+        if let Some(message) = group(self.message) {
+            problem.message = message.to_string();
+        }
+    }
+}
+
+impl Problem {
+    fn validate(self) -> Option<Self> {
+        (!self.path.is_empty() && self.line > 0).then_some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(output: &str) -> Vec<String> {
+        output.lines().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_rustc_problem_matcher() {
+        let output = lines(
+            "   Compiling foo v0.1.0 (/tmp/foo)
+error[E0425]: cannot find value `x` in this scope
+ --> src/main.rs:2:13
+  |
+2 |     let y = x;
+  |             ^ not found in this scope
+
+warning: unused variable: `y`
+ --> src/lib.rs:10:9
+error: could not compile `foo` (bin \"foo\") due to 1 previous error",
+        );
+        let problems = ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)
+            .match_lines(&output)
+            .unwrap();
+        assert_eq!(
+            problems,
+            vec![
+                Problem {
+                    path: "src/main.rs".to_string(),
+                    line: 2,
+                    column: Some(13),
+                    severity: ProblemSeverity::Error,
+                    code: Some("E0425".to_string()),
+                    message: "cannot find value `x` in this scope".to_string(),
+                    ..Default::default()
+                },
+                Problem {
+                    path: "src/lib.rs".to_string(),
+                    line: 10,
+                    column: Some(9),
+                    severity: ProblemSeverity::Warning,
+                    message: "unused variable: `y`".to_string(),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tsc_problem_matcher() {
+        let output = lines("src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\nFound 1 error.");
+        let problems = ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)
+            .match_lines(&output)
+            .unwrap();
+        assert_eq!(
+            problems,
+            vec![Problem {
+                path: "src/index.ts".to_string(),
+                line: 3,
+                column: Some(7),
+                severity: ProblemSeverity::Error,
+                code: Some("TS2322".to_string()),
+                message: "Type 'string' is not assignable to type 'number'.".to_string(),
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_looping_problem_matcher() {
+        let output = lines(
+            "
+/home/user/project/index.js
+  1:7   error    'a' is assigned a value but never used  no-unused-vars
+  2:1   warning  Unexpected console statement            no-console
+
+✖ 2 problems (1 error, 1 warning)",
+        );
+        let problems = ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Eslint)
+            .match_lines(&output)
+            .unwrap();
+        assert_eq!(
+            problems,
+            vec![
+                Problem {
+                    path: "/home/user/project/index.js".to_string(),
+                    line: 1,
+                    column: Some(7),
+                    severity: ProblemSeverity::Error,
+                    code: Some("no-unused-vars".to_string()),
+                    message: "'a' is assigned a value but never used".to_string(),
+                    ..Default::default()
+                },
+                Problem {
+                    path: "/home/user/project/index.js".to_string(),
+                    line: 2,
+                    column: Some(1),
+                    severity: ProblemSeverity::Warning,
+                    code: Some("no-console".to_string()),
+                    message: "Unexpected console statement".to_string(),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_problem_matcher() {
+        let matcher: ProblemMatcher = serde_json_lenient::from_str(
+            r#"{
+                "pattern": [{
+                    "regexp": "^(.*):(\\d+): (.*)$",
+                    "file": 1,
+                    "line": 2,
+                    "message": 3
+                }]
+            }"#,
+        )
+        .unwrap();
+        let problems = matcher
+            .match_lines(&lines("checking...\nfoo.txt:4: trailing whitespace"))
+            .unwrap();
+        assert_eq!(
+            problems,
+            vec![Problem {
+                path: "foo.txt".to_string(),
+                line: 4,
+                message: "trailing whitespace".to_string(),
+                ..Default::default()
+            }]
+        );
+
+        let matcher: ProblemMatcher = serde_json_lenient::from_str(r#""gcc""#).unwrap();
+        assert_eq!(matcher, ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Gcc));
+    }
+
+    #[test]
+    fn test_problem_line_matcher() {
+        let mut matcher = ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)
+            .line_matcher()
+            .unwrap();
+        assert_eq!(matcher.push_line("   Compiling foo v0.1.0".to_string()), []);
+        assert_eq!(matcher.push_line("error: mismatched types".to_string()), []);
+        assert_eq!(
+            matcher.push_line(" --> src/main.rs:3:5".to_string()),
+            [Problem {
+                path: "src/main.rs".to_string(),
+                line: 3,
+                column: Some(5),
+                message: "mismatched types".to_string(),
+                ..Default::default()
+            }]
+        );
+        assert_eq!(matcher.pending_lines.len(), 0);
+
+        let mut matcher = ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Eslint)
+            .line_matcher()
+            .unwrap();
+        assert_eq!(matcher.push_line("/tmp/foo.js".to_string()), []);
+        assert_eq!(
+            matcher.push_line("  1:1  error  Unexpected var  no-var".to_string()),
+            []
+        );
+        assert_eq!(
+            matcher.finish(),
+            [Problem {
+                path: "/tmp/foo.js".to_string(),
+                line: 1,
+                column: Some(1),
+                code: Some("no-var".to_string()),
+                message: "Unexpected var".to_string(),
+                ..Default::default()
+            }]
+        );
+    }
+}
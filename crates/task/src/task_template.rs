@@ -9,8 +9,8 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
-    ZED_VARIABLE_NAME_PREFIX,
+    ProblemMatcher, ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId,
+    VariableName, ZED_VARIABLE_NAME_PREFIX,
 };
 
 /// A template definition of a Zed task to run.
@@ -25,6 +25,8 @@ pub struct TaskTemplate {
     /// Human readable name of the task to display in the UI.
     pub label: String,
     /// Executable command to spawn.
+    /// May be omitted for tasks that only run their dependencies.
+    #[serde(default)]
     pub command: String,
     /// Arguments to the command.
     #[serde(default)]
@@ -70,6 +72,37 @@ pub struct TaskTemplate {
     /// Whether to show the command line in the task output.
     #[serde(default = "default_true")]
     pub show_command: bool,
+    /// How to find problems in the task output, to show them as diagnostics after the task finishes:
+    /// * one of the built-in matchers: `rustc`, `tsc`, `eslint`, `gcc` or `go`
+    /// * or `{ "pattern": [{ "regexp": "...", "file": 1, "line": 2, "message": 3 }] }`,
+    ///   with the capture groups of the regular expression that contain each part of the problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub problem_matcher: Option<ProblemMatcher>,
+    /// Labels of the tasks to run before this one. If any of them fails, this task is not run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// How to run the tasks from `depends_on`:
+    /// * `sequence` — one after another, in the order they are listed (default)
+    /// * `parallel` — all at once
+    #[serde(default, skip_serializing_if = "DependsOrder::is_sequence")]
+    pub depends_order: DependsOrder,
+}
+
+/// How to run the dependencies of a task.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Run the dependencies one after another, in the order they are listed.
+    #[default]
+    Sequence,
+    /// Run all dependencies at once.
+    Parallel,
+}
+
+impl DependsOrder {
+    fn is_sequence(&self) -> bool {
+        *self == Self::Sequence
+    }
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -122,7 +155,9 @@ impl TaskTemplate {
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        if self.label.trim().is_empty()
+            || (self.command.trim().is_empty() && self.depends_on.is_empty())
+        {
             return None;
         }
 
@@ -221,6 +256,7 @@ impl TaskTemplate {
             id: id.clone(),
             substituted_variables,
             original_task: self.clone(),
+            task_context: cx.clone(),
             resolved_label: full_label.clone(),
             resolved: Some(SpawnInTerminal {
                 id,
@@ -246,6 +282,9 @@ impl TaskTemplate {
                 shell: self.shell.clone(),
                 show_summary: self.show_summary,
                 show_command: self.show_command,
+                problem_matcher: self.problem_matcher.clone(),
+                dependencies: Vec::new(),
+                depends_order: self.depends_order,
             }),
         })
     }
@@ -396,6 +435,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolving_templates_with_dependencies_only() {
+        let compound_task = TaskTemplate {
+            label: "build and test".to_string(),
+            depends_on: vec!["build".to_string(), "test".to_string()],
+            depends_order: DependsOrder::Parallel,
+            ..TaskTemplate::default()
+        };
+        let spawn_in_terminal = compound_task
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .expect("should resolve a task that only has dependencies")
+            .resolved
+            .unwrap();
+        assert_eq!(spawn_in_terminal.command, "");
+        assert_eq!(spawn_in_terminal.depends_order, DependsOrder::Parallel);
+        assert!(
+            spawn_in_terminal.dependencies.is_empty(),
+            "dependencies are resolved by the task inventory, not by the template"
+        );
+    }
+
     #[test]
     fn test_template_cwd_resolution() {
         let task_without_cwd = TaskTemplate {
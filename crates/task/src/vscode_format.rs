@@ -1,9 +1,12 @@
-use anyhow::bail;
+use anyhow::{bail, Context as _};
 use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    BuiltInProblemMatcher, CustomProblemMatcher, DependsOrder, ProblemMatcher, ProblemPattern,
+    TaskTemplate, TaskTemplates, VariableName,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -41,6 +44,130 @@ enum Command {
     },
 }
 
+/// Labels of the tasks that a task depends on: either a single label, or a list of them.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeDependsOn {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl VsCodeDependsOn {
+    fn into_labels(self) -> Vec<String> {
+        match self {
+            Self::One(label) => vec![label],
+            Self::Many(labels) => labels,
+        }
+    }
+}
+
+/// A problem matcher, as defined in `tasks.json`: either a reference to a named matcher,
+/// a matcher definition, or a list of those.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemMatcher {
+    Named(String),
+    Custom(VsCodeCustomProblemMatcher),
+    Many(Vec<VsCodeProblemMatcher>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeCustomProblemMatcher {
+    base: Option<String>,
+    pattern: Option<VsCodeProblemPatterns>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemPatterns {
+    One(VsCodeProblemPattern),
+    Many(Vec<VsCodeProblemPattern>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemPattern {
+    regexp: String,
+    file: Option<usize>,
+    line: Option<usize>,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    severity: Option<usize>,
+    code: Option<usize>,
+    message: Option<usize>,
+    #[serde(default)]
+    r#loop: bool,
+}
+
+impl VsCodeProblemMatcher {
+    fn into_zed_format(self) -> Option<ProblemMatcher> {
+        match self {
+            Self::Named(name) => named_problem_matcher(&name),
+            Self::Custom(VsCodeCustomProblemMatcher {
+                pattern: Some(patterns),
+                ..
+            }) => {
+                let pattern = match patterns {
+                    // Single patterns fall back to VS Code's default capture groups.
+                    VsCodeProblemPatterns::One(pattern) => vec![ProblemPattern {
+                        file: pattern.file.or(Some(1)),
+                        line: pattern.line.or(Some(2)),
+                        column: pattern.column.or(Some(3)),
+                        message: pattern.message.or(Some(0)),
+                        ..pattern.into_zed_format()
+                    }],
+                    VsCodeProblemPatterns::Many(patterns) => patterns
+                        .into_iter()
+                        .map(VsCodeProblemPattern::into_zed_format)
+                        .collect(),
+                };
+                Some(ProblemMatcher::Custom(CustomProblemMatcher { pattern }))
+            }
+            Self::Custom(VsCodeCustomProblemMatcher {
+                base: Some(base), ..
+            }) => named_problem_matcher(&base),
+            Self::Custom(_) => None,
+            Self::Many(matchers) => matchers
+                .into_iter()
+                .find_map(VsCodeProblemMatcher::into_zed_format),
+        }
+    }
+}
+
+impl VsCodeProblemPattern {
+    fn into_zed_format(self) -> ProblemPattern {
+        ProblemPattern {
+            regexp: self.regexp,
+            file: self.file,
+            line: self.line,
+            column: self.column,
+            end_line: self.end_line,
+            end_column: self.end_column,
+            severity: self.severity,
+            code: self.code,
+            message: self.message,
+            r#loop: self.r#loop,
+        }
+    }
+}
+
+fn named_problem_matcher(name: &str) -> Option<ProblemMatcher> {
+    let matcher = match name {
+        "$rustc" | "$rustc-watch" => BuiltInProblemMatcher::Rustc,
+        "$tsc" | "$tsc-watch" => BuiltInProblemMatcher::Tsc,
+        "$eslint-stylish" => BuiltInProblemMatcher::Eslint,
+        "$gcc" => BuiltInProblemMatcher::Gcc,
+        "$go" => BuiltInProblemMatcher::Go,
+        _ => {
+            log::warn!("Skipping unsupported problem matcher `{name}`");
+            return None;
+        }
+    };
+    Some(ProblemMatcher::BuiltIn(matcher))
+}
+
 type VsCodeEnvVariable = String;
 type ZedEnvVariable = String;
 
@@ -85,21 +212,32 @@ impl EnvVariableReplacer {
 
 impl VsCodeTaskDefinition {
     fn into_zed_format(self, replacer: &EnvVariableReplacer) -> anyhow::Result<TaskTemplate> {
-        if self.other_attributes.contains_key("dependsOn") {
-            bail!("Encountered unsupported `dependsOn` key during deserialization");
-        }
-        // `type` might not be set in e.g. tasks that use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
-        // as that way we can provide more specific description of why deserialization failed.
-        // E.g. if the command is missing due to `dependsOn` presence, we can check other_attributes first before doing this (and provide nice error message)
-        // catch-all if on value.command presence.
-        let Some(command) = self.command else {
-            bail!("Missing `type` field in task");
+        let depends_on = match self.other_attributes.get("dependsOn") {
+            Some(depends_on) => {
+                serde_json_lenient::from_value::<VsCodeDependsOn>(depends_on.clone())
+                    .context("parsing `dependsOn`")?
+                    .into_labels()
+            }
+            None => Vec::new(),
+        };
+        let depends_order = match self
+            .other_attributes
+            .get("dependsOrder")
+            .and_then(|order| order.as_str())
+        {
+            Some("sequence") => DependsOrder::Sequence,
+            // VS Code runs the dependencies in parallel by default.
+            _ if !depends_on.is_empty() => DependsOrder::Parallel,
+            _ => DependsOrder::default(),
         };
 
-        let (command, args) = match command {
-            Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
-            Command::Shell { command, args } => (command, args),
-            Command::Gulp { task } => ("gulp".to_owned(), vec![task]),
+        // `type` might not be set in tasks that use `dependsOn`, as those might only run other tasks.
+        let (command, args) = match self.command {
+            Some(Command::Npm { script }) => ("npm".to_owned(), vec!["run".to_string(), script]),
+            Some(Command::Shell { command, args }) => (command, args),
+            Some(Command::Gulp { task }) => ("gulp".to_owned(), vec![task]),
+            None if !depends_on.is_empty() => (String::new(), Vec::new()),
+            None => bail!("Missing `type` field in task"),
         };
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
         let command = replacer.replace(&command);
@@ -108,8 +246,16 @@ impl VsCodeTaskDefinition {
             label: self.label,
             command,
             args,
+            depends_on,
+            depends_order,
             ..Default::default()
         };
+        if let Some(problem_matcher) = self.other_attributes.get("problemMatcher") {
+            ret.problem_matcher =
+                serde_json_lenient::from_value::<VsCodeProblemMatcher>(problem_matcher.clone())
+                    .log_err()
+                    .and_then(VsCodeProblemMatcher::into_zed_format);
+        }
         if let Some(options) = self.options {
             ret.cwd = options.cwd.map(|cwd| replacer.replace(&cwd));
             ret.env = options.env;
@@ -155,7 +301,8 @@ mod tests {
 
     use crate::{
         vscode_format::{Command, VsCodeTaskDefinition},
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
+        BuiltInProblemMatcher, DependsOrder, ProblemMatcher, TaskTemplate, TaskTemplates,
+        VsCodeTaskFile,
     };
 
     use super::EnvVariableReplacer;
@@ -257,6 +404,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matcher: Some(ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)),
                 ..Default::default()
             },
             TaskTemplate {
@@ -268,18 +416,21 @@ mod tests {
                     "${ZED_WORKTREE_ROOT}/src".to_string(),
                     "--watch".to_string(),
                 ],
+                problem_matcher: Some(ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)),
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matcher: Some(ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)),
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matcher: Some(ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)),
                 ..Default::default()
             },
         ];
@@ -360,28 +511,50 @@ mod tests {
                 label: "Build Extension in Background".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "watch".to_string()],
+                problem_matcher: Some(ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)),
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matcher: Some(ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)),
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matcher: Some(ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)),
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matcher: Some(ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)),
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matcher: Some(ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)),
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server and Extension".to_string(),
+                depends_on: vec!["Build Server".to_string(), "Build Extension".to_string()],
+                depends_order: DependsOrder::Parallel,
+                problem_matcher: Some(ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)),
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server (Release) and Extension".to_string(),
+                depends_on: vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ],
+                depends_order: DependsOrder::Parallel,
+                problem_matcher: Some(ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)),
                 ..Default::default()
             },
         ];
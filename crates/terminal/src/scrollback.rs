@@ -0,0 +1,234 @@
+use std::fmt::Write as _;
+
+use alacritty_terminal::{
+    event::EventListener,
+    grid::Dimensions,
+    index::{Column, Line, Point as AlacPoint},
+    term::{cell::Flags, TermMode},
+    vte::ansi::{Color as AnsiColor, NamedColor, Processor},
+    Term,
+};
+
+/// The cell flags that are preserved when the scrollback is saved.
+const STYLE_FLAGS: Flags = Flags::BOLD
+    .union(Flags::DIM)
+    .union(Flags::ITALIC)
+    .union(Flags::ALL_UNDERLINES)
+    .union(Flags::INVERSE)
+    .union(Flags::HIDDEN)
+    .union(Flags::STRIKEOUT);
+
+#[derive(Clone, Copy, PartialEq)]
+struct Style {
+    fg: AnsiColor,
+    bg: AnsiColor,
+    flags: Flags,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fg: AnsiColor::Named(NamedColor::Foreground),
+            bg: AnsiColor::Named(NamedColor::Background),
+            flags: Flags::empty(),
+        }
+    }
+}
+
+impl Style {
+    fn write_sgr(&self, output: &mut String) {
+        output.push_str("\x1b[0");
+        for (flag, parameter) in [
+            (Flags::BOLD, "1"),
+            (Flags::DIM, "2"),
+            (Flags::ITALIC, "3"),
+            (Flags::ALL_UNDERLINES, "4"),
+            (Flags::INVERSE, "7"),
+            (Flags::HIDDEN, "8"),
+            (Flags::STRIKEOUT, "9"),
+        ] {
+            if self.flags.intersects(flag) {
+                output.push(';');
+                output.push_str(parameter);
+            }
+        }
+        write_sgr_color(&self.fg, true, output);
+        write_sgr_color(&self.bg, false, output);
+        output.push('m');
+    }
+}
+
+fn write_sgr_color(color: &AnsiColor, foreground: bool, output: &mut String) {
+    let (base, bright_base, extended) = if foreground {
+        (30, 90, 38)
+    } else {
+        (40, 100, 48)
+    };
+    match color {
+        AnsiColor::Named(named) => {
+            let index = *named as usize;
+            if index < 8 {
+                write!(output, ";{}", base + index).ok();
+            } else if index < 16 {
+                write!(output, ";{}", bright_base + index - 8).ok();
+            }
+        }
+        AnsiColor::Indexed(index) => {
+            write!(output, ";{extended};5;{index}").ok();
+        }
+        AnsiColor::Spec(rgb) => {
+            write!(output, ";{extended};2;{};{};{}", rgb.r, rgb.g, rgb.b).ok();
+        }
+    }
+}
+
+/// Serializes the lines above the cursor, including the history, into text with ANSI escape sequences
+/// that keep the colors and styles of the cells.
+/// Returns `None` if there is nothing to save or the alternate screen is active.
+pub(crate) fn serialize_scrollback<T>(term: &Term<T>, max_lines: usize) -> Option<String> {
+    if term.mode().contains(TermMode::ALT_SCREEN) || max_lines == 0 {
+        return None;
+    }
+
+    let grid = term.grid();
+    let cursor_line = grid.cursor.point.line;
+    let mut start_line = term.topmost_line().max(cursor_line - max_lines);
+    let is_empty_line = |line: Line| {
+        (0..term.columns()).all(|column| {
+            let cell = &grid[AlacPoint::new(line, Column(column))];
+            cell.c == ' ' && cell.bg == AnsiColor::Named(NamedColor::Background)
+        })
+    };
+    while start_line < cursor_line && is_empty_line(start_line) {
+        start_line += 1;
+    }
+    if start_line >= cursor_line {
+        return None;
+    }
+
+    let mut output = String::new();
+    let mut current_style = Style::default();
+    let mut line = start_line;
+    while line < cursor_line {
+        let row = &grid[line];
+        let wrapped = row[term.last_column()].flags.contains(Flags::WRAPLINE);
+        let end_column = if wrapped {
+            term.columns()
+        } else {
+            (0..term.columns())
+                .rposition(|column| {
+                    let cell = &row[Column(column)];
+                    cell.c != ' '
+                        || cell.bg != AnsiColor::Named(NamedColor::Background)
+                        || cell.flags.intersects(STYLE_FLAGS)
+                })
+                .map_or(0, |column| column + 1)
+        };
+
+        for column in 0..end_column {
+            let cell = &row[Column(column)];
+            if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                continue;
+            }
+            let style = Style {
+                fg: cell.fg,
+                bg: cell.bg,
+                flags: cell.flags & STYLE_FLAGS,
+            };
+            if style != current_style {
+                style.write_sgr(&mut output);
+                current_style = style;
+            }
+            output.push(cell.c);
+            if let Some(zerowidth) = cell.zerowidth() {
+                output.extend(zerowidth);
+            }
+        }
+
+        if !wrapped {
+            if current_style != Style::default() {
+                output.push_str("\x1b[0m");
+                current_style = Style::default();
+            }
+            output.push_str("\r\n");
+        }
+        line += 1;
+    }
+    if current_style != Style::default() {
+        output.push_str("\x1b[0m");
+    }
+    if !output.ends_with('\n') {
+        output.push_str("\r\n");
+    }
+
+    Some(output)
+}
+
+/// Writes the previously serialized scrollback into the terminal, as if it was printed by the shell.
+pub(crate) fn restore_scrollback<T: EventListener>(term: &mut Term<T>, scrollback: &str) {
+    let mut processor: Processor = Processor::new();
+    for byte in scrollback.bytes() {
+        processor.advance(term, byte);
+    }
+    for byte in b"\x1b[0m" {
+        processor.advance(term, *byte);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{
+        event::VoidListener,
+        index::{Column, Line, Point as AlacPoint},
+        term::{test::TermSize, Config},
+        vte::ansi::{Color as AnsiColor, NamedColor, Processor},
+        Term,
+    };
+
+    use super::{restore_scrollback, serialize_scrollback};
+
+    fn term_with_output(columns: usize, lines: usize, output: &str) -> Term<VoidListener> {
+        let mut term = Term::new(
+            Config::default(),
+            &TermSize::new(columns, lines),
+            VoidListener,
+        );
+        let mut processor: Processor = Processor::new();
+        for byte in output.bytes() {
+            processor.advance(&mut term, byte);
+        }
+        term
+    }
+
+    #[test]
+    fn test_scrollback_round_trip() {
+        let term = term_with_output(
+            10,
+            3,
+            "$ ls\r\n\x1b[31mred\x1b[0m plain\r\na line that wraps\r\n\r\nlast\r\n$ ",
+        );
+        let scrollback = serialize_scrollback(&term, 100).unwrap();
+        assert_eq!(
+            scrollback,
+            "$ ls\r\n\x1b[0;31mred\x1b[0m plain\r\na line that wraps\r\n\r\nlast\r\n"
+        );
+
+        let mut restored = term_with_output(10, 3, "");
+        restore_scrollback(&mut restored, &scrollback);
+        assert_eq!(serialize_scrollback(&restored, 100).unwrap(), scrollback);
+        let red_cell = &restored.grid()[AlacPoint::new(Line(-3), Column(0))];
+        assert_eq!(red_cell.c, 'r');
+        assert_eq!(red_cell.fg, AnsiColor::Named(NamedColor::Red));
+    }
+
+    #[test]
+    fn test_scrollback_size_cap() {
+        let term = term_with_output(10, 3, "one\r\ntwo\r\nthree\r\nfour\r\n$ ");
+        assert_eq!(serialize_scrollback(&term, 2).unwrap(), "three\r\nfour\r\n");
+        assert_eq!(serialize_scrollback(&term, 0), None);
+        assert_eq!(
+            serialize_scrollback(&term_with_output(10, 3, "$ "), 100),
+            None
+        );
+    }
+}
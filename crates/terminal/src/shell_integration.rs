@@ -0,0 +1,558 @@
+//! Shell integration: the prompt and command marks that shells emit with OSC 133 (FinalTerm)
+//! and OSC 633 (VS Code) escape sequences.
+//!
+//! Alacritty ignores these sequences, so the PTY output is scanned before Alacritty parses it.
+//! Every mark is removed from the output and reported through a channel. To know where the mark
+//! was in the terminal grid, the character printed after it is wrapped in a hyperlink with a
+//! [`SHELL_MARK_URI_SCHEME`] URI, which Alacritty keeps on the cell while the grid scrolls and reflows.
+//!
+//! The scanner can also report the printed text line by line, without the escape sequences,
+//! e.g. to find the problems in the output of a task no matter how much of it the grid keeps.
+
+use std::{collections::VecDeque, io, sync::Arc};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    term::cell::Hyperlink,
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+};
+use futures::channel::mpsc::UnboundedSender;
+use polling::{Event as PollingEvent, PollMode, Poller};
+
+/// The scheme of the hyperlinks that mark the positions of shell marks in the terminal grid.
+const SHELL_MARK_URI_SCHEME: &str = "zed-shell-mark:";
+/// OSC sequences longer than this are passed to Alacritty without being inspected.
+const MAX_OSC_LEN: usize = 4096;
+/// Placing marks makes the output longer than the PTY output it was scanned from.
+/// Reading less than the caller asked for makes the scanned output fit into its buffer.
+const READ_SIZE_DIVISOR: usize = 4;
+
+/// What a shell reported about its prompt or the command it runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShellMarkKind {
+    /// `A`: the shell is about to print a prompt.
+    PromptStart,
+    /// `B`: the prompt was printed, the command line follows.
+    CommandStart,
+    /// `C`: the command line was submitted, the command's output follows.
+    CommandExecuted,
+    /// `D`: the command finished, with its exit code if the shell reported one.
+    CommandFinished { exit_code: Option<i32> },
+    /// `E` (OSC 633 only): the command line that the shell runs.
+    CommandLine(String),
+}
+
+/// A shell integration mark, with an id to find its position in the terminal grid by.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShellMark {
+    pub id: usize,
+    pub kind: ShellMarkKind,
+}
+
+/// Returns the ids of the shell marks placed on a cell with the given hyperlink.
+pub(crate) fn shell_mark_ids(hyperlink: &Hyperlink) -> Option<impl Iterator<Item = usize> + '_> {
+    let ids = hyperlink.uri().strip_prefix(SHELL_MARK_URI_SCHEME)?;
+    Some(ids.split(',').filter_map(|id| id.parse().ok()))
+}
+
+/// Whether the hyperlink only marks the position of shell marks, and was not printed by a program.
+pub fn is_shell_mark_hyperlink(hyperlink: &Hyperlink) -> bool {
+    hyperlink.uri().starts_with(SHELL_MARK_URI_SCHEME)
+}
+
+fn parse_shell_mark(osc: &[u8]) -> Option<ShellMarkKind> {
+    let osc = std::str::from_utf8(osc).ok()?;
+    let (is_vscode, mark) = if let Some(mark) = osc.strip_prefix("133;") {
+        (false, mark)
+    } else {
+        (true, osc.strip_prefix("633;")?)
+    };
+    let mut params = mark.split(';');
+    let kind = match params.next()? {
+        "A" => ShellMarkKind::PromptStart,
+        "B" => ShellMarkKind::CommandStart,
+        "C" => ShellMarkKind::CommandExecuted,
+        "D" => ShellMarkKind::CommandFinished {
+            exit_code: params.next().and_then(|exit_code| exit_code.parse().ok()),
+        },
+        "E" if is_vscode => ShellMarkKind::CommandLine(unescape_command_line(params.next()?)),
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Command lines in OSC 633 escape backslashes as `\\`, and semicolons and control characters as `\xAB`.
+fn unescape_command_line(escaped: &str) -> String {
+    let mut command_line = String::with_capacity(escaped.len());
+    let mut rest = escaped;
+    while let Some(ix) = rest.find('\\') {
+        command_line.push_str(&rest[..ix]);
+        rest = &rest[ix + 1..];
+        if let Some(after_backslash) = rest.strip_prefix('\\') {
+            command_line.push('\\');
+            rest = after_backslash;
+        } else if let Some(byte) = rest
+            .strip_prefix('x')
+            .and_then(|hex| hex.get(..2))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            command_line.push(byte as char);
+            rest = &rest[3..];
+        } else {
+            command_line.push('\\');
+        }
+    }
+    command_line.push_str(rest);
+    command_line
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScanState {
+    Ground,
+    Escape,
+    EscapeIntermediate,
+    Csi,
+    Osc,
+    OscEscape,
+    OscPassthrough,
+    OscPassthroughEscape,
+    String,
+    StringEscape,
+}
+
+/// Scans the PTY output for shell integration marks, in the subset of the VT parser states
+/// needed to tell the printed characters from the escape sequences.
+pub(crate) struct ShellMarkScanner {
+    state: ScanState,
+    osc: Vec<u8>,
+    next_mark_id: usize,
+    unplaced_mark_ids: Vec<usize>,
+    /// The continuation bytes left of the character that the marks are placed on.
+    marked_char_bytes_left: usize,
+    /// The hyperlink that the program started last, restored after the hyperlink of a mark ends.
+    program_hyperlink: Option<Vec<u8>>,
+    marks_tx: UnboundedSender<ShellMark>,
+    /// The printed text of the current line, reported through `output_lines_tx` when it ends.
+    output_line: Vec<u8>,
+    /// Whether the cursor returned to the start of the line, so that the next character overwrites it.
+    output_line_returned: bool,
+    output_lines_tx: Option<UnboundedSender<String>>,
+}
+
+impl ShellMarkScanner {
+    pub(crate) fn new(
+        marks_tx: UnboundedSender<ShellMark>,
+        output_lines_tx: Option<UnboundedSender<String>>,
+    ) -> Self {
+        Self {
+            state: ScanState::Ground,
+            osc: Vec::new(),
+            next_mark_id: 0,
+            unplaced_mark_ids: Vec::new(),
+            marked_char_bytes_left: 0,
+            program_hyperlink: None,
+            marks_tx,
+            output_line: Vec::new(),
+            output_line_returned: false,
+            output_lines_tx,
+        }
+    }
+
+    pub(crate) fn scan(&mut self, input: &[u8], output: &mut VecDeque<u8>) {
+        for &byte in input {
+            match self.state {
+                ScanState::Ground => self.scan_ground(byte, output),
+                ScanState::Escape => match byte {
+                    0x1b => output.push_back(0x1b),
+                    b']' => {
+                        self.osc.clear();
+                        self.state = ScanState::Osc;
+                    }
+                    _ => {
+                        output.extend([0x1b, byte]);
+                        self.state = match byte {
+                            b'[' => ScanState::Csi,
+                            b'P' | b'X' | b'^' | b'_' => ScanState::String,
+                            0x20..=0x2f => ScanState::EscapeIntermediate,
+                            _ => ScanState::Ground,
+                        };
+                    }
+                },
+                ScanState::EscapeIntermediate => {
+                    output.push_back(byte);
+                    if !(0x20..=0x2f).contains(&byte) {
+                        self.state = ScanState::Ground;
+                    }
+                }
+                ScanState::Csi => {
+                    output.push_back(byte);
+                    if (0x40..=0x7e).contains(&byte) {
+                        self.state = ScanState::Ground;
+                    }
+                }
+                ScanState::Osc => match byte {
+                    0x07 => self.finish_osc(&[0x07], output),
+                    0x1b => self.state = ScanState::OscEscape,
+                    _ if self.osc.len() >= MAX_OSC_LEN => {
+                        output.extend([0x1b, b']']);
+                        output.extend(self.osc.drain(..));
+                        output.push_back(byte);
+                        self.state = ScanState::OscPassthrough;
+                    }
+                    _ => self.osc.push(byte),
+                },
+                ScanState::OscEscape => {
+                    self.finish_osc(&[0x1b, b'\\'], output);
+                    if byte != b'\\' {
+                        // An escape that does not end the sequence with ST starts a new one.
+                        self.state = ScanState::Escape;
+                        self.scan(&[byte], output);
+                    }
+                }
+                ScanState::OscPassthrough => {
+                    output.push_back(byte);
+                    match byte {
+                        0x07 => self.state = ScanState::Ground,
+                        0x1b => self.state = ScanState::OscPassthroughEscape,
+                        _ => {}
+                    }
+                }
+                ScanState::OscPassthroughEscape | ScanState::StringEscape => {
+                    output.push_back(byte);
+                    self.state = if byte == b'\\' {
+                        ScanState::Ground
+                    } else if self.state == ScanState::StringEscape {
+                        ScanState::String
+                    } else {
+                        ScanState::OscPassthrough
+                    };
+                }
+                ScanState::String => {
+                    output.push_back(byte);
+                    if byte == 0x1b {
+                        self.state = ScanState::StringEscape;
+                    }
+                }
+            }
+        }
+    }
+
+    fn scan_ground(&mut self, byte: u8, output: &mut VecDeque<u8>) {
+        let printable = byte >= 0x20 && byte != 0x7f;
+        if self.marked_char_bytes_left > 0 && !(0x80..=0xbf).contains(&byte) {
+            // The marked character ended early, with an invalid UTF-8 sequence.
+            self.end_mark_hyperlink(output);
+        }
+
+        if byte == 0x1b {
+            self.state = ScanState::Escape;
+            return;
+        }
+        if self.output_lines_tx.is_some() {
+            self.push_output_line_byte(byte, printable);
+        }
+
+        if printable && self.marked_char_bytes_left == 0 && !self.unplaced_mark_ids.is_empty() {
+            let ids = self
+                .unplaced_mark_ids
+                .drain(..)
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            output.extend(format!("\x1b]8;;{SHELL_MARK_URI_SCHEME}{ids}\x1b\\").bytes());
+            self.marked_char_bytes_left = match byte {
+                0xf0..=0xff => 4,
+                0xe0..=0xef => 3,
+                0xc0..=0xdf => 2,
+                _ => 1,
+            };
+            output.push_back(byte);
+            self.marked_char_bytes_left -= 1;
+            if self.marked_char_bytes_left == 0 {
+                self.end_mark_hyperlink(output);
+            }
+            return;
+        }
+
+        output.push_back(byte);
+        if self.marked_char_bytes_left > 0 {
+            self.marked_char_bytes_left -= 1;
+            if self.marked_char_bytes_left == 0 {
+                self.end_mark_hyperlink(output);
+            }
+        }
+    }
+
+    fn push_output_line_byte(&mut self, byte: u8, printable: bool) {
+        match byte {
+            b'\n' => self.send_output_line(),
+            b'\r' => self.output_line_returned = true,
+            0x08 => {
+                self.output_line.pop();
+            }
+            b'\t' => self.output_line.push(byte),
+            _ if printable => {
+                if self.output_line_returned {
+                    self.output_line.clear();
+                    self.output_line_returned = false;
+                }
+                self.output_line.push(byte);
+            }
+            _ => {}
+        }
+    }
+
+    fn send_output_line(&mut self) {
+        self.output_line_returned = false;
+        let line = String::from_utf8_lossy(&self.output_line).into_owned();
+        self.output_line.clear();
+        if let Some(output_lines_tx) = &self.output_lines_tx {
+            output_lines_tx.unbounded_send(line).ok();
+        }
+    }
+
+    fn end_mark_hyperlink(&mut self, output: &mut VecDeque<u8>) {
+        self.marked_char_bytes_left = 0;
+        output.extend(b"\x1b]8;;\x1b\\");
+        if let Some(program_hyperlink) = &self.program_hyperlink {
+            output.extend(program_hyperlink);
+        }
+    }
+
+    fn finish_osc(&mut self, terminator: &[u8], output: &mut VecDeque<u8>) {
+        self.state = ScanState::Ground;
+        if let Some(kind) = parse_shell_mark(&self.osc) {
+            let id = self.next_mark_id;
+            self.next_mark_id += 1;
+            self.unplaced_mark_ids.push(id);
+            self.marks_tx.unbounded_send(ShellMark { id, kind }).ok();
+            return;
+        }
+
+        let mut sequence = vec![0x1b, b']'];
+        sequence.append(&mut self.osc);
+        sequence.extend_from_slice(terminator);
+        if let Some(hyperlink) = sequence.strip_prefix(b"\x1b]8;") {
+            let has_uri = hyperlink
+                .iter()
+                .position(|&byte| byte == b';')
+                .map_or(false, |ix| ix + 1 + terminator.len() < hyperlink.len());
+            self.program_hyperlink = has_uri.then(|| sequence.clone());
+        }
+        output.extend(sequence);
+    }
+}
+
+impl Drop for ShellMarkScanner {
+    fn drop(&mut self) {
+        if !self.output_line.is_empty() {
+            self.send_output_line();
+        }
+    }
+}
+
+/// A PTY that removes the shell integration marks from the output it reads.
+pub(crate) struct ShellIntegrationPty<T> {
+    pty: T,
+    scanner: ShellMarkScanner,
+    read_buffer: Vec<u8>,
+    output: VecDeque<u8>,
+}
+
+impl<T> ShellIntegrationPty<T> {
+    pub(crate) fn new(
+        pty: T,
+        marks_tx: UnboundedSender<ShellMark>,
+        output_lines_tx: Option<UnboundedSender<String>>,
+    ) -> Self {
+        Self {
+            pty,
+            scanner: ShellMarkScanner::new(marks_tx, output_lines_tx),
+            read_buffer: Vec::new(),
+            output: VecDeque::new(),
+        }
+    }
+}
+
+impl<T: EventedReadWrite> io::Read for ShellIntegrationPty<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.output.is_empty() {
+            self.read_buffer
+                .resize((buf.len() / READ_SIZE_DIVISOR).max(1), 0);
+            let read = self.pty.reader().read(&mut self.read_buffer)?;
+            if read == 0 {
+                return Ok(0);
+            }
+            self.scanner
+                .scan(&self.read_buffer[..read], &mut self.output);
+        }
+
+        let len = buf.len().min(self.output.len());
+        for (target, byte) in buf.iter_mut().zip(self.output.drain(..len)) {
+            *target = byte;
+        }
+        Ok(len)
+    }
+}
+
+impl<T: EventedReadWrite> EventedReadWrite for ShellIntegrationPty<T> {
+    type Reader = Self;
+    type Writer = T::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.register(poll, interest, mode)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl<T: EventedPty> EventedPty for ShellIntegrationPty<T> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl<T: OnResize> OnResize for ShellIntegrationPty<T> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.pty.on_resize(window_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::channel::mpsc;
+
+    use super::*;
+
+    fn scan(chunks: &[&[u8]]) -> (String, Vec<ShellMark>) {
+        let (marks_tx, mut marks_rx) = mpsc::unbounded();
+        let mut scanner = ShellMarkScanner::new(marks_tx, None);
+        let mut output = VecDeque::new();
+        for chunk in chunks {
+            scanner.scan(chunk, &mut output);
+        }
+        let mut marks = Vec::new();
+        while let Ok(Some(mark)) = marks_rx.try_next() {
+            marks.push(mark);
+        }
+        (
+            String::from_utf8(output.into_iter().collect()).unwrap(),
+            marks,
+        )
+    }
+
+    #[test]
+    fn test_marks_are_placed_on_the_next_printed_character() {
+        let (output, marks) = scan(&[
+            b"\x1b]133;A\x07\x1b[1m$ \x1b[0m\x1b]133;B\x07ls\r\n\x1b]133;C\x07",
+            b"file\r\n\x1b]133;D;1\x1b\\\x1b]133;A\x07$ ",
+        ]);
+        assert_eq!(
+            output,
+            "\x1b[1m\x1b]8;;zed-shell-mark:0\x1b\\$\x1b]8;;\x1b\\ \x1b[0m\
+             \x1b]8;;zed-shell-mark:1\x1b\\l\x1b]8;;\x1b\\s\r\n\
+             \x1b]8;;zed-shell-mark:2\x1b\\f\x1b]8;;\x1b\\ile\r\n\
+             \x1b]8;;zed-shell-mark:3,4\x1b\\$\x1b]8;;\x1b\\ "
+        );
+        assert_eq!(
+            marks.into_iter().map(|mark| mark.kind).collect::<Vec<_>>(),
+            vec![
+                ShellMarkKind::PromptStart,
+                ShellMarkKind::CommandStart,
+                ShellMarkKind::CommandExecuted,
+                ShellMarkKind::CommandFinished { exit_code: Some(1) },
+                ShellMarkKind::PromptStart,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_marks_split_across_reads() {
+        let (output, marks) = scan(&[
+            b"\x1b]63",
+            b"3;E;echo a\\x3bb\\\\;nonce",
+            b"\x07\x1b]633;C\x07",
+            "é".as_bytes(),
+        ]);
+        assert_eq!(output, "\x1b]8;;zed-shell-mark:0,1\x1b\\é\x1b]8;;\x1b\\");
+        assert_eq!(
+            marks.into_iter().map(|mark| mark.kind).collect::<Vec<_>>(),
+            vec![
+                ShellMarkKind::CommandLine("echo a;b\\".to_string()),
+                ShellMarkKind::CommandExecuted,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_other_sequences_are_kept() {
+        let program_hyperlink = "\x1b]8;;https://zed.dev\x1b\\";
+        let input = format!(
+            "\x1b]0;title\x07{program_hyperlink}\x1b]133;C\x07zed\x1b]8;;\x1b\\\x1bP1$r\x1b\\"
+        );
+        let (output, marks) = scan(&[input.as_bytes()]);
+        assert_eq!(
+            output,
+            format!(
+                "\x1b]0;title\x07{program_hyperlink}\
+                 \x1b]8;;zed-shell-mark:0\x1b\\z\x1b]8;;\x1b\\{program_hyperlink}ed\
+                 \x1b]8;;\x1b\\\x1bP1$r\x1b\\"
+            )
+        );
+        assert_eq!(marks.len(), 1);
+    }
+
+    #[test]
+    fn test_output_lines() {
+        let (marks_tx, _marks_rx) = mpsc::unbounded();
+        let (output_lines_tx, mut output_lines_rx) = mpsc::unbounded();
+        let mut scanner = ShellMarkScanner::new(marks_tx, Some(output_lines_tx));
+        let mut output = VecDeque::new();
+        for chunk in [
+            &b"\x1b]133;C\x07\x1b[1m\x1b[31merror\x1b[0m: mis"[..],
+            b"matched types\r\n  --> src/main.rs:3:5\r\n",
+            b"Building 1/2\rBuilding 2/2\r\nunfinished",
+        ] {
+            scanner.scan(chunk, &mut output);
+        }
+        drop(scanner);
+
+        let mut lines = Vec::new();
+        while let Ok(Some(line)) = output_lines_rx.try_next() {
+            lines.push(line);
+        }
+        assert_eq!(
+            lines,
+            [
+                "error: mismatched types",
+                "  --> src/main.rs:3:5",
+                "Building 2/2",
+                "unfinished",
+            ]
+        );
+    }
+}
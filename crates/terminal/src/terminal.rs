@@ -3,6 +3,8 @@ pub mod mappings;
 pub use alacritty_terminal;
 
 mod pty_info;
+mod scrollback;
+pub mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -13,7 +15,7 @@ use alacritty_terminal::{
     selection::{Selection, SelectionRange, SelectionType},
     sync::FairMutex,
     term::{
-        cell::Cell,
+        cell::{Cell, Hyperlink},
         search::{Match, RegexIter, RegexSearch},
         Config, RenderableCursor, TermMode,
     },
@@ -41,6 +43,9 @@ use futures::StreamExt;
 use pty_info::PtyProcessInfo;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{
+    is_shell_mark_hyperlink, shell_mark_ids, ShellIntegrationPty, ShellMark, ShellMarkKind,
+};
 use smol::channel::{Receiver, Sender};
 use task::{HideStrategy, Shell, TaskId};
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
@@ -79,6 +84,9 @@ actions!(
         ScrollPageDown,
         ScrollToTop,
         ScrollToBottom,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        CopyLastCommandOutput,
         ToggleViMode,
     ]
 );
@@ -91,6 +99,8 @@ const SCROLL_MULTIPLIER: f32 = 4.;
 #[cfg(not(target_os = "macos"))]
 const SCROLL_MULTIPLIER: f32 = 1.;
 const MAX_SEARCH_LINES: usize = 100;
+/// How many of the last commands reported by shell integration to remember.
+const MAX_SHELL_COMMANDS: usize = 1000;
 const DEBUG_TERMINAL_WIDTH: Pixels = px(500.);
 const DEBUG_TERMINAL_HEIGHT: Pixels = px(30.);
 const DEBUG_CELL_WIDTH: Pixels = px(5.);
@@ -331,9 +341,11 @@ impl TerminalBuilder {
         cursor_shape: CursorShape,
         alternate_scroll: AlternateScroll,
         max_scroll_history_lines: Option<usize>,
+        restored_scrollback: Option<String>,
         is_ssh_terminal: bool,
         window: AnyWindowHandle,
         completion_tx: Sender<()>,
+        output_lines_tx: Option<UnboundedSender<String>>,
         cx: &AppContext,
     ) -> Result<TerminalBuilder> {
         // If the parent environment doesn't have a locale set
@@ -415,6 +427,11 @@ impl TerminalBuilder {
             term.unset_private_mode(PrivateMode::Named(NamedPrivateMode::AlternateScroll));
         }
 
+        // Show the scrollback of a restored terminal before the shell prints its prompt.
+        if let Some(restored_scrollback) = restored_scrollback {
+            scrollback::restore_scrollback(&mut term, &restored_scrollback);
+        }
+
         let term = Arc::new(FairMutex::new(term));
 
         //Setup the pty...
@@ -434,6 +451,8 @@ impl TerminalBuilder {
         };
 
         let pty_info = PtyProcessInfo::new(&pty);
+        let (shell_marks_tx, shell_marks_rx) = unbounded();
+        let pty = ShellIntegrationPty::new(pty, shell_marks_tx, output_lines_tx);
 
         //And connect them together
         let event_loop = EventLoop::new(
@@ -473,6 +492,8 @@ impl TerminalBuilder {
             vi_mode_enabled: false,
             is_ssh_terminal,
             python_venv_directory,
+            shell_marks_rx,
+            shell_commands: VecDeque::new(),
         };
 
         Ok(TerminalBuilder {
@@ -571,6 +592,7 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub size: TerminalSize,
     pub last_hovered_word: Option<HoveredWord>,
+    pub prompt_marks: Vec<PromptMark>,
 }
 
 #[derive(Clone)]
@@ -595,6 +617,7 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             size: Default::default(),
             last_hovered_word: None,
+            prompt_marks: Vec::new(),
         }
     }
 }
@@ -632,6 +655,28 @@ pub struct Terminal {
     task: Option<TaskState>,
     vi_mode_enabled: bool,
     is_ssh_terminal: bool,
+    shell_marks_rx: UnboundedReceiver<ShellMark>,
+    shell_commands: VecDeque<ShellCommand>,
+}
+
+/// A command that the shell reported with shell integration marks.
+#[derive(Clone, Debug, Default)]
+pub struct ShellCommand {
+    prompt_mark: Option<usize>,
+    command_mark: Option<usize>,
+    output_mark: Option<usize>,
+    finish_mark: Option<usize>,
+    /// The command line, if the shell reported it.
+    pub command_line: Option<String>,
+    /// The exit code of the command, if it finished and the shell reported it.
+    pub exit_code: Option<i32>,
+}
+
+/// A prompt shown in the terminal, of a command that finished with a known exit code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PromptMark {
+    pub line: Line,
+    pub succeeded: bool,
 }
 
 pub struct TaskState {
@@ -644,6 +689,9 @@ pub struct TaskState {
     pub hide: HideStrategy,
     pub show_summary: bool,
     pub show_command: bool,
+    /// The exit code of the last command that the task's shell reported with shell integration marks.
+    /// Used when the exit code of the task's process is not known.
+    pub last_command_exit_code: Option<i32>,
 }
 
 /// A status of the current terminal tab's task.
@@ -712,6 +760,7 @@ impl Terminal {
                 //NOOP, Handled in render
             }
             AlacTermEvent::Wakeup => {
+                self.process_shell_marks();
                 cx.emit(Event::Wakeup);
 
                 if self.pty_info.has_changed() {
@@ -893,13 +942,13 @@ impl Terminal {
                 )
                 .grid_clamp(term, Boundary::Grid);
 
-                let link = term.grid().index(point).hyperlink();
+                let link = cell_hyperlink(term.grid().index(point));
                 let found_word = if link.is_some() {
                     let mut min_index = point;
                     loop {
                         let new_min_index = min_index.sub(term, Boundary::Cursor, 1);
                         if new_min_index == min_index
-                            || term.grid().index(new_min_index).hyperlink() != link
+                            || cell_hyperlink(term.grid().index(new_min_index)) != link
                         {
                             break;
                         } else {
@@ -911,7 +960,7 @@ impl Terminal {
                     loop {
                         let new_max_index = max_index.add(term, Boundary::Cursor, 1);
                         if new_max_index == max_index
-                            || term.grid().index(new_max_index).hyperlink() != link
+                            || cell_hyperlink(term.grid().index(new_max_index)) != link
                         {
                             break;
                         } else {
@@ -1309,6 +1358,156 @@ impl Terminal {
         }
 
         self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content.prompt_marks = self.visible_prompt_marks();
+    }
+
+    fn process_shell_marks(&mut self) {
+        while let Ok(Some(mark)) = self.shell_marks_rx.try_next() {
+            let current_command = self
+                .shell_commands
+                .back_mut()
+                .filter(|command| command.finish_mark.is_none());
+            let command = match (mark.kind.clone(), current_command) {
+                (ShellMarkKind::PromptStart, _) | (_, None) => {
+                    if self.shell_commands.len() == MAX_SHELL_COMMANDS {
+                        self.shell_commands.pop_front();
+                    }
+                    self.shell_commands.push_back(ShellCommand::default());
+                    self.shell_commands.back_mut().unwrap()
+                }
+                (_, Some(command)) => command,
+            };
+            match mark.kind {
+                ShellMarkKind::PromptStart => command.prompt_mark = Some(mark.id),
+                ShellMarkKind::CommandStart => command.command_mark = Some(mark.id),
+                ShellMarkKind::CommandExecuted => command.output_mark = Some(mark.id),
+                ShellMarkKind::CommandFinished { exit_code } => {
+                    command.finish_mark = Some(mark.id);
+                    command.exit_code = exit_code;
+                    if let Some(task) = &mut self.task {
+                        task.last_command_exit_code = exit_code.or(task.last_command_exit_code);
+                    }
+                }
+                ShellMarkKind::CommandLine(command_line) => {
+                    command.command_line = Some(command_line)
+                }
+            }
+        }
+    }
+
+    fn visible_prompt_marks(&self) -> Vec<PromptMark> {
+        let mut prompt_marks = Vec::new();
+        for indexed in &self.last_content.cells {
+            let Some(mark_ids) = indexed.hyperlink().and_then(|hyperlink| {
+                shell_mark_ids(&hyperlink).map(|mark_ids| mark_ids.collect::<Vec<_>>())
+            }) else {
+                continue;
+            };
+            let finished_command = self.shell_commands.iter().find(|command| {
+                command
+                    .prompt_mark
+                    .map_or(false, |prompt_mark| mark_ids.contains(&prompt_mark))
+            });
+            if let Some(exit_code) = finished_command.and_then(|command| command.exit_code) {
+                prompt_marks.push(PromptMark {
+                    line: indexed.point.line,
+                    succeeded: exit_code == 0,
+                });
+            }
+        }
+        prompt_marks
+    }
+
+    /// Positions of the shell integration marks that are still in the terminal grid.
+    fn shell_mark_points(term: &Term<ZedListener>) -> HashMap<usize, AlacPoint> {
+        let mut points = HashMap::default();
+        for line in term.topmost_line().0..=term.bottommost_line().0 {
+            for column in 0..term.columns() {
+                let point = AlacPoint::new(Line(line), Column(column));
+                if let Some(hyperlink) = term.grid()[point].hyperlink() {
+                    if let Some(mark_ids) = shell_mark_ids(&hyperlink) {
+                        for mark_id in mark_ids {
+                            points.insert(mark_id, point);
+                        }
+                    }
+                }
+            }
+        }
+        points
+    }
+
+    /// Scrolls the terminal to show the closest prompt above the top of the viewport at its top.
+    pub fn scroll_to_previous_prompt(&mut self) {
+        self.scroll_to_prompt(
+            |prompt_line, top_line| prompt_line < top_line,
+            Iterator::max,
+        );
+    }
+
+    /// Scrolls the terminal to show the closest prompt below the top of the viewport at its top.
+    pub fn scroll_to_next_prompt(&mut self) {
+        self.scroll_to_prompt(
+            |prompt_line, top_line| prompt_line > top_line,
+            Iterator::min,
+        );
+    }
+
+    fn scroll_to_prompt(
+        &mut self,
+        is_candidate: impl Fn(Line, Line) -> bool,
+        pick: impl FnOnce(std::vec::IntoIter<Line>) -> Option<Line>,
+    ) {
+        let (candidates, top_line) = {
+            let term = self.term.lock();
+            let top_line = Line(-(term.grid().display_offset() as i32));
+            let points = Self::shell_mark_points(&term);
+            let candidates = self
+                .shell_commands
+                .iter()
+                .filter_map(|command| points.get(&command.prompt_mark?))
+                .map(|point| point.line)
+                .filter(|line| is_candidate(*line, top_line))
+                .collect::<Vec<_>>();
+            (candidates, top_line)
+        };
+        if let Some(prompt_line) = pick(candidates.into_iter()) {
+            // Lines of the viewport below the screen's bottom can not be scrolled to the top.
+            let delta = (top_line.0 - prompt_line.0).max(top_line.0);
+            self.events
+                .push_back(InternalEvent::Scroll(AlacScroll::Delta(delta)));
+        }
+    }
+
+    /// The output of the last command that printed any, as reported by shell integration marks.
+    pub fn last_command_output(&self) -> Option<String> {
+        let term = self.term.lock();
+        let points = Self::shell_mark_points(&term);
+        self.shell_commands.iter().rev().find_map(|command| {
+            let start = *points.get(&command.output_mark?)?;
+            let end = match command.finish_mark.and_then(|mark| points.get(&mark)) {
+                Some(finish) => finish.sub(&*term, Boundary::Grid, 1),
+                None if command.finish_mark.is_none() => {
+                    AlacPoint::new(term.bottommost_line(), term.last_column())
+                }
+                None => return None,
+            };
+            if end < start {
+                return None;
+            }
+            let output = term.bounds_to_string(start, end);
+            let output = output.trim_end();
+            (!output.is_empty()).then(|| output.to_string())
+        })
+    }
+
+    /// Serializes up to `max_lines` lines above the cursor, with their colors, to restore them later.
+    pub fn serialize_scrollback(&self, max_lines: usize) -> Option<String> {
+        scrollback::serialize_scrollback(&self.term.lock(), max_lines)
+    }
+
+    /// The commands run in the terminal, as reported by shell integration marks.
+    pub fn shell_commands(&self) -> &VecDeque<ShellCommand> {
+        &self.shell_commands
     }
 
     fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
@@ -1336,6 +1535,7 @@ impl Terminal {
             cursor_char: term.grid()[content.cursor.point].c,
             size: last_content.size,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            prompt_marks: Vec::new(),
         }
     }
 
@@ -1553,7 +1753,7 @@ impl Terminal {
             //Hyperlinks
             if self.selection_phase == SelectionPhase::Ended {
                 let mouse_cell_index = content_index_for_mouse(position, &self.last_content.size);
-                if let Some(link) = self.last_content.cells[mouse_cell_index].hyperlink() {
+                if let Some(link) = cell_hyperlink(&self.last_content.cells[mouse_cell_index]) {
                     cx.open_url(link.uri());
                 } else if self.secondary_pressed {
                     self.events
@@ -1755,6 +1955,7 @@ impl Terminal {
         if task.status != TaskStatus::Running {
             return;
         }
+        let error_code = error_code.or(task.last_command_exit_code);
         match error_code {
             Some(error_code) => {
                 task.status.register_task_exit(error_code);
@@ -1795,6 +1996,12 @@ impl Terminal {
     }
 }
 
+/// The hyperlink of a cell, unless it only marks the position of a shell integration mark.
+pub fn cell_hyperlink(cell: &Cell) -> Option<Hyperlink> {
+    cell.hyperlink()
+        .filter(|hyperlink| !is_shell_mark_hyperlink(hyperlink))
+}
+
 const TASK_DELIMITER: &str = "⏵ ";
 fn task_summary(task: &TaskState, error_code: Option<i32>) -> (bool, String, String) {
     let escaped_full_label = task.full_label.replace("\r\n", "\r").replace('\n', "\r");
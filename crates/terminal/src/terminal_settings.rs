@@ -47,6 +47,8 @@ pub struct TerminalSettings {
     pub detect_venv: VenvSettings,
     pub max_scroll_history_lines: Option<usize>,
     pub toolbar: Toolbar,
+    pub persist_scrollback: bool,
+    pub max_persisted_scrollback_lines: usize,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -187,6 +189,16 @@ pub struct TerminalSettingsContent {
     pub max_scroll_history_lines: Option<usize>,
     /// Toolbar related settings
     pub toolbar: Option<ToolbarContent>,
+    /// Whether to save the scrollback of the terminals when Zed quits,
+    /// and show it above the shell prompt when they are restored.
+    ///
+    /// Default: false
+    pub persist_scrollback: Option<bool>,
+    /// The maximum number of lines of a terminal's scrollback to save,
+    /// when `persist_scrollback` is enabled.
+    ///
+    /// Default: 1000
+    pub max_persisted_scrollback_lines: Option<usize>,
 }
 
 impl settings::Settings for TerminalSettings {
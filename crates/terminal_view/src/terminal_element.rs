@@ -20,6 +20,7 @@ use terminal::{
             CursorShape as AlacCursorShape, NamedColor,
         },
     },
+    cell_hyperlink,
     terminal_settings::TerminalSettings,
     HoveredWord, IndexedCell, Terminal, TerminalContent, TerminalSize,
 };
@@ -45,6 +46,7 @@ pub struct LayoutState {
     display_offset: usize,
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
+    prompt_markers: Vec<(i32, Hsla)>,
     last_hovered_word: Option<HoveredWord>,
     block_below_cursor_element: Option<AnyElement>,
 }
@@ -353,7 +355,7 @@ impl TerminalElement {
         }
 
         let underline = (flags.intersects(Flags::ALL_UNDERLINES)
-            || indexed.cell.hyperlink().is_some())
+            || cell_hyperlink(&indexed.cell).is_some())
         .then(|| UnderlineStyle {
             color: Some(fg),
             thickness: Pixels::from(1.0),
@@ -735,11 +737,25 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    prompt_marks,
                     ..
                 } = &self.terminal.read(cx).last_content;
                 let mode = *mode;
                 let display_offset = *display_offset;
 
+                // Prompts of finished commands get a success or failure marker in the gutter.
+                let prompt_markers = prompt_marks
+                    .iter()
+                    .map(|prompt_mark| {
+                        let color = if prompt_mark.succeeded {
+                            theme.status().success
+                        } else {
+                            theme.status().error
+                        };
+                        (prompt_mark.line.0 + display_offset as i32, color)
+                    })
+                    .collect();
+
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
                 for search_match in search_matches {
@@ -855,6 +871,7 @@ impl Element for TerminalElement {
                     display_offset,
                     hyperlink_tooltip,
                     gutter,
+                    prompt_markers,
                     last_hovered_word,
                     block_below_cursor_element,
                 }
@@ -919,6 +936,15 @@ impl Element for TerminalElement {
                         rect.paint(origin, &layout.dimensions, cx);
                     }
 
+                    for (line, color) in &layout.prompt_markers {
+                        let marker_origin = point(
+                            bounds.origin.x,
+                            origin.y + *line as f32 * layout.dimensions.line_height,
+                        );
+                        let marker_size = size(layout.gutter / 3., layout.dimensions.line_height);
+                        cx.paint_quad(fill(Bounds::new(marker_origin, marker_size), *color));
+                    }
+
                     for (relative_highlighted_range, color) in
                         layout.relative_highlighted_ranges.iter()
                     {
@@ -1060,7 +1086,7 @@ pub fn is_blank(cell: &IndexedCell) -> bool {
         return false;
     }
 
-    if cell.hyperlink().is_some() {
+    if cell_hyperlink(&cell.cell).is_some() {
         return false;
     }
 
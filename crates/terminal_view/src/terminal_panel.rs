@@ -1,4 +1,4 @@
-use std::{cmp, ops::ControlFlow, path::PathBuf, sync::Arc, time::Duration};
+use std::{cmp, mem, ops::ControlFlow, path::PathBuf, sync::Arc, time::Duration};
 
 use crate::{
     default_working_directory,
@@ -20,10 +20,10 @@ use itertools::Itertools;
 use project::{terminals::TerminalKind, Fs, Project, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use settings::Settings;
-use task::{RevealStrategy, RevealTarget, Shell, SpawnInTerminal, TaskId};
+use task::{DependsOrder, RevealStrategy, RevealTarget, Shell, SpawnInTerminal, TaskId};
 use terminal::{
     terminal_settings::{TerminalDockPosition, TerminalSettings},
-    Terminal,
+    TaskStatus, Terminal,
 };
 use ui::{
     prelude::*, ButtonCommon, Clickable, ContextMenu, FluentBuilder, PopoverMenu, Toggleable,
@@ -380,7 +380,7 @@ impl TerminalPanel {
         let window = cx.window_handle();
         let terminal = project
             .update(cx, |project, cx| {
-                project.create_terminal_with_venv(kind, python_venv_directory, window, cx)
+                project.create_terminal_with_venv(kind, python_venv_directory, None, window, cx)
             })
             .ok()?;
 
@@ -428,6 +428,28 @@ impl TerminalPanel {
     }
 
     fn spawn_task(&mut self, spawn_in_terminal: &SpawnInTerminal, cx: &mut ViewContext<Self>) {
+        if !spawn_in_terminal.dependencies.is_empty() {
+            let mut spawn_in_terminal = spawn_in_terminal.clone();
+            let dependencies = mem::take(&mut spawn_in_terminal.dependencies);
+            let depends_order = spawn_in_terminal.depends_order;
+            cx.spawn(|terminal_panel, mut cx| async move {
+                if run_task_dependencies(&terminal_panel, dependencies, depends_order, &mut cx)
+                    .await?
+                {
+                    terminal_panel.update(&mut cx, |terminal_panel, cx| {
+                        terminal_panel.spawn_task(&spawn_in_terminal, cx)
+                    })?;
+                }
+                anyhow::Ok(())
+            })
+            .detach_and_log_err(cx);
+            return;
+        }
+        // Tasks that only run other tasks have nothing left to spawn.
+        if spawn_in_terminal.command.is_empty() {
+            return;
+        }
+
         let mut spawn_task = spawn_in_terminal.clone();
         let Ok(is_local) = self
             .workspace
@@ -515,6 +537,78 @@ impl TerminalPanel {
         .detach()
     }
 
+    /// Runs a task that another task depends on, reusing the terminal of its previous run if there is one,
+    /// and resolves to whether the task and all of its own dependencies succeeded.
+    fn run_task_dependency(
+        &mut self,
+        mut spawn_task: SpawnInTerminal,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<bool>> {
+        let dependencies = mem::take(&mut spawn_task.dependencies);
+        let depends_order = spawn_task.depends_order;
+        let Ok(is_local) = self
+            .workspace
+            .update(cx, |workspace, cx| workspace.project().read(cx).is_local())
+        else {
+            return Task::ready(Ok(false));
+        };
+        cx.spawn(|terminal_panel, mut cx| async move {
+            if !run_task_dependencies(&terminal_panel, dependencies, depends_order, &mut cx).await?
+            {
+                return Ok(false);
+            }
+            if spawn_task.command.is_empty() {
+                return Ok(true);
+            }
+
+            let mut task_to_spawn = spawn_task.clone();
+            if let ControlFlow::Break(_) =
+                Self::fill_command(is_local, &spawn_task, &mut task_to_spawn)
+            {
+                return Ok(false);
+            }
+            let terminals_for_task = terminal_panel.update(&mut cx, |terminal_panel, cx| {
+                terminal_panel.terminals_for_task(&spawn_task.full_label, cx)
+            })?;
+            if !spawn_task.allow_concurrent_runs {
+                wait_for_terminals_tasks(terminals_for_task.clone(), &mut cx).await;
+            }
+            let terminal = match terminals_for_task.last() {
+                Some((item_index, task_pane, terminal_view)) if !spawn_task.use_new_terminal => {
+                    terminal_panel
+                        .update(&mut cx, |terminal_panel, cx| {
+                            terminal_panel.replace_terminal(
+                                task_to_spawn,
+                                task_pane.clone(),
+                                *item_index,
+                                terminal_view.clone(),
+                                cx,
+                            )
+                        })?
+                        .await;
+                    terminal_view
+                        .update(&mut cx, |terminal_view, _| terminal_view.terminal().clone())?
+                }
+                _ => {
+                    terminal_panel
+                        .update(&mut cx, |terminal_panel, cx| {
+                            terminal_panel.spawn_in_new_terminal(task_to_spawn, cx)
+                        })?
+                        .await?
+                }
+            };
+
+            terminal
+                .update(&mut cx, |terminal, cx| terminal.wait_for_completed_task(cx))?
+                .await;
+            terminal.update(&mut cx, |terminal, _| {
+                terminal.task().map_or(false, |task| {
+                    task.status == TaskStatus::Completed { success: true }
+                })
+            })
+        })
+    }
+
     pub fn fill_command(
         is_local: bool,
         spawn_in_terminal: &SpawnInTerminal,
@@ -1094,6 +1188,43 @@ pub fn new_terminal_pane(
     pane
 }
 
+/// Runs the dependencies of a task, and resolves to whether all of them succeeded.
+/// Dependencies that run in sequence stop at the first failure.
+async fn run_task_dependencies(
+    terminal_panel: &WeakView<TerminalPanel>,
+    dependencies: Vec<SpawnInTerminal>,
+    depends_order: DependsOrder,
+    cx: &mut AsyncWindowContext,
+) -> Result<bool> {
+    match depends_order {
+        DependsOrder::Sequence => {
+            for dependency in dependencies {
+                let run = terminal_panel.update(cx, |terminal_panel, cx| {
+                    terminal_panel.run_task_dependency(dependency, cx)
+                })?;
+                if !run.await? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        DependsOrder::Parallel => {
+            let runs = terminal_panel.update(cx, |terminal_panel, cx| {
+                dependencies
+                    .into_iter()
+                    .map(|dependency| terminal_panel.run_task_dependency(dependency, cx))
+                    .collect::<Vec<_>>()
+            })?;
+            join_all(runs)
+                .await
+                .into_iter()
+                .try_fold(true, |all_succeeded, succeeded| {
+                    Ok(all_succeeded && succeeded?)
+                })
+        }
+    }
+}
+
 async fn wait_for_terminals_tasks(
     terminals_for_task: Vec<(usize, View<Pane>, View<TerminalView>)>,
     cx: &mut AsyncWindowContext,
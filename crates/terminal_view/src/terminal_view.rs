@@ -6,10 +6,10 @@ use collections::HashSet;
 use editor::{actions::SelectAll, scroll::Autoscroll, Editor};
 use futures::{stream::FuturesUnordered, StreamExt};
 use gpui::{
-    anchored, deferred, div, impl_actions, AnyElement, AppContext, DismissEvent, EventEmitter,
-    FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model, MouseButton,
-    MouseDownEvent, Pixels, Render, ScrollWheelEvent, Styled, Subscription, Task, View,
-    VisualContext, WeakModel, WeakView,
+    anchored, deferred, div, impl_actions, AnyElement, AppContext, ClipboardItem, DismissEvent,
+    EventEmitter, FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model,
+    MouseButton, MouseDownEvent, Pixels, Render, ScrollWheelEvent, Styled, Subscription, Task,
+    View, VisualContext, WeakModel, WeakView,
 };
 use language::Bias;
 use persistence::TERMINAL_DB;
@@ -20,9 +20,10 @@ use terminal::{
         term::{search::RegexSearch, TermMode},
     },
     terminal_settings::{CursorShape, TerminalBlink, TerminalSettings, WorkingDirectory},
-    Clear, Copy, Event, MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp, ScrollPageDown,
-    ScrollPageUp, ScrollToBottom, ScrollToTop, ShowCharacterPalette, TaskStatus, Terminal,
-    TerminalSize, ToggleViMode,
+    Clear, Copy, CopyLastCommandOutput, Event, MaybeNavigationTarget, Paste, ScrollLineDown,
+    ScrollLineUp, ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToNextPrompt,
+    ScrollToPreviousPrompt, ScrollToTop, ShowCharacterPalette, TaskStatus, Terminal, TerminalSize,
+    ToggleViMode,
 };
 use terminal_element::{is_blank, TerminalElement};
 use terminal_panel::TerminalPanel;
@@ -158,6 +159,7 @@ impl TerminalView {
         let cursor_shape = TerminalSettings::get_global(cx)
             .cursor_shape
             .unwrap_or_default();
+        let save_scrollback = Self::save_scrollback_on_quit(&terminal, cx);
 
         Self {
             terminal,
@@ -180,11 +182,43 @@ impl TerminalView {
                 focus_in,
                 focus_out,
                 cx.observe_global::<SettingsStore>(Self::settings_changed),
+                save_scrollback,
             ],
             _terminal_subscriptions: terminal_subscriptions,
         }
     }
 
+    fn save_scrollback_on_quit(
+        terminal: &Model<Terminal>,
+        cx: &mut ViewContext<Self>,
+    ) -> Subscription {
+        let terminal_view = cx.view().downgrade();
+        terminal.update(cx, |_, cx| {
+            cx.on_app_quit(move |terminal, cx| {
+                // Without scrollback to keep, the previous one is cleared so that it isn't
+                // restored again on the next launch.
+                let settings = TerminalSettings::get_global(cx);
+                let scrollback = if settings.persist_scrollback && terminal.task().is_none() {
+                    terminal.serialize_scrollback(settings.max_persisted_scrollback_lines)
+                } else {
+                    None
+                };
+                let workspace_id = terminal_view
+                    .upgrade()
+                    .and_then(|terminal_view| terminal_view.read(cx).workspace_id);
+                let item_id = terminal_view.entity_id().as_u64();
+                async move {
+                    if let Some(workspace_id) = workspace_id {
+                        TERMINAL_DB
+                            .save_scrollback(item_id, workspace_id, scrollback)
+                            .await
+                            .log_err();
+                    }
+                }
+            })
+        })
+    }
+
     pub fn model(&self) -> &Model<Terminal> {
         &self.terminal
     }
@@ -415,6 +449,28 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(&mut self, _: &ScrollToNextPrompt, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn copy_last_command_output(&mut self, _: &CopyLastCommandOutput, cx: &mut ViewContext<Self>) {
+        if let Some(output) = self.terminal.read(cx).last_command_output() {
+            cx.write_to_clipboard(ClipboardItem::new_string(output));
+        }
+    }
+
     fn toggle_vi_mode(&mut self, _: &ToggleViMode, cx: &mut ViewContext<Self>) {
         self.terminal.update(cx, |term, _| term.toggle_vi_mode());
         cx.notify();
@@ -957,6 +1013,9 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::scroll_page_down))
             .on_action(cx.listener(TerminalView::scroll_to_top))
             .on_action(cx.listener(TerminalView::scroll_to_bottom))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
             .on_action(cx.listener(TerminalView::toggle_vi_mode))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
@@ -1093,6 +1152,7 @@ impl Item for TerminalView {
                 project.create_terminal_with_venv(
                     TerminalKind::Shell(working_directory),
                     python_venv_directory,
+                    None,
                     window,
                     cx,
                 )
@@ -1230,10 +1290,27 @@ impl SerializableItem for TerminalView {
                 })
                 .ok()
                 .flatten();
+            let persist_scrollback = cx
+                .update(|cx| TerminalSettings::get_global(cx).persist_scrollback)
+                .unwrap_or(false);
+            let restored_scrollback = if persist_scrollback {
+                TERMINAL_DB
+                    .get_scrollback(item_id, workspace_id)
+                    .log_err()
+                    .flatten()
+                    .filter(|scrollback| !scrollback.is_empty())
+            } else {
+                None
+            };
 
             let terminal = project
                 .update(&mut cx, |project, cx| {
-                    project.create_terminal(TerminalKind::Shell(cwd), window, cx)
+                    project.create_terminal_with_scrollback(
+                        TerminalKind::Shell(cwd),
+                        restored_scrollback,
+                        window,
+                        cx,
+                    )
                 })?
                 .await?;
             cx.update(|cx| {
@@ -1261,6 +1338,7 @@ impl SearchableItem for TerminalView {
             regex: true,
             replacement: false,
             selection: false,
+            syntax_scope: false,
         }
     }
 
@@ -1319,6 +1397,8 @@ impl SearchableItem for TerminalView {
                 .unwrap()),
             ),
             SearchQuery::Regex { .. } => regex_search_for_query(&query),
+            // Terminal output has no syntax tree to match against.
+            SearchQuery::Structural { .. } => None,
         };
 
         if let Some(s) = searcher {
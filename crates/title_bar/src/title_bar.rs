@@ -429,29 +429,37 @@ impl TitleBar {
 
             names_and_branches.next().flatten()
         };
-        let workspace = self.workspace.upgrade()?;
         let branch_name = entry
             .as_ref()
             .and_then(RepositoryEntry::branch)
             .map(|branch| util::truncate_and_trailoff(&branch, MAX_BRANCH_NAME_LENGTH))?;
         Some(
-            Button::new("project_branch_trigger", branch_name)
-                .color(Color::Muted)
-                .style(ButtonStyle::Subtle)
-                .label_size(LabelSize::Small)
-                .tooltip(move |cx| {
-                    Tooltip::with_meta(
-                        "Recent Branches",
-                        Some(&zed_actions::branches::OpenRecent),
-                        "Local branches only",
-                        cx,
-                    )
+            PopoverMenu::new("project-branch-menu")
+                .menu(|cx| {
+                    ContextMenu::build(cx, |menu, _| {
+                        menu.action(
+                            "Switch Branch…",
+                            zed_actions::branches::OpenRecent.boxed_clone(),
+                        )
+                        .separator()
+                        .action(
+                            "Open Pull Request for Current Branch",
+                            zed_actions::branches::OpenPullRequest.boxed_clone(),
+                        )
+                        .action(
+                            "Compare Branch on Remote",
+                            zed_actions::branches::CompareOnRemote.boxed_clone(),
+                        )
+                    })
+                    .into()
                 })
-                .on_click(move |_, cx| {
-                    let _ = workspace.update(cx, |_this, cx| {
-                        cx.dispatch_action(zed_actions::branches::OpenRecent.boxed_clone());
-                    });
-                }),
+                .trigger(
+                    Button::new("project_branch_trigger", branch_name)
+                        .color(Color::Muted)
+                        .style(ButtonStyle::Subtle)
+                        .label_size(LabelSize::Small)
+                        .tooltip(move |cx| Tooltip::text("Branch Menu", cx)),
+                ),
         )
     }
 
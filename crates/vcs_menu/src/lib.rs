@@ -1,10 +1,14 @@
 use anyhow::{anyhow, Context, Result};
 use fuzzy::{StringMatch, StringMatchCandidate};
-use git::repository::Branch;
+use git::{
+    repository::{is_dirty_worktree_error, Branch},
+    BranchUrlKind,
+};
 use gpui::{
     rems, AnyElement, AppContext, AsyncAppContext, DismissEvent, EventEmitter, FocusHandle,
-    FocusableView, InteractiveElement, IntoElement, ParentElement, Render, SharedString, Styled,
-    Subscription, Task, View, ViewContext, VisualContext, WeakView, WindowContext,
+    FocusableView, InteractiveElement, IntoElement, ParentElement, PromptLevel, Render,
+    SharedString, Styled, Subscription, Task, View, ViewContext, VisualContext, WeakView,
+    WindowContext,
 };
 use picker::{Picker, PickerDelegate};
 use project::ProjectPath;
@@ -13,15 +17,41 @@ use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
 use util::ResultExt;
 use workspace::notifications::DetachAndPromptErr;
 use workspace::{ModalView, Workspace};
-use zed_actions::branches::OpenRecent;
+use zed_actions::branches::{CompareOnRemote, OpenPullRequest, OpenRecent};
 
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(|workspace: &mut Workspace, _| {
         workspace.register_action(BranchList::open);
+        workspace.register_action(|workspace, _: &OpenPullRequest, cx| {
+            open_branch_url(workspace, BranchUrlKind::CreatePullRequest, cx)
+        });
+        workspace.register_action(|workspace, _: &CompareOnRemote, cx| {
+            open_branch_url(workspace, BranchUrlKind::Compare, cx)
+        });
     })
     .detach();
 }
 
+/// Opens the page of the git hosting provider for the branch checked out in the first worktree.
+fn open_branch_url(
+    workspace: &mut Workspace,
+    kind: BranchUrlKind,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let project = workspace.project().read(cx);
+    let Some(worktree) = project.visible_worktrees(cx).next() else {
+        return;
+    };
+    let repository = ProjectPath::root_path(worktree.read(cx).id());
+    let url = project.git_branch_url(repository, kind, cx);
+    cx.spawn(|_, mut cx| async move {
+        let url = url.await?;
+        cx.update(|cx| cx.open_url(url.as_str()))?;
+        anyhow::Ok(())
+    })
+    .detach_and_prompt_err("Failed to open branch on remote", cx, |_, _| None)
+}
+
 pub struct BranchList {
     pub picker: View<Picker<BranchListDelegate>>,
     rem_width: f32,
@@ -229,34 +259,71 @@ impl PickerDelegate for BranchListDelegate {
         cx.spawn({
             let branch = branch.clone();
             |picker, mut cx| async move {
-                let branch_change_task = picker.update(&mut cx, |this, cx| {
-                    let workspace = this
-                        .delegate
-                        .workspace
-                        .upgrade()
-                        .ok_or_else(|| anyhow!("workspace was dropped"))?;
-
-                    let project = workspace.read(cx).project().read(cx);
-                    let branch_to_checkout = match branch {
-                        BranchEntry::Branch(branch) => branch.string,
-                        BranchEntry::NewBranch { name: branch_name } => branch_name,
-                    };
-                    let worktree = project
-                        .visible_worktrees(cx)
-                        .next()
-                        .context("worktree disappeared")?;
-                    let repository = ProjectPath::root_path(worktree.read(cx).id());
-
-                    anyhow::Ok(project.update_or_create_branch(repository, branch_to_checkout, cx))
-                })??;
-
-                branch_change_task.await?;
-
-                picker.update(&mut cx, |_, cx| {
-                    cx.emit(DismissEvent);
-
-                    Ok::<(), anyhow::Error>(())
-                })
+                let (project, repository, branch_to_checkout) =
+                    picker.update(&mut cx, |this, cx| {
+                        let workspace = this
+                            .delegate
+                            .workspace
+                            .upgrade()
+                            .ok_or_else(|| anyhow!("workspace was dropped"))?;
+
+                        let project = workspace.read(cx).project().clone();
+                        let branch_to_checkout = match branch {
+                            BranchEntry::Branch(branch) => branch.string,
+                            BranchEntry::NewBranch { name: branch_name } => branch_name,
+                        };
+                        let worktree = project
+                            .read(cx)
+                            .visible_worktrees(cx)
+                            .next()
+                            .context("worktree disappeared")?;
+                        let repository = ProjectPath::root_path(worktree.read(cx).id());
+
+                        anyhow::Ok((project, repository, branch_to_checkout))
+                    })??;
+
+                let branch_change_task = project.update(&mut cx, |project, cx| {
+                    project.update_or_create_branch(
+                        repository.clone(),
+                        branch_to_checkout.clone(),
+                        cx,
+                    )
+                })?;
+                if let Err(error) = branch_change_task.await {
+                    // Switching fails when it would overwrite uncommitted changes,
+                    // which can be stashed to switch anyway.
+                    if !is_dirty_worktree_error(&error) {
+                        return Err(error);
+                    }
+                    let answer = picker.update(&mut cx, |_, cx| {
+                        cx.prompt(
+                            PromptLevel::Warning,
+                            &format!("Failed to change branch: {error}"),
+                            Some("Stash the uncommitted changes and try again?"),
+                            &["Stash and Switch", "Cancel"],
+                        )
+                    })?;
+                    if answer.await != Ok(0) {
+                        return Ok(());
+                    }
+
+                    project
+                        .update(&mut cx, |project, cx| {
+                            project.git_stash_push(
+                                repository.clone(),
+                                Some(format!("Switching to {branch_to_checkout}")),
+                                cx,
+                            )
+                        })?
+                        .await?;
+                    project
+                        .update(&mut cx, |project, cx| {
+                            project.update_or_create_branch(repository, branch_to_checkout, cx)
+                        })?
+                        .await?;
+                }
+
+                picker.update(&mut cx, |_, cx| cx.emit(DismissEvent))
             }
         })
         .detach_and_prompt_err("Failed to change branch", cx, |_, _| None);
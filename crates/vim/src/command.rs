@@ -1,4 +1,5 @@
 use std::{
+    collections::VecDeque,
     iter::Peekable,
     ops::{Deref, Range},
     str::Chars,
@@ -11,9 +12,9 @@ use command_palette_hooks::CommandInterceptResult;
 use editor::{
     actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive},
     display_map::ToDisplayPoint,
-    Bias, Editor, ToPoint,
+    Anchor, Bias, Editor, ToPoint,
 };
-use gpui::{actions, impl_actions, Action, AppContext, Global, ViewContext};
+use gpui::{actions, impl_actions, Action, AppContext, Global, Keystroke, ViewContext, WeakView};
 use language::Point;
 use multi_buffer::MultiBufferRow;
 use regex::Regex;
@@ -63,7 +64,14 @@ struct WrappedAction(Box<dyn Action>);
 actions!(vim, [VisualCommand, CountCommand]);
 impl_actions!(
     vim,
-    [GoToLine, YankCommand, WithRange, WithCount, OnMatchingLines]
+    [
+        GoToLine,
+        YankCommand,
+        WithRange,
+        WithCount,
+        OnMatchingLines,
+        ExLineCommand,
+    ]
 );
 
 impl<'de> Deserialize<'de> for WrappedAction {
@@ -214,6 +222,10 @@ pub fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
 
     Vim::action(editor, cx, |vim, action: &OnMatchingLines, cx| {
         action.run(vim, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &ExLineCommand, cx| {
+        action.run(vim, cx)
     })
 }
 
@@ -430,6 +442,10 @@ enum Position {
 }
 
 impl Position {
+    fn is_line_zero(&self) -> bool {
+        matches!(self, Position::Line { row: 0, offset: 0 })
+    }
+
     fn buffer_row(
         &self,
         vim: &Vim,
@@ -823,7 +839,13 @@ pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Option<CommandIn
             None
         }
     } else {
-        None
+        LineCommand::parse(query).map(|command| {
+            ExLineCommand {
+                range: range.clone(),
+                command,
+            }
+            .boxed_clone()
+        })
     };
     if let Some(action) = action {
         let string = input.to_string();
@@ -998,6 +1020,11 @@ impl OnMatchingLines {
             });
         };
 
+        let line_command = action
+            .as_any()
+            .downcast_ref::<ExLineCommand>()
+            .map(|line_command| line_command.command.clone());
+        let vim_handle = cx.view().downgrade();
         vim.update_editor(cx, |_, editor, cx| {
             let snapshot = editor.snapshot(cx);
             let mut row = range.start.0;
@@ -1007,10 +1034,11 @@ impl OnMatchingLines {
                     .buffer_snapshot
                     .clip_point(Point::new(range.end.0 + 1, 0), Bias::Left);
             cx.spawn(|editor, mut cx| async move {
-                let new_selections = cx
+                let (matching_rows, new_selections) = cx
                     .background_executor()
                     .spawn(async move {
                         let mut line = String::new();
+                        let mut matching_rows = Vec::new();
                         let mut new_selections = Vec::new();
                         let chunks = snapshot
                             .buffer_snapshot
@@ -1023,6 +1051,7 @@ impl OnMatchingLines {
                                     if regexes.iter().all(|(regex, should_match)| {
                                         regex.is_match(&line) == *should_match
                                     }) {
+                                        matching_rows.push(MultiBufferRow(row));
                                         new_selections
                                             .push(Point::new(row, 0).to_display_point(&snapshot))
                                     }
@@ -1033,13 +1062,23 @@ impl OnMatchingLines {
                             }
                         }
 
-                        new_selections
+                        (matching_rows, new_selections)
                     })
                     .await;
 
                 if new_selections.is_empty() {
                     return;
                 }
+                // Commands like `:normal` and `:move` run on each line in turn,
+                // rather than once with a cursor on every line.
+                if let Some(command) = line_command {
+                    vim_handle
+                        .update(&mut cx, |vim, cx| {
+                            LineCommandRunner::start(vim, command, Some(matching_rows), cx)
+                        })
+                        .ok();
+                    return;
+                }
                 editor
                     .update(&mut cx, |editor, cx| {
                         editor.start_transaction_at(Instant::now(), cx);
@@ -1062,6 +1101,442 @@ impl OnMatchingLines {
     }
 }
 
+/// An ex command that operates on whole lines and, under `:global`, runs on
+/// each matching line in turn.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) enum LineCommand {
+    /// `:normal {keys}` runs the keys as normal mode commands.
+    Normal { keys: String },
+    /// `:move {address}` moves the lines below the addressed line.
+    Move { address: Position },
+    /// `:copy {address}` (or `:t`) copies the lines below the addressed line.
+    Copy { address: Position },
+    /// `:put [x]` puts the register as lines below the line, or above it with `:put!`.
+    Put { register: Option<char>, above: bool },
+}
+
+impl LineCommand {
+    fn parse(query: &str) -> Option<Self> {
+        let name_len = query
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(query.len());
+        let (name, args) = query.split_at(name_len);
+        let (bang, args) = match args.strip_prefix('!') {
+            Some(args) => (true, args),
+            None => (false, args),
+        };
+        let args = args.trim_start();
+
+        match name {
+            "m" | "mo" | "mov" | "move" => Some(Self::Move {
+                address: Self::parse_address(args)?,
+            }),
+            "t" | "co" | "cop" | "copy" => Some(Self::Copy {
+                address: Self::parse_address(args)?,
+            }),
+            "norm" | "norma" | "normal" if !args.is_empty() => Some(Self::Normal {
+                keys: args.to_string(),
+            }),
+            "pu" | "put" => {
+                let mut chars = args.chars();
+                let register = chars.next();
+                if chars.next().is_some() {
+                    return None;
+                }
+                Some(Self::Put {
+                    register,
+                    above: bang,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_address(args: &str) -> Option<Position> {
+        let mut chars = args.chars().peekable();
+        let address = VimCommand::parse_position(&mut chars)?;
+        chars.next().is_none().then_some(address)
+    }
+
+    /// The keystrokes that type out the keys of a `:normal` command.
+    fn keystrokes(keys: &str) -> impl Iterator<Item = Keystroke> + '_ {
+        keys.chars().filter_map(|c| {
+            let source = if c == ' ' {
+                "space".to_string()
+            } else if c.is_uppercase() {
+                format!("shift-{}", c.to_lowercase())
+            } else {
+                c.to_string()
+            };
+            Keystroke::parse(&source).log_err()
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct ExLineCommand {
+    range: Option<CommandRange>,
+    command: LineCommand,
+}
+
+impl ExLineCommand {
+    pub fn run(&self, vim: &mut Vim, cx: &mut ViewContext<Vim>) {
+        let result = vim.update_editor(cx, |vim, editor, cx| match &self.range {
+            Some(range) => range.buffer_range(vim, editor, cx),
+            None => {
+                let row = editor.selections.newest::<Point>(cx).head().row;
+                Ok(MultiBufferRow(row)..MultiBufferRow(row))
+            }
+        });
+        let rows = match result {
+            None => return,
+            Some(e @ Err(_)) => {
+                let Some(workspace) = vim.workspace(cx) else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    e.notify_err(workspace, cx);
+                });
+                return;
+            }
+            Some(Ok(result)) => result,
+        };
+
+        let result = match &self.command {
+            LineCommand::Normal { .. } => {
+                // Without a range, the keys run from the cursor rather than
+                // from the start of the line.
+                let rows = self
+                    .range
+                    .as_ref()
+                    .map(|_| (rows.start.0..=rows.end.0).map(MultiBufferRow).collect());
+                LineCommandRunner::start(vim, self.command.clone(), rows, cx);
+                return;
+            }
+            LineCommand::Move { address } => vim.move_lines(rows, address, false, cx),
+            LineCommand::Copy { address } => vim.move_lines(rows, address, true, cx),
+            LineCommand::Put { register, above } => {
+                // `:0put` puts the register above the first line.
+                let above_first_line = self
+                    .range
+                    .as_ref()
+                    .is_some_and(|range| range.end.is_none() && range.start.is_line_zero());
+                vim.put_lines(rows.end, *register, *above || above_first_line, cx);
+                Ok(())
+            }
+        };
+        if let e @ Err(_) = result {
+            let Some(workspace) = vim.workspace(cx) else {
+                return;
+            };
+            workspace.update(cx, |workspace, cx| {
+                e.notify_err(workspace, cx);
+            });
+        }
+    }
+}
+
+enum LineCommandStep {
+    /// Runs the command with the cursor at the start of a line. The line is
+    /// skipped if an earlier step deleted it.
+    Line {
+        start: Anchor,
+        end: Anchor,
+        was_empty: bool,
+    },
+    Keystroke(Keystroke),
+    /// Ends the insert mode or the incomplete command left behind by the keys
+    /// of a `:normal` command, as vim does.
+    Escape,
+    Finish,
+}
+
+/// Runs a line command on several lines, one after the other, as a single undo step.
+///
+/// The keys of `:normal` are dispatched to the window like typed keystrokes,
+/// so every step is deferred until the previous one, and any macro it
+/// replays, has finished.
+struct LineCommandRunner {
+    vim: WeakView<Vim>,
+    command: LineCommand,
+    steps: VecDeque<LineCommandStep>,
+}
+
+impl LineCommandRunner {
+    /// Runs the command on each of the rows, or once at the cursor if there are none.
+    fn start(
+        vim: &mut Vim,
+        command: LineCommand,
+        rows: Option<Vec<MultiBufferRow>>,
+        cx: &mut ViewContext<Vim>,
+    ) {
+        let Some(mut steps) = vim.update_editor(cx, |_, editor, cx| {
+            editor.start_transaction_at(Instant::now(), cx);
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            rows.into_iter()
+                .flatten()
+                .map(|row| {
+                    let line_len = snapshot.line_len(row);
+                    LineCommandStep::Line {
+                        start: snapshot.anchor_before(Point::new(row.0, 0)),
+                        end: snapshot.anchor_after(Point::new(row.0, line_len)),
+                        was_empty: line_len == 0,
+                    }
+                })
+                .collect::<VecDeque<_>>()
+        }) else {
+            return;
+        };
+
+        let mut runner = Self {
+            vim: cx.view().downgrade(),
+            command,
+            steps: VecDeque::new(),
+        };
+        if steps.is_empty() {
+            runner.push_keystrokes();
+        }
+        runner.steps.append(&mut steps);
+        runner.steps.push_back(LineCommandStep::Finish);
+        cx.window_context().defer(move |cx| runner.step(cx));
+    }
+
+    fn push_keystrokes(&mut self) {
+        let LineCommand::Normal { keys } = &self.command else {
+            return;
+        };
+        let mut steps = LineCommand::keystrokes(keys)
+            .map(LineCommandStep::Keystroke)
+            .collect::<VecDeque<_>>();
+        steps.push_back(LineCommandStep::Escape);
+        steps.append(&mut self.steps);
+        self.steps = steps;
+    }
+
+    fn step(mut self, cx: &mut WindowContext) {
+        if Vim::globals(cx).replayer.is_some() {
+            cx.defer(move |cx| self.step(cx));
+            return;
+        }
+        let Some(vim) = self.vim.upgrade() else {
+            return;
+        };
+        let Some(step) = self.steps.pop_front() else {
+            return;
+        };
+
+        match step {
+            LineCommandStep::Keystroke(keystroke) => {
+                let focused = cx.focused();
+                cx.dispatch_keystroke(keystroke);
+                if cx.focused() != focused {
+                    // Like `workspace::SendKeystrokes`, draw so that focus
+                    // changes are handled before the next keystroke.
+                    cx.draw();
+                }
+            }
+            LineCommandStep::Escape => {
+                let vim = vim.read(cx);
+                let pending = vim.mode != Mode::Normal || vim.active_operator().is_some();
+                if pending {
+                    if let Some(escape) = Keystroke::parse("escape").log_err() {
+                        cx.dispatch_keystroke(escape);
+                    }
+                }
+            }
+            LineCommandStep::Line {
+                start,
+                end,
+                was_empty,
+            } => {
+                let command = self.command.clone();
+                let result = vim.update(cx, |vim, cx| {
+                    let row = vim
+                        .update_editor(cx, |_, editor, cx| {
+                            let snapshot = editor.buffer().read(cx).snapshot(cx);
+                            let start = start.to_point(&snapshot);
+                            let deleted = !was_empty && start == end.to_point(&snapshot);
+                            (!deleted).then_some(MultiBufferRow(start.row))
+                        })
+                        .flatten();
+                    let Some(row) = row else {
+                        return Ok(false);
+                    };
+                    match command {
+                        LineCommand::Normal { .. } => {
+                            vim.switch_mode(Mode::Normal, false, cx);
+                            vim.update_editor(cx, |_, editor, cx| {
+                                editor.change_selections(None, cx, |s| {
+                                    s.select_ranges([Point::new(row.0, 0)..Point::new(row.0, 0)]);
+                                });
+                            });
+                            Ok(true)
+                        }
+                        LineCommand::Move { address } => {
+                            vim.move_lines(row..row, &address, false, cx).map(|_| false)
+                        }
+                        LineCommand::Copy { address } => {
+                            vim.move_lines(row..row, &address, true, cx).map(|_| false)
+                        }
+                        LineCommand::Put { register, above } => {
+                            vim.put_lines(row, register, above, cx);
+                            Ok(false)
+                        }
+                    }
+                });
+                match result {
+                    Ok(true) => self.push_keystrokes(),
+                    Ok(false) => {}
+                    Err(error) => {
+                        // Like vim, stop at the first line the command fails on.
+                        self.steps
+                            .retain(|step| matches!(step, LineCommandStep::Finish));
+                        vim.update(cx, |vim, cx| {
+                            let Some(workspace) = vim.workspace(cx) else {
+                                return;
+                            };
+                            workspace.update(cx, |workspace, cx| {
+                                Err::<(), _>(error).notify_err(workspace, cx);
+                            });
+                        });
+                    }
+                }
+            }
+            LineCommandStep::Finish => {
+                vim.update(cx, |vim, cx| {
+                    vim.update_editor(cx, |_, editor, cx| {
+                        editor.end_transaction_at(Instant::now(), cx);
+                    });
+                });
+                return;
+            }
+        }
+        cx.defer(move |cx| self.step(cx));
+    }
+}
+
+impl Vim {
+    /// Moves or copies the lines below the line `address` refers to, like `:move` and `:copy`.
+    /// The cursor ends up on the last of the moved lines.
+    fn move_lines(
+        &mut self,
+        rows: Range<MultiBufferRow>,
+        address: &Position,
+        copy: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<()> {
+        self.update_editor(cx, |vim, editor, cx| {
+            // `None` stands for address 0, the top of the buffer.
+            let target = if address.is_line_zero() {
+                None
+            } else {
+                Some(address.buffer_row(vim, editor, cx)?.0)
+            };
+            let (start, end) = (rows.start.0, rows.end.0);
+            if !copy && target.is_some_and(|target| start <= target && target < end) {
+                return Err(anyhow!("Cannot move a range of lines into itself"));
+            }
+
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let max_row = snapshot.max_row().0;
+            let line_count = end - start + 1;
+            let text = snapshot
+                .text_for_range(
+                    Point::new(start, 0)..Point::new(end, snapshot.line_len(MultiBufferRow(end))),
+                )
+                .collect::<String>();
+            let column = editor.selections.newest::<Point>(cx).head().column;
+
+            let first_row = match target {
+                Some(target) if !copy && target > end => target + 1 - line_count,
+                Some(target) => target + 1,
+                None => 0,
+            };
+            let unchanged = !copy && first_row == start;
+            editor.transact(cx, |editor, cx| {
+                if !unchanged {
+                    let insertion = match target {
+                        Some(target) if target == max_row => {
+                            let point =
+                                Point::new(target, snapshot.line_len(MultiBufferRow(target)));
+                            (snapshot.anchor_before(point), format!("\n{text}"))
+                        }
+                        Some(target) => (
+                            snapshot.anchor_before(Point::new(target + 1, 0)),
+                            format!("{text}\n"),
+                        ),
+                        None => (snapshot.anchor_before(Point::zero()), format!("{text}\n")),
+                    };
+                    if !copy {
+                        let deletion = if end < max_row {
+                            Point::new(start, 0)..Point::new(end + 1, 0)
+                        } else if start > 0 {
+                            let previous_row = MultiBufferRow(start - 1);
+                            Point::new(start - 1, snapshot.line_len(previous_row))
+                                ..Point::new(end, snapshot.line_len(MultiBufferRow(end)))
+                        } else {
+                            Point::zero()..snapshot.max_point()
+                        };
+                        editor.edit([(deletion, "")], cx);
+                    }
+                    let (anchor, text) = insertion;
+                    let point = anchor.to_point(&editor.buffer().read(cx).snapshot(cx));
+                    editor.edit([(point..point, text)], cx);
+                }
+
+                let last_row = MultiBufferRow(first_row + line_count - 1);
+                let line_len = editor.buffer().read(cx).snapshot(cx).line_len(last_row);
+                let cursor = Point::new(last_row.0, column.min(line_len.saturating_sub(1)));
+                editor.change_selections(None, cx, |s| s.select_ranges([cursor..cursor]));
+            });
+            Ok(())
+        })
+        .unwrap_or(Ok(()))
+    }
+
+    /// Puts the text of a register as whole lines below `row`, or above it, like `:put`.
+    /// The cursor ends up on the first non-blank character of the last line put.
+    fn put_lines(
+        &mut self,
+        row: MultiBufferRow,
+        register: Option<char>,
+        above: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            let Some(register) = Vim::update_globals(cx, |globals, cx| {
+                globals.read_register(register, Some(editor), cx)
+            }) else {
+                return;
+            };
+            let text = register.text.strip_suffix('\n').unwrap_or(&register.text);
+            let line_count = text.split('\n').count() as u32;
+
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let (point, text, first_row) = if above {
+                (Point::new(row.0, 0), format!("{text}\n"), row.0)
+            } else if row < snapshot.max_row() {
+                (Point::new(row.0 + 1, 0), format!("{text}\n"), row.0 + 1)
+            } else {
+                (
+                    Point::new(row.0, snapshot.line_len(row)),
+                    format!("\n{text}"),
+                    row.0 + 1,
+                )
+            };
+
+            editor.transact(cx, |editor, cx| {
+                editor.edit([(point..point, text)], cx);
+                let last_row = MultiBufferRow(first_row + line_count - 1);
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let indent = snapshot.indent_size_for_line(last_row).len;
+                let cursor = Point::new(last_row.0, indent);
+                editor.change_selections(None, cx, |s| s.select_ranges([cursor..cursor]));
+            });
+        });
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::path::Path;
@@ -1374,4 +1849,154 @@ mod test {
             a
             ˇa"});
     }
+
+    #[gpui::test]
+    async fn test_command_normal(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c"})
+            .await;
+        cx.simulate_shared_keystrokes(": % n o r m a l space shift-a ; enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            a;
+            b;
+            cˇ;"});
+
+        cx.set_shared_state("aˇbc").await;
+        cx.simulate_shared_keystrokes(": n o r m a l space x enter")
+            .await;
+        cx.shared_state().await.assert_eq("aˇc");
+
+        cx.set_shared_state(indoc! {"
+            a
+            ˇb
+            c
+            d"})
+            .await;
+        cx.simulate_shared_keystrokes(": 2 , 3 n o r m a l space shift-i - - enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            a
+            --b
+            -ˇ-c
+            d"});
+    }
+
+    #[gpui::test]
+    async fn test_command_matching_lines_normal(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇfoo
+            bar
+            foo bar
+            baz"})
+            .await;
+        cx.simulate_shared_keystrokes("q q shift-a ! escape q")
+            .await;
+        cx.simulate_shared_keystrokes(": g / b a r / n o r m a l space @ q enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            foo!
+            bar!
+            foo barˇ!
+            baz"});
+
+        cx.simulate_shared_keystrokes(": v / ! / n o r m a l space d d enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            foo!
+            bar!
+            ˇfoo bar!"});
+    }
+
+    #[gpui::test]
+    async fn test_command_move_and_copy(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d"})
+            .await;
+        cx.simulate_shared_keystrokes(": m $ enter").await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            b
+            c
+            d
+            ˇa"});
+        cx.simulate_shared_keystrokes(": 1 , 2 c o space 0 enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            b
+            ˇc
+            b
+            c
+            d
+            a"});
+
+        cx.set_shared_state(indoc! {"
+            ˇa1
+            b
+            a2
+            b
+            a3"})
+            .await;
+        cx.simulate_shared_keystrokes(": g / a / m 0 enter").await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            ˇa3
+            a2
+            a1
+            b
+            b"});
+
+        cx.set_shared_state(indoc! {"
+            ˇa1
+            b
+            a2
+            b
+            a3"})
+            .await;
+        cx.simulate_shared_keystrokes(": g / a / t $ enter").await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            a1
+            b
+            a2
+            b
+            a3
+            a1
+            a2
+            ˇa3"});
+    }
+
+    #[gpui::test]
+    async fn test_command_put(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c"})
+            .await;
+        cx.simulate_shared_keystrokes("\" x y y j").await;
+        cx.simulate_shared_keystrokes(": p u t space x enter").await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            a
+            b
+            ˇa
+            c"});
+        cx.simulate_shared_keystrokes(": 0 p u t space x enter")
+            .await;
+        cx.shared_state().await.assert_eq(indoc! {"
+            ˇa
+            a
+            b
+            a
+            c"});
+    }
 }
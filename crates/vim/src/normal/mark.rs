@@ -1,17 +1,18 @@
-use std::{ops::Range, sync::Arc};
+use std::{ops::Range, path::PathBuf, sync::Arc};
 
 use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
     movement,
     scroll::Autoscroll,
-    Anchor, Bias, DisplayPoint,
+    Anchor, Bias, DisplayPoint, Editor,
 };
 use gpui::ViewContext;
-use language::SelectionGoal;
+use language::{Point, SelectionGoal};
+use multi_buffer::MultiBufferRow;
 
 use crate::{
     motion::{self, Motion},
-    state::Mode,
+    state::{GlobalMark, Mode},
     Vim,
 };
 
@@ -27,6 +28,15 @@ impl Vim {
         }) else {
             return;
         };
+        if let Some(name) = global_mark_name(&text) {
+            let mark = self.update_editor(cx, |_, editor, cx| {
+                let (path, point) = file_position(editor, *anchors.last()?, cx)?;
+                Some(GlobalMark { path, point })
+            });
+            if let Some(mark) = mark.flatten() {
+                Vim::globals(cx).global_marks.insert(name, mark);
+            }
+        }
         self.marks.insert(text.to_string(), anchors);
         self.clear_operator(cx);
     }
@@ -88,6 +98,23 @@ impl Vim {
             _ => self.marks.get(&*text).cloned(),
         };
 
+        // A global mark set in another file, or in an earlier session, is jumped to by
+        // opening its file. Otherwise the local mark is used, as it follows edits.
+        if let Some(mark) = global_mark_name(&text)
+            .and_then(|name| Vim::globals(cx).global_marks.get(&name).cloned())
+        {
+            let current_path = self
+                .update_editor(cx, |_, editor, cx| {
+                    let head = editor.selections.newest_anchor().head();
+                    file_position(editor, head, cx).map(|(path, _)| path)
+                })
+                .flatten();
+            if anchors.is_none() || current_path.as_ref() != Some(&mark.path) {
+                self.jump_to_global_mark(mark, line, cx);
+                return;
+            }
+        }
+
         let Some(anchors) = anchors else { return };
 
         let is_active_operator = self.active_operator().is_some();
@@ -126,6 +153,57 @@ impl Vim {
     }
 }
 
+impl Vim {
+    fn jump_to_global_mark(&mut self, mark: GlobalMark, line: bool, cx: &mut ViewContext<Self>) {
+        // Like vim, operators can't span files.
+        if self.active_operator().is_some() {
+            self.clear_operator(cx);
+            return;
+        }
+        let Some(workspace) = self.workspace(cx) else {
+            return;
+        };
+        let open = workspace.update(cx, |workspace, cx| {
+            workspace.open_abs_path(mark.path.clone(), true, cx)
+        });
+        cx.spawn(|_, mut cx| async move {
+            let item = open.await?;
+            let Some(editor) = cx.update(|cx| item.act_as::<Editor>(cx))? else {
+                return Ok(());
+            };
+            editor.update(&mut cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let mut point = snapshot.clip_point(mark.point, Bias::Left);
+                if line {
+                    let indent = snapshot.indent_size_for_line(MultiBufferRow(point.row));
+                    point = Point::new(point.row, indent.len);
+                }
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([point..point])
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+/// The uppercase marks are global: they remember the file as well as the position.
+fn global_mark_name(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    let name = chars.next().filter(|name| name.is_ascii_uppercase())?;
+    chars.next().is_none().then_some(name)
+}
+
+fn file_position(
+    editor: &Editor,
+    anchor: Anchor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<(PathBuf, Point)> {
+    let (buffer, point, _) = editor.buffer().read(cx).point_to_buffer_point(anchor, cx)?;
+    let path = buffer.read(cx).file()?.as_local()?.abs_path(cx);
+    Some((path, point))
+}
+
 pub fn jump_motion(
     map: &DisplaySnapshot,
     anchor: Anchor,
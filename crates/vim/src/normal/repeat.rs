@@ -41,7 +41,7 @@ fn repeatable_insert(action: &ReplayableAction) -> Option<Box<dyn Action>> {
                 None
             }
         }
-        ReplayableAction::Insertion { .. } => None,
+        ReplayableAction::Insertion { .. } | ReplayableAction::Keystroke(_) => None,
     }
 }
 
@@ -56,7 +56,12 @@ pub(crate) fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
     Vim::action(editor, cx, |vim, _: &ToggleRecord, cx| {
         let globals = Vim::globals(cx);
         if let Some(char) = globals.recording_register.take() {
-            globals.last_recorded_register = Some(char)
+            globals.last_recorded_register = Some(char);
+            // Leave out the keystrokes that stopped the recording.
+            let sequence_len = globals.keystroke_sequence_len;
+            if let Some(keystrokes) = globals.recorded_keystrokes.get_mut(&char) {
+                keystrokes.truncate(keystrokes.len().saturating_sub(sequence_len));
+            }
         } else {
             vim.push_operator(Operator::RecordRegister, cx);
         }
@@ -143,6 +148,15 @@ impl Replayer {
                     })
                     .log_err();
             }
+            ReplayableAction::Keystroke(keystroke) => {
+                let focused = cx.focused();
+                cx.dispatch_keystroke(keystroke);
+                if cx.focused() != focused {
+                    // Like `workspace::SendKeystrokes`, draw so that focus
+                    // changes are handled before the next keystroke.
+                    cx.draw();
+                }
+            }
         }
         cx.defer(move |cx| self.next(cx));
     }
@@ -153,6 +167,7 @@ impl Vim {
         let globals = Vim::globals(cx);
         globals.recording_register = Some(register);
         globals.recordings.remove(&register);
+        globals.recorded_keystrokes.remove(&register);
         globals.ignore_current_insertion = true;
         self.clear_operator(cx)
     }
@@ -327,11 +342,13 @@ mod test {
     use futures::StreamExt;
     use indoc::indoc;
 
-    use gpui::ViewInputHandler;
+    use gpui::{KeyBinding, ViewInputHandler};
+    use workspace::SendKeystrokes;
 
     use crate::{
         state::Mode,
         test::{NeovimBackedTestContext, VimTestContext},
+        Vim,
     };
 
     #[gpui::test]
@@ -638,6 +655,85 @@ mod test {
         cx.shared_state().await.assert_eq("j ˇj");
     }
 
+    #[gpui::test]
+    async fn test_record_replay_restored(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone two
+            three four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("q a d w shift-a ! escape j 0 q");
+        cx.assert_state(
+            indoc! {"
+            two!
+            ˇthree four"},
+            Mode::Normal,
+        );
+
+        // Macros are persisted as the keystrokes that recorded them, including
+        // the ones of multi-keystroke bindings, but not the ones that stopped it.
+        let state = cx.update(|cx| {
+            let globals = Vim::globals(cx);
+            let state = globals.serialize();
+            globals.recordings.clear();
+            globals.recorded_keystrokes.clear();
+            state
+        });
+        assert_eq!(
+            state.recordings,
+            vec![(
+                "a".to_string(),
+                r#"["d","w","shift-a->A","!","escape","j","0"]"#.to_string()
+            )]
+        );
+
+        cx.update(|cx| Vim::globals(cx).restore(state));
+        cx.simulate_keystrokes("@ a");
+        cx.assert_state(
+            indoc! {"
+            two!
+            ˇfour!"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_record_send_keystrokes(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.update(|cx| {
+            cx.bind_keys([KeyBinding::new(
+                "g x",
+                SendKeystrokes("d w".to_string()),
+                Some("vim_mode == normal"),
+            )])
+        });
+        cx.set_state("ˇone two three", Mode::Normal);
+        cx.simulate_keystrokes("q a g x");
+        cx.simulate_keystrokes("q");
+        cx.assert_state("ˇtwo three", Mode::Normal);
+
+        // Only the keystrokes typed by the user are recorded, not the ones they sent.
+        let state = cx.update(|cx| {
+            let globals = Vim::globals(cx);
+            let state = globals.serialize();
+            globals.recordings.clear();
+            globals.recorded_keystrokes.clear();
+            state
+        });
+        assert_eq!(
+            state.recordings,
+            vec![("a".to_string(), r#"["g","x"]"#.to_string())]
+        );
+
+        cx.update(|cx| Vim::globals(cx).restore(state));
+        cx.simulate_keystrokes("@ a");
+        cx.assert_state("ˇthree", Mode::Normal);
+    }
+
     #[gpui::test]
     async fn test_record_replay_count(cx: &mut gpui::TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
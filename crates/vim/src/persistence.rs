@@ -0,0 +1,234 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+
+use workspace::{WorkspaceDb, WorkspaceId};
+
+/// The number of jumplist entries kept for every workspace, as in Neovim.
+pub(crate) const MAX_JUMPS: usize = 100;
+
+/// The registers, macros and global marks that are kept across sessions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct SerializedVimState {
+    /// The name, text and JSON encoded clipboard selections of each named register.
+    pub(crate) registers: Vec<(String, String, Option<String>)>,
+    /// The name and JSON encoded keystrokes of each recorded macro.
+    pub(crate) recordings: Vec<(String, String)>,
+    /// The name, file, row and column of each global mark.
+    pub(crate) marks: Vec<(String, PathBuf, u32, u32)>,
+}
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // vim_registers(
+    //   name: String,
+    //   text: String,
+    //   clipboard_selections: Option<String>,
+    // )
+    //
+    // vim_recordings(
+    //   name: String,
+    //   keystrokes: String,
+    // )
+    //
+    // vim_global_marks(
+    //   name: String,
+    //   path: PathBuf,
+    //   point_row: u32,
+    //   point_column: u32,
+    // )
+    //
+    // vim_jumps(
+    //   workspace_id: usize,
+    //   ix: usize,
+    //   path: PathBuf,
+    //   point_row: u32,
+    //   point_column: u32,
+    // )
+    pub static ref VIM_DB: VimDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE vim_registers(
+                name TEXT PRIMARY KEY,
+                text TEXT NOT NULL,
+                clipboard_selections TEXT
+            ) STRICT;
+
+            CREATE TABLE vim_recordings(
+                name TEXT PRIMARY KEY,
+                keystrokes TEXT NOT NULL
+            ) STRICT;
+
+            CREATE TABLE vim_global_marks(
+                name TEXT PRIMARY KEY,
+                path BLOB NOT NULL,
+                point_row INTEGER NOT NULL,
+                point_column INTEGER NOT NULL
+            ) STRICT;
+
+            CREATE TABLE vim_jumps(
+                workspace_id INTEGER NOT NULL,
+                ix INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                point_row INTEGER NOT NULL,
+                point_column INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, ix),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        )];
+);
+
+impl VimDb {
+    /// Replaces the stored registers, macros and global marks.
+    pub(crate) async fn save_state(&self, state: SerializedVimState) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec(sql!(DELETE FROM vim_registers))?()?;
+            conn.exec(sql!(DELETE FROM vim_recordings))?()?;
+            conn.exec(sql!(DELETE FROM vim_global_marks))?()?;
+
+            let mut insert_register = conn.exec_bound::<(String, String, Option<String>)>(sql!(
+                INSERT INTO vim_registers(name, text, clipboard_selections) VALUES (?, ?, ?)
+            ))?;
+            for register in state.registers {
+                insert_register(register)?;
+            }
+            let mut insert_recording = conn.exec_bound::<(String, String)>(sql!(
+                INSERT INTO vim_recordings(name, keystrokes) VALUES (?, ?)
+            ))?;
+            for recording in state.recordings {
+                insert_recording(recording)?;
+            }
+            let mut insert_mark = conn.exec_bound::<(String, PathBuf, u32, u32)>(sql!(
+                INSERT INTO vim_global_marks(name, path, point_row, point_column) VALUES (?, ?, ?, ?)
+            ))?;
+            for mark in state.marks {
+                insert_mark(mark)?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    pub(crate) fn state(&self) -> Result<SerializedVimState> {
+        Ok(SerializedVimState {
+            registers: self.registers()?,
+            recordings: self.recordings()?,
+            marks: self.global_marks()?,
+        })
+    }
+
+    query! {
+        fn registers() -> Result<Vec<(String, String, Option<String>)>> {
+            SELECT name, text, clipboard_selections
+            FROM vim_registers
+            ORDER BY name ASC
+        }
+    }
+
+    query! {
+        fn recordings() -> Result<Vec<(String, String)>> {
+            SELECT name, keystrokes
+            FROM vim_recordings
+            ORDER BY name ASC
+        }
+    }
+
+    query! {
+        fn global_marks() -> Result<Vec<(String, PathBuf, u32, u32)>> {
+            SELECT name, path, point_row, point_column
+            FROM vim_global_marks
+            ORDER BY name ASC
+        }
+    }
+
+    /// Replaces the jumplist of a workspace with the given file positions, oldest first.
+    pub(crate) async fn save_jumps(
+        &self,
+        workspace_id: WorkspaceId,
+        jumps: Vec<(PathBuf, u32, u32)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec_bound::<WorkspaceId>(sql!(
+                DELETE FROM vim_jumps WHERE workspace_id = ?
+            ))?(workspace_id)?;
+
+            let skipped = jumps.len().saturating_sub(MAX_JUMPS);
+            let mut insert_jump =
+                conn.exec_bound::<(WorkspaceId, usize, PathBuf, u32, u32)>(sql!(
+                    INSERT INTO vim_jumps(workspace_id, ix, path, point_row, point_column)
+                    VALUES (?, ?, ?, ?, ?)
+                ))?;
+            for (ix, (path, row, column)) in jumps.into_iter().skip(skipped).enumerate() {
+                insert_jump((workspace_id, ix, path, row, column))?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    query! {
+        pub(crate) fn jumps(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, u32, u32)>> {
+            SELECT path, point_row, point_column
+            FROM vim_jumps
+            WHERE workspace_id = ?
+            ORDER BY ix ASC
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_save_state() {
+        let state = SerializedVimState {
+            registers: vec![
+                ("u".to_string(), "one\n".to_string(), None),
+                (
+                    "v".to_string(),
+                    "two".to_string(),
+                    Some(r#"[{"len":3,"is_entire_line":false,"first_line_indent":0}]"#.into()),
+                ),
+            ],
+            recordings: vec![("u".to_string(), r#"["shift-a","!","escape"]"#.to_string())],
+            marks: vec![("U".to_string(), PathBuf::from("/root/a.rs"), 3, 4)],
+        };
+        VIM_DB.save_state(state.clone()).await.unwrap();
+        assert_eq!(VIM_DB.state().unwrap(), state);
+
+        // Saving replaces everything that was saved before.
+        let state = SerializedVimState {
+            registers: vec![("w".to_string(), "three".to_string(), None)],
+            ..Default::default()
+        };
+        VIM_DB.save_state(state.clone()).await.unwrap();
+        assert_eq!(VIM_DB.state().unwrap(), state);
+
+        VIM_DB.save_state(Default::default()).await.unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_save_jumps() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+
+        let jumps = (0..MAX_JUMPS as u32 + 10)
+            .map(|row| (PathBuf::from("/root/a.rs"), row, 0))
+            .collect::<Vec<_>>();
+        VIM_DB
+            .save_jumps(workspace_id, jumps.clone())
+            .await
+            .unwrap();
+        // Only the most recent jumps are kept.
+        assert_eq!(VIM_DB.jumps(workspace_id).unwrap(), jumps[10..].to_vec());
+
+        VIM_DB
+            .save_jumps(workspace_id, jumps[..2].to_vec())
+            .await
+            .unwrap();
+        assert_eq!(VIM_DB.jumps(workspace_id).unwrap(), jumps[..2].to_vec());
+    }
+}
@@ -1,22 +1,26 @@
 use std::borrow::BorrowMut;
+use std::path::PathBuf;
 use std::{fmt::Display, ops::Range, sync::Arc};
 
 use crate::command::command_interceptor;
 use crate::normal::repeat::Replayer;
+use crate::persistence::{SerializedVimState, VIM_DB};
 use crate::surrounds::SurroundsType;
 use crate::{motion::Motion, object::Object};
 use crate::{UseSystemClipboard, Vim, VimSettings};
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use command_palette_hooks::{CommandPaletteFilter, CommandPaletteInterceptor};
 use editor::{Anchor, ClipboardSelection, Editor};
 use gpui::{
-    Action, AppContext, BorrowAppContext, ClipboardEntry, ClipboardItem, Global, View, WeakView,
+    Action, AppContext, BorrowAppContext, ClipboardEntry, ClipboardItem, Global, Keystroke, View,
+    WeakView,
 };
 use language::Point;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
 use ui::{SharedString, ViewContext};
-use workspace::searchable::Direction;
+use util::ResultExt;
+use workspace::{searchable::Direction, WorkspaceId};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Mode {
@@ -138,6 +142,13 @@ impl From<ClipboardItem> for Register {
     }
 }
 
+/// An uppercase mark, which can be jumped to from any file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlobalMark {
+    pub path: PathBuf,
+    pub point: Point,
+}
+
 impl From<String> for Register {
     fn from(text: String) -> Self {
         Register {
@@ -174,6 +185,17 @@ pub struct VimGlobals {
     pub last_yank: Option<SharedString>,
     pub registers: HashMap<char, Register>,
     pub recordings: HashMap<char, Vec<ReplayableAction>>,
+    /// The keystrokes typed while recording each macro, which is how
+    /// the macros are kept across sessions.
+    pub recorded_keystrokes: HashMap<char, Vec<Keystroke>>,
+    /// The number of keystrokes in the key sequence being dispatched, so that
+    /// the keystrokes that stop a recording can be left out of it.
+    pub keystroke_sequence_len: usize,
+
+    pub global_marks: HashMap<char, GlobalMark>,
+
+    pub restored_state: bool,
+    pub restored_jumplists: HashSet<WorkspaceId>,
 
     pub focused_vim: Option<WeakView<Vim>>,
 }
@@ -182,6 +204,31 @@ impl Global for VimGlobals {}
 impl VimGlobals {
     pub(crate) fn register(cx: &mut AppContext) {
         cx.set_global(VimGlobals::default());
+        VimGlobals::restore_persisted_state(cx);
+
+        cx.intercept_keystrokes(|event, cx| {
+            // Keystrokes sent by zed itself, e.g. by `workspace::SendKeystrokes` or `:normal`,
+            // are reproduced by replaying the keystrokes that triggered them.
+            if event.is_simulated {
+                return;
+            }
+            let pending_len = cx
+                .pending_input_keystrokes()
+                .map_or(0, |pending| pending.len());
+            Vim::globals(cx).intercept_keystroke(&event.keystroke, pending_len);
+        })
+        .detach();
+
+        cx.on_app_quit(|cx| {
+            let state = (Vim::enabled(cx) && VimSettings::get_global(cx).persist_state)
+                .then(|| Vim::globals(cx).serialize());
+            async move {
+                if let Some(state) = state {
+                    VIM_DB.save_state(state).await.log_err();
+                }
+            }
+        })
+        .detach();
 
         cx.observe_keystrokes(|event, cx| {
             let Some(action) = event.action.as_ref().map(|action| action.boxed_clone()) else {
@@ -199,6 +246,7 @@ impl VimGlobals {
                 CommandPaletteInterceptor::update_global(cx, |interceptor, _| {
                     interceptor.set(Box::new(command_interceptor));
                 });
+                VimGlobals::restore_persisted_state(cx);
             } else {
                 *Vim::globals(cx) = VimGlobals::default();
                 CommandPaletteInterceptor::update_global(cx, |interceptor, _| {
@@ -394,9 +442,164 @@ impl VimGlobals {
         }
     }
 
+    fn intercept_keystroke(&mut self, keystroke: &Keystroke, pending_len: usize) {
+        self.keystroke_sequence_len = pending_len + 1;
+        if self.replayer.is_none() {
+            if let Some(recording_register) = self.recording_register {
+                self.recorded_keystrokes
+                    .entry(recording_register)
+                    .or_default()
+                    .push(keystroke.clone());
+            }
+        }
+    }
+
     pub fn focused_vim(&self) -> Option<View<Vim>> {
         self.focused_vim.as_ref().and_then(|vim| vim.upgrade())
     }
+
+    /// Loads the registers, macros and global marks of the previous session, once vim mode is enabled.
+    fn restore_persisted_state(cx: &mut AppContext) {
+        if !Vim::enabled(cx)
+            || !VimSettings::get_global(cx).persist_state
+            || Vim::globals(cx).restored_state
+        {
+            return;
+        }
+        Vim::globals(cx).restored_state = true;
+        cx.spawn(|mut cx| async move {
+            let state = cx
+                .background_executor()
+                .spawn(async move { VIM_DB.state() })
+                .await?;
+            cx.update(|cx| Vim::globals(cx).restore(state))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// The named registers, recorded macros and global marks to keep across sessions.
+    pub(crate) fn serialize(&self) -> SerializedVimState {
+        let registers = self
+            .registers
+            .iter()
+            .filter(|(name, _)| name.is_ascii_lowercase())
+            .map(|(name, register)| {
+                let clipboard_selections = register
+                    .clipboard_selections
+                    .as_ref()
+                    .and_then(|selections| serde_json::to_string(selections).log_err());
+                (
+                    name.to_string(),
+                    register.text.to_string(),
+                    clipboard_selections,
+                )
+            })
+            .collect();
+        let recordings = self
+            .recorded_keystrokes
+            .iter()
+            .filter(|(name, _)| name.is_ascii_alphanumeric())
+            .filter_map(|(name, keystrokes)| {
+                let keystrokes = keystrokes
+                    .iter()
+                    .map(serialize_keystroke)
+                    .collect::<Vec<_>>();
+                Some((
+                    name.to_string(),
+                    serde_json::to_string(&keystrokes).log_err()?,
+                ))
+            })
+            .collect();
+        let marks = self
+            .global_marks
+            .iter()
+            .map(|(name, mark)| {
+                (
+                    name.to_string(),
+                    mark.path.clone(),
+                    mark.point.row,
+                    mark.point.column,
+                )
+            })
+            .collect();
+        SerializedVimState {
+            registers,
+            recordings,
+            marks,
+        }
+    }
+
+    /// Restores the state of the previous session, keeping anything that was set since launch.
+    pub(crate) fn restore(&mut self, state: SerializedVimState) {
+        fn name(name: &str) -> Option<char> {
+            let mut chars = name.chars();
+            let name = chars.next()?;
+            chars.next().is_none().then_some(name)
+        }
+
+        for (register, text, clipboard_selections) in state.registers {
+            let Some(register) = name(&register) else {
+                continue;
+            };
+            self.registers.entry(register).or_insert_with(|| Register {
+                text: text.into(),
+                clipboard_selections: clipboard_selections
+                    .and_then(|selections| serde_json::from_str(&selections).log_err()),
+            });
+        }
+
+        for (register, keystrokes) in state.recordings {
+            let Some(register) = name(&register) else {
+                continue;
+            };
+            if self.recordings.contains_key(&register) {
+                continue;
+            }
+            let Some(keystrokes) = serde_json::from_str::<Vec<String>>(&keystrokes).log_err()
+            else {
+                continue;
+            };
+            let keystrokes = keystrokes
+                .iter()
+                .filter_map(|keystroke| Keystroke::parse(keystroke).log_err())
+                .collect::<Vec<_>>();
+            self.recordings.insert(
+                register,
+                keystrokes
+                    .iter()
+                    .cloned()
+                    .map(ReplayableAction::Keystroke)
+                    .collect(),
+            );
+            self.recorded_keystrokes.insert(register, keystrokes);
+        }
+
+        for (mark, path, row, column) in state.marks {
+            let Some(mark) = name(&mark) else {
+                continue;
+            };
+            self.global_marks.entry(mark).or_insert(GlobalMark {
+                path,
+                point: Point::new(row, column),
+            });
+        }
+    }
+}
+
+/// Writes a keystroke the way [`Keystroke::parse`] reads it, including the
+/// character it typed when that differs from its key.
+fn serialize_keystroke(keystroke: &Keystroke) -> String {
+    let mut source = keystroke.unparse();
+    if let Some(key_char) = keystroke.key_char.as_ref().filter(|key_char| {
+        **key_char != keystroke.key
+            && keystroke.key != "-"
+            && !key_char.contains('-')
+            && !key_char.trim().is_empty()
+    }) {
+        source.push_str("->");
+        source.push_str(key_char);
+    }
+    source
 }
 
 impl Vim {
@@ -419,6 +622,8 @@ pub enum ReplayableAction {
         text: Arc<str>,
         utf16_range_to_replace: Option<Range<isize>>,
     },
+    /// A keystroke of a macro that was restored from a previous session.
+    Keystroke(Keystroke),
 }
 
 impl Clone for ReplayableAction {
@@ -432,6 +637,7 @@ impl Clone for ReplayableAction {
                 text: text.clone(),
                 utf16_range_to_replace: utf16_range_to_replace.clone(),
             },
+            Self::Keystroke(keystroke) => Self::Keystroke(keystroke.clone()),
         }
     }
 }
@@ -22,6 +22,11 @@ impl VimTestContext {
             command_palette::init(cx);
             crate::init(cx);
             search::init(cx);
+            // Tests share the process-wide database, so they must neither restore the state of
+            // other tests nor save their own.
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<VimSettings>(cx, |s| s.persist_state = Some(false));
+            });
         });
     }
 
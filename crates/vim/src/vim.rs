@@ -13,6 +13,7 @@ mod mode_indicator;
 mod motion;
 mod normal;
 mod object;
+mod persistence;
 mod replace;
 mod rewrap;
 mod state;
@@ -34,17 +35,19 @@ use language::{CursorShape, Point, Selection, SelectionGoal, TransactionId};
 pub use mode_indicator::ModeIndicator;
 use motion::Motion;
 use normal::search::SearchSubmit;
+use persistence::VIM_DB;
 use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_derive::Serialize;
 use settings::{update_settings_file, Settings, SettingsSources, SettingsStore};
 use state::{Mode, Operator, RecordedSelection, SearchState, VimGlobals};
-use std::{mem, ops::Range, sync::Arc};
+use std::{mem, ops::Range, path::PathBuf, sync::Arc};
 use surrounds::SurroundsType;
 use theme::ThemeSettings;
 use ui::{px, IntoElement, VisualContext};
+use util::ResultExt;
 use vim_mode_setting::VimModeSetting;
-use workspace::{self, Pane, ResizeIntent, Workspace};
+use workspace::{self, Pane, ResizeIntent, Workspace, WorkspaceId};
 
 use crate::state::ReplayableAction;
 
@@ -103,7 +106,31 @@ pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(|editor: &mut Editor, cx| Vim::register(editor, cx))
         .detach();
 
-    cx.observe_new_views(|workspace: &mut Workspace, _| {
+    cx.on_app_quit(|cx| {
+        let jumplists = cx
+            .windows()
+            .into_iter()
+            .filter_map(|window| window.downcast::<Workspace>())
+            .filter_map(|workspace| serialize_jumplist(workspace.read(cx).ok()?, cx))
+            .collect::<Vec<_>>();
+        async move {
+            for (workspace_id, jumps) in jumplists {
+                VIM_DB.save_jumps(workspace_id, jumps).await.log_err();
+            }
+        }
+    })
+    .detach();
+
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        cx.on_release(|workspace, _, cx| {
+            if let Some((workspace_id, jumps)) = serialize_jumplist(workspace, cx) {
+                cx.background_executor()
+                    .spawn(async move { VIM_DB.save_jumps(workspace_id, jumps).await.log_err() })
+                    .detach();
+            }
+        })
+        .detach();
+
         workspace.register_action(|workspace, _: &ToggleVimMode, cx| {
             let fs = workspace.app_state().fs.clone();
             let currently_enabled = Vim::enabled(cx);
@@ -396,6 +423,46 @@ impl Vim {
             .map(|workspace| workspace.read(cx).focused_pane(cx))
     }
 
+    /// Restores the jumplist of the previous session into the active pane, the first time
+    /// an editor of the workspace is focused. Files are only reopened when jumped to.
+    /// Jumps into files outside of the project are dropped.
+    fn restore_jumplist(&mut self, cx: &mut ViewContext<Self>) {
+        if !VimSettings::get_global(cx).persist_state {
+            return;
+        }
+        let Some(workspace) = self.workspace(cx) else {
+            return;
+        };
+        let Some(workspace_id) = workspace.read(cx).database_id() else {
+            return;
+        };
+        if !Vim::globals(cx).restored_jumplists.insert(workspace_id) {
+            return;
+        }
+        let pane = workspace.read(cx).active_pane().downgrade();
+        let project = workspace.read(cx).project().clone();
+        cx.spawn(|_, mut cx| async move {
+            let jumps = cx
+                .background_executor()
+                .spawn(async move { VIM_DB.jumps(workspace_id) })
+                .await?;
+            pane.update(&mut cx, |pane, cx| {
+                for (path, row, column) in jumps {
+                    let Some(project_path) = project.read(cx).find_project_path(&path, cx) else {
+                        continue;
+                    };
+                    pane.nav_history_mut().push_unopened(
+                        Some(Point::new(row, column)),
+                        project_path,
+                        Some(path),
+                        cx,
+                    );
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     pub fn enabled(cx: &mut AppContext) -> bool {
         VimModeSetting::get_global(cx).0
     }
@@ -730,6 +797,7 @@ impl Vim {
         let Some(editor) = self.editor() else {
             return;
         };
+        self.restore_jumplist(cx);
         let newest_selection_empty = editor.update(cx, |editor, cx| {
             editor.selections.newest::<usize>(cx).is_empty()
         });
@@ -1225,6 +1293,7 @@ struct VimSettings {
     pub use_smartcase_find: bool,
     pub custom_digraphs: HashMap<String, Arc<str>>,
     pub highlight_on_yank_duration: u64,
+    pub persist_state: bool,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -1235,6 +1304,7 @@ struct VimSettingsContent {
     pub use_smartcase_find: Option<bool>,
     pub custom_digraphs: Option<HashMap<String, Arc<str>>>,
     pub highlight_on_yank_duration: Option<u64>,
+    pub persist_state: Option<bool>,
 }
 
 impl Settings for VimSettings {
@@ -1246,3 +1316,33 @@ impl Settings for VimSettings {
         sources.json_merge()
     }
 }
+
+/// The jumplist of the active pane of a workspace, as the file positions that going back would visit.
+fn serialize_jumplist(
+    workspace: &Workspace,
+    cx: &AppContext,
+) -> Option<(WorkspaceId, Vec<(PathBuf, u32, u32)>)> {
+    if !VimModeSetting::get_global(cx).0 || !VimSettings::get_global(cx).persist_state {
+        return None;
+    }
+    let workspace_id = workspace.database_id()?;
+    let project = workspace.project().read(cx);
+    let mut jumps = Vec::new();
+    workspace
+        .active_pane()
+        .read(cx)
+        .nav_history()
+        .for_each_backward_entry(cx, |entry, (project_path, abs_path)| {
+            let Some(point) = entry
+                .data
+                .as_deref()
+                .and_then(Editor::navigation_entry_position)
+            else {
+                return;
+            };
+            if let Some(path) = abs_path.or_else(|| project.absolute_path(&project_path, cx)) {
+                jumps.push((path, point.row, point.column));
+            }
+        });
+    Some((workspace_id, jumps))
+}
@@ -3113,6 +3113,25 @@ impl NavHistory {
             })
     }
 
+    /// Calls `f` for each entry that going back would visit, from the oldest to the most recent.
+    pub fn for_each_backward_entry(
+        &self,
+        cx: &AppContext,
+        mut f: impl FnMut(&NavigationEntry, (ProjectPath, Option<PathBuf>)),
+    ) {
+        let borrowed_history = self.0.lock();
+        for entry in &borrowed_history.backward_stack {
+            if let Some(project_and_abs_path) = borrowed_history.paths_by_item.get(&entry.item.id())
+            {
+                f(entry, project_and_abs_path.clone());
+            } else if let Some(item) = entry.item.upgrade() {
+                if let Some(path) = item.project_path(cx) {
+                    f(entry, (path, None));
+                }
+            }
+        }
+    }
+
     pub fn set_mode(&mut self, mode: NavigationMode) {
         self.0.lock().mode = mode;
     }
@@ -3205,6 +3224,24 @@ impl NavHistory {
         state.did_update(cx);
     }
 
+    /// Pushes an entry for an item that is not open, such as one restored from a previous
+    /// session. Navigating to the entry opens the item from the given path.
+    pub fn push_unopened<D: 'static + Send + Any>(
+        &mut self,
+        data: Option<D>,
+        project_path: ProjectPath,
+        abs_path: Option<PathBuf>,
+        cx: &mut WindowContext,
+    ) {
+        // Reserve an entity id that no item will ever have, to key the entry's path by.
+        let item_id = cx.reserve_model::<()>().entity_id();
+        self.0
+            .lock()
+            .paths_by_item
+            .insert(item_id, (project_path, abs_path));
+        self.push(data, Arc::new(UnopenedItem(item_id)), false, cx);
+    }
+
     pub fn remove_item(&mut self, item_id: EntityId) {
         let mut state = self.0.lock();
         state.paths_by_item.remove(&item_id);
@@ -3224,6 +3261,23 @@ impl NavHistory {
     }
 }
 
+/// The handle of a navigation history entry for an item that is not open.
+struct UnopenedItem(EntityId);
+
+impl WeakItemHandle for UnopenedItem {
+    fn id(&self) -> EntityId {
+        self.0
+    }
+
+    fn boxed_clone(&self) -> Box<dyn WeakItemHandle> {
+        Box::new(UnopenedItem(self.0))
+    }
+
+    fn upgrade(&self) -> Option<Box<dyn ItemHandle>> {
+        None
+    }
+}
+
 impl NavHistoryState {
     pub fn did_update(&self, cx: &mut WindowContext) {
         if let Some(pane) = self.pane.upgrade() {
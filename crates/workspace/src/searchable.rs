@@ -42,6 +42,8 @@ pub struct SearchOptions {
     /// Specifies whether the  supports search & replace.
     pub replacement: bool,
     pub selection: bool,
+    /// Specifies whether matches can be restricted to comments, strings or code.
+    pub syntax_scope: bool,
 }
 
 pub trait SearchableItem: Item + EventEmitter<SearchEvent> {
@@ -54,6 +56,7 @@ pub trait SearchableItem: Item + EventEmitter<SearchEvent> {
             regex: true,
             replacement: true,
             selection: true,
+            syntax_scope: true,
         }
     }
 
@@ -1,3 +1,4 @@
+use anyhow::Context as _;
 use project::TaskSourceKind;
 use remote::ConnectionState;
 use task::{ResolvedTask, TaskContext, TaskTemplate};
@@ -46,6 +47,31 @@ pub fn schedule_resolved_task(
     omit_history: bool,
     cx: &mut ViewContext<'_, Workspace>,
 ) {
+    if !resolved_task.original_task().depends_on.is_empty() {
+        let task_inventory = workspace
+            .project()
+            .read(cx)
+            .task_store()
+            .read(cx)
+            .task_inventory()
+            .cloned();
+        if let Some(task_inventory) = task_inventory {
+            if let Err(e) = task_inventory
+                .read(cx)
+                .resolve_dependencies(&task_source_kind, &mut resolved_task)
+                .with_context(|| {
+                    format!(
+                        "resolving dependencies of task {:?}",
+                        resolved_task.resolved_label
+                    )
+                })
+            {
+                workspace.show_error(&e, cx);
+                return;
+            }
+        }
+    }
+
     if let Some(spawn_in_terminal) = resolved_task.resolved.take() {
         if !omit_history {
             resolved_task.resolved = Some(spawn_in_terminal.clone());
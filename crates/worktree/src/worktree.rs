@@ -20,7 +20,7 @@ use futures::{
 use fuzzy::CharBag;
 use git::GitHostingProviderRegistry;
 use git::{
-    repository::{GitFileStatus, GitRepository, RepoPath},
+    repository::{DiffBase, GitFileStatus, GitRepository, RepoPath},
     status::GitStatus,
     COOKIES, DOT_GIT, FSMONITOR_DAEMON, GITIGNORE,
 };
@@ -238,6 +238,18 @@ impl RepositoryEntry {
             relativize_path(path)
         }
     }
+
+    /// unrelativize is the inverse of relativize, returning the project path of a path
+    /// relative to the root folder of the repository, or `None` if it lies outside of the
+    /// worktree.
+    pub fn unrelativize(&self, worktree: &Snapshot, path: &RepoPath) -> Option<Arc<Path>> {
+        let work_directory = &worktree.entry_for_id(self.work_directory.0)?.path;
+        let path = match &self.location_in_repo {
+            Some(location_in_repo) => path.strip_prefix(location_in_repo).ok()?,
+            None => path.as_path(),
+        };
+        Some(work_directory.join(path).into())
+    }
 }
 
 impl From<&RepositoryEntry> for proto::RepositoryEntry {
@@ -709,10 +721,17 @@ impl Worktree {
         }
     }
 
-    pub fn load_staged_file(&self, path: &Path, cx: &AppContext) -> Task<Result<Option<String>>> {
+    /// Loads the text that the changes of the file at the given path are computed against.
+    pub fn load_diff_base_file(
+        &self,
+        path: &Path,
+        diff_base: &DiffBase,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
         match self {
             Worktree::Local(this) => {
                 let path = Arc::from(path);
+                let diff_base = diff_base.clone();
                 let snapshot = this.snapshot();
                 cx.background_executor().spawn(async move {
                     if let Some(repo) = snapshot.repository_for_path(&path) {
@@ -720,7 +739,9 @@ impl Worktree {
                             if let Some(git_repo) =
                                 snapshot.git_repositories.get(&*repo.work_directory)
                             {
-                                return Ok(git_repo.repo_ptr.load_index_text(&repo_path));
+                                return Ok(
+                                    diff_base.load_text(git_repo.repo_ptr.as_ref(), &repo_path)
+                                );
                             }
                         }
                     }
@@ -728,7 +749,7 @@ impl Worktree {
                 })
             }
             Worktree::Remote(_) => {
-                Task::ready(Err(anyhow!("remote worktrees can't yet load staged files")))
+                Task::ready(Err(anyhow!("remote worktrees can't yet load diff bases")))
             }
         }
     }
@@ -444,6 +444,8 @@ fn main() {
         project_symbols::init(cx);
         project_panel::init(Assets, cx);
         git_ui::git_panel::init(cx);
+        git_ui::diff_base_picker::init(cx);
+        git_ui::stash_picker::init(cx);
         outline_panel::init(Assets, cx);
         tasks_ui::init(cx);
         snippets_ui::init(cx);
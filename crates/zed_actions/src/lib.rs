@@ -48,7 +48,7 @@ actions!(
 pub mod branches {
     use gpui::actions;
 
-    actions!(branches, [OpenRecent]);
+    actions!(branches, [OpenRecent, OpenPullRequest, CompareOnRemote]);
 }
 
 pub mod command_palette {